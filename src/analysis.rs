@@ -0,0 +1,889 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
+use serde::Serialize;
+
+use crate::models::{Exercise, ExerciseHistoryEntry, Routine};
+use crate::table::{Color, Column, Style, Table, Units};
+
+/// Rep targets per set of a routine: `(exercise_template_id, set_index)`
+/// to the `(lo, hi)` reps the set aims for.
+///
+/// A rep range gives its bounds (8 when the start is missing, and the
+/// start when the end is). Fixed reps `r` allow `r - 1` to `r + 1`, and a
+/// set with neither counts as 10 reps. Exercises without a template ID are
+/// skipped.
+pub fn set_targets_from_routine(routine: &Routine) -> HashMap<(String, usize), (i64, i64)> {
+    let mut set_targets = HashMap::new();
+    for ex in &routine.exercises {
+        if let Some(ref tmpl_id) = ex.exercise_template_id {
+            for (i, s) in ex.sets.iter().enumerate() {
+                let (lo, hi) = if let Some(ref range) = s.rep_range {
+                    let lo = range.start.unwrap_or(8);
+                    let hi = range.end.unwrap_or(lo);
+                    (lo, hi)
+                } else {
+                    let r = s.reps.unwrap_or(10);
+                    (r.saturating_sub(1), r + 1)
+                };
+                set_targets.insert((tmpl_id.clone(), i), (lo, hi));
+            }
+        }
+    }
+    set_targets
+}
+
+/// How a set's reps compare with its `(lo, hi)` target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetResult {
+    /// Fewer than `lo` reps.
+    Struggled,
+    /// `lo` to `hi` reps.
+    Succeeded,
+    /// More than `hi` reps.
+    Exceeded,
+}
+
+impl SetResult {
+    /// The color results are shown in: yellow, green or cyan.
+    pub fn color(self) -> Color {
+        match self {
+            SetResult::Struggled => Color::Yellow,
+            SetResult::Succeeded => Color::Green,
+            SetResult::Exceeded => Color::Cyan,
+        }
+    }
+}
+
+impl fmt::Display for SetResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SetResult::Struggled => "Struggled",
+            SetResult::Succeeded => "Succeeded",
+            SetResult::Exceeded => "Exceeded",
+        })
+    }
+}
+
+/// Classify `reps` against the inclusive target range `lo..=hi`.
+pub fn classify_set_result(reps: i64, lo: i64, hi: i64) -> SetResult {
+    if reps < lo {
+        SetResult::Struggled
+    } else if reps <= hi {
+        SetResult::Succeeded
+    } else {
+        SetResult::Exceeded
+    }
+}
+
+/// A run of consecutive exercises sharing a superset, or one exercise
+/// outside any superset (`superset_id` of `None`).
+#[derive(Debug)]
+pub struct SupersetGroup<'a> {
+    pub superset_id: Option<i64>,
+    pub exercises: Vec<&'a Exercise>,
+}
+
+/// Split `exercises` into superset groups, keeping their order. Only
+/// neighbouring exercises are grouped, as Hevy keeps a superset together.
+pub fn group_by_superset(exercises: &[Exercise]) -> Vec<SupersetGroup<'_>> {
+    let mut groups: Vec<SupersetGroup<'_>> = Vec::new();
+    for exercise in exercises {
+        match groups.last_mut() {
+            Some(group)
+                if exercise.supersets_id.is_some()
+                    && group.superset_id == exercise.supersets_id =>
+            {
+                group.exercises.push(exercise);
+            }
+            _ => groups.push(SupersetGroup {
+                superset_id: exercise.supersets_id,
+                exercises: vec![exercise],
+            }),
+        }
+    }
+    groups
+}
+
+/// Rep range buckets of `history distribution`: label and lowest reps.
+const REP_RANGES: [(&str, i64); 5] = [
+    ("1-3", 1),
+    ("4-6", 4),
+    ("7-10", 7),
+    ("11-15", 11),
+    ("16+", 16),
+];
+
+/// Intensity buckets of `history distribution`: label and lowest share of
+/// the best e1RM.
+const INTENSITIES: [(&str, f64); 5] = [
+    ("<60%", 0.0),
+    ("60-70%", 0.6),
+    ("70-80%", 0.7),
+    ("80-90%", 0.8),
+    ("90%+", 0.9),
+];
+
+/// `history distribution` output.
+#[derive(Debug, Serialize)]
+pub struct Distribution {
+    pub exercise_template_id: String,
+    /// Best estimated one-rep max over the whole history (Epley:
+    /// weight × (1 + reps / 30)), null when no set has both weight and
+    /// reps.
+    pub best_e1rm_kg: Option<f64>,
+    pub total_sets: usize,
+    /// Sets by reps, then an `unclassified` bucket for sets without weight
+    /// or reps.
+    pub rep_ranges: Vec<Bucket>,
+    /// Sets by weight as a share of `best_e1rm_kg`, then `unclassified`;
+    /// null without a best e1RM.
+    pub intensity: Option<Vec<Bucket>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Bucket {
+    pub bucket: &'static str,
+    pub sets: usize,
+    /// Weight × reps of the bucket's sets.
+    pub tonnage_kg: f64,
+}
+
+impl Bucket {
+    fn new(bucket: &'static str) -> Self {
+        Bucket {
+            bucket,
+            sets: 0,
+            tonnage_kg: 0.0,
+        }
+    }
+
+    fn add(&mut self, tonnage_kg: f64) {
+        self.sets += 1;
+        self.tonnage_kg += tonnage_kg;
+    }
+}
+
+/// Bucket the sets of `history` starting within `[since, until)` by rep
+/// range and by intensity. `history` should be the exercise's whole
+/// history, as the e1RM that intensity is measured against is all-time.
+pub fn distribution(
+    exercise_template_id: String,
+    history: &[ExerciseHistoryEntry],
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Distribution {
+    let best_e1rm_kg = history
+        .iter()
+        .filter_map(weight_reps)
+        .map(|(weight, reps)| e1rm(weight, reps))
+        .reduce(f64::max);
+
+    let mut rep_ranges: Vec<Bucket> = REP_RANGES.iter().map(|(b, _)| Bucket::new(b)).collect();
+    rep_ranges.push(Bucket::new("unclassified"));
+    let mut intensity: Vec<Bucket> = INTENSITIES.iter().map(|(b, _)| Bucket::new(b)).collect();
+    intensity.push(Bucket::new("unclassified"));
+    let mut total_sets = 0;
+    for entry in history {
+        let start = entry.workout_start_time;
+        if since.is_some_and(|s| start.is_none_or(|t| t < s))
+            || until.is_some_and(|u| start.is_none_or(|t| t >= u))
+        {
+            continue;
+        }
+        total_sets += 1;
+        let Some((weight, reps)) = weight_reps(entry) else {
+            rep_ranges.last_mut().unwrap().add(0.0);
+            intensity.last_mut().unwrap().add(0.0);
+            continue;
+        };
+        let tonnage = weight * reps as f64;
+        let range = REP_RANGES.iter().rposition(|(_, lo)| reps >= *lo).unwrap();
+        rep_ranges[range].add(tonnage);
+        if let Some(best) = best_e1rm_kg {
+            let share = weight / best;
+            let zone = INTENSITIES
+                .iter()
+                .rposition(|(_, lo)| share >= *lo)
+                .unwrap();
+            intensity[zone].add(tonnage);
+        }
+    }
+    for bucket in rep_ranges.iter_mut().chain(&mut intensity) {
+        bucket.tonnage_kg = round(bucket.tonnage_kg);
+    }
+
+    Distribution {
+        exercise_template_id,
+        best_e1rm_kg: best_e1rm_kg.map(round),
+        total_sets,
+        rep_ranges,
+        intensity: best_e1rm_kg.map(|_| intensity),
+    }
+}
+
+/// A set's weight and reps, when it has both.
+pub fn weight_reps(entry: &ExerciseHistoryEntry) -> Option<(f64, i64)> {
+    entry
+        .weight_kg
+        .filter(|w| *w > 0.0)
+        .zip(entry.reps.filter(|r| *r > 0))
+}
+
+/// Epley's estimate of the one-rep max from a set.
+pub fn e1rm(weight_kg: f64, reps: i64) -> f64 {
+    if reps == 1 {
+        weight_kg
+    } else {
+        weight_kg * (1.0 + reps as f64 / 30.0)
+    }
+}
+
+/// Round to one decimal place.
+fn round(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+/// Print `distribution` as bar charts of sets per rep range and per
+/// intensity zone.
+pub fn print_distribution(
+    out: &mut impl Write,
+    distribution: &Distribution,
+    style: &Style,
+) -> Result<()> {
+    writeln!(out)?;
+    writeln!(
+        out,
+        "  {} set(s) of {}",
+        distribution.total_sets, distribution.exercise_template_id
+    )?;
+    if let Some(best) = distribution.best_e1rm_kg {
+        let units = match style.units {
+            Units::Lbs => "lbs",
+            Units::Kg => "kg",
+        };
+        writeln!(out, "  Best e1RM {} {units}", style.units.weight(best))?;
+    }
+    writeln!(out)?;
+    print_buckets(out, "Reps", &distribution.rep_ranges, style)?;
+    if let Some(intensity) = &distribution.intensity {
+        print_buckets(out, "Intensity", intensity, style)?;
+    }
+    Ok(())
+}
+
+/// Width the bar charts' table takes before its bars: the indent, the
+/// fixed columns and their gaps, and the gap the table keeps before
+/// free-form text.
+const BAR_OFFSET: usize = 2 + 12 + 6 + 14 + 2 * 2 + 3;
+
+fn print_buckets(
+    out: &mut impl Write,
+    header: &'static str,
+    buckets: &[Bucket],
+    style: &Style,
+) -> Result<()> {
+    let mut table = Table::new(vec![
+        Column::left(header, 12),
+        Column::right("Sets", 6),
+        Column::right(
+            match style.units {
+                Units::Lbs => "Tonnage (lbs)",
+                Units::Kg => "Tonnage (kg)",
+            },
+            14,
+        ),
+        Column::free(""),
+    ])
+    .gap("  ");
+    let most = buckets.iter().map(|b| b.sets).max().unwrap_or(0);
+    let room = style.width.saturating_sub(BAR_OFFSET).clamp(1, 40);
+    for b in buckets {
+        let bar = match most {
+            0 => 0,
+            _ => (b.sets * room).div_ceil(most),
+        };
+        table.row(vec![
+            b.bucket.into(),
+            b.sets.to_string().into(),
+            style.units.weight(b.tonnage_kg).into(),
+            "█".repeat(bar).into(),
+        ]);
+    }
+    table.render(out, style)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Thresholds of `history analyze`.
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    /// Sessions without a new best e1RM that make a plateau.
+    pub plateau_sessions: usize,
+    /// Sessions in each rolling average.
+    pub window: usize,
+    /// How many sessions back the earlier rolling average ends.
+    pub lookback: usize,
+    /// Drop between the rolling averages, in percent, that makes a
+    /// regression.
+    pub regression_pct: f64,
+    /// Drop of a week's volume below its neighbours', in percent, that
+    /// makes a likely deload.
+    pub deload_drop_pct: f64,
+}
+
+/// `history analyze` output.
+#[derive(Debug, Serialize)]
+pub struct Analysis {
+    pub exercise_template_id: String,
+    /// One line summing up the verdicts.
+    pub summary: String,
+    pub plateau: Option<Plateau>,
+    pub regression: Option<Regression>,
+    pub deload_weeks: Vec<DeloadWeek>,
+    /// Oldest first.
+    pub sessions: Vec<SessionBest>,
+}
+
+/// The best set of one workout.
+#[derive(Debug, Serialize)]
+pub struct SessionBest {
+    pub workout_id: Option<String>,
+    pub date: NaiveDate,
+    pub best_e1rm_kg: f64,
+    /// Weight × reps of every set.
+    pub volume_kg: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Plateau {
+    pub flagged: bool,
+    pub sessions_since_pr: usize,
+    pub threshold_sessions: usize,
+    pub best_e1rm_kg: f64,
+    pub last_pr_date: NaiveDate,
+}
+
+/// The mean best e1RM of the last `window` sessions against the same mean
+/// `lookback` sessions earlier.
+#[derive(Debug, Serialize)]
+pub struct Regression {
+    pub flagged: bool,
+    pub current_avg_e1rm_kg: f64,
+    pub previous_avg_e1rm_kg: f64,
+    pub change_pct: f64,
+    pub window: usize,
+    pub lookback: usize,
+    pub threshold_pct: f64,
+}
+
+/// A training week whose volume fell well below the weeks around it.
+#[derive(Debug, Serialize)]
+pub struct DeloadWeek {
+    /// Monday of the ISO week.
+    pub week_start: NaiveDate,
+    pub volume_kg: f64,
+    /// Mean volume of the training weeks just before and after.
+    pub surrounding_avg_kg: f64,
+    pub drop_pct: f64,
+}
+
+/// Group `history` into sessions (by workout) and look for a plateau, a
+/// regression and deload weeks in their best e1RM and volume. Sessions
+/// without a set having both weight and reps are left out.
+pub fn analyze(
+    exercise_template_id: String,
+    history: &[ExerciseHistoryEntry],
+    thresholds: Thresholds,
+) -> Analysis {
+    let sessions = sessions(history);
+    let plateau = plateau(&sessions, thresholds.plateau_sessions);
+    let regression = regression(&sessions, thresholds);
+    let deload_weeks = deload_weeks(&sessions, thresholds.deload_drop_pct);
+
+    let mut verdicts = Vec::new();
+    match &plateau {
+        Some(p) if p.flagged => verdicts.push(format!(
+            "No e1RM PR in {} sessions; last PR {} at {} kg",
+            p.sessions_since_pr, p.last_pr_date, p.best_e1rm_kg
+        )),
+        Some(p) => verdicts.push(format!(
+            "Last e1RM PR {} at {} kg, {} session(s) ago",
+            p.last_pr_date, p.best_e1rm_kg, p.sessions_since_pr
+        )),
+        None => verdicts.push("No sets with both weight and reps".to_string()),
+    }
+    if let Some(r) = regression.as_ref().filter(|r| r.flagged) {
+        verdicts.push(format!(
+            "e1RM down {:.1}% over the last {} sessions",
+            -r.change_pct, r.lookback
+        ));
+    }
+    if !deload_weeks.is_empty() {
+        verdicts.push(format!("{} likely deload week(s)", deload_weeks.len()));
+    }
+
+    Analysis {
+        exercise_template_id,
+        summary: verdicts.join("; "),
+        plateau,
+        regression,
+        deload_weeks,
+        sessions,
+    }
+}
+
+/// One [`SessionBest`] per workout, oldest first. Sets without a workout
+/// ID are grouped by their start time.
+fn sessions(history: &[ExerciseHistoryEntry]) -> Vec<SessionBest> {
+    // (start, workout ID) -> (best e1RM, volume), so sessions sort by date.
+    let mut by_workout = BTreeMap::new();
+    for entry in history {
+        let (Some(start), Some((weight, reps))) = (entry.workout_start_time, weight_reps(entry))
+        else {
+            continue;
+        };
+        let (best, volume) = by_workout
+            .entry((start, entry.workout_id.as_deref()))
+            .or_insert((0.0_f64, 0.0));
+        *best = best.max(e1rm(weight, reps));
+        *volume += weight * reps as f64;
+    }
+    by_workout
+        .into_iter()
+        .map(|((start, workout_id), (best, volume))| SessionBest {
+            workout_id: workout_id.map(str::to_string),
+            date: start.date_naive(),
+            best_e1rm_kg: round(best),
+            volume_kg: round(volume),
+        })
+        .collect()
+}
+
+fn plateau(sessions: &[SessionBest], threshold_sessions: usize) -> Option<Plateau> {
+    // The last session that beat every one before it.
+    let mut pr: Option<(usize, &SessionBest)> = None;
+    for (i, session) in sessions.iter().enumerate() {
+        if pr.is_none_or(|(_, best)| session.best_e1rm_kg > best.best_e1rm_kg) {
+            pr = Some((i, session));
+        }
+    }
+    let (i, best) = pr?;
+    let sessions_since_pr = sessions.len() - 1 - i;
+    Some(Plateau {
+        flagged: sessions_since_pr >= threshold_sessions,
+        sessions_since_pr,
+        threshold_sessions,
+        best_e1rm_kg: best.best_e1rm_kg,
+        last_pr_date: best.date,
+    })
+}
+
+/// None until there are `window + lookback` sessions.
+fn regression(sessions: &[SessionBest], thresholds: Thresholds) -> Option<Regression> {
+    let Thresholds {
+        window, lookback, ..
+    } = thresholds;
+    let end = sessions.len();
+    if window == 0 || end < window + lookback {
+        return None;
+    }
+    let mean = |slice: &[SessionBest]| {
+        slice.iter().map(|s| s.best_e1rm_kg).sum::<f64>() / slice.len() as f64
+    };
+    let current = mean(&sessions[end - window..]);
+    let previous = mean(&sessions[end - window - lookback..end - lookback]);
+    let change_pct = (current - previous) / previous * 100.0;
+    Some(Regression {
+        flagged: change_pct < -thresholds.regression_pct,
+        current_avg_e1rm_kg: round(current),
+        previous_avg_e1rm_kg: round(previous),
+        change_pct: round(change_pct),
+        window,
+        lookback,
+        threshold_pct: thresholds.regression_pct,
+    })
+}
+
+/// Training weeks (ISO weeks with a session) whose volume is more than
+/// `drop_pct` below the mean of the training weeks before and after.
+fn deload_weeks(sessions: &[SessionBest], drop_pct: f64) -> Vec<DeloadWeek> {
+    let mut weeks: BTreeMap<NaiveDate, f64> = BTreeMap::new();
+    for session in sessions {
+        let week = session.date.iso_week();
+        let monday = NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Mon)
+            .unwrap_or(session.date);
+        *weeks.entry(monday).or_default() += session.volume_kg;
+    }
+    let weeks: Vec<(NaiveDate, f64)> = weeks.into_iter().collect();
+    weeks
+        .windows(3)
+        .filter_map(|w| {
+            let [(_, before), (week_start, volume), (_, after)] = *w else {
+                return None;
+            };
+            let surrounding = (before + after) / 2.0;
+            let drop = (surrounding - volume) / surrounding * 100.0;
+            (surrounding > 0.0 && drop > drop_pct).then(|| DeloadWeek {
+                week_start,
+                volume_kg: round(volume),
+                surrounding_avg_kg: round(surrounding),
+                drop_pct: round(drop),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn targets_of(routine: serde_json::Value) -> HashMap<(String, usize), (i64, i64)> {
+        set_targets_from_routine(&serde_json::from_value(routine).unwrap())
+    }
+
+    fn key(template_id: &str, set: usize) -> (String, usize) {
+        (template_id.to_string(), set)
+    }
+
+    #[test]
+    fn rep_ranges_give_their_bounds() {
+        let targets = targets_of(json!({
+            "exercises": [{
+                "exercise_template_id": "D04AC939",
+                "sets": [
+                    { "rep_range": { "start": 8, "end": 12 } },
+                    { "rep_range": { "start": 5, "end": null } },
+                    { "rep_range": { "start": null, "end": 15 } },
+                    { "reps": 3, "rep_range": { "start": 6, "end": 8 } }
+                ]
+            }]
+        }));
+        assert_eq!(targets[&key("D04AC939", 0)], (8, 12));
+        // Open-ended: the start is both bounds.
+        assert_eq!(targets[&key("D04AC939", 1)], (5, 5));
+        assert_eq!(targets[&key("D04AC939", 2)], (8, 15));
+        // A range wins over fixed reps.
+        assert_eq!(targets[&key("D04AC939", 3)], (6, 8));
+    }
+
+    #[test]
+    fn fixed_reps_allow_one_either_side() {
+        let targets = targets_of(json!({
+            "exercises": [{
+                "exercise_template_id": "79D0BB3A",
+                "sets": [{ "reps": 5 }, { "reps": 1, "rep_range": null }]
+            }]
+        }));
+        assert_eq!(targets[&key("79D0BB3A", 0)], (4, 6));
+        assert_eq!(targets[&key("79D0BB3A", 1)], (0, 2));
+    }
+
+    #[test]
+    fn missing_reps_count_as_ten() {
+        let targets = targets_of(json!({
+            "exercises": [{
+                "exercise_template_id": "B8127AD1",
+                "sets": [{ "type": "normal", "weight_kg": 40 }, { "reps": null }]
+            }]
+        }));
+        assert_eq!(targets[&key("B8127AD1", 0)], (9, 11));
+        assert_eq!(targets[&key("B8127AD1", 1)], (9, 11));
+    }
+
+    #[test]
+    fn sets_are_keyed_by_exercise_and_position() {
+        let targets = targets_of(json!({
+            "exercises": [
+                { "exercise_template_id": "D04AC939", "sets": [{ "reps": 5 }, { "reps": 3 }] },
+                { "title": "No template", "sets": [{ "reps": 8 }] },
+                { "exercise_template_id": "79D0BB3A", "sets": [{ "reps": 10 }] }
+            ]
+        }));
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets[&key("D04AC939", 1)], (2, 4));
+        assert_eq!(targets[&key("79D0BB3A", 0)], (9, 11));
+        assert!(!targets.contains_key(&key("79D0BB3A", 1)));
+        assert!(targets_of(json!({ "exercises": null })).is_empty());
+    }
+
+    #[test]
+    fn classify_at_the_bounds() {
+        use SetResult::*;
+        for lo in 0..=12 {
+            for hi in lo..=15 {
+                assert_eq!(
+                    classify_set_result(lo - 1, lo, hi),
+                    Struggled,
+                    "{lo}..={hi}"
+                );
+                assert_eq!(classify_set_result(lo, lo, hi), Succeeded, "{lo}..={hi}");
+                assert_eq!(classify_set_result(hi, lo, hi), Succeeded, "{lo}..={hi}");
+                assert_eq!(classify_set_result(hi + 1, lo, hi), Exceeded, "{lo}..={hi}");
+                for reps in lo..=hi {
+                    assert_eq!(classify_set_result(reps, lo, hi), Succeeded);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn classify_without_an_upper_bound() {
+        for reps in [5, 6, 20, 1000, i64::MAX] {
+            assert_eq!(classify_set_result(reps, 5, i64::MAX), SetResult::Succeeded);
+        }
+        assert_eq!(classify_set_result(4, 5, i64::MAX), SetResult::Struggled);
+        // An open-ended routine range targets its start, so more is better.
+        let targets = targets_of(json!({
+            "exercises": [{
+                "exercise_template_id": "D04AC939",
+                "sets": [{ "rep_range": { "start": 5, "end": null } }]
+            }]
+        }));
+        let (lo, hi) = targets[&key("D04AC939", 0)];
+        assert_eq!(classify_set_result(5, lo, hi), SetResult::Succeeded);
+        assert_eq!(classify_set_result(8, lo, hi), SetResult::Exceeded);
+    }
+
+    #[test]
+    fn results_print_their_names_and_colors() {
+        assert_eq!(SetResult::Struggled.to_string(), "Struggled");
+        assert_eq!(SetResult::Succeeded.to_string(), "Succeeded");
+        assert_eq!(SetResult::Exceeded.to_string(), "Exceeded");
+        assert_eq!(SetResult::Struggled.color(), Color::Yellow);
+        assert_eq!(SetResult::Succeeded.color(), Color::Green);
+        assert_eq!(SetResult::Exceeded.color(), Color::Cyan);
+    }
+
+    fn exercises(supersets: &[Option<i64>]) -> Vec<Exercise> {
+        supersets
+            .iter()
+            .enumerate()
+            .map(|(i, superset)| {
+                serde_json::from_value(json!({ "index": i, "supersets_id": superset })).unwrap()
+            })
+            .collect()
+    }
+
+    /// Each group's superset ID and the indexes of its exercises.
+    fn grouped(supersets: &[Option<i64>]) -> Vec<(Option<i64>, Vec<u32>)> {
+        let exercises = exercises(supersets);
+        group_by_superset(&exercises)
+            .into_iter()
+            .map(|g| {
+                let indexes = g.exercises.iter().map(|e| e.index.unwrap()).collect();
+                (g.superset_id, indexes)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn two_exercise_supersets_are_grouped() {
+        assert_eq!(
+            grouped(&[None, Some(0), Some(0), None, Some(1), Some(1)]),
+            [
+                (None, vec![0]),
+                (Some(0), vec![1, 2]),
+                (None, vec![3]),
+                (Some(1), vec![4, 5]),
+            ]
+        );
+    }
+
+    #[test]
+    fn exercises_outside_supersets_stay_apart() {
+        assert_eq!(
+            grouped(&[None, None, None]),
+            [(None, vec![0]), (None, vec![1]), (None, vec![2])]
+        );
+        assert!(grouped(&[]).is_empty());
+    }
+
+    #[test]
+    fn only_neighbours_share_a_group() {
+        assert_eq!(
+            grouped(&[Some(3), Some(3), Some(3), Some(4), Some(4), Some(3)]),
+            [
+                (Some(3), vec![0, 1, 2]),
+                (Some(4), vec![3, 4]),
+                (Some(3), vec![5]),
+            ]
+        );
+        // A lone superset ID still forms its own group.
+        assert_eq!(
+            grouped(&[Some(7), None]),
+            [(Some(7), vec![0]), (None, vec![1])]
+        );
+    }
+
+    const THRESHOLDS: Thresholds = Thresholds {
+        plateau_sessions: 4,
+        window: 3,
+        lookback: 3,
+        regression_pct: 5.0,
+        deload_drop_pct: 30.0,
+    };
+
+    /// One workout per `(day, weight_kg, reps)`, `day` days after Monday
+    /// 2024-01-01, each a single set.
+    fn workouts(sets: &[(i64, f64, i64)]) -> Vec<ExerciseHistoryEntry> {
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        sets.iter()
+            .map(|&(day, weight_kg, reps)| {
+                let start = (monday + chrono::Duration::days(day))
+                    .and_hms_opt(18, 0, 0)
+                    .unwrap()
+                    .and_utc();
+                serde_json::from_value(json!({
+                    "workout_id": format!("w{day}"),
+                    "workout_start_time": start.to_rfc3339(),
+                    "exercise_template_id": "D04AC939",
+                    "weight_kg": weight_kg,
+                    "reps": reps,
+                }))
+                .unwrap()
+            })
+            .collect()
+    }
+
+    /// Single-rep sets every other day, so each session's e1RM is its weight.
+    fn singles(weights: &[f64]) -> Vec<ExerciseHistoryEntry> {
+        let sets: Vec<_> = weights
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| (2 * i as i64, w, 1))
+            .collect();
+        workouts(&sets)
+    }
+
+    fn analyzed(history: &[ExerciseHistoryEntry], thresholds: Thresholds) -> Analysis {
+        analyze("D04AC939".to_string(), history, thresholds)
+    }
+
+    #[test]
+    fn no_pr_for_the_threshold_is_a_plateau() {
+        let history = singles(&[100.0, 105.0, 110.0, 108.0, 109.0, 110.0, 107.0]);
+        let plateau = analyzed(&history, THRESHOLDS).plateau.unwrap();
+        // Matching the best is not a PR.
+        assert_eq!(plateau.sessions_since_pr, 4);
+        assert!(plateau.flagged);
+        assert_eq!(plateau.best_e1rm_kg, 110.0);
+        assert_eq!(
+            plateau.last_pr_date,
+            NaiveDate::from_ymd_opt(2024, 1, 5).unwrap()
+        );
+
+        let stricter = Thresholds {
+            plateau_sessions: 5,
+            ..THRESHOLDS
+        };
+        assert!(!analyzed(&history, stricter).plateau.unwrap().flagged);
+    }
+
+    #[test]
+    fn a_recent_pr_is_no_plateau() {
+        let analysis = analyzed(&singles(&[100.0, 102.5, 105.0]), THRESHOLDS);
+        let plateau = analysis.plateau.unwrap();
+        assert_eq!((plateau.sessions_since_pr, plateau.flagged), (0, false));
+        assert_eq!(
+            analysis.summary,
+            "Last e1RM PR 2024-01-05 at 105 kg, 0 session(s) ago"
+        );
+    }
+
+    #[test]
+    fn a_falling_average_is_a_regression() {
+        let history = singles(&[100.0, 100.0, 100.0, 90.0, 90.0, 90.0]);
+        let analysis = analyzed(&history, THRESHOLDS);
+        let regression = analysis.regression.unwrap();
+        assert_eq!(regression.previous_avg_e1rm_kg, 100.0);
+        assert_eq!(regression.current_avg_e1rm_kg, 90.0);
+        assert_eq!(regression.change_pct, -10.0);
+        assert!(regression.flagged);
+        assert!(
+            analysis
+                .summary
+                .contains("e1RM down 10.0% over the last 3 sessions"),
+            "{}",
+            analysis.summary
+        );
+
+        // A drop of exactly the threshold is not flagged.
+        let at_threshold = Thresholds {
+            regression_pct: 10.0,
+            ..THRESHOLDS
+        };
+        assert!(!analyzed(&history, at_threshold).regression.unwrap().flagged);
+        // Nor is a rise.
+        let rising = singles(&[90.0, 90.0, 90.0, 100.0, 100.0, 100.0]);
+        assert!(!analyzed(&rising, THRESHOLDS).regression.unwrap().flagged);
+    }
+
+    #[test]
+    fn regression_needs_two_full_windows() {
+        let history = singles(&[100.0, 100.0, 90.0, 90.0, 90.0]);
+        assert!(analyzed(&history, THRESHOLDS).regression.is_none());
+        let no_window = Thresholds {
+            window: 0,
+            ..THRESHOLDS
+        };
+        assert!(
+            analyzed(&singles(&[100.0; 8]), no_window)
+                .regression
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn a_light_week_between_normal_ones_is_a_deload() {
+        // One session a week, on Wednesdays: 1000, 1000, 400, 1000 kg.
+        let history = workouts(&[
+            (2, 100.0, 10),
+            (9, 100.0, 10),
+            (16, 40.0, 10),
+            (23, 100.0, 10),
+        ]);
+        let analysis = analyzed(&history, THRESHOLDS);
+        let [deload] = &analysis.deload_weeks[..] else {
+            panic!("expected one deload week: {:?}", analysis.deload_weeks);
+        };
+        assert_eq!(
+            deload.week_start,
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+        );
+        assert_eq!(deload.volume_kg, 400.0);
+        assert_eq!(deload.surrounding_avg_kg, 1000.0);
+        assert_eq!(deload.drop_pct, 60.0);
+        assert!(analysis.summary.ends_with("1 likely deload week(s)"));
+    }
+
+    #[test]
+    fn weeks_are_summed_before_comparing() {
+        // Week 2 has two sessions of 500 kg, as much as its neighbours.
+        let history = workouts(&[
+            (2, 100.0, 10),
+            (8, 50.0, 10),
+            (10, 50.0, 10),
+            (16, 100.0, 10),
+        ]);
+        assert!(analyzed(&history, THRESHOLDS).deload_weeks.is_empty());
+        // The first and last weeks have no neighbour on one side.
+        let history = workouts(&[(2, 10.0, 10), (9, 100.0, 10), (16, 10.0, 10)]);
+        assert!(analyzed(&history, THRESHOLDS).deload_weeks.is_empty());
+    }
+
+    #[test]
+    fn sets_without_weight_or_reps_are_ignored() {
+        let mut history = workouts(&[(0, 0.0, 10), (2, 100.0, 0)]);
+        history[0].weight_kg = None;
+        let analysis = analyzed(&history, THRESHOLDS);
+        assert!(analysis.sessions.is_empty() && analysis.plateau.is_none());
+        assert!(analysis.regression.is_none() && analysis.deload_weeks.is_empty());
+        assert_eq!(analysis.summary, "No sets with both weight and reps");
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::HashSet;
+use std::future::Future;
+
+use futures::{Stream, StreamExt, TryFutureExt, TryStreamExt, future, stream};
+
+use crate::client::{MAX_EXERCISE_TEMPLATE_PAGE_SIZE, MAX_PAGE_SIZE};
+use crate::error::{Error, Result};
+use crate::models::*;
+
+/// The Hevy API operations, implemented over HTTP by
+/// [`HevyClient`](crate::client::HevyClient).
+///
+/// Commands take `&impl HevyApi` rather than the concrete client so they
+/// can run against a stand-in that serves canned responses. Only the
+/// endpoints must be implemented; the paginated helpers are built on them.
+pub trait HevyApi: Sync {
+    /// How many pages the paginated helpers fetch at once after the first.
+    /// One by default.
+    fn page_concurrency(&self) -> usize {
+        1
+    }
+
+    /// Page size the paginated helpers request from an endpoint serving at
+    /// most `max` items per page. `max` by default.
+    fn page_size(&self, max: u32) -> u32 {
+        max
+    }
+
+    // ── Workouts ───────────────────────────────────────
+
+    /// GET /v1/workouts — paginated list of workouts.
+    fn list_workouts(
+        &self,
+        page: u32,
+        page_size: u32,
+    ) -> impl Future<Output = Result<WorkoutsPage>> + Send;
+
+    /// GET /v1/workouts/{id} — single workout by ID.
+    fn get_workout(&self, workout_id: &str) -> impl Future<Output = Result<Workout>> + Send;
+
+    /// POST /v1/workouts — create a new workout.
+    fn create_workout(
+        &self,
+        body: &PostWorkoutBody,
+    ) -> impl Future<Output = Result<Workout>> + Send;
+
+    /// PUT /v1/workouts/{id} — update an existing workout.
+    fn update_workout(
+        &self,
+        workout_id: &str,
+        body: &PostWorkoutBody,
+    ) -> impl Future<Output = Result<Workout>> + Send;
+
+    /// GET /v1/workouts/count — total workout count.
+    fn workout_count(&self) -> impl Future<Output = Result<WorkoutCountResponse>> + Send;
+
+    /// GET /v1/workouts/events — paginated workout events (updates/deletes).
+    fn workout_events(
+        &self,
+        page: u32,
+        page_size: u32,
+        since: Option<Timestamp>,
+    ) -> impl Future<Output = Result<PaginatedWorkoutEvents>> + Send;
+
+    /// Stream every page of GET /v1/workouts, newest workouts first.
+    fn workout_pages(&self) -> impl Stream<Item = Result<Page<Workout>>> + Send + '_ {
+        paginate(self.page_concurrency(), move |page| async move {
+            let data = self
+                .list_workouts(page, self.page_size(MAX_PAGE_SIZE))
+                .await?;
+            Ok((data.workouts, data.page_count))
+        })
+    }
+
+    /// Stream every workout, newest first, fetching pages as the stream is
+    /// polled. Stops after the last page; a failed page ends the stream
+    /// with its error.
+    fn workouts_stream(&self) -> impl Stream<Item = Result<Workout>> + Send + '_ {
+        flatten_pages(self.workout_pages())
+    }
+
+    /// Stream every page of GET /v1/workouts/events since `since`.
+    fn workout_event_pages<'a>(
+        &'a self,
+        since: Option<Timestamp>,
+    ) -> impl Stream<Item = Result<Page<WorkoutEvent>>> + Send + 'a {
+        paginate(self.page_concurrency(), move |page| async move {
+            let data = self
+                .workout_events(page, self.page_size(MAX_PAGE_SIZE), since)
+                .await?;
+            Ok((data.events, data.page_count))
+        })
+    }
+
+    /// Fetch every workout event since `since` by walking all pages of
+    /// GET /v1/workouts/events (newest first, as returned by the API).
+    fn all_workout_events<'a>(
+        &'a self,
+        since: Option<Timestamp>,
+    ) -> impl Future<Output = Result<Vec<WorkoutEvent>>> + Send + 'a {
+        self.workout_event_pages(since)
+            .map_ok(|page| page.items)
+            .try_concat()
+    }
+
+    // ── Routines ──────────────────────────────────────
+
+    /// GET /v1/routines — paginated list of routines.
+    fn list_routines(
+        &self,
+        page: u32,
+        page_size: u32,
+    ) -> impl Future<Output = Result<RoutinesPage>> + Send;
+
+    /// GET /v1/routines/{id} — single routine by ID.
+    fn get_routine(
+        &self,
+        routine_id: &str,
+    ) -> impl Future<Output = Result<SingleRoutineResponse>> + Send;
+
+    /// POST /v1/routines — create a new routine.
+    fn create_routine(
+        &self,
+        body: &PostRoutineBody,
+    ) -> impl Future<Output = Result<Routine>> + Send;
+
+    /// PUT /v1/routines/{id} — update an existing routine.
+    fn update_routine(
+        &self,
+        routine_id: &str,
+        body: &PutRoutineBody,
+    ) -> impl Future<Output = Result<Routine>> + Send;
+
+    /// Stream every page of GET /v1/routines.
+    fn routine_pages(&self) -> impl Stream<Item = Result<Page<Routine>>> + Send + '_ {
+        paginate(self.page_concurrency(), move |page| async move {
+            let data = self
+                .list_routines(page, self.page_size(MAX_PAGE_SIZE))
+                .await?;
+            Ok((data.routines, data.page_count))
+        })
+    }
+
+    /// Stream every routine, fetching pages as the stream is polled.
+    fn routines_stream(&self) -> impl Stream<Item = Result<Routine>> + Send + '_ {
+        flatten_pages(self.routine_pages())
+    }
+
+    /// Fetch every routine by walking all pages of GET /v1/routines.
+    fn all_routines(&self) -> impl Future<Output = Result<Vec<Routine>>> + Send {
+        self.routine_pages()
+            .map_ok(|page| page.items)
+            .try_concat()
+            .map_ok(dedup_by_id)
+    }
+
+    // ── Exercise Templates ────────────────────────────
+
+    /// GET /v1/exercise_templates — paginated list.
+    fn list_exercise_templates(
+        &self,
+        page: u32,
+        page_size: u32,
+    ) -> impl Future<Output = Result<ExerciseTemplatesPage>> + Send;
+
+    /// GET /v1/exercise_templates/{id} — single template by ID.
+    fn get_exercise_template(
+        &self,
+        template_id: &str,
+    ) -> impl Future<Output = Result<ExerciseTemplate>> + Send;
+
+    /// POST /v1/exercise_templates — create a custom exercise template.
+    fn create_exercise_template(
+        &self,
+        body: &CreateExerciseBody,
+    ) -> impl Future<Output = Result<CreateExerciseResponse>> + Send;
+
+    /// Stream every page of GET /v1/exercise_templates, bypassing any cache.
+    fn exercise_template_pages(
+        &self,
+    ) -> impl Stream<Item = Result<Page<ExerciseTemplate>>> + Send + '_ {
+        paginate(self.page_concurrency(), move |page| async move {
+            let data = self
+                .list_exercise_templates(page, self.page_size(MAX_EXERCISE_TEMPLATE_PAGE_SIZE))
+                .await?;
+            Ok((data.exercise_templates, data.page_count))
+        })
+    }
+
+    /// Stream every exercise template, fetching pages as the stream is
+    /// polled and bypassing any cache.
+    fn exercise_templates_stream(
+        &self,
+    ) -> impl Stream<Item = Result<ExerciseTemplate>> + Send + '_ {
+        flatten_pages(self.exercise_template_pages())
+    }
+
+    /// Locally cached exercise templates that may be used instead of
+    /// fetching. None by default.
+    fn cached_exercise_templates(&self) -> Option<Vec<ExerciseTemplate>> {
+        None
+    }
+
+    /// Fetch every exercise template by walking all pages of
+    /// GET /v1/exercise_templates.
+    fn all_exercise_templates(&self) -> impl Future<Output = Result<Vec<ExerciseTemplate>>> + Send {
+        self.exercise_template_pages()
+            .map_ok(|page| page.items)
+            .try_concat()
+            .map_ok(dedup_by_id)
+    }
+
+    // ── Routine Folders ───────────────────────────────
+
+    /// GET /v1/routine_folders — paginated list.
+    fn list_routine_folders(
+        &self,
+        page: u32,
+        page_size: u32,
+    ) -> impl Future<Output = Result<RoutineFoldersPage>> + Send;
+
+    /// GET /v1/routine_folders/{id} — single folder by ID.
+    fn get_routine_folder(
+        &self,
+        folder_id: &str,
+    ) -> impl Future<Output = Result<RoutineFolder>> + Send;
+
+    /// POST /v1/routine_folders — create a new routine folder.
+    fn create_routine_folder(
+        &self,
+        body: &PostRoutineFolderBody,
+    ) -> impl Future<Output = Result<RoutineFolder>> + Send;
+
+    /// Stream every page of GET /v1/routine_folders.
+    fn routine_folder_pages(&self) -> impl Stream<Item = Result<Page<RoutineFolder>>> + Send + '_ {
+        paginate(self.page_concurrency(), move |page| async move {
+            let data = self
+                .list_routine_folders(page, self.page_size(MAX_PAGE_SIZE))
+                .await?;
+            Ok((data.routine_folders, data.page_count))
+        })
+    }
+
+    /// Fetch every routine folder by walking all pages of
+    /// GET /v1/routine_folders.
+    fn all_routine_folders(&self) -> impl Future<Output = Result<Vec<RoutineFolder>>> + Send {
+        self.routine_folder_pages()
+            .map_ok(|page| page.items)
+            .try_concat()
+    }
+
+    // ── Exercise History ──────────────────────────────
+
+    /// GET /v1/exercise_history/{exerciseTemplateId} — history for a specific exercise.
+    fn exercise_history(
+        &self,
+        template_id: &str,
+        start_date: Option<&str>,
+        end_date: Option<&str>,
+    ) -> impl Future<Output = Result<ExerciseHistoryResponse>> + Send;
+
+    // ── User ──────────────────────────────────────────
+
+    /// GET /v1/user/info — authenticated user info.
+    fn user_info(&self) -> impl Future<Output = Result<UserInfoResponse>> + Send;
+}
+
+/// One page of a paginated list, with the page count the API reported.
+#[non_exhaustive]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page_count: i64,
+}
+
+/// Drop items whose ID already appeared earlier in `items`, keeping the
+/// first. Items added or removed while pages are being fetched shift the
+/// page boundaries, so one item can be returned on two pages. Items without
+/// an ID are kept. Logs a warning when anything is removed.
+pub fn dedup_by_id<T: HasId>(items: Vec<T>) -> Vec<T> {
+    let total = items.len();
+    let mut seen = HashSet::new();
+    let items: Vec<T> = items
+        .into_iter()
+        .filter(|item| item.id().is_none_or(|id| seen.insert(id.to_string())))
+        .collect();
+    let removed = total - items.len();
+    if removed > 0 {
+        tracing::warn!("removed {removed} duplicate(s) returned on more than one page");
+    }
+    items
+}
+
+/// [`dedup_by_id`] for a stream: drop each item whose ID was already
+/// yielded as it arrives, so a `take(n)` after it counts distinct items.
+/// Logs a warning for each one dropped.
+pub fn dedup_stream_by_id<T: HasId + Send>(
+    items: impl Stream<Item = Result<T>> + Send,
+) -> impl Stream<Item = Result<T>> + Send {
+    let mut seen = HashSet::new();
+    items.try_filter(move |item| {
+        let first = item.id().is_none_or(|id| seen.insert(id.to_string()));
+        if !first {
+            tracing::warn!("removed a duplicate returned on more than one page");
+        }
+        future::ready(first)
+    })
+}
+
+/// Yield the items of each page in turn.
+fn flatten_pages<T: Send>(
+    pages: impl Stream<Item = Result<Page<T>>> + Send,
+) -> impl Stream<Item = Result<T>> + Send {
+    pages
+        .map_ok(|page| stream::iter(page.items.into_iter().map(Ok)))
+        .try_flatten()
+}
+
+/// Walk a paginated endpoint lazily, yielding one page at a time in order.
+///
+/// `fetch` loads a 1-based page and returns its items together with the
+/// reported `page_count`. The first page is fetched alone to learn the page
+/// count; the rest are fetched up to `concurrency` at a time. The stream
+/// ends after the first failed page, even if later ones were already
+/// fetched.
+fn paginate<T, F, Fut>(concurrency: usize, fetch: F) -> impl Stream<Item = Result<Page<T>>> + Send
+where
+    T: Send,
+    F: Fn(u32) -> Fut + Send,
+    Fut: Future<Output = Result<(Vec<T>, i64)>> + Send,
+{
+    stream::once(async move {
+        let (items, page_count) = fetch(1).await?;
+        let last = u32::try_from(page_count).unwrap_or(u32::MAX);
+        let rest = stream::iter(2..=last)
+            .map(fetch)
+            .buffered(concurrency.max(1))
+            .map_ok(|(items, page_count)| Page { items, page_count });
+        let first = Page { items, page_count };
+        Ok::<_, Error>(stream::once(future::ready(Ok(first))).chain(rest))
+    })
+    .try_flatten()
+    .scan(false, |failed, page| {
+        if *failed {
+            return future::ready(None);
+        }
+        *failed = page.is_err();
+        future::ready(Some(page))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    /// Page `n` of `page_count` holds the items `n * 10` and `n * 10 + 1`.
+    /// Later pages answer faster.
+    async fn numbers(page: u32, page_count: i64) -> Result<(Vec<u32>, i64)> {
+        tokio::time::sleep(Duration::from_millis(10 * u64::from(5 - page.min(5)))).await;
+        Ok((vec![page * 10, page * 10 + 1], page_count))
+    }
+
+    async fn pages<T: Send>(
+        pages: impl Stream<Item = Result<Page<T>>> + Send,
+    ) -> Vec<Result<Page<T>>> {
+        pages.collect().await
+    }
+
+    #[tokio::test]
+    async fn pages_arrive_in_order_with_the_page_count() {
+        let pages = pages(paginate(3, |page| numbers(page, 4))).await;
+        let pages: Vec<Page<u32>> = pages.into_iter().map(|p| p.unwrap()).collect();
+        let items: Vec<&[u32]> = pages.iter().map(|p| p.items.as_slice()).collect();
+        assert_eq!(items, [[10, 11], [20, 21], [30, 31], [40, 41]]);
+        assert!(pages.iter().all(|p| p.page_count == 4));
+    }
+
+    #[tokio::test]
+    async fn no_pages_after_the_first_when_the_count_is_zero_or_one() {
+        for page_count in [0, 1] {
+            let pages = pages(paginate(3, |page| numbers(page, page_count))).await;
+            assert_eq!(pages.len(), 1);
+            assert_eq!(pages[0].as_ref().unwrap().items, [10, 11]);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_failed_page_ends_the_stream_with_its_error() {
+        let fetch = |page| async move {
+            if page == 3 {
+                return Err(Error::Invalid("page 3 failed".to_string()));
+            }
+            numbers(page, 5).await
+        };
+        let items: Vec<Result<u32>> = flatten_pages(paginate(2, fetch)).collect().await;
+        let (ok, err): (Vec<_>, Vec<_>) = items.into_iter().partition(|i| i.is_ok());
+        let ok: Vec<u32> = ok.into_iter().map(|i| i.unwrap()).collect();
+        assert_eq!(ok, [10, 11, 20, 21]);
+        assert_eq!(err.len(), 1);
+        assert_eq!(err[0].as_ref().unwrap_err().to_string(), "page 3 failed");
+    }
+
+    #[test]
+    fn dedup_by_id_keeps_the_first_copy_and_items_without_an_id() {
+        let workout = |id: Option<&str>, title: &str| -> Workout {
+            serde_json::from_value(serde_json::json!({ "id": id, "title": title })).unwrap()
+        };
+        let workouts = dedup_by_id(vec![
+            workout(Some("w1"), "first"),
+            workout(None, "no id"),
+            workout(Some("w2"), "other"),
+            workout(Some("w1"), "second"),
+            workout(None, "no id"),
+        ]);
+        let titles: Vec<_> = workouts
+            .iter()
+            .map(|w| w.title.as_deref().unwrap())
+            .collect();
+        assert_eq!(titles, ["first", "no id", "other", "no id"]);
+    }
+
+    #[tokio::test]
+    async fn dedup_stream_by_id_drops_repeats_before_take() {
+        let workout = |id: &str| -> Result<Workout> {
+            Ok(serde_json::from_value(serde_json::json!({ "id": id })).unwrap())
+        };
+        // w2 shifted onto the next page, so it arrives twice.
+        let items = stream::iter([workout("w1"), workout("w2"), workout("w2"), workout("w3")]);
+        let workouts: Vec<Workout> = dedup_stream_by_id(items)
+            .take(3)
+            .try_collect()
+            .await
+            .unwrap();
+        let ids: Vec<_> = workouts.iter().map(|w| w.id.as_deref().unwrap()).collect();
+        assert_eq!(ids, ["w1", "w2", "w3"]);
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use futures::TryStreamExt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::api::{self, HevyApi};
+use crate::models::*;
+
+/// Layout version of the backups this build writes and can restore.
+const BACKUP_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const WORKOUTS_DIR: &str = "workouts";
+const ROUTINES_DIR: &str = "routines";
+const EXERCISE_TEMPLATES_DIR: &str = "exercise_templates";
+const ROUTINE_FOLDERS_DIR: &str = "routine_folders";
+
+/// `manifest.json` at the top of a backup directory.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Manifest {
+    pub version: u32,
+    pub account_id: Option<String>,
+    pub account_name: Option<String>,
+    /// When the backup was taken, RFC 3339 in UTC.
+    pub created_at: String,
+    pub counts: Counts,
+}
+
+/// How many items of each kind a backup holds.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Counts {
+    pub workouts: usize,
+    pub routines: usize,
+    pub exercise_templates: usize,
+    pub routine_folders: usize,
+}
+
+/// Fetch everything in the account and write it under `dir`, one JSON
+/// file per item, plus a manifest. `dir` must not exist or be empty.
+pub async fn backup(client: &impl HevyApi, dir: &Path) -> Result<Manifest> {
+    if dir
+        .read_dir()
+        .is_ok_and(|mut entries| entries.next().is_some())
+    {
+        anyhow::bail!(
+            "{} is not empty; back up into a new directory",
+            dir.display()
+        );
+    }
+
+    eprintln!("Fetching account data...");
+    let (user, workouts, routines, templates, folders) = tokio::try_join!(
+        client.user_info(),
+        client.workouts_stream().try_collect::<Vec<_>>(),
+        client.all_routines(),
+        client.all_exercise_templates(),
+        client.all_routine_folders(),
+    )?;
+    let workouts = api::dedup_by_id(workouts);
+
+    write_items(dir, WORKOUTS_DIR, &workouts, |w| w.id.clone())?;
+    write_items(dir, ROUTINES_DIR, &routines, |r| r.id.clone())?;
+    write_items(dir, EXERCISE_TEMPLATES_DIR, &templates, |t| t.id.clone())?;
+    write_items(dir, ROUTINE_FOLDERS_DIR, &folders, |f| {
+        f.id.map(|id| id.to_string())
+    })?;
+
+    let manifest = Manifest {
+        version: BACKUP_VERSION,
+        account_id: user.data.id,
+        account_name: user.data.name,
+        created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        counts: Counts {
+            workouts: workouts.len(),
+            routines: routines.len(),
+            exercise_templates: templates.len(),
+            routine_folders: folders.len(),
+        },
+    };
+    write_json(&dir.join(MANIFEST_FILE), &manifest)?;
+    Ok(manifest)
+}
+
+/// Write each item to `dir/sub/<id>.json`. Items without an ID are named
+/// after their position.
+fn write_items<T: Serialize>(
+    dir: &Path,
+    sub: &str,
+    items: &[T],
+    id: impl Fn(&T) -> Option<String>,
+) -> Result<()> {
+    let sub = dir.join(sub);
+    std::fs::create_dir_all(&sub).with_context(|| format!("Failed to create {}", sub.display()))?;
+    for (i, item) in items.iter().enumerate() {
+        let name = id(item).unwrap_or_else(|| format!("no-id-{}", i + 1));
+        write_json(&sub.join(format!("{name}.json")), item)?;
+    }
+    Ok(())
+}
+
+fn write_json(path: &Path, value: &impl Serialize) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Outcome of `workouts restore`, per kind of item.
+#[derive(Debug, Serialize)]
+pub struct RestoreReport {
+    pub dry_run: bool,
+    pub routine_folders: Tally,
+    pub routines: Tally,
+    pub workouts: Tally,
+}
+
+impl RestoreReport {
+    pub fn failed(&self) -> usize {
+        self.routine_folders.failed.len() + self.routines.failed.len() + self.workouts.failed.len()
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Tally {
+    pub total: usize,
+    pub restored: usize,
+    pub failed: Vec<RestoreFailure>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreFailure {
+    /// The item's ID in the backup.
+    pub id: Option<String>,
+    pub title: String,
+    pub error: String,
+}
+
+impl Tally {
+    fn record<T>(&mut self, id: Option<String>, title: &str, result: Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                self.restored += 1;
+                Some(value)
+            }
+            Err(e) => {
+                eprintln!("  Failed: {e:#}");
+                self.failed.push(RestoreFailure {
+                    id,
+                    title: title.to_string(),
+                    error: format!("{e:#}"),
+                });
+                None
+            }
+        }
+    }
+}
+
+/// Re-create the folders, routines and workouts of the backup in `dir`,
+/// in that order, continuing past failures. Routines go into the new
+/// copies of their folders. With `dry_run` the bodies are only built.
+pub async fn restore(client: &impl HevyApi, dir: &Path, dry_run: bool) -> Result<RestoreReport> {
+    let manifest: Manifest = read_json(&dir.join(MANIFEST_FILE))?;
+    if manifest.version > BACKUP_VERSION {
+        anyhow::bail!(
+            "Backup version {} is newer than this hevy-bridge supports ({BACKUP_VERSION})",
+            manifest.version
+        );
+    }
+    let mut folders: Vec<RoutineFolder> = read_items(dir, ROUTINE_FOLDERS_DIR)?;
+    let routines: Vec<Routine> = read_items(dir, ROUTINES_DIR)?;
+    let mut workouts: Vec<Workout> = read_items(dir, WORKOUTS_DIR)?;
+    let found = (folders.len(), routines.len(), workouts.len());
+    let expected = (
+        manifest.counts.routine_folders,
+        manifest.counts.routines,
+        manifest.counts.workouts,
+    );
+    if found != expected {
+        anyhow::bail!(
+            "{} is incomplete: the manifest lists {} folder(s), {} routine(s) and {} workout(s), \
+             but {} folder(s), {} routine(s) and {} workout(s) were found",
+            dir.display(),
+            expected.0,
+            expected.1,
+            expected.2,
+            found.0,
+            found.1,
+            found.2
+        );
+    }
+    folders.sort_by_key(|f| f.index);
+    // Oldest first, so the new account's history fills in order.
+    workouts.sort_by_key(|w| w.start_time);
+
+    let mut report = RestoreReport {
+        dry_run,
+        routine_folders: Tally {
+            total: folders.len(),
+            ..Tally::default()
+        },
+        routines: Tally {
+            total: routines.len(),
+            ..Tally::default()
+        },
+        workouts: Tally {
+            total: workouts.len(),
+            ..Tally::default()
+        },
+    };
+
+    // Backup folder ID -> ID of its re-created copy.
+    let mut folder_ids: HashMap<u64, u64> = HashMap::new();
+    for (i, f) in folders.iter().enumerate() {
+        let title = f.title.as_deref().unwrap_or("Untitled Folder");
+        eprintln!(
+            "[{}/{}] Restoring folder \"{title}\"...",
+            i + 1,
+            folders.len()
+        );
+        let result = if dry_run {
+            Ok(f.id)
+        } else {
+            let body = PostRoutineFolderBody {
+                routine_folder: PostRoutineFolderInner {
+                    title: title.to_string(),
+                },
+            };
+            client
+                .create_routine_folder(&body)
+                .await
+                .map(|created| created.id)
+                .map_err(Into::into)
+        };
+        let created = report
+            .routine_folders
+            .record(f.id.map(|id| id.to_string()), title, result);
+        if let (Some(old), Some(Some(new))) = (f.id, created) {
+            folder_ids.insert(old, new);
+        }
+    }
+
+    for (i, r) in routines.iter().enumerate() {
+        let title = r.title.as_deref().unwrap_or("Untitled Routine");
+        eprintln!(
+            "[{}/{}] Restoring routine \"{title}\"...",
+            i + 1,
+            routines.len()
+        );
+        let result = async {
+            let mut routine = PostRoutineInner::try_from(r)?;
+            if let Some(old) = routine.folder_id {
+                let new = folder_ids
+                    .get(&old)
+                    .with_context(|| format!("Its folder {old} was not restored"))?;
+                routine.folder_id = Some(*new);
+            }
+            if !dry_run {
+                client.create_routine(&PostRoutineBody { routine }).await?;
+            }
+            Ok(())
+        }
+        .await;
+        report.routines.record(r.id.clone(), title, result);
+    }
+
+    for (i, w) in workouts.iter().enumerate() {
+        let title = w.title.as_deref().unwrap_or("Untitled");
+        eprintln!(
+            "[{}/{}] Restoring workout \"{title}\"...",
+            i + 1,
+            workouts.len()
+        );
+        let result = async {
+            let workout = PostWorkoutInner::try_from(w)?;
+            if !dry_run {
+                client.create_workout(&PostWorkoutBody { workout }).await?;
+            }
+            Ok(())
+        }
+        .await;
+        report.workouts.record(w.id.clone(), title, result);
+    }
+
+    Ok(report)
+}
+
+/// Every `*.json` file in `dir/sub`, in file name order.
+fn read_items<T: DeserializeOwned>(dir: &Path, sub: &str) -> Result<Vec<T>> {
+    let sub = dir.join(sub);
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&sub)
+        .with_context(|| format!("Failed to read {}", sub.display()))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()
+        .with_context(|| format!("Failed to read {}", sub.display()))?;
+    paths.retain(|p| p.extension().is_some_and(|ext| ext == "json"));
+    paths.sort();
+    paths.iter().map(|p| read_json(p)).collect()
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("{} is not valid", path.display()))
+}
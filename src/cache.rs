@@ -0,0 +1,199 @@
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+use crate::models::ExerciseTemplate;
+
+/// How long fetched exercise templates may be served from the local cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Use the cached copy whenever one exists, regardless of age.
+    Always,
+    /// Refetch once the cached copy is older than the given duration.
+    IfOlderThan(Duration),
+    /// Never read or write the cache.
+    Never,
+}
+
+impl Default for CachePolicy {
+    /// Cache exercise templates for 24 hours.
+    fn default() -> Self {
+        CachePolicy::IfOlderThan(Duration::from_secs(24 * 60 * 60))
+    }
+}
+
+/// On-disk representation of the exercise template cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedTemplates {
+    /// Unix timestamp (seconds) of when the templates were fetched.
+    pub cached_at: u64,
+    pub exercise_templates: Vec<ExerciseTemplate>,
+}
+
+impl CachedTemplates {
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now_unix().saturating_sub(self.cached_at))
+    }
+
+    /// Whether this cached copy may be used under `policy`.
+    pub fn is_fresh(&self, policy: CachePolicy) -> bool {
+        match policy {
+            CachePolicy::Always => true,
+            CachePolicy::IfOlderThan(max_age) => self.age() <= max_age,
+            CachePolicy::Never => false,
+        }
+    }
+}
+
+/// ~/.cache/hevy-bridge
+pub fn cache_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("hevy-bridge")
+}
+
+/// ~/.cache/hevy-bridge/exercise_templates, one file per API key.
+pub fn exercise_templates_dir() -> PathBuf {
+    cache_dir().join("exercise_templates")
+}
+
+/// The templates include the account's custom ones, so like
+/// [`http_entry_path`] the file is keyed by API key, named by its hash.
+pub fn exercise_templates_path(api_key: &str) -> PathBuf {
+    let digest = Sha256::digest(api_key);
+    exercise_templates_dir().join(format!("{}.json", hex::encode(digest)))
+}
+
+/// The single file templates were cached in before they were keyed by API
+/// key; only removed by [`clear`].
+fn legacy_exercise_templates_path() -> PathBuf {
+    cache_dir().join("exercise_templates.json")
+}
+
+/// Read the exercise templates cached for `api_key`, if present and readable.
+pub fn load_exercise_templates(api_key: &str) -> Option<CachedTemplates> {
+    let data = std::fs::read_to_string(exercise_templates_path(api_key)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn store_exercise_templates(api_key: &str, templates: &[ExerciseTemplate]) -> Result<()> {
+    let path = exercise_templates_path(api_key);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| Error::io("Failed to create cache directory", e))?;
+    }
+    let data = CachedTemplates {
+        cached_at: now_unix(),
+        exercise_templates: templates.to_vec(),
+    };
+    let json = serde_json::to_string(&data)
+        .map_err(|e| Error::json("Failed to serialize exercise template cache", e))?;
+    std::fs::write(&path, json)
+        .map_err(|e| Error::io("Failed to write exercise template cache", e))?;
+    Ok(())
+}
+
+/// A cached GET response (`--cache`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    /// Unix timestamp (seconds) of when the response was fetched.
+    cached_at: u64,
+    url: String,
+    body: String,
+}
+
+/// ~/.cache/hevy-bridge/http, one file per cached GET response.
+pub fn http_dir() -> PathBuf {
+    cache_dir().join("http")
+}
+
+/// How long a GET response for `path` (relative to the API base URL) may be
+/// served from the response cache, or None if it is never cached.
+pub fn http_ttl(path: &str) -> Option<Duration> {
+    const MINUTE: u64 = 60;
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["exercise_templates"] | ["exercise_templates", _] => {
+            Some(Duration::from_secs(7 * 24 * 60 * MINUTE))
+        }
+        ["workouts"] => Some(Duration::from_secs(5 * MINUTE)),
+        ["workouts", "count" | "events"] => None,
+        ["workouts", _] => Some(Duration::from_secs(60 * MINUTE)),
+        _ => None,
+    }
+}
+
+/// Responses are keyed by API key and URL, so profiles never share entries;
+/// the file name is a hash and reveals neither.
+fn http_entry_path(api_key: &str, url: &str) -> PathBuf {
+    let digest = Sha256::digest(format!("{api_key}\n{url}"));
+    http_dir().join(format!("{}.json", hex::encode(digest)))
+}
+
+/// The cached body for `url`, if one was stored within `ttl`.
+pub fn load_http(api_key: &str, url: &str, ttl: Duration) -> Option<String> {
+    let data = std::fs::read_to_string(http_entry_path(api_key, url)).ok()?;
+    let cached: CachedResponse = serde_json::from_str(&data).ok()?;
+    let age = Duration::from_secs(now_unix().saturating_sub(cached.cached_at));
+    (cached.url == url && age <= ttl).then_some(cached.body)
+}
+
+pub fn store_http(api_key: &str, url: &str, body: &str) -> Result<()> {
+    std::fs::create_dir_all(http_dir())
+        .map_err(|e| Error::io("Failed to create cache directory", e))?;
+    let data = CachedResponse {
+        cached_at: now_unix(),
+        url: url.to_string(),
+        body: body.to_string(),
+    };
+    let json = serde_json::to_string(&data)
+        .map_err(|e| Error::json("Failed to serialize response cache", e))?;
+    std::fs::write(http_entry_path(api_key, url), json)
+        .map_err(|e| Error::io("Failed to write response cache", e))?;
+    Ok(())
+}
+
+/// Number of cached GET responses on disk.
+pub fn http_entry_count() -> usize {
+    std::fs::read_dir(http_dir())
+        .map(|entries| entries.filter_map(|e| e.ok()).count())
+        .unwrap_or(0)
+}
+
+/// Remove every cached GET response. Returns how many were removed.
+pub fn clear_http() -> Result<usize> {
+    let count = http_entry_count();
+    let dir = http_dir();
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)
+            .map_err(|e| Error::io(format!("Failed to remove {}", dir.display()), e))?;
+    }
+    Ok(count)
+}
+
+/// Remove the exercise template cache of every API key. Returns `false` if
+/// there was nothing to remove.
+pub fn clear() -> Result<bool> {
+    let dir = exercise_templates_dir();
+    let legacy = legacy_exercise_templates_path();
+    let found = dir.exists() || legacy.exists();
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)
+            .map_err(|e| Error::io(format!("Failed to remove {}", dir.display()), e))?;
+    }
+    if legacy.exists() {
+        std::fs::remove_file(&legacy)
+            .map_err(|e| Error::io(format!("Failed to remove {}", legacy.display()), e))?;
+    }
+    Ok(found)
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
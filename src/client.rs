@@ -1,428 +1,803 @@
-use anyhow::{Context, Result};
-use reqwest::Client;
-
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use futures::TryStreamExt;
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::time::Instant;
+use tracing::Level;
+
+use crate::api::{HevyApi, dedup_by_id};
+use crate::cache::{self, CachePolicy};
+use crate::error::{self, Error, Result};
 use crate::models::*;
+use crate::strict::{self, Strictness};
+use crate::transport::{HttpTransport, RecordingTransport, ReplayTransport, Transport};
 
 const BASE_URL: &str = "https://api.hevyapp.com/v1";
 
+/// Path prefix of every endpoint under [`BASE_URL`].
+const API_PATH: &str = "/v1";
+
+/// Largest page the API serves for workouts, events, routines and folders.
+pub const MAX_PAGE_SIZE: u32 = 10;
+
+/// Largest page the API serves for exercise templates.
+pub const MAX_EXERCISE_TEMPLATE_PAGE_SIZE: u32 = 100;
+
+/// Retries used when the builder is not told otherwise.
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Overall time limit for one request attempt, unless the builder is told
+/// otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Time limit for establishing a connection, unless the builder is told
+/// otherwise.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pages fetched at once when the builder is not told otherwise.
+pub const DEFAULT_CONCURRENCY: usize = 3;
+
+/// Requests per second sent when the builder is not told otherwise.
+pub const DEFAULT_MAX_RPS: f64 = 2.0;
+
+/// Identifies this client to the API provider.
+const USER_AGENT: &str = concat!("hevy-bridge/", env!("CARGO_PKG_VERSION"));
+
+/// First retry delay; doubled on each further attempt unless the server
+/// sends `Retry-After`.
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Bodies logged by `--debug-http` are cut off after this many bytes.
+const MAX_LOGGED_BODY: usize = 4096;
+
+/// A non-success HTTP response from the Hevy API.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct ApiError {
+    /// Method and path, e.g. `GET /workouts/{id}` with the id filled in.
+    pub endpoint: String,
+    pub status: StatusCode,
+    pub body: String,
+    /// Parsed `Retry-After` header, when the server sent one.
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == StatusCode::TOO_MANY_REQUESTS
+    }
+
+    /// Whether the API rejected the API key.
+    pub fn is_unauthorized(&self) -> bool {
+        self.status == StatusCode::UNAUTHORIZED
+    }
+
+    /// Whether the API refused the API key (401 or 403).
+    pub fn is_auth_failure(&self) -> bool {
+        self.is_unauthorized() || self.status == StatusCode::FORBIDDEN
+    }
+
+    /// Whether repeating the request might succeed (rate limit or server error).
+    pub fn is_transient(&self) -> bool {
+        self.is_rate_limited() || self.status.is_server_error()
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} returned {}: {}",
+            self.endpoint, self.status, self.body
+        )
+    }
+}
+
+impl std::error::Error for ApiError {}
+
 /// HTTP client wrapper for the Hevy API.
 ///
 /// All endpoints require an API key passed via the `api-key` header.
 /// Obtain your key at <https://hevy.com/settings?developer> (Hevy Pro required).
+///
+/// Cloning is cheap, so each task can own a client. Clones share the
+/// connection pool (as clones of a `reqwest::Client` do), the rate limit
+/// and any backoff after a 429, so together they pace requests like one
+/// client:
+///
+/// ```no_run
+/// use hevy_bridge::{HevyApi, HevyClient};
+///
+/// # async fn run() -> hevy_bridge::Result<()> {
+/// let client = HevyClient::new("YOUR_API_KEY")?;
+/// let tasks: Vec<_> = ["id-a", "id-b"]
+///     .into_iter()
+///     .map(|id| {
+///         let client = client.clone();
+///         tokio::spawn(async move { client.get_workout(id).await })
+///     })
+///     .collect();
+/// for task in tasks {
+///     println!("{:?}", task.await.unwrap()?.title);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
 pub struct HevyClient {
+    /// Builds requests; they are sent through `transport`.
     client: Client,
+    transport: Arc<dyn Transport>,
     api_key: String,
+    base_url: String,
+    exercise_cache_policy: CachePolicy,
+    max_retries: u32,
+    concurrency: usize,
+    page_size: Option<u32>,
+    /// While rate limited, no request is sent before this instant.
+    throttled_until: Arc<Mutex<Option<Instant>>>,
+    limiter: Option<Arc<RateLimiter>>,
+    http_cache: bool,
+    strictness: Strictness,
 }
 
-impl HevyClient {
-    pub fn new(api_key: String) -> Self {
-        Self {
-            client: Client::new(),
-            api_key,
-        }
+/// Shows the API key only by its last four characters, so a client can
+/// be logged without leaking it:
+///
+/// ```
+/// use hevy_bridge::HevyClient;
+///
+/// let client = HevyClient::new("0123456789abcdef")?;
+/// let debug = format!("{client:?}");
+/// assert!(debug.contains(r#"api_key: "****cdef""#));
+/// assert!(!debug.contains("0123456789abcdef"));
+/// # Ok::<(), hevy_bridge::Error>(())
+/// ```
+impl fmt::Debug for HevyClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // A key of four characters or fewer is hidden entirely.
+        let chars: Vec<char> = self.api_key.chars().collect();
+        let tail: String = match chars.len() {
+            n if n > 4 => chars[n - 4..].iter().collect(),
+            _ => String::new(),
+        };
+        let api_key = format!("****{tail}");
+        f.debug_struct("HevyClient")
+            .field("api_key", &api_key)
+            .field("base_url", &self.base_url)
+            .finish_non_exhaustive()
     }
+}
 
-    // ── Workouts ───────────────────────────────────────
+/// Spaces requests evenly across every task sharing the client: a token
+/// bucket that holds a single token.
+struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Option<Instant>>,
+}
 
-    /// GET /v1/workouts — paginated list of workouts.
-    pub async fn list_workouts(&self, page: u32, page_size: u32) -> Result<WorkoutsPage> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/workouts"))
-            .header("api-key", &self.api_key)
-            .query(&[("page", page), ("pageSize", page_size)])
-            .send()
-            .await
-            .context("Failed to send request to GET /workouts")?;
+impl RateLimiter {
+    /// None when `max_rps` is 0 (no limit).
+    fn new(max_rps: f64) -> Option<Self> {
+        (max_rps > 0.0).then(|| RateLimiter {
+            interval: Duration::from_secs_f64(1.0 / max_rps),
+            next_slot: Mutex::new(None),
+        })
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /workouts returned {status}: {body}");
-        }
+    /// Wait for the next free slot, reserving it for this request.
+    async fn acquire(&self) {
+        let slot = {
+            let mut next_slot = self
+                .next_slot
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            let now = Instant::now();
+            let slot = next_slot.map_or(now, |next| next.max(now));
+            *next_slot = Some(slot + self.interval);
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
 
-        resp.json::<WorkoutsPage>()
-            .await
-            .context("Failed to parse workouts response")
+/// Builder for [`HevyClient`] with non-default settings.
+pub struct HevyClientBuilder {
+    api_key: String,
+    base_url: String,
+    exercise_cache_policy: CachePolicy,
+    max_retries: u32,
+    timeout: Duration,
+    connect_timeout: Duration,
+    concurrency: usize,
+    page_size: Option<u32>,
+    max_rps: f64,
+    http_cache: bool,
+    record: Option<PathBuf>,
+    replay: Option<PathBuf>,
+    strictness: Strictness,
+}
+
+impl HevyClientBuilder {
+    /// Send requests to `url` instead of the Hevy API, e.g. a local test
+    /// server. Like the default, it includes the `/v1` prefix.
+    pub fn base_url(mut self, url: impl Into<String>) -> Self {
+        self.base_url = url.into();
+        self
     }
 
-    /// GET /v1/workouts/{id} — single workout by ID.
-    pub async fn get_workout(&self, workout_id: &str) -> Result<Workout> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/workouts/{workout_id}"))
-            .header("api-key", &self.api_key)
-            .send()
-            .await
-            .context("Failed to send request to GET /workouts/{id}")?;
+    /// Control how long exercise templates are served from the disk cache
+    /// (default: 24 hours).
+    pub fn exercise_cache_policy(mut self, policy: CachePolicy) -> Self {
+        self.exercise_cache_policy = policy;
+        self
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /workouts/{workout_id} returned {status}: {body}");
-        }
+    /// How many times a request is retried after a rate limit (any method)
+    /// or a server error (non-POST only) before giving up (default: 3).
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
 
-        resp.json::<Workout>()
-            .await
-            .context("Failed to parse workout response")
+    /// Give up on a request attempt that has not completed, response body
+    /// included, within `timeout` (default: 30 seconds).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
     }
 
-    /// POST /v1/workouts — create a new workout.
-    pub async fn create_workout(&self, body: &PostWorkoutBody) -> Result<Workout> {
-        let resp = self
-            .client
-            .post(format!("{BASE_URL}/workouts"))
-            .header("api-key", &self.api_key)
-            .json(body)
-            .send()
-            .await
-            .context("Failed to send request to POST /workouts")?;
+    /// Give up on connecting to the API after `timeout` (default: 10 seconds).
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = timeout;
+        self
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("POST /workouts returned {status}: {body}");
-        }
+    /// How many pages of a list are fetched at once after the first
+    /// (default: 3). Results still arrive in page order.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
 
-        resp.json::<Workout>()
-            .await
-            .context("Failed to parse created workout response")
+    /// Page size used when walking every page of a list, capped at each
+    /// endpoint's maximum (default: the maximum).
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = Some(page_size.max(1));
+        self
     }
 
-    /// PUT /v1/workouts/{id} — update an existing workout.
-    pub async fn update_workout(
-        &self,
-        workout_id: &str,
-        body: &PostWorkoutBody,
-    ) -> Result<Workout> {
-        let resp = self
-            .client
-            .put(format!("{BASE_URL}/workouts/{workout_id}"))
-            .header("api-key", &self.api_key)
-            .json(body)
-            .send()
-            .await
-            .context("Failed to send request to PUT /workouts/{id}")?;
+    /// Send at most this many requests per second, retries included, or
+    /// any number when 0 (default: 2).
+    pub fn max_rps(mut self, max_rps: f64) -> Self {
+        self.max_rps = max_rps;
+        self
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("PUT /workouts/{workout_id} returned {status}: {body}");
-        }
+    /// Cache GET responses for workouts and exercise templates on disk
+    /// (default: off). See [`cache::http_ttl`] for how long each is kept.
+    pub fn http_cache(mut self, enabled: bool) -> Self {
+        self.http_cache = enabled;
+        self
+    }
 
-        resp.json::<Workout>()
-            .await
-            .context("Failed to parse updated workout response")
+    /// Save every request and response as a JSON cassette in `dir`, with
+    /// the API key redacted.
+    pub fn record(mut self, dir: PathBuf) -> Self {
+        self.record = Some(dir);
+        self
     }
 
-    /// GET /v1/workouts/count — total workout count.
-    pub async fn workout_count(&self) -> Result<WorkoutCountResponse> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/workouts/count"))
-            .header("api-key", &self.api_key)
-            .send()
-            .await
-            .context("Failed to send request to GET /workouts/count")?;
+    /// Answer requests from the cassettes in `dir` instead of the network;
+    /// a request without a cassette fails. Takes precedence over
+    /// [`record`](Self::record), and turns off the rate limiter.
+    pub fn replay(mut self, dir: PathBuf) -> Self {
+        self.replay = Some(dir);
+        self
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /workouts/count returned {status}: {body}");
-        }
+    /// What to do with response fields the models do not capture
+    /// (default: [`Strictness::Lenient`], ignore them).
+    pub fn strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
 
-        resp.json::<WorkoutCountResponse>()
-            .await
-            .context("Failed to parse workout count response")
+    pub fn build(self) -> Result<HevyClient> {
+        let client = Client::builder()
+            .timeout(self.timeout)
+            .connect_timeout(self.connect_timeout)
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(|e| Error::http("Failed to initialize the HTTP client", e))?;
+        let http = HttpTransport::new(client.clone(), self.timeout, self.connect_timeout);
+        let (transport, max_rps): (Arc<dyn Transport>, f64) = match (self.replay, self.record) {
+            (Some(dir), _) => (Arc::new(ReplayTransport::new(dir)), 0.0),
+            (None, Some(dir)) => (Arc::new(RecordingTransport::new(http, dir)?), self.max_rps),
+            (None, None) => (Arc::new(http), self.max_rps),
+        };
+        Ok(HevyClient {
+            client,
+            transport,
+            api_key: self.api_key,
+            base_url: self.base_url,
+            exercise_cache_policy: self.exercise_cache_policy,
+            max_retries: self.max_retries,
+            concurrency: self.concurrency,
+            page_size: self.page_size,
+            throttled_until: Arc::new(Mutex::new(None)),
+            limiter: RateLimiter::new(max_rps).map(Arc::new),
+            http_cache: self.http_cache,
+            strictness: self.strictness,
+        })
     }
+}
 
-    /// GET /v1/workouts/events — paginated workout events (updates/deletes).
-    pub async fn workout_events(
-        &self,
-        page: u32,
-        page_size: u32,
-        since: Option<&str>,
-    ) -> Result<PaginatedWorkoutEvents> {
-        let mut req = self
-            .client
-            .get(format!("{BASE_URL}/workouts/events"))
+impl HevyClient {
+    /// A client with the default settings.
+    ///
+    /// ```no_run
+    /// use hevy_bridge::{HevyApi, HevyClient};
+    ///
+    /// # async fn run() -> hevy_bridge::Result<()> {
+    /// let count = HevyClient::new("YOUR_API_KEY")?.workout_count().await?;
+    /// println!("{} workouts", count.workout_count);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(api_key: impl Into<String>) -> Result<Self> {
+        Self::builder(api_key.into()).build()
+    }
+
+    pub fn builder(api_key: String) -> HevyClientBuilder {
+        HevyClientBuilder {
+            api_key,
+            base_url: BASE_URL.to_string(),
+            exercise_cache_policy: CachePolicy::default(),
+            max_retries: DEFAULT_MAX_RETRIES,
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            concurrency: DEFAULT_CONCURRENCY,
+            page_size: None,
+            max_rps: DEFAULT_MAX_RPS,
+            http_cache: false,
+            record: None,
+            replay: None,
+            strictness: Strictness::default(),
+        }
+    }
+
+    /// Start a request to `path` (relative to the API base URL) with the
+    /// API key attached.
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{path}", self.base_url))
             .header("api-key", &self.api_key)
-            .query(&[("page", page), ("pageSize", page_size)]);
+    }
 
-        if let Some(since) = since {
-            req = req.query(&[("since", since)]);
+    /// Send a request and decode its JSON body, retrying transient failures.
+    ///
+    /// `endpoint` names the call in error messages (e.g. `GET /workouts`);
+    /// `what` describes the payload for parse errors. With the response
+    /// cache on, cacheable GETs are answered from disk while fresh, and any
+    /// successful write empties the cache.
+    async fn send_json<T: DeserializeOwned + Serialize>(
+        &self,
+        req: RequestBuilder,
+        endpoint: &str,
+        what: &str,
+    ) -> Result<T> {
+        let req = req
+            .build()
+            .map_err(|e| Error::http(format!("Failed to build request to {endpoint}"), e))?;
+        let url = req.url().to_string();
+        let ttl = (self.http_cache && req.method() == Method::GET)
+            .then(|| {
+                let path = req.url().path();
+                cache::http_ttl(path.strip_prefix(API_PATH).unwrap_or(path))
+            })
+            .flatten();
+
+        if let Some(ttl) = ttl
+            && let Some(body) = cache::load_http(&self.api_key, &url, ttl)
+        {
+            tracing::debug!("{endpoint} served from the response cache");
+            return self.decode(
+                body.as_bytes(),
+                endpoint,
+                format!("Failed to parse cached {what} response"),
+            );
         }
 
-        let resp = req
-            .send()
-            .await
-            .context("Failed to send request to GET /workouts/events")?;
+        let is_write = req.method() != Method::GET;
+        let body = self.fetch_body(req, endpoint).await?;
+        let value = self.decode(&body, endpoint, format!("Failed to parse {what} response"))?;
+
+        if self.http_cache {
+            let stored = if is_write {
+                cache::clear_http().map(drop)
+            } else if ttl.is_some() {
+                cache::store_http(&self.api_key, &url, &String::from_utf8_lossy(&body))
+            } else {
+                Ok(())
+            };
+            if let Err(e) = stored {
+                tracing::warn!("{}", error::report(&e));
+            }
+        }
+        Ok(value)
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /workouts/events returned {status}: {body}");
+    /// Parse a response body, checking for fields the models do not
+    /// capture unless the client is lenient.
+    fn decode<T: DeserializeOwned + Serialize>(
+        &self,
+        body: &[u8],
+        endpoint: &str,
+        context: String,
+    ) -> Result<T> {
+        if self.strictness == Strictness::Lenient {
+            return serde_json::from_slice(body).map_err(|e| Error::json(context, e));
+        }
+        let raw: serde_json::Value =
+            serde_json::from_slice(body).map_err(|e| Error::json(context.clone(), e))?;
+        let value = T::deserialize(&raw).map_err(|e| Error::json(context.clone(), e))?;
+        let captured = serde_json::to_value(&value).map_err(|e| Error::json(context, e))?;
+        let fields = strict::uncaptured_fields(&raw, &captured);
+        if fields.is_empty() {
+            return Ok(value);
+        }
+        let err = Error::UnknownFields {
+            endpoint: endpoint.to_string(),
+            fields,
+        };
+        match self.strictness {
+            Strictness::Error => Err(err),
+            _ => {
+                tracing::warn!("{err}");
+                Ok(value)
+            }
         }
+    }
 
-        resp.json::<PaginatedWorkoutEvents>()
-            .await
-            .context("Failed to parse workout events response")
+    /// Send a request and read its body, retrying transient failures. POST
+    /// requests are only retried on 429, since a server error may have
+    /// happened after the resource was created.
+    async fn fetch_body(&self, req: reqwest::Request, endpoint: &str) -> Result<Vec<u8>> {
+        let mut delay = RETRY_BASE_DELAY;
+        let mut attempt = 0;
+        loop {
+            self.wait_for_throttle().await;
+            if let Some(limiter) = &self.limiter {
+                limiter.acquire().await;
+            }
+            // Request bodies are always buffered JSON, never streams.
+            let attempt_req = req.try_clone().expect("request body is buffered");
+            let err = match self.send_once(attempt_req, endpoint).await {
+                Ok(body) => return Ok(body),
+                Err(e) => e,
+            };
+
+            let retryable = err.as_api().and_then(|api| {
+                let allowed =
+                    api.is_rate_limited() || (api.is_transient() && req.method() != Method::POST);
+                allowed.then_some((api.retry_after, api.is_rate_limited()))
+            });
+            match retryable {
+                Some((retry_after, rate_limited)) if attempt < self.max_retries => {
+                    let wait = retry_after.unwrap_or(delay);
+                    tracing::warn!(
+                        "{err}; retrying in {}s ({}/{})",
+                        wait.as_secs(),
+                        attempt + 1,
+                        self.max_retries
+                    );
+                    if rate_limited {
+                        // Hold back every request, not just this one; the
+                        // wait happens at the top of the loop.
+                        self.throttle(wait);
+                    } else {
+                        tokio::time::sleep(wait).await;
+                    }
+                    delay *= 2;
+                    attempt += 1;
+                }
+                _ => return Err(err),
+            }
+        }
     }
 
-    // ── Routines ──────────────────────────────────────
+    /// Sleep until any rate-limit pause set by [`HevyClient::throttle`] is over.
+    async fn wait_for_throttle(&self) {
+        let until = *self
+            .throttled_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(until) = until {
+            tokio::time::sleep_until(until).await;
+        }
+    }
 
-    /// GET /v1/routines — paginated list of routines.
-    pub async fn list_routines(&self, page: u32, page_size: u32) -> Result<RoutinesPage> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/routines"))
-            .header("api-key", &self.api_key)
-            .query(&[("page", page), ("pageSize", page_size)])
-            .send()
-            .await
-            .context("Failed to send request to GET /routines")?;
+    /// Pause all requests made through this client for `wait`, so concurrent
+    /// page fetches back off together after a 429.
+    fn throttle(&self, wait: Duration) {
+        let until = Instant::now() + wait;
+        let mut throttled_until = self
+            .throttled_until
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if throttled_until.is_none_or(|current| current < until) {
+            *throttled_until = Some(until);
+        }
+    }
 
-        let status = resp.status();
+    /// Execute a single attempt and return the body, turning non-success
+    /// statuses into [`ApiError`].
+    async fn send_once(&self, req: reqwest::Request, endpoint: &str) -> Result<Vec<u8>> {
+        log_request(&req);
+        let started = Instant::now();
+        let resp = self.transport.send(req, endpoint).await?;
+        let status = resp.status;
+        tracing::debug!(
+            "{endpoint} returned {status} in {}ms",
+            started.elapsed().as_millis()
+        );
+        tracing::trace!("response body:\n{}", format_body(&resp.body));
         if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /routines returned {status}: {body}");
+            return Err(ApiError {
+                endpoint: endpoint.to_string(),
+                status,
+                body: String::from_utf8_lossy(&resp.body).into_owned(),
+                retry_after: resp.retry_after,
+            }
+            .into());
         }
+        Ok(resp.body)
+    }
+}
 
-        resp.json::<RoutinesPage>()
-            .await
-            .context("Failed to parse routines response")
+/// Log a request about to be sent: method and URL at debug level, headers
+/// and body at trace level. The `api-key` header is never logged.
+fn log_request(req: &reqwest::Request) {
+    tracing::debug!("{} {}", req.method(), req.url());
+    if !tracing::enabled!(Level::TRACE) {
+        return;
+    }
+    for (name, value) in req.headers() {
+        let value = if name == "api-key" {
+            "<redacted>"
+        } else {
+            value.to_str().unwrap_or("<binary>")
+        };
+        tracing::trace!("  {name}: {value}");
     }
+    if let Some(body) = req.body().and_then(|b| b.as_bytes()) {
+        tracing::trace!("request body:\n{}", format_body(body));
+    }
+}
 
-    /// GET /v1/routines/{id} — single routine by ID.
-    pub async fn get_routine(&self, routine_id: &str) -> Result<SingleRoutineResponse> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/routines/{routine_id}"))
-            .header("api-key", &self.api_key)
-            .send()
-            .await
-            .context("Failed to send request to GET /routines/{id}")?;
+/// Pretty-print a body for logging when it is JSON, truncating long bodies.
+fn format_body(body: &[u8]) -> String {
+    let text = match serde_json::from_slice::<serde_json::Value>(body) {
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_default(),
+        Err(_) => String::from_utf8_lossy(body).into_owned(),
+    };
+    if text.len() <= MAX_LOGGED_BODY {
+        return text;
+    }
+    let mut end = MAX_LOGGED_BODY;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!(
+        "{}\n… ({} more bytes truncated)",
+        &text[..end],
+        text.len() - end
+    )
+}
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /routines/{routine_id} returned {status}: {body}");
-        }
+impl HevyApi for HevyClient {
+    fn page_concurrency(&self) -> usize {
+        self.concurrency
+    }
 
-        resp.json::<SingleRoutineResponse>()
-            .await
-            .context("Failed to parse routine response")
+    fn page_size(&self, max: u32) -> u32 {
+        self.page_size.map_or(max, |size| size.min(max))
     }
 
-    /// POST /v1/routines — create a new routine.
-    pub async fn create_routine(&self, body: &PostRoutineBody) -> Result<Routine> {
-        let resp = self
-            .client
-            .post(format!("{BASE_URL}/routines"))
-            .header("api-key", &self.api_key)
-            .json(body)
-            .send()
-            .await
-            .context("Failed to send request to POST /routines")?;
+    // ── Workouts ───────────────────────────────────────
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("POST /routines returned {status}: {body}");
-        }
+    async fn list_workouts(&self, page: u32, page_size: u32) -> Result<WorkoutsPage> {
+        let req = self
+            .request(Method::GET, "/workouts")
+            .query(&[("page", page), ("pageSize", page_size)]);
+        self.send_json(req, "GET /workouts", "workouts").await
+    }
 
-        resp.json::<Routine>()
+    async fn get_workout(&self, workout_id: &str) -> Result<Workout> {
+        let req = self.request(Method::GET, &format!("/workouts/{workout_id}"));
+        self.send_json(req, &format!("GET /workouts/{workout_id}"), "workout")
             .await
-            .context("Failed to parse created routine response")
     }
 
-    /// PUT /v1/routines/{id} — update an existing routine.
-    pub async fn update_routine(
-        &self,
-        routine_id: &str,
-        body: &PutRoutineBody,
-    ) -> Result<Routine> {
-        let resp = self
-            .client
-            .put(format!("{BASE_URL}/routines/{routine_id}"))
-            .header("api-key", &self.api_key)
-            .json(body)
-            .send()
+    async fn create_workout(&self, body: &PostWorkoutBody) -> Result<Workout> {
+        let req = self.request(Method::POST, "/workouts").json(body);
+        self.send_json(req, "POST /workouts", "created workout")
             .await
-            .context("Failed to send request to PUT /routines/{id}")?;
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("PUT /routines/{routine_id} returned {status}: {body}");
-        }
+    async fn update_workout(&self, workout_id: &str, body: &PostWorkoutBody) -> Result<Workout> {
+        let req = self
+            .request(Method::PUT, &format!("/workouts/{workout_id}"))
+            .json(body);
+        self.send_json(
+            req,
+            &format!("PUT /workouts/{workout_id}"),
+            "updated workout",
+        )
+        .await
+    }
 
-        resp.json::<Routine>()
+    async fn workout_count(&self) -> Result<WorkoutCountResponse> {
+        let req = self.request(Method::GET, "/workouts/count");
+        self.send_json(req, "GET /workouts/count", "workout count")
             .await
-            .context("Failed to parse updated routine response")
     }
 
-    // ── Exercise Templates ────────────────────────────
-
-    /// GET /v1/exercise_templates — paginated list.
-    pub async fn list_exercise_templates(
+    async fn workout_events(
         &self,
         page: u32,
         page_size: u32,
-    ) -> Result<ExerciseTemplatesPage> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/exercise_templates"))
-            .header("api-key", &self.api_key)
-            .query(&[("page", page), ("pageSize", page_size)])
-            .send()
-            .await
-            .context("Failed to send request to GET /exercise_templates")?;
+        since: Option<Timestamp>,
+    ) -> Result<PaginatedWorkoutEvents> {
+        let mut req = self
+            .request(Method::GET, "/workouts/events")
+            .query(&[("page", page), ("pageSize", page_size)]);
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /exercise_templates returned {status}: {body}");
+        if let Some(since) = since {
+            req = req.query(&[("since", since.to_string())]);
         }
 
-        resp.json::<ExerciseTemplatesPage>()
+        self.send_json(req, "GET /workouts/events", "workout events")
             .await
-            .context("Failed to parse exercise templates response")
     }
 
-    /// GET /v1/exercise_templates/{id} — single template by ID.
-    pub async fn get_exercise_template(&self, template_id: &str) -> Result<ExerciseTemplate> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/exercise_templates/{template_id}"))
-            .header("api-key", &self.api_key)
-            .send()
-            .await
-            .context("Failed to send request to GET /exercise_templates/{id}")?;
+    // ── Routines ──────────────────────────────────────
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /exercise_templates/{template_id} returned {status}: {body}");
-        }
+    async fn list_routines(&self, page: u32, page_size: u32) -> Result<RoutinesPage> {
+        let req = self
+            .request(Method::GET, "/routines")
+            .query(&[("page", page), ("pageSize", page_size)]);
+        self.send_json(req, "GET /routines", "routines").await
+    }
 
-        resp.json::<ExerciseTemplate>()
+    async fn get_routine(&self, routine_id: &str) -> Result<SingleRoutineResponse> {
+        let req = self.request(Method::GET, &format!("/routines/{routine_id}"));
+        self.send_json(req, &format!("GET /routines/{routine_id}"), "routine")
             .await
-            .context("Failed to parse exercise template response")
     }
 
-    /// POST /v1/exercise_templates — create a custom exercise template.
-    pub async fn create_exercise_template(
-        &self,
-        body: &CreateExerciseBody,
-    ) -> Result<CreateExerciseResponse> {
-        let resp = self
-            .client
-            .post(format!("{BASE_URL}/exercise_templates"))
-            .header("api-key", &self.api_key)
-            .json(body)
-            .send()
+    async fn create_routine(&self, body: &PostRoutineBody) -> Result<Routine> {
+        let req = self.request(Method::POST, "/routines").json(body);
+        self.send_json(req, "POST /routines", "created routine")
             .await
-            .context("Failed to send request to POST /exercise_templates")?;
-
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("POST /exercise_templates returned {status}: {body}");
-        }
+    }
 
-        resp.json::<CreateExerciseResponse>()
-            .await
-            .context("Failed to parse create exercise template response")
+    async fn update_routine(&self, routine_id: &str, body: &PutRoutineBody) -> Result<Routine> {
+        let req = self
+            .request(Method::PUT, &format!("/routines/{routine_id}"))
+            .json(body);
+        self.send_json(
+            req,
+            &format!("PUT /routines/{routine_id}"),
+            "updated routine",
+        )
+        .await
     }
 
-    // ── Routine Folders ───────────────────────────────
+    // ── Exercise Templates ────────────────────────────
 
-    /// GET /v1/routine_folders — paginated list.
-    pub async fn list_routine_folders(
+    async fn list_exercise_templates(
         &self,
         page: u32,
         page_size: u32,
-    ) -> Result<RoutineFoldersPage> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/routine_folders"))
-            .header("api-key", &self.api_key)
-            .query(&[("page", page), ("pageSize", page_size)])
-            .send()
+    ) -> Result<ExerciseTemplatesPage> {
+        let req = self
+            .request(Method::GET, "/exercise_templates")
+            .query(&[("page", page), ("pageSize", page_size)]);
+        self.send_json(req, "GET /exercise_templates", "exercise templates")
             .await
-            .context("Failed to send request to GET /routine_folders")?;
-
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /routine_folders returned {status}: {body}");
-        }
+    }
 
-        resp.json::<RoutineFoldersPage>()
-            .await
-            .context("Failed to parse routine folders response")
+    /// The cached exercise templates, if the client's [`CachePolicy`] allows
+    /// using them.
+    fn cached_exercise_templates(&self) -> Option<Vec<ExerciseTemplate>> {
+        cache::load_exercise_templates(&self.api_key)
+            .filter(|cached| cached.is_fresh(self.exercise_cache_policy))
+            .map(|cached| cached.exercise_templates)
     }
 
-    /// GET /v1/routine_folders/{id} — single folder by ID.
-    pub async fn get_routine_folder(&self, folder_id: &str) -> Result<RoutineFolder> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/routine_folders/{folder_id}"))
-            .header("api-key", &self.api_key)
-            .send()
-            .await
-            .context("Failed to send request to GET /routine_folders/{id}")?;
+    /// Fetch every exercise template, served from the local disk cache when
+    /// the client's [`CachePolicy`] allows it.
+    async fn all_exercise_templates(&self) -> Result<Vec<ExerciseTemplate>> {
+        if let Some(templates) = self.cached_exercise_templates() {
+            return Ok(templates);
+        }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /routine_folders/{folder_id} returned {status}: {body}");
+        let templates = self
+            .exercise_template_pages()
+            .map_ok(|page| page.items)
+            .try_concat()
+            .await?;
+        let templates = dedup_by_id(templates);
+
+        if self.exercise_cache_policy != CachePolicy::Never
+            && let Err(e) = cache::store_exercise_templates(&self.api_key, &templates)
+        {
+            tracing::warn!("{}", error::report(&e));
         }
+        Ok(templates)
+    }
 
-        resp.json::<RoutineFolder>()
-            .await
-            .context("Failed to parse routine folder response")
+    async fn get_exercise_template(&self, template_id: &str) -> Result<ExerciseTemplate> {
+        let req = self.request(Method::GET, &format!("/exercise_templates/{template_id}"));
+        self.send_json(
+            req,
+            &format!("GET /exercise_templates/{template_id}"),
+            "exercise template",
+        )
+        .await
     }
 
-    /// POST /v1/routine_folders — create a new routine folder.
-    pub async fn create_routine_folder(
+    async fn create_exercise_template(
         &self,
-        body: &PostRoutineFolderBody,
-    ) -> Result<RoutineFolder> {
-        let resp = self
-            .client
-            .post(format!("{BASE_URL}/routine_folders"))
-            .header("api-key", &self.api_key)
-            .json(body)
-            .send()
+        body: &CreateExerciseBody,
+    ) -> Result<CreateExerciseResponse> {
+        let req = self.request(Method::POST, "/exercise_templates").json(body);
+        self.send_json(req, "POST /exercise_templates", "create exercise template")
             .await
-            .context("Failed to send request to POST /routine_folders")?;
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("POST /routine_folders returned {status}: {body}");
-        }
+    // ── Routine Folders ───────────────────────────────
 
-        resp.json::<RoutineFolder>()
+    async fn list_routine_folders(&self, page: u32, page_size: u32) -> Result<RoutineFoldersPage> {
+        let req = self
+            .request(Method::GET, "/routine_folders")
+            .query(&[("page", page), ("pageSize", page_size)]);
+        self.send_json(req, "GET /routine_folders", "routine folders")
+            .await
+    }
+
+    async fn get_routine_folder(&self, folder_id: &str) -> Result<RoutineFolder> {
+        let req = self.request(Method::GET, &format!("/routine_folders/{folder_id}"));
+        self.send_json(
+            req,
+            &format!("GET /routine_folders/{folder_id}"),
+            "routine folder",
+        )
+        .await
+    }
+
+    async fn create_routine_folder(&self, body: &PostRoutineFolderBody) -> Result<RoutineFolder> {
+        let req = self.request(Method::POST, "/routine_folders").json(body);
+        self.send_json(req, "POST /routine_folders", "created routine folder")
             .await
-            .context("Failed to parse created routine folder response")
     }
 
     // ── Exercise History ──────────────────────────────
 
-    /// GET /v1/exercise_history/{exerciseTemplateId} — history for a specific exercise.
-    pub async fn exercise_history(
+    async fn exercise_history(
         &self,
         template_id: &str,
         start_date: Option<&str>,
         end_date: Option<&str>,
     ) -> Result<ExerciseHistoryResponse> {
-        let mut req = self
-            .client
-            .get(format!("{BASE_URL}/exercise_history/{template_id}"))
-            .header("api-key", &self.api_key);
+        let mut req = self.request(Method::GET, &format!("/exercise_history/{template_id}"));
 
         if let Some(s) = start_date {
             req = req.query(&[("start_date", s)]);
@@ -431,42 +806,156 @@ impl HevyClient {
             req = req.query(&[("end_date", e)]);
         }
 
-        let resp = req
-            .send()
-            .await
-            .context("Failed to send request to GET /exercise_history/{id}")?;
+        self.send_json(
+            req,
+            &format!("GET /exercise_history/{template_id}"),
+            "exercise history",
+        )
+        .await
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /exercise_history/{template_id} returned {status}: {body}");
-        }
+    // ── User ──────────────────────────────────────────
 
-        resp.json::<ExerciseHistoryResponse>()
-            .await
-            .context("Failed to parse exercise history response")
+    async fn user_info(&self) -> Result<UserInfoResponse> {
+        let req = self.request(Method::GET, "/user/info");
+        self.send_json(req, "GET /user/info", "user info").await
     }
+}
 
-    // ── User ──────────────────────────────────────────
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_uses_the_defaults() {
+        let client = HevyClient::new("0123456789abcdef").unwrap();
+        assert_eq!(client.api_key, "0123456789abcdef");
+        assert_eq!(client.base_url, "https://api.hevyapp.com/v1");
+        assert_eq!(client.max_retries, DEFAULT_MAX_RETRIES);
+        assert_eq!(client.page_concurrency(), DEFAULT_CONCURRENCY);
+        assert_eq!(client.page_size(MAX_PAGE_SIZE), MAX_PAGE_SIZE);
+        assert_eq!(client.exercise_cache_policy, CachePolicy::default());
+        assert_eq!(client.strictness, Strictness::Lenient);
+        assert!(!client.http_cache);
+        let interval = client.limiter.as_ref().unwrap().interval;
+        assert_eq!(interval, Duration::from_secs_f64(1.0 / DEFAULT_MAX_RPS));
+
+        let builder = HevyClient::builder("key".to_string());
+        assert_eq!(builder.timeout, DEFAULT_TIMEOUT);
+        assert_eq!(builder.connect_timeout, DEFAULT_CONNECT_TIMEOUT);
+        assert!(builder.record.is_none() && builder.replay.is_none());
+    }
 
-    /// GET /v1/user/info — authenticated user info.
-    pub async fn user_info(&self) -> Result<UserInfoResponse> {
-        let resp = self
-            .client
-            .get(format!("{BASE_URL}/user/info"))
-            .header("api-key", &self.api_key)
-            .send()
-            .await
-            .context("Failed to send request to GET /user/info")?;
+    #[test]
+    fn builder_settings_are_clamped() {
+        let client = HevyClient::builder("key".to_string())
+            .concurrency(0)
+            .page_size(50)
+            .max_rps(0.0)
+            .build()
+            .unwrap();
+        assert_eq!(client.page_concurrency(), 1);
+        // Capped at each endpoint's own maximum.
+        assert_eq!(client.page_size(MAX_PAGE_SIZE), MAX_PAGE_SIZE);
+        assert_eq!(client.page_size(MAX_EXERCISE_TEMPLATE_PAGE_SIZE), 50);
+        assert!(client.limiter.is_none());
+    }
 
-        let status = resp.status();
-        if !status.is_success() {
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("GET /user/info returned {status}: {body}");
+    #[test]
+    fn zero_rps_means_no_limiter() {
+        assert!(RateLimiter::new(0.0).is_none());
+        let limiter = RateLimiter::new(4.0).unwrap();
+        assert_eq!(limiter.interval, Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn requests_are_spaced_by_the_interval() {
+        // 50 per second: one slot every 20ms, the first one free.
+        let limiter = RateLimiter::new(50.0).unwrap();
+        let started = Instant::now();
+        let mut slots = Vec::new();
+        for _ in 0..6 {
+            limiter.acquire().await;
+            slots.push(started.elapsed());
+        }
+        assert!(slots[0] < Duration::from_millis(15), "{slots:?}");
+        for pair in slots.windows(2) {
+            assert!(pair[1] - pair[0] >= Duration::from_millis(19), "{slots:?}");
         }
+        assert!(slots[5] >= Duration::from_millis(100), "{slots:?}");
+    }
 
-        resp.json::<UserInfoResponse>()
-            .await
-            .context("Failed to parse user info response")
+    #[tokio::test]
+    async fn tasks_sharing_a_limiter_take_turns() {
+        let limiter = Arc::new(RateLimiter::new(50.0).unwrap());
+        let started = Instant::now();
+        let tasks: Vec<_> = (0..3)
+            .map(|_| {
+                let limiter = Arc::clone(&limiter);
+                tokio::spawn(async move {
+                    for _ in 0..2 {
+                        limiter.acquire().await;
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        // Six slots in all, as if one task had made every request.
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn an_idle_limiter_does_not_save_up_slots() {
+        let limiter = RateLimiter::new(50.0).unwrap();
+        limiter.acquire().await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let started = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        // The first is free again, but the second still waits its turn.
+        assert!(started.elapsed() >= Duration::from_millis(19));
+    }
+
+    #[test]
+    fn clones_keep_the_settings_and_share_the_pacing() {
+        let client = HevyClient::builder("0123456789abcdef".to_string())
+            .base_url("http://127.0.0.1:9/v1")
+            .max_retries(1)
+            .build()
+            .unwrap();
+        let clone = client.clone();
+        assert_eq!(clone.api_key, "0123456789abcdef");
+        assert_eq!(clone.base_url, "http://127.0.0.1:9/v1");
+        assert_eq!(clone.max_retries, 1);
+        assert!(Arc::ptr_eq(&client.throttled_until, &clone.throttled_until));
+        assert!(Arc::ptr_eq(
+            client.limiter.as_ref().unwrap(),
+            clone.limiter.as_ref().unwrap()
+        ));
+    }
+
+    #[test]
+    fn debug_never_shows_the_key() {
+        let debug = |key: &str| format!("{:?}", HevyClient::new(key).unwrap());
+
+        let long = debug("a1b2c3d4-0000-4000-8000-00000000beef");
+        assert!(long.contains(r#"api_key: "****beef""#), "{long}");
+        assert!(!long.contains("a1b2c3d4"), "{long}");
+        assert!(long.contains(r#"base_url: "https://api.hevyapp.com/v1""#));
+
+        // Four characters or fewer reveal nothing at all.
+        for key in ["wxyz", "q9", "Q"] {
+            let short = debug(key);
+            assert!(short.contains(r#"api_key: "****""#), "{short}");
+            assert!(!short.contains(key), "{short}");
+        }
+        let five = debug("abcde");
+        assert!(five.contains(r#"api_key: "****bcde""#), "{five}");
+        assert!(!five.contains("abcde"), "{five}");
+        // Counted in characters, so a multibyte key is cut cleanly.
+        let wide = debug("ключ-ёжик");
+        assert!(wide.contains(r#"api_key: "****ёжик""#), "{wide}");
     }
 }
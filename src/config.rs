@@ -0,0 +1,906 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::output::OutputFormat;
+use crate::table::{ColorChoice, Units};
+
+/// Profile used when none is selected and no default has been chosen.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Page size used by list commands when neither `--page-size` nor a stored
+/// preference is given.
+pub const DEFAULT_PAGE_SIZE: u32 = 5;
+
+/// Largest page size a stored preference may hold (the exercise template
+/// limit); other endpoints cap it lower.
+pub const MAX_DEFAULT_PAGE_SIZE: u32 = 100;
+
+/// Layout version of the config file written by this build. Files with an
+/// older (or no) `version` are upgraded by [`migrate_config_if_needed`].
+pub const CONFIG_VERSION: u32 = 1;
+
+/// ~/.config/hevy-bridge/config.json
+pub fn config_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("hevy-bridge")
+        .join("config.json")
+}
+
+/// On-disk configuration.
+///
+/// Files from older versions are migrated to [`CONFIG_VERSION`] when
+/// loaded, so the next save writes the current layout.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    version: u32,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub defaults: Defaults,
+    /// Short names for exercise templates (`alias set`), lowercase, to
+    /// template IDs.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            version: CONFIG_VERSION,
+            profiles: BTreeMap::new(),
+            default_profile: None,
+            defaults: Defaults::default(),
+            aliases: BTreeMap::new(),
+        }
+    }
+}
+
+/// Values used when the matching flag and environment variable are unset.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Defaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub units: Option<Units>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<OutputFormat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorChoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rps: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bodyweight_kg: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub plates: Option<Vec<f64>>,
+}
+
+/// A setting in the `defaults` section (`config set/get <KEY>`).
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DefaultKey {
+    /// kg or lbs, for weights in tables (--units).
+    Units,
+    /// json, table, ndjson or ics (--format).
+    Format,
+    /// Items per page for list commands, 1-100 (--page-size).
+    #[value(name = "page_size", alias = "page-size")]
+    PageSize,
+    /// auto, always or never (--color).
+    Color,
+    /// API requests per second, 0 for no limit (--max-rps).
+    #[value(name = "max_rps", alias = "max-rps")]
+    MaxRps,
+    /// true or false, whether to cache API responses on disk (--cache).
+    Cache,
+    /// Body weight in kg for strength scores (--bodyweight-kg).
+    #[value(name = "bodyweight_kg", alias = "bodyweight-kg")]
+    BodyweightKg,
+    /// Plate sizes on hand, comma-separated, in the units in use (plates
+    /// --available).
+    Plates,
+}
+
+/// Settings for one Hevy account.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+impl Config {
+    /// Read the config file, migrating older layouts in memory. A missing
+    /// or unreadable file gives an empty config.
+    pub fn load() -> Self {
+        Self::load_from(&config_path())
+    }
+
+    /// [`Config::load`] from the file at `path`.
+    pub fn load_from(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .and_then(|mut value| {
+                // A file from a newer build is read as well as it can be.
+                let _ = migrate(&mut value);
+                serde_json::from_value(value).ok()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&config_path())
+    }
+
+    /// Write the config to `path`, creating its directory if needed.
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .context("Failed to write config file")?;
+        Ok(())
+    }
+
+    /// The profile commands use when `--profile` is not given.
+    pub fn active_profile(&self) -> &str {
+        self.default_profile.as_deref().unwrap_or(DEFAULT_PROFILE)
+    }
+
+    /// Store `key` in `profile` (the active profile if `None`), returning the
+    /// profile's name. A `None` key records the profile without a key in the
+    /// file, for keys kept in the OS keyring. The first profile created
+    /// becomes the default.
+    pub fn set_api_key(&mut self, profile: Option<&str>, key: Option<&str>) -> String {
+        let name = profile.unwrap_or(self.active_profile()).to_string();
+        self.default_profile.get_or_insert_with(|| name.clone());
+        self.profiles.entry(name.clone()).or_default().api_key = key.map(str::to_string);
+        name
+    }
+
+    /// Store `value` as the default for `key`, rejecting values the matching
+    /// flag would not accept.
+    pub fn set_default(&mut self, key: DefaultKey, value: &str) -> Result<()> {
+        let defaults = &mut self.defaults;
+        match key {
+            DefaultKey::Units => defaults.units = Some(parse_value(value)?),
+            DefaultKey::Format => {
+                let format = parse_value(value)?;
+                if format == OutputFormat::Ics {
+                    anyhow::bail!(
+                        "Invalid format \"{value}\": ics is only for `workouts export --format ics`"
+                    );
+                }
+                defaults.format = Some(format);
+            }
+            DefaultKey::Color => defaults.color = Some(parse_value(value)?),
+            DefaultKey::PageSize => {
+                let size: u32 = value
+                    .parse()
+                    .ok()
+                    .filter(|size| (1..=MAX_DEFAULT_PAGE_SIZE).contains(size))
+                    .with_context(|| {
+                        format!("Invalid page_size \"{value}\": expected 1-{MAX_DEFAULT_PAGE_SIZE}")
+                    })?;
+                defaults.page_size = Some(size);
+            }
+            DefaultKey::MaxRps => defaults.max_rps = Some(parse_max_rps(value)?),
+            DefaultKey::Cache => {
+                let enabled = value.parse().ok().with_context(|| {
+                    format!("Invalid cache \"{value}\": expected true or false")
+                })?;
+                defaults.cache = Some(enabled);
+            }
+            DefaultKey::BodyweightKg => {
+                let kg: f64 = value
+                    .parse()
+                    .ok()
+                    .filter(|kg: &f64| kg.is_finite() && *kg > 0.0)
+                    .with_context(|| {
+                        format!("Invalid bodyweight_kg \"{value}\": expected a weight in kg")
+                    })?;
+                defaults.bodyweight_kg = Some(kg);
+            }
+            DefaultKey::Plates => {
+                let plates: Vec<f64> = value
+                    .split(',')
+                    .map(|plate| {
+                        plate
+                            .trim()
+                            .parse()
+                            .ok()
+                            .filter(|p: &f64| p.is_finite() && *p > 0.0)
+                    })
+                    .collect::<Option<_>>()
+                    .with_context(|| {
+                        format!("Invalid plates \"{value}\": expected weights such as 25,20,10,5")
+                    })?;
+                defaults.plates = Some(plates);
+            }
+        }
+        Ok(())
+    }
+
+    /// The stored default for `key`, as `config set` would accept it.
+    pub fn get_default(&self, key: DefaultKey) -> Option<String> {
+        let defaults = &self.defaults;
+        match key {
+            DefaultKey::Units => defaults.units.as_ref().map(value_name),
+            DefaultKey::Format => defaults.format.as_ref().map(value_name),
+            DefaultKey::Color => defaults.color.as_ref().map(value_name),
+            DefaultKey::PageSize => defaults.page_size.map(|size| size.to_string()),
+            DefaultKey::MaxRps => defaults.max_rps.map(|rps| rps.to_string()),
+            DefaultKey::Cache => defaults.cache.map(|enabled| enabled.to_string()),
+            DefaultKey::BodyweightKg => defaults.bodyweight_kg.map(|kg| kg.to_string()),
+            DefaultKey::Plates => defaults.plates.as_ref().map(|plates| {
+                let plates: Vec<String> = plates.iter().map(f64::to_string).collect();
+                plates.join(",")
+            }),
+        }
+    }
+
+    /// Make `profile` the default for later commands.
+    pub fn use_profile(&mut self, profile: &str) -> Result<()> {
+        if !self.profiles.contains_key(profile) {
+            anyhow::bail!(
+                "No profile named \"{profile}\". Create it with \
+                 `hevy-bridge config set-key --profile {profile} <KEY>`"
+            );
+        }
+        self.default_profile = Some(profile.to_string());
+        Ok(())
+    }
+
+    fn stored_api_key(&self, profile: &str) -> Option<&str> {
+        self.profiles.get(profile)?.api_key.as_deref()
+    }
+
+    /// Remove `profile`'s key from the file, returning whether it had one.
+    pub fn remove_api_key(&mut self, profile: &str) -> bool {
+        self.profiles
+            .get_mut(profile)
+            .and_then(|p| p.api_key.take())
+            .is_some()
+    }
+
+    /// Make `name` (case-insensitive) an alias for `template_id`. An alias
+    /// already pointing at a different template is an error.
+    pub fn set_alias(&mut self, name: &str, template_id: &str) -> Result<()> {
+        let name = name.trim().to_lowercase();
+        if name.is_empty() || name.contains(',') {
+            anyhow::bail!("Invalid alias \"{name}\": it must be non-empty and contain no commas");
+        }
+        match self.aliases.get(&name) {
+            Some(existing) if existing != template_id => anyhow::bail!(
+                "Alias \"{name}\" already points to {existing}. Remove it first with \
+                 `hevy-bridge alias rm {name}`"
+            ),
+            _ => {
+                self.aliases.insert(name, template_id.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// The template ID `name` is an alias for, if it is one.
+    pub fn alias(&self, name: &str) -> Option<&str> {
+        self.aliases
+            .get(&name.trim().to_lowercase())
+            .map(String::as_str)
+    }
+
+    /// Aliases keyed by template ID, for display. A template with several
+    /// aliases shows the first in alphabetical order.
+    pub fn aliases_by_template(&self) -> HashMap<String, String> {
+        let mut by_template = HashMap::new();
+        for (name, id) in &self.aliases {
+            by_template
+                .entry(id.clone())
+                .or_insert_with(|| name.clone());
+        }
+        by_template
+    }
+
+    /// Remove the alias `name`, returning whether it existed.
+    pub fn remove_alias(&mut self, name: &str) -> bool {
+        self.aliases.remove(&name.trim().to_lowercase()).is_some()
+    }
+}
+
+/// Upgrade steps; `MIGRATIONS[n]` turns a version `n` file into version
+/// `n + 1`.
+const MIGRATIONS: [fn(&mut Map<String, Value>); CONFIG_VERSION as usize] = [migrate_v0_to_v1];
+
+/// Upgrade the config file at `path` to [`CONFIG_VERSION`] in place,
+/// returning whether it changed. A missing file needs no migration.
+pub fn migrate_config_if_needed(path: &Path) -> Result<bool> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", path.display()));
+        }
+    };
+    let mut value: Value = serde_json::from_str(&data)
+        .with_context(|| format!("{} is not valid JSON", path.display()))?;
+    let Some(from) = migrate(&mut value)? else {
+        return Ok(false);
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&value)?)
+        .context("Failed to write config file")?;
+    eprintln!(
+        "Migrated {} from version {from} to {CONFIG_VERSION}",
+        path.display()
+    );
+    Ok(true)
+}
+
+/// Run the migrations `config` needs, returning the version it had if any
+/// ran. Files written by a newer build are refused.
+fn migrate(config: &mut Value) -> Result<Option<u32>> {
+    let Some(map) = config.as_object_mut() else {
+        anyhow::bail!("Config file is not a JSON object");
+    };
+    let from = match map.get("version") {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .with_context(|| format!("Invalid config version {v}"))?,
+    };
+    if from > CONFIG_VERSION {
+        anyhow::bail!(
+            "Config file version {from} is newer than this hevy-bridge supports \
+             ({CONFIG_VERSION}); upgrade hevy-bridge"
+        );
+    }
+    if from == CONFIG_VERSION {
+        return Ok(None);
+    }
+    for step in &MIGRATIONS[from as usize..] {
+        step(map);
+    }
+    map.insert("version".to_string(), CONFIG_VERSION.into());
+    Ok(Some(from))
+}
+
+/// Version 0 held a single top-level `api_key` and `default_page_size`.
+/// They move into the "default" profile and `defaults.page_size`, without
+/// overwriting values already there.
+fn migrate_v0_to_v1(config: &mut Map<String, Value>) {
+    if let Some(key) = config.remove("api_key").filter(|v| !v.is_null()) {
+        let profiles = object_entry(config, "profiles");
+        let profile = object_entry(profiles, DEFAULT_PROFILE);
+        profile.entry("api_key").or_insert(key);
+        config
+            .entry("default_profile")
+            .or_insert_with(|| DEFAULT_PROFILE.into());
+    }
+    if let Some(size) = config.remove("default_page_size").filter(|v| !v.is_null()) {
+        object_entry(config, "defaults")
+            .entry("page_size")
+            .or_insert(size);
+    }
+}
+
+/// The object under `key`, created (or replacing a non-object) if needed.
+fn object_entry<'a>(map: &'a mut Map<String, Value>, key: &str) -> &'a mut Map<String, Value> {
+    let entry = map.entry(key).or_insert_with(|| Value::Object(Map::new()));
+    if !entry.is_object() {
+        *entry = Value::Object(Map::new());
+    }
+    entry.as_object_mut().expect("just made an object")
+}
+
+/// Where a resolved API key came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    Flag,
+    KeyFile,
+    Env,
+    Keyring,
+    File,
+}
+
+impl fmt::Display for KeySource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KeySource::Flag => "the --api-key flag",
+            KeySource::KeyFile => "the --api-key-file (or HEVY_API_KEY_FILE) file",
+            KeySource::Env => "the HEVY_API_KEY environment variable",
+            KeySource::Keyring => "the OS keyring",
+            KeySource::File => "the config file",
+        })
+    }
+}
+
+/// How to supply an API key, for messages about a missing or rejected one.
+pub const KEY_HELP: &str = "Supply one via:\n  \
+    1. --api-key <KEY>\n  \
+    2. HEVY_API_KEY environment variable\n  \
+    3. `hevy-bridge config set-key <KEY>` to persist it";
+
+/// An API key together with where it was found.
+#[derive(Debug, Clone)]
+pub struct ApiKey {
+    pub key: String,
+    pub source: KeySource,
+}
+
+impl ApiKey {
+    /// The key redacted to its last four characters.
+    pub fn masked(&self) -> String {
+        let chars: Vec<char> = self.key.chars().collect();
+        if chars.len() <= 4 {
+            return "*".repeat(chars.len());
+        }
+        let tail: String = chars[chars.len() - 4..].iter().collect();
+        format!("…{tail}")
+    }
+}
+
+/// `profile`'s stored key: the OS keyring first, then the config file.
+pub fn stored_key(config: &Config, profile: &str) -> Option<ApiKey> {
+    if let Some(key) = keyring::get(profile) {
+        return Some(ApiKey {
+            key,
+            source: KeySource::Keyring,
+        });
+    }
+    config.stored_api_key(profile).map(|key| ApiKey {
+        key: key.to_string(),
+        source: KeySource::File,
+    })
+}
+
+/// The global flags that choose an API key.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeyArgs<'a> {
+    /// --api-key
+    pub api_key: Option<&'a str>,
+    /// --api-key-file (or HEVY_API_KEY_FILE)
+    pub api_key_file: Option<&'a Path>,
+    /// --profile (or HEVY_PROFILE)
+    pub profile: Option<&'a str>,
+}
+
+/// Resolve the API key from (in priority order):
+///   1. --api-key flag
+///   2. --api-key-file (or HEVY_API_KEY_FILE): the file's contents
+///   3. --profile (or HEVY_PROFILE): that profile's stored key
+///   4. HEVY_API_KEY environment variable
+///   5. The default profile's key in the OS keyring
+///   6. The default profile's key in the config file
+pub fn resolve_api_key(args: &KeyArgs) -> Result<String> {
+    find_api_key(args).map(|k| k.key)
+}
+
+/// Like [`resolve_api_key`], also reporting where the key came from.
+pub fn find_api_key(args: &KeyArgs) -> Result<ApiKey> {
+    if let Some(key) = args.api_key {
+        return Ok(ApiKey {
+            key: key.to_string(),
+            source: KeySource::Flag,
+        });
+    }
+    if let Some(path) = args.api_key_file {
+        return Ok(ApiKey {
+            key: read_key_file(path)?,
+            source: KeySource::KeyFile,
+        });
+    }
+    let config = Config::load();
+    if let Some(profile) = args.profile {
+        return stored_key(&config, profile).with_context(|| {
+            format!(
+                "No API key stored for profile \"{profile}\". Save one with \
+                 `hevy-bridge config set-key --profile {profile} <KEY>`"
+            )
+        });
+    }
+    if let Ok(key) = std::env::var("HEVY_API_KEY")
+        && !key.is_empty()
+    {
+        return Ok(ApiKey {
+            key,
+            source: KeySource::Env,
+        });
+    }
+    if let Some(key) = stored_key(&config, config.active_profile()) {
+        return Ok(key);
+    }
+    anyhow::bail!("No API key provided. {KEY_HELP}")
+}
+
+/// Read an API key from `path`, ignoring surrounding whitespace such as the
+/// trailing newline most editors and secret mounts leave behind.
+fn read_key_file(path: &Path) -> Result<String> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read API key file {}", path.display()))?;
+    let key = contents.trim();
+    if key.is_empty() {
+        anyhow::bail!(
+            "API key file {} is empty; it should contain only the key",
+            path.display()
+        );
+    }
+    Ok(key.to_string())
+}
+
+/// API keys in the OS keyring, stored under the service "hevy-bridge" with
+/// the profile name as the user. Builds without the `keyring` feature see
+/// an empty keyring.
+pub mod keyring {
+    use anyhow::Result;
+
+    #[cfg(feature = "keyring")]
+    const SERVICE: &str = "hevy-bridge";
+
+    /// The key stored for `profile`, if the keyring is available and has one.
+    pub fn get(profile: &str) -> Option<String> {
+        #[cfg(feature = "keyring")]
+        {
+            ::keyring::Entry::new(SERVICE, profile)
+                .ok()?
+                .get_password()
+                .ok()
+        }
+        #[cfg(not(feature = "keyring"))]
+        {
+            let _ = profile;
+            None
+        }
+    }
+
+    pub fn set(profile: &str, key: &str) -> Result<()> {
+        #[cfg(feature = "keyring")]
+        {
+            use anyhow::Context;
+            ::keyring::Entry::new(SERVICE, profile)
+                .and_then(|entry| entry.set_password(key))
+                .context("Failed to store the API key in the OS keyring")
+        }
+        #[cfg(not(feature = "keyring"))]
+        {
+            let _ = (profile, key);
+            anyhow::bail!(
+                "This build has no keyring support; rebuild with `--features keyring` \
+                 or store the key in the config file"
+            )
+        }
+    }
+
+    /// Delete the key stored for `profile`, returning whether there was one.
+    pub fn delete(profile: &str) -> Result<bool> {
+        #[cfg(feature = "keyring")]
+        {
+            use anyhow::Context;
+            match ::keyring::Entry::new(SERVICE, profile).and_then(|e| e.delete_credential()) {
+                Ok(()) => Ok(true),
+                Err(::keyring::Error::NoEntry) => Ok(false),
+                Err(e) => Err(e).context("Failed to delete the API key from the OS keyring"),
+            }
+        }
+        #[cfg(not(feature = "keyring"))]
+        {
+            let _ = profile;
+            Ok(false)
+        }
+    }
+}
+
+fn parse_value<T: ValueEnum>(value: &str) -> Result<T> {
+    T::from_str(value, true).map_err(|_| {
+        let allowed: Vec<String> = T::value_variants().iter().map(value_name).collect();
+        anyhow::anyhow!(
+            "Invalid value \"{value}\": expected one of {}",
+            allowed.join(", ")
+        )
+    })
+}
+
+/// The name clap accepts for `value`.
+pub fn value_name<T: ValueEnum>(value: &T) -> String {
+    value
+        .to_possible_value()
+        .map(|v| v.get_name().to_string())
+        .unwrap_or_default()
+}
+
+/// Parse a requests-per-second limit: a non-negative number, 0 meaning
+/// unlimited.
+pub fn parse_max_rps(value: &str) -> Result<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|rps| rps.is_finite() && *rps >= 0.0)
+        .with_context(|| format!("Invalid max_rps \"{value}\": expected a number, 0 for no limit"))
+}
+
+/// Resolve a list command's page size from (in priority order):
+///   1. --page-size flag
+///   2. HEVY_PAGE_SIZE environment variable
+///   3. `defaults.page_size` in the config file
+///   4. DEFAULT_PAGE_SIZE
+///
+/// Stored and environment values are capped at the resource's `max` so one
+/// preference works for every list command.
+pub fn resolve_page_size(flag: Option<u32>, max: u32) -> u32 {
+    if let Some(size) = flag {
+        return size;
+    }
+    std::env::var("HEVY_PAGE_SIZE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .or(Config::load().defaults.page_size)
+        .filter(|&size| size > 0)
+        .map(|size| size.min(max))
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn key(key: &str) -> ApiKey {
+        ApiKey {
+            key: key.to_string(),
+            source: KeySource::File,
+        }
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hevy-bridge").join("config.json");
+
+        let mut config = Config::default();
+        config.set_api_key(Some("me"), Some("key-of-me-1111"));
+        config.set_api_key(Some("partner"), Some("key-of-partner-2222"));
+        config.set_default(DefaultKey::Units, "lbs").unwrap();
+        config.set_default(DefaultKey::PageSize, "10").unwrap();
+        config
+            .set_default(DefaultKey::Plates, "20, 10,2.5")
+            .unwrap();
+        config.set_alias("Bench", "D04AC939").unwrap();
+        config.save_to(&path).unwrap();
+
+        let loaded = Config::load_from(&path);
+        assert_eq!(loaded.version, CONFIG_VERSION);
+        assert_eq!(loaded.active_profile(), "me");
+        assert_eq!(loaded.stored_api_key("me"), Some("key-of-me-1111"));
+        assert_eq!(
+            loaded.stored_api_key("partner"),
+            Some("key-of-partner-2222")
+        );
+        assert_eq!(
+            loaded.get_default(DefaultKey::Units).as_deref(),
+            Some("lbs")
+        );
+        assert_eq!(
+            loaded.get_default(DefaultKey::PageSize).as_deref(),
+            Some("10")
+        );
+        assert_eq!(
+            loaded.get_default(DefaultKey::Plates).as_deref(),
+            Some("20,10,2.5")
+        );
+        assert_eq!(loaded.get_default(DefaultKey::Format), None);
+        assert_eq!(loaded.alias("bench"), Some("D04AC939"));
+
+        // Saving what was loaded writes the same file.
+        let first = std::fs::read_to_string(&path).unwrap();
+        loaded.save_to(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), first);
+    }
+
+    #[test]
+    fn unset_values_are_left_out_of_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        Config::default().save_to(&path).unwrap();
+
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(
+            written,
+            json!({ "version": CONFIG_VERSION, "defaults": {} })
+        );
+    }
+
+    #[test]
+    fn missing_or_broken_file_loads_as_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = Config::load_from(&dir.path().join("config.json"));
+        assert!(missing.profiles.is_empty());
+        assert_eq!(missing.active_profile(), DEFAULT_PROFILE);
+
+        let path = dir.path().join("broken.json");
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(Config::load_from(&path).profiles.is_empty());
+    }
+
+    #[test]
+    fn removed_key_stays_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let mut config = Config::default();
+        config.set_api_key(None, Some("key-of-me-1111"));
+        assert!(config.remove_api_key(DEFAULT_PROFILE));
+        assert!(!config.remove_api_key(DEFAULT_PROFILE));
+        config.save_to(&path).unwrap();
+
+        let loaded = Config::load_from(&path);
+        assert_eq!(loaded.stored_api_key(DEFAULT_PROFILE), None);
+        // The profile itself is kept, so it can still be selected.
+        assert!(loaded.profiles.contains_key(DEFAULT_PROFILE));
+    }
+
+    #[test]
+    fn masked_keeps_only_the_last_four_characters() {
+        assert_eq!(
+            key("a1b2c3d4-0000-4000-8000-00000000beef").masked(),
+            "…beef"
+        );
+        assert_eq!(key("12345").masked(), "…2345");
+        // Keys of four characters or fewer show nothing at all.
+        assert_eq!(key("1234").masked(), "****");
+        assert_eq!(key("ab").masked(), "**");
+        assert_eq!(key("").masked(), "");
+        // Counted in characters, not bytes.
+        assert_eq!(key("ключ-ёжик").masked(), "…ёжик");
+    }
+
+    #[test]
+    fn key_file_resolves_like_the_flag() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hevy-api-key");
+        std::fs::write(&path, "key\n").unwrap();
+
+        let from_file = find_api_key(&KeyArgs {
+            api_key_file: Some(&path),
+            ..Default::default()
+        })
+        .unwrap();
+        let from_flag = resolve_api_key(&KeyArgs {
+            api_key: Some("key"),
+            ..Default::default()
+        })
+        .unwrap();
+        assert_eq!(from_file.key, from_flag);
+        assert_eq!(from_file.source, KeySource::KeyFile);
+
+        // CRLF and surrounding blanks are trimmed too.
+        std::fs::write(&path, "  key\r\n\n").unwrap();
+        assert_eq!(read_key_file(&path).unwrap(), "key");
+    }
+
+    #[test]
+    fn key_file_errors_name_the_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty-key");
+        std::fs::write(&empty, "\n").unwrap();
+        let err = read_key_file(&empty).unwrap_err().to_string();
+        assert!(err.contains(&empty.display().to_string()), "{err}");
+        assert!(err.contains("is empty"), "{err}");
+
+        let missing = dir.path().join("missing-key");
+        let err = read_key_file(&missing).unwrap_err().to_string();
+        assert!(err.contains(&missing.display().to_string()), "{err}");
+    }
+
+    /// A copy of `tests/fixtures/config/<name>` in a fresh directory.
+    fn fixture(name: &str) -> (tempfile::TempDir, PathBuf) {
+        let source = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/config")
+            .join(name);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::copy(&source, &path).unwrap();
+        (dir, path)
+    }
+
+    fn read(path: &Path) -> Value {
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn v0_key_moves_into_the_default_profile() {
+        let (_dir, path) = fixture("v0_key_only.json");
+        assert!(migrate_config_if_needed(&path).unwrap());
+        assert_eq!(
+            read(&path),
+            json!({
+                "version": 1,
+                "profiles": { "default": { "api_key": "a1b2c3d4-0000-4000-8000-00000000abcd" } },
+                "default_profile": "default"
+            })
+        );
+        // Migrating again finds nothing to do.
+        assert!(!migrate_config_if_needed(&path).unwrap());
+    }
+
+    #[test]
+    fn v0_page_size_moves_into_defaults_and_the_rest_is_kept() {
+        let (_dir, path) = fixture("v0_full.json");
+        assert!(migrate_config_if_needed(&path).unwrap());
+        let migrated = read(&path);
+        assert_eq!(migrated["defaults"], json!({ "page_size": 10 }));
+        assert_eq!(migrated["aliases"], json!({ "bench": "79D0BB3A" }));
+        assert!(migrated.get("api_key").is_none());
+        assert!(migrated.get("default_page_size").is_none());
+
+        let config = Config::load_from(&path);
+        assert_eq!(
+            config.stored_api_key(DEFAULT_PROFILE),
+            Some("a1b2c3d4-0000-4000-8000-00000000abcd")
+        );
+        assert_eq!(
+            config.get_default(DefaultKey::PageSize).as_deref(),
+            Some("10")
+        );
+        assert_eq!(config.alias("bench"), Some("79D0BB3A"));
+    }
+
+    #[test]
+    fn v0_migration_keeps_values_already_in_the_new_places() {
+        let (_dir, path) = fixture("v0_partly_migrated.json");
+        assert!(migrate_config_if_needed(&path).unwrap());
+        assert_eq!(
+            read(&path),
+            json!({
+                "version": 1,
+                "profiles": { "default": { "api_key": "new-key-9999" } },
+                "default_profile": "work",
+                "defaults": { "units": "kg" }
+            })
+        );
+    }
+
+    #[test]
+    fn current_version_is_left_alone() {
+        let (_dir, path) = fixture("v1.json");
+        let before = std::fs::read_to_string(&path).unwrap();
+        assert!(!migrate_config_if_needed(&path).unwrap());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn newer_version_is_refused_and_not_touched() {
+        let (_dir, path) = fixture("v99.json");
+        let before = std::fs::read_to_string(&path).unwrap();
+        let err = migrate_config_if_needed(&path).unwrap_err().to_string();
+        assert!(err.contains("version 99 is newer"), "{err}");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn loading_migrates_in_memory_only() {
+        let (dir, path) = fixture("v0_key_only.json");
+        let before = std::fs::read_to_string(&path).unwrap();
+        let config = Config::load_from(&path);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.active_profile(), DEFAULT_PROFILE);
+        assert_eq!(
+            config.stored_api_key(DEFAULT_PROFILE),
+            Some("a1b2c3d4-0000-4000-8000-00000000abcd")
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), before);
+
+        assert!(!migrate_config_if_needed(&dir.path().join("missing.json")).unwrap());
+    }
+}
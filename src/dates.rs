@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Days, NaiveDate, SubsecRound, Utc};
+use hevy_bridge::models::Timestamp;
+
+/// Parse a date given on the command line as the start of a range.
+///
+/// Accepts RFC 3339 (`2024-01-15T00:00:00Z`) or a plain `YYYY-MM-DD`,
+/// which is taken as midnight UTC.
+pub fn parse_start(s: &str) -> Result<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    let date = parse_plain_date(s)?;
+    Ok(date.and_time(chrono::NaiveTime::MIN).and_utc())
+}
+
+/// Parse a date given on the command line as the (exclusive) end of a range.
+///
+/// A plain `YYYY-MM-DD` covers the whole day, so it resolves to the
+/// following midnight UTC.
+pub fn parse_end(s: &str) -> Result<DateTime<Utc>> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    let next = parse_plain_date(s)?
+        .checked_add_days(Days::new(1))
+        .context("Date out of range")?;
+    Ok(next.and_time(chrono::NaiveTime::MIN).and_utc())
+}
+
+/// Parse an RFC 3339 timestamp given on the command line, such as the
+/// `--since` of workout events, which is passed to the API as is.
+pub fn parse_timestamp(s: &str) -> Result<Timestamp> {
+    Ok(Timestamp::try_from(s)?)
+}
+
+/// Parse an IANA time zone name such as `Europe/Berlin`.
+pub fn parse_time_zone(s: &str) -> Result<chrono_tz::Tz> {
+    s.trim().parse().map_err(|_| {
+        anyhow::anyhow!("Unknown time zone \"{s}\". Use an IANA name, e.g. Europe/Berlin")
+    })
+}
+
+/// A `start..end` range of time given on the command line.
+#[derive(Debug, Clone, Copy)]
+pub struct DateRange {
+    pub start: DateTime<Utc>,
+    /// Exclusive.
+    pub end: DateTime<Utc>,
+}
+
+/// Parse a range such as `2024-01-01..2024-02-28`, `8w..4w` or `4w..now`.
+///
+/// Each side is a date as [`parse_start`] and [`parse_end`] take it (so a
+/// plain end date covers that whole day), `now`, or a time before now in
+/// days or weeks (`10d`, `4w`).
+pub fn parse_range(s: &str) -> Result<DateRange> {
+    let (start, end) = s
+        .split_once("..")
+        .with_context(|| format!("Invalid range \"{s}\". Use START..END, e.g. 8w..4w"))?;
+    // Whole seconds, so `8w..4w` and `4w..now` given together meet.
+    let now = Utc::now().trunc_subsecs(0);
+    let start = ago(start.trim(), now).map_or_else(|| parse_start(start.trim()), Ok)?;
+    let end = ago(end.trim(), now).map_or_else(|| parse_end(end.trim()), Ok)?;
+    if end <= start {
+        anyhow::bail!("Invalid range \"{s}\": it ends before it starts");
+    }
+    Ok(DateRange { start, end })
+}
+
+/// `now`, or `now` less a number of days (`10d`) or weeks (`4w`).
+fn ago(s: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if s == "now" {
+        return Some(now);
+    }
+    let (i, unit) = s.char_indices().last()?;
+    let n: u32 = s[..i].parse().ok()?;
+    let days = match unit {
+        'd' => n,
+        'w' => n.checked_mul(7)?,
+        _ => return None,
+    };
+    now.checked_sub_days(Days::new(days.into()))
+}
+
+/// Midnight-to-midnight UTC bounds of the day `days_back` days before today.
+pub fn utc_day_bounds(days_back: u64) -> (DateTime<Utc>, DateTime<Utc>) {
+    let day = Utc::now().date_naive() - Days::new(days_back);
+    let start = day.and_time(chrono::NaiveTime::MIN).and_utc();
+    (start, start + chrono::Duration::days(1))
+}
+
+/// Parse an interval such as `90`, `90s`, `5m`, or `1h` (bare numbers are
+/// seconds). Zero is rejected, as polling without a pause would flood the
+/// API.
+pub fn parse_interval(s: &str) -> Result<std::time::Duration> {
+    let s = s.trim();
+    let (number, unit_secs) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1),
+        Some((i, 'm')) => (&s[..i], 60),
+        Some((i, 'h')) => (&s[..i], 60 * 60),
+        _ => (s, 1),
+    };
+    let invalid = || format!("Invalid interval \"{s}\". Use e.g. 30s, 5m, or 1h");
+    let n: u64 = number.parse().with_context(invalid)?;
+    if n == 0 {
+        anyhow::bail!("Invalid interval \"{s}\": it must be at least 1s");
+    }
+    let secs = n
+        .checked_mul(unit_secs)
+        .with_context(|| format!("{}: it is too long", invalid()))?;
+    Ok(std::time::Duration::from_secs(secs))
+}
+
+fn parse_plain_date(s: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d").with_context(|| {
+        format!("Invalid date \"{s}\". Use YYYY-MM-DD or ISO 8601 (2024-01-15T00:00:00Z)")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn intervals_in_each_unit() {
+        assert_eq!(parse_interval("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_interval(" 90s ").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_interval("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_interval("1h").unwrap(), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn zero_garbage_and_overflowing_intervals_are_rejected() {
+        for s in ["0", "0m", "", "5d", "-1s", "99999999999999999h"] {
+            let err = parse_interval(s).unwrap_err().to_string();
+            assert!(err.starts_with("Invalid interval"), "{s}: {err}");
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Finding workouts that were posted twice, or that overlap in time.
+
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::Serialize;
+
+use crate::models::{Workout, format_timestamp};
+use crate::table::{Column, Style, Table};
+
+/// How far apart two copies of a workout may start.
+const WINDOW: TimeDelta = TimeDelta::minutes(10);
+
+#[derive(Debug, Serialize)]
+pub struct DedupeReport {
+    pub duplicates: Vec<DuplicateGroup>,
+    /// Pairs of workouts whose times overlap but which are not copies of
+    /// each other, usually a timestamp mistake.
+    pub overlaps: Vec<Overlap>,
+}
+
+/// Copies of one workout: the first created is kept, the rest are extra.
+#[derive(Debug, Serialize)]
+pub struct DuplicateGroup {
+    pub keep: WorkoutRef,
+    pub extra: Vec<WorkoutRef>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Overlap {
+    pub first: WorkoutRef,
+    pub second: WorkoutRef,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorkoutRef {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub created_at: Option<String>,
+}
+
+impl From<&Workout> for WorkoutRef {
+    fn from(w: &Workout) -> Self {
+        WorkoutRef {
+            id: w.id.clone(),
+            title: w.title.clone(),
+            start_time: w.start_time.as_ref().map(format_timestamp),
+            end_time: w.end_time.as_ref().map(format_timestamp),
+            created_at: w.created_at.as_ref().map(format_timestamp),
+        }
+    }
+}
+
+/// Group `workouts` that start within 10 minutes of a group's first
+/// workout and have its exercises (by template ID, in any order, counting
+/// repeats), and list the remaining pairs whose start-to-end times
+/// overlap. Measuring from the first workout keeps groups from chaining:
+/// copies at +0, +9 and +18 minutes make two groups, not one spanning 18
+/// minutes. Workouts without a start time are left out.
+pub fn find(workouts: &[Workout]) -> DedupeReport {
+    let mut sorted: Vec<(&Workout, DateTime<Utc>)> = workouts
+        .iter()
+        .filter_map(|w| Some((w, w.start_time?)))
+        .collect();
+    sorted.sort_by_key(|(_, start)| *start);
+
+    // Indices into `sorted` of each group's members, in start order.
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of = vec![0; sorted.len()];
+    for (i, (w, start)) in sorted.iter().enumerate() {
+        let exercises = exercise_ids(w);
+        let existing = groups.iter().position(|members| {
+            let (other, other_start) = sorted[members[0]];
+            *start - other_start <= WINDOW && exercise_ids(other) == exercises
+        });
+        match existing {
+            Some(g) => {
+                groups[g].push(i);
+                group_of[i] = g;
+            }
+            None => {
+                group_of[i] = groups.len();
+                groups.push(vec![i]);
+            }
+        }
+    }
+
+    let duplicates = groups
+        .iter()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let mut members: Vec<&Workout> = members.iter().map(|&i| sorted[i].0).collect();
+            // Earliest created first; a missing created_at sorts last.
+            members.sort_by_key(|w| (w.created_at.is_none(), w.created_at, w.start_time));
+            DuplicateGroup {
+                keep: members[0].into(),
+                extra: members[1..].iter().map(|w| (*w).into()).collect(),
+            }
+        })
+        .collect();
+
+    let mut overlaps = Vec::new();
+    for (i, (a, _)) in sorted.iter().enumerate() {
+        let Some(a_end) = a.end_time else {
+            continue;
+        };
+        for (j, (b, b_start)) in sorted.iter().enumerate().skip(i + 1) {
+            if *b_start >= a_end {
+                break;
+            }
+            if group_of[i] != group_of[j] && b.end_time.is_some() {
+                overlaps.push(Overlap {
+                    first: (*a).into(),
+                    second: (*b).into(),
+                });
+            }
+        }
+    }
+
+    DedupeReport {
+        duplicates,
+        overlaps,
+    }
+}
+
+/// The workout's exercise template IDs, sorted, so equal lists mean the
+/// same exercises with the same repeats.
+fn exercise_ids(w: &Workout) -> Vec<&str> {
+    let mut ids: Vec<&str> = w
+        .exercises
+        .iter()
+        .map(|e| e.exercise_template_id.as_deref().unwrap_or(""))
+        .collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// Print the duplicate groups, then the overlapping pairs.
+pub fn print_report(out: &mut impl Write, report: &DedupeReport, style: &Style) -> Result<()> {
+    let mut table = Table::new(vec![
+        Column::right("Group", 5),
+        Column::left("", 6),
+        Column::left("ID", 36),
+        Column::left("Start", 20),
+        Column::left("Title", 30).flex(),
+    ])
+    .gap("  ");
+    for (i, group) in report.duplicates.iter().enumerate() {
+        let rows =
+            std::iter::once(("keep", &group.keep)).chain(group.extra.iter().map(|w| ("extra", w)));
+        for (action, w) in rows {
+            table.row(vec![
+                (i + 1).to_string().into(),
+                action.into(),
+                w.id.as_deref().unwrap_or("—").into(),
+                w.start_time.as_deref().unwrap_or("—").into(),
+                w.title.as_deref().unwrap_or("Untitled").into(),
+            ]);
+        }
+    }
+    writeln!(out, "\nDuplicates: {} group(s)", report.duplicates.len())?;
+    if !report.duplicates.is_empty() {
+        table.render(out, style)?;
+    }
+
+    writeln!(out, "\nOverlapping: {} pair(s)", report.overlaps.len())?;
+    for overlap in &report.overlaps {
+        for w in [&overlap.first, &overlap.second] {
+            writeln!(
+                out,
+                "  {}  {} – {}  {}",
+                w.id.as_deref().unwrap_or("—"),
+                w.start_time.as_deref().unwrap_or("—"),
+                w.end_time.as_deref().unwrap_or("—"),
+                w.title.as_deref().unwrap_or("Untitled")
+            )?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// A workout starting `start` minutes after 18:00, lasting `minutes`,
+    /// created `created` minutes after 18:00, with these exercises.
+    fn workout(id: &str, start: i64, minutes: i64, created: i64, exercises: &[&str]) -> Workout {
+        let at = |m: i64| {
+            let base = chrono::NaiveDate::from_ymd_opt(2024, 8, 14)
+                .unwrap()
+                .and_hms_opt(18, 0, 0)
+                .unwrap()
+                .and_utc();
+            (base + TimeDelta::minutes(m)).to_rfc3339()
+        };
+        let exercises: Vec<_> = exercises
+            .iter()
+            .map(|id| json!({ "exercise_template_id": id, "sets": [] }))
+            .collect();
+        serde_json::from_value(json!({
+            "id": id,
+            "title": "Push",
+            "start_time": at(start),
+            "end_time": at(start + minutes),
+            "created_at": at(created),
+            "exercises": exercises,
+        }))
+        .unwrap()
+    }
+
+    fn ids(workouts: &[WorkoutRef]) -> Vec<&str> {
+        workouts.iter().map(|w| w.id.as_deref().unwrap()).collect()
+    }
+
+    /// Each group as its kept ID followed by its extras.
+    fn groups(report: &DedupeReport) -> Vec<Vec<&str>> {
+        report
+            .duplicates
+            .iter()
+            .map(|g| [ids(std::slice::from_ref(&g.keep)), ids(&g.extra)].concat())
+            .collect()
+    }
+
+    fn overlaps(report: &DedupeReport) -> Vec<(&str, &str)> {
+        report
+            .overlaps
+            .iter()
+            .map(|o| {
+                (
+                    o.first.id.as_deref().unwrap(),
+                    o.second.id.as_deref().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn copies_keep_the_first_created() {
+        // Posted twice, the second copy saved first with a later start.
+        let report = find(&[
+            workout("a", 0, 60, 70, &["bench", "row"]),
+            workout("b", 2, 60, 65, &["row", "bench"]),
+        ]);
+        assert_eq!(groups(&report), [["b", "a"]]);
+        assert!(report.overlaps.is_empty());
+    }
+
+    #[test]
+    fn different_exercises_are_not_copies() {
+        let report = find(&[
+            workout("a", 0, 60, 60, &["bench", "row"]),
+            workout("b", 5, 60, 65, &["bench", "squat"]),
+            // Repeats count: two rows are not one.
+            workout("c", 300, 60, 360, &["row"]),
+            workout("d", 305, 60, 365, &["row", "row"]),
+        ]);
+        assert!(report.duplicates.is_empty());
+        assert_eq!(overlaps(&report), [("a", "b"), ("c", "d")]);
+    }
+
+    #[test]
+    fn groups_do_not_chain() {
+        // +9 is within 10 minutes of +0, +18 only of +9.
+        let report = find(&[
+            workout("a", 0, 5, 5, &["bench"]),
+            workout("b", 9, 5, 14, &["bench"]),
+            workout("c", 18, 5, 23, &["bench"]),
+        ]);
+        assert_eq!(groups(&report), [["a", "b"]]);
+        assert!(report.overlaps.is_empty());
+    }
+
+    #[test]
+    fn overlaps_skip_copies_and_back_to_back_workouts() {
+        let report = find(&[
+            workout("a", 0, 60, 60, &["bench"]),
+            workout("b", 1, 59, 61, &["bench"]),
+            // Starts as a and b end: no overlap.
+            workout("c", 60, 30, 90, &["squat"]),
+            // Starts inside c.
+            workout("d", 75, 30, 105, &["row"]),
+        ]);
+        assert_eq!(groups(&report), [["a", "b"]]);
+        assert_eq!(overlaps(&report), [("c", "d")]);
+
+        // Workouts without a start time are left out.
+        let mut untimed = workout("e", 75, 30, 105, &["row"]);
+        untimed.start_time = None;
+        let report = find(&[workout("c", 60, 30, 90, &["squat"]), untimed]);
+        assert!(report.duplicates.is_empty() && report.overlaps.is_empty());
+    }
+}
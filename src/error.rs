@@ -0,0 +1,269 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::client::ApiError;
+
+/// Result type returned by the library.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Everything that can go wrong talking to the Hevy API.
+///
+/// New variants may be added in minor releases, so matches need a
+/// wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The API answered with a non-success status.
+    Api(ApiError),
+    /// A request attempt did not finish within the configured limit.
+    Timeout {
+        /// Method and path, e.g. `GET /workouts`.
+        endpoint: String,
+        /// The limit that was hit.
+        limit: Duration,
+        /// Whether the connection itself could not be established in time.
+        connecting: bool,
+    },
+    /// A request could not be built or sent, or its response not read.
+    Http {
+        context: String,
+        source: reqwest::Error,
+    },
+    /// A body could not be parsed or serialized as JSON.
+    Json {
+        context: String,
+        source: serde_json::Error,
+    },
+    /// Reading or writing a local file (cache, cassette) failed.
+    Io {
+        context: String,
+        source: std::io::Error,
+    },
+    /// A request body built with one of the [`models`](crate::models)
+    /// builders broke a rule of the API, e.g. an unknown set type.
+    Invalid(String),
+    /// A response had fields the models do not capture, with the client
+    /// set to [`Strictness::Error`](crate::strict::Strictness::Error).
+    UnknownFields {
+        endpoint: String,
+        /// Paths of the fields, e.g. `workouts[].tempo`.
+        fields: Vec<String>,
+    },
+    /// Replaying, and no cassette was recorded for this request.
+    NotRecorded {
+        endpoint: String,
+        url: String,
+        path: PathBuf,
+    },
+}
+
+impl Error {
+    /// The API error response, if this is one.
+    pub fn as_api(&self) -> Option<&ApiError> {
+        match self {
+            Error::Api(api) => Some(api),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn http(context: impl Into<String>, source: reqwest::Error) -> Self {
+        Error::Http {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn json(context: impl Into<String>, source: serde_json::Error) -> Self {
+        Error::Json {
+            context: context.into(),
+            source,
+        }
+    }
+
+    pub(crate) fn io(context: impl Into<String>, source: std::io::Error) -> Self {
+        Error::Io {
+            context: context.into(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Api(api) => api.fmt(f),
+            Error::Timeout {
+                endpoint,
+                limit,
+                connecting: true,
+            } => write!(f, "Connecting for {endpoint} timed out after {limit:?}"),
+            Error::Timeout {
+                endpoint, limit, ..
+            } => write!(f, "Request to {endpoint} timed out after {limit:?}"),
+            Error::Http { context, .. }
+            | Error::Json { context, .. }
+            | Error::Io { context, .. } => f.write_str(context),
+            Error::Invalid(message) => f.write_str(message),
+            Error::UnknownFields { endpoint, fields } => write!(
+                f,
+                "{endpoint} returned fields hevy-bridge does not capture: {}",
+                fields.join(", ")
+            ),
+            Error::NotRecorded {
+                endpoint,
+                url,
+                path,
+            } => write!(
+                f,
+                "No recorded response for {endpoint} ({url}): {} not found",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Http { source, .. } => Some(source),
+            Error::Json { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// `err` followed by its causes, separated by `: `, for log messages.
+pub(crate) fn report(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(cause) = source {
+        message.push_str(&format!(": {cause}"));
+        source = cause.source();
+    }
+    message
+}
+
+impl From<ApiError> for Error {
+    fn from(err: ApiError) -> Self {
+        Error::Api(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use reqwest::StatusCode;
+
+    use super::*;
+
+    fn api_error(status: StatusCode) -> ApiError {
+        ApiError {
+            endpoint: "GET /workouts/w1".to_string(),
+            status,
+            body: r#"{"error":"Not found"}"#.to_string(),
+            retry_after: None,
+        }
+    }
+
+    #[test]
+    fn api_errors_name_the_endpoint_status_and_body() {
+        let err = Error::from(api_error(StatusCode::NOT_FOUND));
+        assert_eq!(
+            err.to_string(),
+            r#"GET /workouts/w1 returned 404 Not Found: {"error":"Not found"}"#
+        );
+        assert_eq!(err.as_api().unwrap().status, StatusCode::NOT_FOUND);
+        assert!(err.source().is_none());
+        assert!(Error::Invalid("bad".to_string()).as_api().is_none());
+    }
+
+    #[test]
+    fn api_error_classification() {
+        let cases = [
+            (StatusCode::UNAUTHORIZED, true, true, false, false),
+            (StatusCode::FORBIDDEN, false, true, false, false),
+            (StatusCode::NOT_FOUND, false, false, false, false),
+            (StatusCode::TOO_MANY_REQUESTS, false, false, true, true),
+            (StatusCode::BAD_GATEWAY, false, false, false, true),
+        ];
+        for (status, unauthorized, auth_failure, rate_limited, transient) in cases {
+            let err = api_error(status);
+            assert_eq!(err.is_unauthorized(), unauthorized, "{status}");
+            assert_eq!(err.is_auth_failure(), auth_failure, "{status}");
+            assert_eq!(err.is_rate_limited(), rate_limited, "{status}");
+            assert_eq!(err.is_transient(), transient, "{status}");
+        }
+    }
+
+    #[test]
+    fn timeouts_say_which_limit_was_hit() {
+        let timeout = |connecting| Error::Timeout {
+            endpoint: "GET /workouts".to_string(),
+            limit: Duration::from_secs(30),
+            connecting,
+        };
+        assert_eq!(
+            timeout(false).to_string(),
+            "Request to GET /workouts timed out after 30s"
+        );
+        assert_eq!(
+            timeout(true).to_string(),
+            "Connecting for GET /workouts timed out after 30s"
+        );
+    }
+
+    #[test]
+    fn wrapped_errors_show_their_context_and_keep_the_cause() {
+        let cause = serde_json::from_str::<serde_json::Value>("{").unwrap_err();
+        let err = Error::json("Failed to parse workouts response", cause);
+        assert_eq!(err.to_string(), "Failed to parse workouts response");
+        assert!(err.source().is_some());
+        assert!(
+            report(&err).starts_with("Failed to parse workouts response: EOF while parsing"),
+            "{}",
+            report(&err)
+        );
+
+        let err = Error::io(
+            "Failed to write response cache",
+            std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied"),
+        );
+        assert_eq!(report(&err), "Failed to write response cache: denied");
+
+        let cause = reqwest::Client::new().get("not a url").build().unwrap_err();
+        let err = Error::http("Failed to build request to GET /workouts", cause);
+        assert_eq!(err.to_string(), "Failed to build request to GET /workouts");
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn other_messages() {
+        assert_eq!(
+            Error::Invalid("RPE must be between 6 and 10".to_string()).to_string(),
+            "RPE must be between 6 and 10"
+        );
+        let err = Error::UnknownFields {
+            endpoint: "GET /workouts".to_string(),
+            fields: vec!["workouts[].tempo".to_string(), "page_total".to_string()],
+        };
+        assert_eq!(
+            err.to_string(),
+            "GET /workouts returned fields hevy-bridge does not capture: \
+             workouts[].tempo, page_total"
+        );
+        let err = Error::NotRecorded {
+            endpoint: "GET /workouts".to_string(),
+            url: "https://api.hevyapp.com/v1/workouts?page=2".to_string(),
+            path: PathBuf::from("cassettes/GET_v1_workouts_0123456789ab.json"),
+        };
+        assert_eq!(
+            err.to_string(),
+            "No recorded response for GET /workouts \
+             (https://api.hevyapp.com/v1/workouts?page=2): \
+             cassettes/GET_v1_workouts_0123456789ab.json not found"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+
+use crate::api::HevyApi;
+use crate::models::*;
+use crate::output::Printer;
+
+/// Kind of workout event, for `--type` filtering.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventType {
+    Updated,
+    Deleted,
+}
+
+/// Whether `event` passes an optional `--type` filter.
+pub fn matches_type(event: &WorkoutEvent, event_type: Option<EventType>) -> bool {
+    matches!(
+        (event_type, event),
+        (None, _)
+            | (Some(EventType::Updated), WorkoutEvent::Updated { .. })
+            | (Some(EventType::Deleted), WorkoutEvent::Deleted { .. })
+    )
+}
+
+/// Print every event since `since` as NDJSON, writing each page as soon as
+/// it arrives instead of collecting them all first.
+///
+/// Updated events carry the full workout; deleted events only `id` and `deleted_at`.
+pub async fn stream_all(
+    client: &impl HevyApi,
+    printer: &Printer,
+    since: Option<Timestamp>,
+    event_type: Option<EventType>,
+) -> Result<()> {
+    let pages = client.workout_event_pages(since);
+    tokio::pin!(pages);
+    while let Some(page) = pages.try_next().await? {
+        let mut out = printer.out();
+        for event in page.items.iter().filter(|e| matches_type(e, event_type)) {
+            printer.write_line(&mut *out, event)?;
+        }
+    }
+    Ok(())
+}
+
+/// Longest pause between polls while backing off from errors.
+const MAX_BACKOFF: Duration = Duration::from_secs(15 * 60);
+
+/// Timestamp an event refers to: `updated_at` for updates, `deleted_at` for deletes.
+pub fn event_timestamp(event: &WorkoutEvent) -> Option<DateTime<Utc>> {
+    match event {
+        WorkoutEvent::Updated { workout } => workout.updated_at,
+        WorkoutEvent::Deleted { deleted_at, .. } => *deleted_at,
+    }
+}
+
+/// Identity of an event, used to suppress duplicates across polls.
+fn event_key(event: &WorkoutEvent) -> String {
+    match event {
+        WorkoutEvent::Updated { workout } => format!(
+            "updated:{}:{}",
+            workout.id.as_deref().unwrap_or(""),
+            workout
+                .updated_at
+                .as_ref()
+                .map(format_timestamp)
+                .unwrap_or_default()
+        ),
+        WorkoutEvent::Deleted { id, deleted_at } => {
+            format!(
+                "deleted:{id}:{}",
+                deleted_at
+                    .as_ref()
+                    .map(format_timestamp)
+                    .unwrap_or_default()
+            )
+        }
+    }
+}
+
+/// Fetch every event since `since`, oldest first.
+async fn poll_events(client: &impl HevyApi, since: Option<Timestamp>) -> Result<Vec<WorkoutEvent>> {
+    let mut events = client.all_workout_events(since).await?;
+    events.reverse();
+    Ok(events)
+}
+
+fn read_state(path: &Path) -> Option<Timestamp> {
+    let data = std::fs::read_to_string(path).ok()?;
+    let v: serde_json::Value = serde_json::from_str(&data).ok()?;
+    Timestamp::try_from(v.get("since")?.as_str()?).ok()
+}
+
+fn write_state(path: &Path, since: Timestamp) -> Result<()> {
+    let data = serde_json::json!({ "since": since });
+    std::fs::write(path, serde_json::to_string_pretty(&data)?)
+        .with_context(|| format!("Failed to write state file {}", path.display()))
+}
+
+/// Poll for workout events forever, printing each new one as a line of
+/// NDJSON, until interrupted with Ctrl-C.
+///
+/// The newest timestamp printed so far becomes the `since` of the next
+/// poll, and is persisted to `state_file` (when given) after every poll.
+/// A stored high-water mark takes precedence over `since`. Events filtered
+/// out by `event_type` still advance the high-water mark.
+pub async fn follow(
+    client: &impl HevyApi,
+    printer: &Printer,
+    since: Option<Timestamp>,
+    interval: Duration,
+    state_file: Option<PathBuf>,
+    event_type: Option<EventType>,
+) -> Result<()> {
+    let mut high_water = state_file.as_deref().and_then(read_state).or(since);
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut delay = interval;
+
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        let polled = tokio::select! {
+            r = poll_events(client, high_water) => r,
+            _ = &mut ctrl_c => break,
+        };
+
+        match polled {
+            Ok(events) => {
+                delay = interval;
+                let mut out = printer.out();
+                for event in &events {
+                    if seen.contains(&event_key(event)) {
+                        continue;
+                    }
+                    if matches_type(event, event_type) {
+                        printer.write_line(&mut *out, event)?;
+                    }
+
+                    if let Some(ts) = event_timestamp(event).map(Timestamp::from)
+                        && high_water.is_none_or(|hw| ts > hw)
+                    {
+                        high_water = Some(ts);
+                    }
+                }
+                // Only events at or after the high-water mark can be returned again.
+                seen = events.iter().map(event_key).collect();
+
+                if let (Some(path), Some(hw)) = (&state_file, high_water) {
+                    write_state(path, hw)?;
+                }
+            }
+            Err(e) => {
+                let api = e
+                    .downcast_ref::<hevy_bridge::Error>()
+                    .and_then(hevy_bridge::Error::as_api);
+                if api.is_some_and(|a| !a.is_transient()) {
+                    return Err(e);
+                }
+                delay = api
+                    .and_then(|a| a.retry_after)
+                    .unwrap_or(delay * 2)
+                    .min(MAX_BACKOFF);
+                eprintln!("Warning: {e:#}; retrying in {}s", delay.as_secs());
+            }
+        }
+
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = &mut ctrl_c => break,
+        }
+    }
+
+    eprintln!("Stopped following workout events.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn events() -> Vec<WorkoutEvent> {
+        serde_json::from_value(serde_json::json!([
+            {
+                "type": "updated",
+                "workout": {
+                    "id": "b459cba5",
+                    "title": "Push",
+                    "start_time": "2024-08-14T12:00:00Z",
+                    "end_time": "2024-08-14T13:00:00Z",
+                    "updated_at": "2024-08-14T13:05:00Z",
+                    "exercises": [{
+                        "index": 0,
+                        "title": "Bench Press (Barbell)",
+                        "exercise_template_id": "D04AC939",
+                        "sets": [{ "index": 0, "type": "normal", "weight_kg": 100, "reps": 5 }]
+                    }]
+                }
+            },
+            { "type": "deleted", "id": "7c1e2f90", "deleted_at": "2024-08-15T09:30:00Z" }
+        ]))
+        .unwrap()
+    }
+
+    fn ndjson(events: &[WorkoutEvent]) -> String {
+        let printer = Printer::new(None, None).unwrap();
+        let mut out = Vec::new();
+        for event in events {
+            printer.write_line(&mut out, event).unwrap();
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn events_round_trip_through_ndjson() {
+        let text = ndjson(&events());
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+
+        let parsed: Vec<WorkoutEvent> = lines
+            .iter()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let WorkoutEvent::Updated { workout } = &parsed[0] else {
+            panic!("expected an update, got {:?}", parsed[0]);
+        };
+        assert_eq!(workout.id.as_deref(), Some("b459cba5"));
+        assert_eq!(workout.exercises[0].sets[0].reps, Some(5));
+        assert_eq!(
+            event_timestamp(&parsed[0])
+                .map(|t| t.to_rfc3339())
+                .as_deref(),
+            Some("2024-08-14T13:05:00+00:00")
+        );
+        let WorkoutEvent::Deleted { id, deleted_at } = &parsed[1] else {
+            panic!("expected a deletion, got {:?}", parsed[1]);
+        };
+        assert_eq!(id, "7c1e2f90");
+        assert_eq!(
+            deleted_at.map(|t| t.to_rfc3339()).as_deref(),
+            Some("2024-08-15T09:30:00+00:00")
+        );
+
+        // Printing the parsed events again gives the same lines.
+        assert_eq!(ndjson(&parsed), text);
+    }
+
+    #[test]
+    fn deleted_events_print_only_id_and_deleted_at() {
+        let text = ndjson(&events()[1..]);
+        let line: serde_json::Value = serde_json::from_str(text.trim_end()).unwrap();
+        let keys: Vec<&str> = line
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(keys, ["type", "id", "deleted_at"]);
+    }
+
+    #[test]
+    fn type_filter() {
+        let events = events();
+        let kept = |t| events.iter().filter(|e| matches_type(e, t)).count();
+        assert_eq!(kept(None), 2);
+        assert_eq!(kept(Some(EventType::Updated)), 1);
+        assert!(matches_type(&events[1], Some(EventType::Deleted)));
+        assert!(!matches_type(&events[1], Some(EventType::Updated)));
+    }
+}
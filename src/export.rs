@@ -0,0 +1,282 @@
+use std::io::Write;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use clap::ValueEnum;
+
+use crate::models::{Set, Workout};
+use crate::table::Units;
+
+/// iCalendar timestamps in UTC: `YYYYMMDDTHHMMSSZ`.
+const ICS_TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// Event length assumed for workouts without an end_time.
+const DEFAULT_EVENT_MINUTES: i64 = 60;
+
+/// RFC 5545 limits content lines to 75 octets, excluding the line break.
+const MAX_LINE_OCTETS: usize = 75;
+
+/// File formats `workouts import` reads.
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+pub enum ImportFormat {
+    /// A JSON array of workouts, as written by `workouts export`.
+    #[default]
+    Json,
+}
+
+/// Writes workouts as an iCalendar (RFC 5545) file with one VEVENT per
+/// workout, for importing a training log into a calendar app.
+pub struct IcsExporter<W: Write> {
+    out: W,
+    stamp: DateTime<Utc>,
+    /// Units of the set weights in descriptions.
+    units: Units,
+}
+
+impl<W: Write> IcsExporter<W> {
+    pub fn new(out: W, units: Units) -> Self {
+        IcsExporter {
+            out,
+            stamp: Utc::now(),
+            units,
+        }
+    }
+
+    /// Write a complete VCALENDAR. Workouts without a parseable start_time
+    /// are skipped with a warning on stderr.
+    pub fn export(mut self, workouts: &[Workout]) -> Result<()> {
+        self.line("BEGIN:VCALENDAR")?;
+        self.line("VERSION:2.0")?;
+        self.line("PRODID:-//hevy-bridge//Workout Export//EN")?;
+        self.line("CALSCALE:GREGORIAN")?;
+        for w in workouts {
+            let Some(start) = w.start_time else {
+                eprintln!(
+                    "Warning: skipping workout {} without a start_time",
+                    w.id.as_deref().unwrap_or("(no id)")
+                );
+                continue;
+            };
+            let end = w
+                .end_time
+                .unwrap_or(start + Duration::minutes(DEFAULT_EVENT_MINUTES));
+            self.event(w, start, end)?;
+        }
+        self.line("END:VCALENDAR")?;
+        self.out.flush()?;
+        Ok(())
+    }
+
+    fn event(&mut self, w: &Workout, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<()> {
+        self.line("BEGIN:VEVENT")?;
+        // A stable UID lets calendar apps update events on re-import
+        // instead of duplicating them.
+        let uid = match &w.id {
+            Some(id) => format!("{id}@hevy-bridge"),
+            None => format!("{}@hevy-bridge", start.format(ICS_TIME_FORMAT)),
+        };
+        self.line(&format!("UID:{}", escape_text(&uid)))?;
+        self.line(&format!("DTSTAMP:{}", self.stamp.format(ICS_TIME_FORMAT)))?;
+        self.line(&format!("DTSTART:{}", start.format(ICS_TIME_FORMAT)))?;
+        self.line(&format!("DTEND:{}", end.format(ICS_TIME_FORMAT)))?;
+        let title = w.title.as_deref().unwrap_or("Workout");
+        self.line(&format!("SUMMARY:{}", escape_text(title)))?;
+        let description = describe(w, self.units);
+        if !description.is_empty() {
+            self.line(&format!("DESCRIPTION:{}", escape_text(&description)))?;
+        }
+        self.line("END:VEVENT")
+    }
+
+    /// Write one content line, folded at 75 octets and ended with CRLF.
+    fn line(&mut self, line: &str) -> Result<()> {
+        write!(self.out, "{}\r\n", fold(line))?;
+        Ok(())
+    }
+}
+
+/// The event description: the workout description, then one line per
+/// exercise with its sets, e.g. `- Squat (Barbell): 225.0 lbs × 5, 225.0 lbs × 5`.
+fn describe(w: &Workout, units: Units) -> String {
+    let mut lines = Vec::new();
+    if let Some(desc) = w.description.as_deref().filter(|d| !d.trim().is_empty()) {
+        lines.push(desc.trim().to_string());
+    }
+    for e in &w.exercises {
+        let title = e.title.as_deref().unwrap_or("Unknown exercise");
+        let sets: Vec<String> = e
+            .sets
+            .iter()
+            .filter_map(|s| describe_set(s, units))
+            .collect();
+        if sets.len() == e.sets.len() && !sets.is_empty() {
+            lines.push(format!("- {title}: {}", sets.join(", ")));
+        } else {
+            let sets = e.sets.len();
+            let plural = if sets == 1 { "" } else { "s" };
+            lines.push(format!("- {title} ({sets} set{plural})"));
+        }
+    }
+    lines.join("\n")
+}
+
+/// `100.0 kg × 5`, `12 reps`, `60 s` or `400 m`; None for a set with
+/// nothing recorded.
+fn describe_set(s: &Set, units: Units) -> Option<String> {
+    let unit = match units {
+        Units::Lbs => "lbs",
+        Units::Kg => "kg",
+    };
+    let weight = s.weight_kg.filter(|kg| *kg > 0.0);
+    match (weight, s.reps, s.duration_seconds, s.distance_meters) {
+        (Some(kg), Some(reps), _, _) => Some(format!("{} {unit} × {reps}", units.weight(kg))),
+        (None, Some(reps), _, _) => Some(format!("{reps} reps")),
+        (Some(kg), None, Some(seconds), _) => {
+            Some(format!("{} {unit} × {seconds} s", units.weight(kg)))
+        }
+        (_, None, Some(seconds), _) => Some(format!("{seconds} s")),
+        (_, None, None, Some(meters)) => Some(format!("{meters} m")),
+        _ => None,
+    }
+}
+
+/// Escape a TEXT property value (RFC 5545 §3.3.11).
+fn escape_text(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Fold a content line so no physical line exceeds 75 octets, breaking only
+/// at character boundaries; continuation lines start with a space.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / MAX_LINE_OCTETS * 3);
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            // The leading space counts toward the continuation line.
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn ics(workouts: serde_json::Value) -> String {
+        let workouts: Vec<Workout> = serde_json::from_value(workouts).unwrap();
+        let mut out = Vec::new();
+        IcsExporter::new(&mut out, Units::Kg)
+            .export(&workouts)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Undo [`fold`]: join continuation lines back onto their line.
+    fn unfold(ics: &str) -> String {
+        ics.replace("\r\n ", "")
+    }
+
+    #[test]
+    fn text_escapes_backslashes_separators_and_newlines() {
+        assert_eq!(
+            escape_text(r"Legs\Core; squats, lunges"),
+            r"Legs\\Core\; squats\, lunges"
+        );
+        assert_eq!(escape_text("line one\nline two"), r"line one\nline two");
+        assert_eq!(escape_text("line one\r\nline two\r"), r"line one\nline two");
+        assert_eq!(escape_text("Push: 5×5"), "Push: 5×5");
+    }
+
+    #[test]
+    fn short_lines_are_not_folded() {
+        let line = "x".repeat(MAX_LINE_OCTETS);
+        assert_eq!(fold(&line), line);
+    }
+
+    #[test]
+    fn ascii_lines_fold_at_75_octets() {
+        let line = format!("DESCRIPTION:{}", "a".repeat(200));
+        let folded = fold(&line);
+        let physical: Vec<&str> = folded.split("\r\n").collect();
+        assert_eq!(physical[0].len(), 75);
+        assert!(physical[1..].iter().all(|l| l.starts_with(' ')));
+        assert!(physical.iter().all(|l| l.len() <= MAX_LINE_OCTETS));
+        assert_eq!(unfold(&folded), line);
+    }
+
+    #[test]
+    fn multibyte_lines_fold_between_characters() {
+        // 3-octet characters after an odd-length prefix, so a break at
+        // exactly 75 octets would split one.
+        let line = format!("SUMMARY:{}", "→".repeat(60));
+        let folded = fold(&line);
+        for physical in folded.split("\r\n") {
+            assert!(physical.len() <= MAX_LINE_OCTETS, "{physical:?}");
+        }
+        // The first line stops at 74 octets rather than cut the 23rd
+        // arrow, which goes on the next line whole; nothing is lost.
+        assert_eq!(folded.split("\r\n").next().unwrap().len(), 74);
+        assert_eq!(unfold(&folded), line);
+        assert!(folded.split("\r\n").nth(1).unwrap().starts_with(" →"));
+    }
+
+    #[test]
+    fn workouts_without_an_end_last_an_hour() {
+        let ics = ics(json!([
+            { "id": "w1", "title": "Push", "start_time": "2024-08-14T18:00:00Z" },
+            {
+                "id": "w2",
+                "title": "Pull",
+                "start_time": "2024-08-15T18:00:00Z",
+                "end_time": "2024-08-15T18:45:00Z"
+            },
+            { "id": "w3", "title": "No start" }
+        ]));
+        assert!(ics.contains("DTSTART:20240814T180000Z\r\nDTEND:20240814T190000Z\r\n"));
+        assert!(ics.contains("DTEND:20240815T184500Z\r\n"));
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n") && ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn descriptions_are_escaped_and_folded() {
+        let ics = ics(json!([{
+            "id": "w1",
+            "title": "Legs; heavy, slow",
+            "description": "Felt strong\nknees fine",
+            "start_time": "2024-08-14T18:00:00Z",
+            "exercises": [{
+                "title": "Squat (Barbell)",
+                "sets": [
+                    { "weight_kg": 140, "reps": 5 },
+                    { "weight_kg": 140, "reps": 5 },
+                    { "weight_kg": 140, "reps": 5 }
+                ]
+            }]
+        }]));
+        assert!(ics.lines().all(|l| l.len() <= MAX_LINE_OCTETS + 1));
+        let ics = unfold(&ics);
+        assert!(ics.contains("SUMMARY:Legs\\; heavy\\, slow\r\n"));
+        assert!(ics.contains(
+            "DESCRIPTION:Felt strong\\nknees fine\\n- Squat (Barbell): \
+             140.0 kg × 5\\, 140.0 kg × 5\\, 140.0 kg × 5\r\n"
+        ));
+    }
+}
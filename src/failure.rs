@@ -0,0 +1,199 @@
+use std::fmt;
+
+use anyhow::Error;
+use hevy_bridge::client::ApiError;
+use reqwest::StatusCode;
+use serde::Serialize;
+
+use crate::config::{self, KeySource};
+
+/// How a failed command reports its error on stderr.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// The error and its causes as text.
+    #[default]
+    Text,
+    /// One JSON object: error_kind, message, status, endpoint and hint.
+    Json,
+}
+
+/// Broad cause of a failed command, which picks its exit status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The API rejected the API key (401 or 403).
+    Auth,
+    /// The API has no such resource (404).
+    NotFound,
+    /// Still rate limited (429) after every retry.
+    RateLimited,
+    /// Bad arguments or JSON given to the command, or a body the API
+    /// refused (400 or 422).
+    InvalidInput,
+    /// Any other error response from the API, or one that could not be read.
+    ApiError,
+    /// The request did not get an answer: network failure, timeout, or a
+    /// request missing from a --replay directory.
+    Transport,
+}
+
+impl ErrorKind {
+    /// Classify `e` by the first library error in its chain. Errors raised
+    /// by the CLI itself are taken as invalid input.
+    pub fn of(e: &Error) -> Self {
+        let Some(error) = library_error(e) else {
+            return ErrorKind::InvalidInput;
+        };
+        match error {
+            hevy_bridge::Error::Api(api) => match api.status {
+                _ if api.is_auth_failure() => ErrorKind::Auth,
+                StatusCode::NOT_FOUND => ErrorKind::NotFound,
+                StatusCode::TOO_MANY_REQUESTS => ErrorKind::RateLimited,
+                StatusCode::BAD_REQUEST | StatusCode::UNPROCESSABLE_ENTITY => {
+                    ErrorKind::InvalidInput
+                }
+                _ => ErrorKind::ApiError,
+            },
+            hevy_bridge::Error::Invalid(_) => ErrorKind::InvalidInput,
+            hevy_bridge::Error::Json { .. } | hevy_bridge::Error::UnknownFields { .. } => {
+                ErrorKind::ApiError
+            }
+            _ => ErrorKind::Transport,
+        }
+    }
+
+    /// Exit status of a command that failed this way.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::ApiError => 1,
+            ErrorKind::InvalidInput => 2,
+            ErrorKind::Auth => 3,
+            ErrorKind::NotFound => 4,
+            ErrorKind::RateLimited => 5,
+            ErrorKind::Transport => 6,
+        }
+    }
+
+    fn hint(self) -> Option<&'static str> {
+        match self {
+            ErrorKind::Auth => Some(
+                "The API key may have expired or been revoked. Get a new one at \
+                 https://hevy.com/settings?developer and save it with \
+                 `hevy-bridge config set-key <KEY>`",
+            ),
+            ErrorKind::NotFound => Some("Check the ID; list commands show the valid ones"),
+            ErrorKind::RateLimited => {
+                Some("Wait a minute and try again, or lower --max-rps or --concurrency")
+            }
+            ErrorKind::InvalidInput => Some("See the command's --help for the expected input"),
+            ErrorKind::Transport => {
+                Some("Check the network connection (or the --replay directory), or raise --timeout")
+            }
+            ErrorKind::ApiError => None,
+        }
+    }
+}
+
+/// Context on the error for a key `config validate` or `config set-key`
+/// checked and the API rejected.
+#[derive(Debug)]
+pub struct InvalidKey {
+    /// The key was given to the command rather than found the usual way.
+    pub given: bool,
+}
+
+impl fmt::Display for InvalidKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("API key is invalid")
+    }
+}
+
+/// The `--error-format json` form of an error.
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    pub error_kind: ErrorKind,
+    /// The error and its causes, separated by `: `.
+    pub message: String,
+    /// HTTP status of an API error response.
+    pub status: Option<u16>,
+    /// Method and path of the request that failed, e.g. `GET /workouts/abc`.
+    pub endpoint: Option<String>,
+    pub hint: Option<&'static str>,
+}
+
+impl ErrorReport {
+    pub fn new(e: &Error) -> Self {
+        let kind = ErrorKind::of(e);
+        let error = library_error(e);
+        let endpoint = match error {
+            Some(hevy_bridge::Error::Api(api)) => Some(&api.endpoint),
+            Some(
+                hevy_bridge::Error::Timeout { endpoint, .. }
+                | hevy_bridge::Error::UnknownFields { endpoint, .. }
+                | hevy_bridge::Error::NotRecorded { endpoint, .. },
+            ) => Some(endpoint),
+            _ => None,
+        };
+        ErrorReport {
+            error_kind: kind,
+            message: format!("{e:#}"),
+            status: error
+                .and_then(hevy_bridge::Error::as_api)
+                .map(|api| api.status.as_u16()),
+            endpoint: endpoint.cloned(),
+            hint: kind.hint(),
+        }
+    }
+}
+
+fn library_error(e: &Error) -> Option<&hevy_bridge::Error> {
+    e.chain().find_map(|cause| cause.downcast_ref())
+}
+
+/// Print `e` on stderr in `format` and return the exit status to use.
+pub fn report(e: &Error, format: ErrorFormat, key_args: &config::KeyArgs) -> i32 {
+    let kind = ErrorKind::of(e);
+    match format {
+        ErrorFormat::Json => match serde_json::to_string(&ErrorReport::new(e)) {
+            Ok(json) => eprintln!("{json}"),
+            Err(_) => eprintln!("Error: {e:#}"),
+        },
+        ErrorFormat::Text => match auth_failure(e) {
+            Some(api) => {
+                let checked = e.downcast_ref::<InvalidKey>();
+                let source = match checked {
+                    Some(InvalidKey { given: true }) => None,
+                    _ => config::find_api_key(key_args).ok().map(|k| k.source),
+                };
+                report_auth_failure(api, source, checked.is_some());
+            }
+            None => eprintln!("Error: {e:?}"),
+        },
+    }
+    kind.exit_code()
+}
+
+/// The API error in `e`'s chain, if it is the API refusing the key.
+fn auth_failure(e: &Error) -> Option<&ApiError> {
+    library_error(e)
+        .and_then(hevy_bridge::Error::as_api)
+        .filter(|api| api.is_auth_failure())
+}
+
+/// Explain a rejected API key in place of the raw API error. `checked` is
+/// set when the command's job was to check the key.
+fn report_auth_failure(api: &ApiError, source: Option<KeySource>, checked: bool) {
+    if checked {
+        eprintln!("✗ API key is invalid ({}).", api.status);
+    } else {
+        eprintln!("✗ The Hevy API rejected the API key ({}).", api.status);
+    }
+    if let Some(source) = source {
+        eprintln!("  The key came from {source}.");
+    }
+    eprintln!(
+        "\nIt may have expired or been revoked. Get a new one at \
+         https://hevy.com/settings?developer (Hevy Pro required).\n{}",
+        config::KEY_HELP
+    );
+}
@@ -0,0 +1,54 @@
+//! A client for [Hevy's API](https://api.hevyapp.com/docs/#/), the library
+//! behind the `hevy-bridge` CLI.
+//!
+//! Every endpoint is a method on the [`HevyApi`] trait, implemented over
+//! HTTP by [`HevyClient`]. An API key is required; Hevy Pro users can get
+//! one at <https://hevy.com/settings?developer>.
+//!
+//! ```no_run
+//! use hevy_bridge::{HevyApi, HevyClient};
+//!
+//! # async fn run() -> hevy_bridge::Result<()> {
+//! let client = HevyClient::new("YOUR_API_KEY")?;
+//! let page = client.list_workouts(1, 5).await?;
+//! for workout in page.workouts {
+//!     println!("{}", workout.title.unwrap_or_default());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The builder tunes retries, timeouts and rate limiting, and the paginated
+//! helpers walk every page:
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use hevy_bridge::{HevyApi, HevyClient};
+//!
+//! # async fn run() -> hevy_bridge::Result<()> {
+//! let client = HevyClient::builder("YOUR_API_KEY".to_string())
+//!     .timeout(Duration::from_secs(10))
+//!     .max_rps(1.0)
+//!     .build()?;
+//! let routines = client.all_routines().await?;
+//! println!("{} routines", routines.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Failures are reported as [`Error`]; a non-success response from the API
+//! is [`Error::Api`], whose [`ApiError`](client::ApiError) carries the status
+//! and body.
+
+pub mod api;
+pub mod cache;
+pub mod client;
+pub mod error;
+pub mod models;
+pub mod strict;
+mod transport;
+
+pub use api::HevyApi;
+pub use client::HevyClient;
+pub use error::{Error, Result};
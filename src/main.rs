@@ -1,67 +1,53 @@
-mod client;
-mod models;
+mod analysis;
+mod backup;
+mod config;
+mod dates;
+mod dedupe;
+mod events;
+mod export;
+mod failure;
+#[cfg(test)]
+mod mock;
+mod output;
+mod plates;
+mod process;
+mod program;
+mod report;
+mod resolver;
+mod table;
+mod warmup;
+mod webhook;
 
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{IsTerminal, Write};
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Datelike, NaiveDate, SecondsFormat, Utc};
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use futures::{StreamExt, TryFutureExt, TryStreamExt, stream};
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+use serde::Serialize;
 
+use hevy_bridge::{api, cache, client, models, strict};
+
+use api::HevyApi;
+use cache::CachePolicy;
 use client::HevyClient;
 use models::*;
+use output::OutputFormat;
+use strict::Strictness;
+use table::{Column, Table};
 
-// ─────────────────────────────────────────────────────
-// Config helpers
-// ─────────────────────────────────────────────────────
-
-fn config_path() -> PathBuf {
-    dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("hevy-bridge")
-        .join("config.json")
-}
-
-fn read_stored_api_key() -> Option<String> {
-    let path = config_path();
-    let data = std::fs::read_to_string(&path).ok()?;
-    let v: serde_json::Value = serde_json::from_str(&data).ok()?;
-    v.get("api_key")?.as_str().map(|s| s.to_string())
-}
+/// Maximum number of requests in flight when fetching many resources.
+const FETCH_CONCURRENCY: usize = 4;
 
-fn store_api_key(key: &str) -> Result<()> {
-    let path = config_path();
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .context("Failed to create config directory")?;
-    }
-    let data = serde_json::json!({ "api_key": key });
-    std::fs::write(&path, serde_json::to_string_pretty(&data)?)
-        .context("Failed to write config file")?;
-    Ok(())
-}
+/// Length of a full workout or routine ID, a UUID. `get` takes anything
+/// shorter as the start of one.
+const UUID_LEN: usize = 36;
 
-/// Resolve the API key from (in priority order):
-///   1. --api-key flag
-///   2. HEVY_API_KEY environment variable
-///   3. Stored config file (~/.config/hevy-bridge/config.json)
-fn resolve_api_key(cli_key: &Option<String>) -> Result<String> {
-    if let Some(k) = cli_key {
-        return Ok(k.clone());
-    }
-    if let Ok(k) = std::env::var("HEVY_API_KEY") {
-        if !k.is_empty() {
-            return Ok(k);
-        }
-    }
-    if let Some(k) = read_stored_api_key() {
-        return Ok(k);
-    }
-    anyhow::bail!(
-        "No API key provided. Supply one via:\n  \
-         1. --api-key <KEY>\n  \
-         2. HEVY_API_KEY environment variable\n  \
-         3. `hevy-bridge config set-key <KEY>` to persist it"
-    )
-}
+/// How long `process-workout --stdin` waits for input before giving up.
+const STDIN_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
 
 // ─────────────────────────────────────────────────────
 // CLI definition
@@ -78,8 +64,14 @@ fn resolve_api_key(cli_key: &Option<String>) -> Result<String> {
 ///
 ///   Provide the key via one of (checked in this order):
 ///     1. --api-key <KEY>           (per-invocation flag)
-///     2. HEVY_API_KEY env var      (session / CI)
-///     3. `hevy-bridge config set-key <KEY>`  (persisted to disk)
+///     2. --api-key-file <PATH>     (or HEVY_API_KEY_FILE; mounted secrets)
+///     3. --profile <NAME>          (a stored profile's key)
+///     4. HEVY_API_KEY env var      (session / CI)
+///     5. `hevy-bridge config set-key <KEY>`  (persisted to disk)
+///
+///   Keys for several accounts can be stored as named profiles with
+///   `config set-key --profile <NAME> <KEY>`; `config use <NAME>` picks
+///   the default.
 ///
 /// OUTPUT:
 ///   All data commands output JSON to stdout so you can pipe them into
@@ -123,13 +115,184 @@ TIPS FOR AI AGENTS:
 )]
 struct Cli {
     /// Hevy API key (overrides env var and stored config).
-    #[arg(long, global = true, env = "HEVY_API_KEY", hide_env = true)]
+    #[arg(long, global = true)]
     api_key: Option<String>,
 
+    /// Read the API key from this file (e.g. a mounted secret). Surrounding
+    /// whitespace is ignored.
+    #[arg(long, global = true, env = "HEVY_API_KEY_FILE")]
+    api_key_file: Option<PathBuf>,
+
+    /// Use the API key stored under this config profile.
+    #[arg(long, global = true, env = "HEVY_PROFILE")]
+    profile: Option<String>,
+
+    /// Output format for data commands that support a table view
+    /// [default: json, or `config set format`].
+    #[arg(long, global = true, value_enum, env = "HEVY_FORMAT")]
+    format: Option<OutputFormat>,
+
+    /// Shorthand for `--format ndjson`: print list results one object per
+    /// line, streaming `--all` output page by page.
+    #[arg(long, global = true)]
+    ndjson: bool,
+
+    /// Print JSON on a single line instead of pretty-printing it.
+    #[arg(long, global = true)]
+    compact: bool,
+
+    /// Only output these fields (comma-separated dot paths, e.g.
+    /// id,title,exercises.title). Arrays are traversed implicitly.
+    #[arg(long, global = true, value_delimiter = ',')]
+    fields: Option<Vec<String>>,
+
+    /// Write output to this file instead of stdout (`-` for stdout). The
+    /// file is only replaced once the command succeeds.
+    #[arg(long, global = true)]
+    output: Option<PathBuf>,
+
+    /// When to color table output. Auto colors only on a terminal and
+    /// honors NO_COLOR [default: auto, or `config set color`].
+    #[arg(long, global = true, value_enum, env = "HEVY_COLOR")]
+    color: Option<table::ColorChoice>,
+
+    /// Never color table output (same as --color never).
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Units for weights in table output; JSON is always in kg
+    /// [default: lbs, or `config set units`].
+    #[arg(long, global = true, value_enum, env = "HEVY_UNITS")]
+    units: Option<table::Units>,
+
+    /// Fit tables to this many columns (default: the terminal width, or
+    /// 120 when not writing to a terminal).
+    #[arg(long, global = true)]
+    width: Option<usize>,
+
+    /// Show exercise aliases (`alias set`) instead of exercise titles in
+    /// tables.
+    #[arg(long, global = true)]
+    use_aliases: bool,
+
+    /// Columns to show in workout summary tables (comma-separated).
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_parser = ["exercise", "sets", "weight", "reps", "result", "rest", "notes"]
+    )]
+    columns: Option<Vec<String>>,
+
+    /// Retries for rate-limited (429) or failed (5xx) API requests.
+    #[arg(long, global = true, default_value_t = client::DEFAULT_MAX_RETRIES)]
+    max_retries: u32,
+
+    /// Seconds before an API request is abandoned, response included.
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        default_value_t = client::DEFAULT_TIMEOUT.as_secs(),
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    timeout: u64,
+
+    /// Seconds allowed for connecting to the API.
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        default_value_t = client::DEFAULT_CONNECT_TIMEOUT.as_secs(),
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    connect_timeout: u64,
+
+    /// Pages fetched at once when walking a whole list (e.g. `--all`),
+    /// 1-10. A rate limit on one request pauses all of them.
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        default_value_t = client::DEFAULT_CONCURRENCY,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..=10)
+    )]
+    concurrency: usize,
+
+    /// Most API requests sent per second, 0 for no limit
+    /// [default: 2, or `config set max_rps`].
+    #[arg(long, global = true, value_name = "RPS", env = "HEVY_MAX_RPS", value_parser = config::parse_max_rps)]
+    max_rps: Option<f64>,
+
+    /// Cache GET responses on disk: exercise templates for 7 days, workout
+    /// lists for 5 minutes, single workouts for 1 hour
+    /// [default: off, or `config set cache true`].
+    #[arg(long, global = true, overrides_with = "no_cache")]
+    cache: bool,
+
+    /// Bypass every local cache, including the exercise template cache.
+    #[arg(long, global = true)]
+    no_cache: bool,
+
+    /// Save every API request and response as a JSON file in DIR, with
+    /// the API key redacted, for later --replay.
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    record: Option<PathBuf>,
+
+    /// Answer API requests from files saved by --record instead of the
+    /// network; a request that was not recorded fails.
+    #[arg(long, global = true, value_name = "DIR")]
+    replay: Option<PathBuf>,
+
+    /// Log each API request's method and URL and the response status to
+    /// stderr; -vv also logs headers and bodies.
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Log full HTTP requests and responses to stderr, bodies included
+    /// (same as -vv). The api-key header is always redacted.
+    #[arg(long, global = true)]
+    debug_http: bool,
+
+    /// Report API response fields this version does not capture, e.g.
+    /// data the API has added since: --strict warns on stderr,
+    /// --strict=error fails the command instead.
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "warn"
+    )]
+    strict: Option<StrictMode>,
+
+    /// How to report a failure on stderr: text, or one JSON object with
+    /// error_kind, message, status, endpoint and hint. The exit status
+    /// depends on the kind of failure either way.
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t
+    )]
+    error_format: failure::ErrorFormat,
+
     #[command(subcommand)]
     command: Commands,
 }
 
+/// What `--strict` does with response fields the models do not capture.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+enum StrictMode {
+    /// Print a warning naming them.
+    Warn,
+    /// Fail the command.
+    Error,
+}
+
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Manage API key configuration.
@@ -183,6 +346,15 @@ enum Commands {
     #[command(subcommand)]
     Exercises(ExerciseCommands),
 
+    /// Short names for exercise templates.
+    ///
+    /// An alias can be used wherever an exercise name is accepted
+    /// (`exercises resolve`, `workouts find --exercise`), and with
+    /// --use-aliases it is shown instead of the exercise's title in tables.
+    /// Aliases are stored in the config file and ignore case.
+    #[command(subcommand)]
+    Alias(AliasCommands),
+
     /// List, view, and create routine folders.
     ///
     /// Routine folders organize your routines. New folders are created
@@ -198,6 +370,14 @@ enum Commands {
     #[command(subcommand)]
     History(HistoryCommands),
 
+    /// Summaries of training over a period.
+    #[command(subcommand)]
+    Report(ReportCommands),
+
+    /// Build the routines of a training program.
+    #[command(subcommand)]
+    Program(ProgramCommands),
+
     /// Process a webhook workout payload and print a summary table.
     ///
     /// Accepts the JSON payload from a Hevy webhook (e.g. from a
@@ -211,12 +391,183 @@ enum Commands {
     ///   Succeeded  — 8 to 10 reps
     ///   Exceeded   — 11 or more reps
     ///
+    /// The payload can also be piped in: pass `--json -` or `--stdin`
+    /// (alias `--read-stdin`) to read it from stdin. Reading gives up with
+    /// an error if nothing arrives within 5 seconds, so an accidental
+    /// interactive run does not hang.
+    ///
+    /// With --webhook-secret the input must be the raw HTTP request as
+    /// relayed (headers, a blank line, then the body), and its
+    /// X-Hevy-Signature header (see --signature-header) must hold the
+    /// hex-encoded HMAC-SHA256 of the body. The payload is rejected before
+    /// any API call if the signature is missing or wrong.
+    ///
     /// Example:
     ///   hevy-bridge process-workout --json '{"workoutId":"ae4f95df-..."}'
+    ///   echo '{"workoutId":"ae4f95df-..."}' | hevy-bridge process-workout --stdin
+    ///   relay-dump | hevy-bridge process-workout --stdin --webhook-secret "$SECRET"
     ProcessWorkout {
-        /// Raw JSON webhook payload containing a "workoutId" field.
-        #[arg(long)]
-        json: String,
+        /// Raw JSON webhook payload containing a "workoutId" field, or `-`
+        /// to read it from stdin.
+        #[arg(long, required_unless_present = "stdin")]
+        json: Option<String>,
+
+        /// Read the JSON webhook payload from stdin.
+        #[arg(long, alias = "read-stdin", conflicts_with = "json")]
+        stdin: bool,
+
+        /// Shared secret for verifying the payload signature.
+        #[arg(long, env = "HEVY_WEBHOOK_SECRET", hide_env_values = true)]
+        webhook_secret: Option<String>,
+
+        /// Header holding the signature.
+        #[arg(long, default_value = webhook::SIGNATURE_HEADER)]
+        signature_header: String,
+    },
+
+    /// Run an HTTP server that processes Hevy webhooks as they arrive.
+    ///
+    /// Listens for `POST /webhook` with the same {"workoutId": "..."}
+    /// payload as `process-workout`, fetches the workout, and prints its
+    /// summary table to stdout. Responds 200 on success, 400 for a bad
+    /// payload, 401 for a bad signature, and 502 if the Hevy API fails.
+    ///
+    /// With --secret, every request must carry an X-Hevy-Signature header
+    /// holding the hex-encoded HMAC-SHA256 of the raw body (an optional
+    /// `sha256=` prefix is accepted). Ctrl-C shuts the server down after
+    /// in-flight requests finish.
+    ///
+    /// Example:
+    ///   hevy-bridge webhook-server --port 8080 --secret "$HEVY_WEBHOOK_SECRET"
+    WebhookServer {
+        /// Address to listen on.
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+
+        /// Port to listen on.
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+
+        /// Shared secret for verifying request signatures.
+        #[arg(long, env = "HEVY_WEBHOOK_SECRET", hide_env_values = true)]
+        secret: Option<String>,
+
+        /// Header holding the signature.
+        #[arg(long, default_value = webhook::SIGNATURE_HEADER)]
+        signature_header: String,
+    },
+
+    /// Work out which plates to load on each side of the bar.
+    ///
+    /// Weights are in --units (lbs unless set): the bar defaults to 45 lbs
+    /// or 20 kg, and the plates on hand to 45,35,25,10,5,2.5 lbs or
+    /// 25,20,15,10,5,2.5,1.25 kg, any number of each. `config set plates`
+    /// stores another inventory. Plates are taken heaviest first, falling
+    /// back to a search of every combination when that misses. A weight
+    /// no combination makes is loaded as close as possible without going
+    /// over, with the remainder reported.
+    ///
+    /// With --routine, loads every weighted set of the routine's barbell
+    /// exercises (those with "Barbell" in the title) instead.
+    ///
+    /// Returns JSON; `--format table` prints the plates per side.
+    ///
+    /// Example: hevy-bridge plates 225 --format table
+    /// Example: hevy-bridge --units kg plates 102.5 --available 20,10,5,2.5
+    /// Example: hevy-bridge plates --routine 8a1f3c2e-... --format table
+    Plates {
+        /// Target weight, bar included.
+        #[arg(
+            required_unless_present = "routine",
+            conflicts_with = "routine",
+            value_parser = parse_plate_weight
+        )]
+        weight: Option<f64>,
+
+        /// Bar weight [default: 45 lbs or 20 kg].
+        #[arg(long, value_parser = parse_plate_weight)]
+        bar: Option<f64>,
+
+        /// Plate sizes on hand, comma-separated [default: the plates config
+        /// default, or a standard set].
+        #[arg(long, value_delimiter = ',')]
+        available: Option<Vec<f64>>,
+
+        /// Load the barbell sets of this routine.
+        #[arg(long, value_name = "ROUTINE_ID")]
+        routine: Option<String>,
+    },
+
+    /// Inspect or clear the local exercise template cache.
+    ///
+    /// Exercise templates are cached for 24 hours under
+    /// ~/.cache/hevy-bridge/exercise_templates, one file per API key (they
+    /// include the account's custom templates), when fetched with
+    /// `exercises list --all`.
+    #[command(subcommand)]
+    Cache(CacheCommands),
+
+    /// Print a shell completion script to stdout.
+    ///
+    /// Redirect the output to wherever your shell loads completions from:
+    ///
+    ///   bash:        hevy-bridge completions bash > ~/.local/share/bash-completion/completions/hevy-bridge
+    ///   zsh:         hevy-bridge completions zsh > ~/.zfunc/_hevy-bridge
+    ///                (with `fpath+=~/.zfunc` before `compinit` in ~/.zshrc)
+    ///   fish:        hevy-bridge completions fish > ~/.config/fish/completions/hevy-bridge.fish
+    ///   PowerShell:  hevy-bridge completions powershell >> $PROFILE
+    ///   elvish:      hevy-bridge completions elvish >> ~/.config/elvish/rc.elv
+    #[command(verbatim_doc_comment)]
+    Completions {
+        /// Shell to generate completions for.
+        shell: clap_complete::Shell,
+    },
+}
+
+// ── Cache ─────────────────────────────────────────────
+
+#[derive(Subcommand, Debug)]
+enum CacheCommands {
+    /// Show where the current API key's template cache lives, when it was
+    /// written, how many templates it holds, and how many responses
+    /// `--cache` has stored.
+    ///
+    /// Example: hevy-bridge cache show
+    Show,
+
+    /// Delete the cached exercise templates and cached responses.
+    ///
+    /// Example: hevy-bridge cache clear
+    Clear,
+}
+
+// ── Alias ─────────────────────────────────────────────
+
+#[derive(Subcommand, Debug)]
+enum AliasCommands {
+    /// Make NAME an alias for an exercise template. Fails if NAME already
+    /// points to a different template.
+    ///
+    /// Example: hevy-bridge alias set squat D04AC939
+    Set {
+        /// The alias, e.g. "squat".
+        name: String,
+
+        /// The exercise template ID it stands for.
+        template_id: String,
+    },
+
+    /// List aliases as a JSON object of alias to template ID.
+    ///
+    /// Example: hevy-bridge alias list
+    List,
+
+    /// Remove an alias.
+    ///
+    /// Example: hevy-bridge alias rm squat
+    Rm {
+        /// The alias to remove.
+        name: String,
     },
 }
 
@@ -226,10 +577,117 @@ enum Commands {
 enum ConfigCommands {
     /// Save your API key to ~/.config/hevy-bridge/config.json
     ///
+    /// The key goes to the --profile given, else the default profile. It is
+    /// checked against the API first (as by `config validate`) and not
+    /// stored if the API rejects it; --no-check skips the check.
+    ///
     /// Example: hevy-bridge config set-key abc123-def456-...
+    ///          hevy-bridge config set-key --profile partner abc123-...
     SetKey {
         /// The Hevy API key to store.
         key: String,
+
+        /// Store the key in the OS keyring instead of the config file
+        /// (requires a build with the `keyring` feature).
+        #[arg(long)]
+        keyring: bool,
+
+        /// Store the key without checking it against the API.
+        #[arg(long)]
+        no_check: bool,
+    },
+
+    /// Check that an API key works by fetching the user's profile.
+    ///
+    /// Checks the key given, else the one other commands would use. Exits
+    /// with status 3, like any rejected key, if the API refuses it.
+    ///
+    /// Example: hevy-bridge config validate
+    ///          hevy-bridge config validate abc123-def456-...
+    Validate {
+        /// The Hevy API key to check.
+        key: Option<String>,
+    },
+
+    /// Show the active configuration as JSON: the profile, where the API
+    /// key comes from (flag, key file, env, keyring or config file), and stored defaults.
+    ///
+    /// The key is redacted to its last four characters unless --reveal is
+    /// given, which only works on a terminal or with --yes.
+    ///
+    /// Example: hevy-bridge config show
+    Show {
+        /// Print the full API key.
+        #[arg(long)]
+        reveal: bool,
+
+        /// Allow --reveal when output is not a terminal.
+        #[arg(long, requires = "reveal")]
+        yes: bool,
+    },
+
+    /// Delete the stored API key of the --profile given (else the default
+    /// profile) from the config file and the OS keyring. Asks for
+    /// confirmation unless --yes is given.
+    ///
+    /// Example: hevy-bridge config unset-key
+    UnsetKey {
+        /// Delete without asking.
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// Save the page size list commands use when --page-size is not given.
+    ///
+    /// The API caps pages at 10 items (100 for exercise templates); larger
+    /// preferences are capped per command.
+    ///
+    /// Example: hevy-bridge config set-page-size 10
+    SetPageSize {
+        /// Items per page (1-100).
+        #[arg(value_parser = clap::value_parser!(u32).range(1..=100))]
+        size: u32,
+    },
+
+    /// Store a default used when the matching flag (and its environment
+    /// variable) is not given. Explicit flags always win.
+    ///
+    ///   units      kg or lbs                  (--units, HEVY_UNITS)
+    ///   format     json, table or ndjson      (--format, HEVY_FORMAT)
+    ///   page_size  1-100                      (--page-size, HEVY_PAGE_SIZE)
+    ///   color      auto, always or never      (--color, HEVY_COLOR)
+    ///   bodyweight_kg  body weight in kg      (report strength-score --bodyweight-kg)
+    ///   plates     plate sizes, e.g. 25,20,10 (plates --available)
+    ///
+    /// Example: hevy-bridge config set units kg
+    #[command(verbatim_doc_comment)]
+    Set {
+        /// Setting to change.
+        key: config::DefaultKey,
+
+        /// New value.
+        value: String,
+    },
+
+    /// Print a stored default (nothing if unset).
+    ///
+    /// Example: hevy-bridge config get format
+    Get {
+        /// Setting to read.
+        key: config::DefaultKey,
+    },
+
+    /// List stored profiles and which one is the default.
+    ///
+    /// Example: hevy-bridge config list-profiles
+    ListProfiles,
+
+    /// Make a stored profile the default for later commands.
+    ///
+    /// Example: hevy-bridge config use partner
+    Use {
+        /// Name of the profile.
+        name: String,
     },
 
     /// Print the path to the config file.
@@ -258,52 +716,288 @@ enum WorkoutCommands {
     /// Each workout includes: id, title, description, start_time, end_time,
     /// created_at, updated_at, routine_id, and exercises[].
     ///
+    /// With --all (implied by the routine and exercise filters) every page is
+    /// fetched and a plain JSON array of workouts is printed instead.
+    ///
+    /// --routine-title resolves each distinct routine_id with one extra
+    /// request and keeps workouts whose routine title contains the text
+    /// (case-insensitive).
+    ///
     /// Example: hevy-bridge workouts list --page 1 --page-size 5
-    List {
-        /// Page number (1-based).
-        #[arg(long, default_value_t = 1)]
-        page: u32,
+    /// Example: hevy-bridge workouts list --routine-id <ROUTINE_ID>
+    /// Example: hevy-bridge workouts list --routine-title "push day"
+    /// Example: hevy-bridge workouts list --exercises-include <ID_A>,<ID_B>
+    List(ListWorkoutsArgs),
+
+    /// The most recent workouts, newest first.
+    ///
+    /// Returns a JSON array of up to -n workouts. More than 10 (the API's
+    /// page size limit) are fetched over several pages.
+    ///
+    /// Example: hevy-bridge workouts recent
+    /// Example: hevy-bridge workouts recent -n 20 --format table
+    Recent {
+        /// Number of workouts.
+        #[arg(short, long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+        n: u32,
+    },
+
+    /// The most recent workout.
+    ///
+    /// Returns the workout as a JSON object. Fails if there are no workouts.
+    ///
+    /// Example: hevy-bridge workouts latest
+    Latest,
+
+    /// Find workouts posted twice, and workouts that overlap in time.
+    ///
+    /// Workouts starting within 10 minutes of the earliest of them with
+    /// the same exercises (by template ID, in any order) are taken as
+    /// copies of one workout: the first created is marked keep, the rest
+    /// extra.
+    /// Other workouts whose start-to-end times overlap are listed too,
+    /// as that usually means a wrong timestamp.
+    ///
+    /// Returns JSON: { duplicates: [{ keep, extra[] }], overlaps: [{ first,
+    /// second }] }, each workout as { id, title, start_time, end_time,
+    /// created_at }. The public API cannot delete workouts, so remove the
+    /// extra copies in the Hevy app.
+    ///
+    /// Example: hevy-bridge workouts dedupe --since 2024-01-01 --format table
+    Dedupe {
+        /// Only check workouts started on or after this date (YYYY-MM-DD or ISO 8601).
+        #[arg(long)]
+        since: Option<String>,
+    },
 
-        /// Items per page (max 10).
-        #[arg(long, default_value_t = 5)]
-        page_size: u32,
+    /// Find workouts that contain a given exercise.
+    ///
+    /// Uses the exercise history endpoint, so only one request is needed per
+    /// lookup. --exercise accepts a template ID or a (case-insensitive)
+    /// template title, resolved through the local exercise template cache.
+    ///
+    /// Returns a JSON array, newest first, of:
+    ///   { workout_id, title, date, sets[], best_set }
+    /// where sets[] are the sets of that exercise in the workout and
+    /// best_set is the heaviest of them. With --full the complete workout
+    /// objects are fetched and printed instead.
+    ///
+    /// Example: hevy-bridge workouts find --exercise "Front Squat" --limit 3
+    /// Example: hevy-bridge workouts find --exercise D04AC939 --since 2024-01-01T00:00:00Z --full
+    Find {
+        /// Exercise template ID or title.
+        #[arg(long)]
+        exercise: String,
+
+        /// Only consider workouts on or after this date (ISO 8601).
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Maximum number of workouts to return.
+        #[arg(long)]
+        limit: Option<usize>,
+
+        /// Fetch and print the complete workouts.
+        #[arg(long)]
+        full: bool,
+    },
+
+    /// Search workouts by title or description.
+    ///
+    /// Pages through the workout list (newest first) and matches QUERY
+    /// case-insensitively against each title and description. With --since,
+    /// paging stops as soon as older workouts are reached. The number of
+    /// pages scanned is reported on stderr.
+    ///
+    /// Returns a JSON array of matching workouts (or a table of id, title,
+    /// and date with --format table).
+    ///
+    /// Example: hevy-bridge workouts search "leg day" --since 2024-01-01
+    /// Example: hevy-bridge workouts search '^push|pull$' --regex
+    Search {
+        /// Text (or regular expression with --regex) to look for.
+        query: String,
+
+        /// Stop once workouts older than this date are reached.
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Treat QUERY as a regular expression.
+        #[arg(long)]
+        regex: bool,
     },
 
-    /// Get a single workout by its ID.
+    /// Get a single workout by its ID, or by its title.
     ///
     /// Returns the full workout JSON including all exercises and sets.
+    /// Like a git short hash, the first few characters of an ID will do
+    /// when only one workout ID starts with them. The API cannot look IDs
+    /// up by prefix, so this lists every workout, which may take many
+    /// requests (--cache serves recent lists from disk; --no-scan skips
+    /// it). With --title, workouts are paged through newest first. When several
+    /// match, their IDs and dates are listed on stderr and the command
+    /// fails, unless --latest or --all-matches picks for it.
     ///
     /// Example: hevy-bridge workouts get b459cba5-cd6d-463c-abd6-54f8eafcadcb
+    /// Example: hevy-bridge workouts get --title "Deadlift Day" --latest
     Get {
-        /// The workout ID (UUID).
-        id: String,
+        /// The workout ID (UUID), or the start of one.
+        #[arg(required_unless_present_any = ["ids_from_stdin", "title"])]
+        id: Option<String>,
+        /// Send an ID shorter than a full UUID to the API as it is, instead
+        /// of listing every workout to find the one it starts.
+        #[arg(long, requires = "id")]
+        no_scan: bool,
+        /// Read workout IDs from stdin, one per line, and print one JSON line
+        /// per ID in input order. IDs that cannot be fetched print
+        /// {"id": ..., "error": ...} instead of stopping the batch.
+        #[arg(long, conflicts_with = "id")]
+        ids_from_stdin: bool,
+        /// With --ids-from-stdin, stop at the first ID that cannot be fetched.
+        #[arg(long, requires = "ids_from_stdin")]
+        fail_fast: bool,
+        /// Get the workout whose title contains TITLE (ignoring case)
+        /// instead of one by ID.
+        #[arg(long, conflicts_with_all = ["id", "ids_from_stdin"])]
+        title: Option<String>,
+        /// With --title, match the whole title (ignoring case).
+        #[arg(long, requires = "title")]
+        exact: bool,
+        /// With --title, print the most recent match when several match.
+        #[arg(long, requires = "title", conflicts_with = "all_matches")]
+        latest: bool,
+        /// With --title, print every match as a JSON array.
+        #[arg(long, requires = "title")]
+        all_matches: bool,
+        /// With --title, only look at workouts since this date.
+        #[arg(long, requires = "title")]
+        since: Option<String>,
     },
 
     /// Get the total number of workouts on the account.
     ///
     /// Returns JSON: { "workout_count": <number> }
     ///
+    /// With --by-routine, fetches every workout and routine and returns
+    /// workout counts by routine ID instead, most followed first, with
+    /// each routine's title (null if deleted):
+    ///   { "counts": { "<routine_id>": <number>, ... },
+    ///     "titles": { "<routine_id>": "<title>", ... } }
+    /// Workouts not started from a routine are counted under "ad_hoc".
+    ///
     /// Example: hevy-bridge workouts count
-    Count,
+    /// Example: hevy-bridge workouts count --by-routine --format table
+    Count {
+        /// Count the workouts of each routine.
+        #[arg(long)]
+        by_routine: bool,
+    },
+
+    /// List the workouts logged today (UTC).
+    ///
+    /// Returns a JSON array; prints [] (and a note on stderr) when nothing
+    /// has been logged yet.
+    ///
+    /// Example: hevy-bridge workouts today
+    Today,
+
+    /// List the workouts logged yesterday (UTC).
+    ///
+    /// Example: hevy-bridge workouts yesterday
+    Yesterday,
+
+    /// Report how long each workout lasted.
+    ///
+    /// Fetches all workouts in the (optional) date range and computes
+    /// end_time - start_time for each. Workouts without an end_time are
+    /// skipped with a warning on stderr.
+    ///
+    /// Returns JSON: { workouts: [{ workout_id, title, date,
+    /// duration_minutes }], avg_duration_minutes, min_duration_minutes,
+    /// max_duration_minutes }, with workouts sorted oldest first.
+    ///
+    /// Dates accept YYYY-MM-DD or ISO 8601; --until is exclusive, and a
+    /// plain date covers that whole day.
+    ///
+    /// Example: hevy-bridge workouts duration --since 2024-01-01 --until 2024-03-31
+    Duration {
+        /// Only workouts starting on or after this date.
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only workouts starting before this date.
+        #[arg(long)]
+        until: Option<String>,
+    },
+
+    /// Compare two workouts exercise by exercise.
+    ///
+    /// Exercises are matched by exercise_template_id and their sets paired
+    /// in order. Returns a JSON array of { exercise_template_id, title,
+    /// sets: [{ set_a: { weight_kg, reps }, set_b, weight_delta_kg,
+    /// reps_delta }] }, where deltas are B minus A. Exercises or sets found
+    /// in only one workout have null for the missing side.
+    ///
+    /// Example: hevy-bridge workouts diff <WORKOUT_ID_A> <WORKOUT_ID_B>
+    Diff {
+        /// The earlier (baseline) workout ID.
+        id_a: String,
+
+        /// The workout ID to compare against it.
+        id_b: String,
+    },
 
     /// List workout events (updates and deletes) since a given date.
     ///
     /// Useful for syncing a local cache. Events are ordered newest to oldest.
     /// Returns: page, page_count, events[] (each tagged "updated" or "deleted").
     ///
+    /// With --follow the command keeps polling (every --interval) and prints
+    /// each new event as one line of JSON (NDJSON), flushing after every
+    /// line. Already-printed events are not repeated, rate limits and server
+    /// errors are retried with backoff, and Ctrl-C stops cleanly. Pass
+    /// --state-file to remember the newest event across restarts.
+    ///
+    /// With --all every page is fetched and merged into one { "events": [...] }
+    /// document. --type keeps only updated or deleted events, and --ndjson
+    /// prints one event per line instead (streamed page by page with --all).
+    ///
     /// Example: hevy-bridge workouts events --since 2024-01-01T00:00:00Z
+    /// Example: hevy-bridge workouts events --all --since 2024-01-01T00:00:00Z
+    /// Example: hevy-bridge workouts events --all --type deleted --ndjson
+    /// Example: hevy-bridge workouts events --follow --interval 5m --state-file ~/.hevy-events.json
     Events {
         /// Page number (1-based).
         #[arg(long, default_value_t = 1)]
         page: u32,
 
-        /// Items per page (max 10).
-        #[arg(long, default_value_t = 5)]
-        page_size: u32,
+        /// Items per page (max 10; default 5 or `config set-page-size`).
+        #[arg(long)]
+        page_size: Option<u32>,
+
+        /// RFC 3339 timestamp to filter events from (e.g. 2024-01-01T00:00:00Z).
+        #[arg(long, value_parser = dates::parse_timestamp)]
+        since: Option<Timestamp>,
+
+        /// Fetch every page instead of one.
+        #[arg(long, conflicts_with_all = ["page", "page_size", "follow"])]
+        all: bool,
+
+        /// Only include events of this type.
+        #[arg(long = "type", value_enum)]
+        event_type: Option<events::EventType>,
 
-        /// ISO 8601 date to filter events from (e.g. 2024-01-01T00:00:00Z).
+        /// Keep polling and print new events as NDJSON.
         #[arg(long)]
-        since: Option<String>,
+        follow: bool,
+
+        /// Poll interval for --follow (e.g. 30s, 5m, 1h).
+        #[arg(long, default_value = "60s", value_parser = dates::parse_interval, requires = "follow")]
+        interval: std::time::Duration,
+
+        /// File storing the newest seen event timestamp for --follow.
+        #[arg(long, requires = "follow")]
+        state_file: Option<PathBuf>,
     },
 
     /// Create a new workout.
@@ -339,11 +1033,63 @@ enum WorkoutCommands {
     /// Set types: "normal", "warmup", "failure", "dropset"
     /// RPE values: 6, 7, 7.5, 8, 8.5, 9, 9.5, 10
     ///
+    /// With --from-routine, the body is built from a routine instead: its
+    /// title and exercises, each set pre-filled with the routine's target
+    /// weight and reps (the low end of a rep range), starting now and
+    /// ending an hour later unless --start or --end say otherwise. Log the
+    /// weights and reps actually done afterwards with `workouts update`.
+    ///
+    /// With --generate-warmups, warm-up sets are put before each exercise's
+    /// first working set: one per step of the spec, at that share of the
+    /// working weight rounded to --round-to in --units, never lighter than
+    /// the bar (45 lbs or 20 kg). Exercises working below the bar get none.
+    ///
     /// Example: hevy-bridge workouts create --json '{"workout":{...}}'
+    /// Example: hevy-bridge workouts create --file workout.json
+    /// Example: hevy-bridge workouts create --from-routine <ROUTINE_ID> --start 2024-08-14T12:00:00Z
+    /// Example: hevy-bridge workouts create --file workout.json --generate-warmups 40%x5,60%x3,80%x1 --dry-run
     Create {
         /// Raw JSON body (PostWorkoutsRequestBody).
+        #[arg(
+            long,
+            required_unless_present_any = ["file", "from_routine"],
+            conflicts_with_all = ["file", "from_routine"]
+        )]
+        json: Option<String>,
+
+        /// Read the JSON body from this file.
+        #[arg(long, conflicts_with = "from_routine")]
+        file: Option<PathBuf>,
+
+        /// Build the workout from this routine (ID).
         #[arg(long)]
-        json: String,
+        from_routine: Option<String>,
+
+        /// Title of the workout [default: the routine's title].
+        #[arg(long, requires = "from_routine")]
+        title: Option<String>,
+
+        /// Start time, ISO 8601 [default: now].
+        #[arg(long, requires = "from_routine")]
+        start: Option<String>,
+
+        /// End time, ISO 8601 [default: an hour after the start].
+        #[arg(long, requires = "from_routine")]
+        end: Option<String>,
+
+        /// Put warm-up sets before each exercise's first working set, e.g.
+        /// "40%x5,60%x3,80%x1" (PERCENT%xREPS steps).
+        #[arg(long, value_name = "SPEC", value_parser = warmup::parse_spec)]
+        generate_warmups: Option<warmup::Spec>,
+
+        /// Round warm-up weights to this increment, in --units [default: 5
+        /// lbs or 2.5 kg].
+        #[arg(long, requires = "generate_warmups")]
+        round_to: Option<f64>,
+
+        /// Print the body that would be sent instead of sending it.
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Update an existing workout.
@@ -359,43 +1105,221 @@ enum WorkoutCommands {
         #[arg(long)]
         json: String,
     },
-}
-
-// ── Routines ──────────────────────────────────────────
 
-#[derive(Subcommand, Debug)]
-enum RoutineCommands {
-    /// List routines (paginated).
+    /// Export workouts, oldest first, for backup or a calendar.
     ///
-    /// Returns: page, page_count, routines[]
-    /// Each routine includes exercises with target sets and optional rep_range.
+    /// --format json (the default) writes a JSON array of workouts and
+    /// ndjson one workout per line. --format ics writes an iCalendar file
+    /// with one event per workout: SUMMARY is the title, DTSTART/DTEND come
+    /// from start_time/end_time (start + 60 minutes when there is no
+    /// end_time), and DESCRIPTION lists each exercise's sets, weights in
+    /// --units. The UID is the workout ID, so re-importing the file updates
+    /// events instead of duplicating them.
     ///
-    /// Example: hevy-bridge routines list --page 1 --page-size 5
-    List {
-        /// Page number (1-based).
-        #[arg(long, default_value_t = 1)]
-        page: u32,
+    /// Dates accept YYYY-MM-DD or ISO 8601; --until is exclusive, and a
+    /// plain date covers that whole day.
+    ///
+    /// Example: hevy-bridge workouts export --format ics --output calendar.ics
+    /// Example: hevy-bridge workouts export --since 2024-01-01 --output workouts.json
+    Export {
+        /// Only workouts starting on or after this date.
+        #[arg(long)]
+        since: Option<String>,
 
-        /// Items per page (max 10).
-        #[arg(long, default_value_t = 5)]
-        page_size: u32,
+        /// Only workouts starting before this date.
+        #[arg(long)]
+        until: Option<String>,
     },
 
-    /// Get a single routine by its ID.
+    /// Create workouts from a `workouts export` file, e.g. in another account.
     ///
-    /// Example: hevy-bridge routines get <ROUTINE_ID>
-    Get {
-        /// The routine ID.
-        id: String,
-    },
-
-    /// Create a new routine.
+    /// Each workout is created with one POST request, in file order. Progress
+    /// goes to stderr; a workout that fails is skipped and the rest are still
+    /// imported. Prints a JSON summary: { total, imported, dry_run,
+    /// failed: [{ index, title, error }] }, and exits non-zero if any failed.
     ///
-    /// JSON schema (PostRoutinesRequestBody):
+    /// Note that the API cannot delete workouts, so importing the same file
+    /// twice creates duplicates.
     ///
-    ///   {
-    ///     "routine": {
-    ///       "title": "Push Day",
+    /// Example: hevy-bridge workouts import workouts.json --delay-ms 500
+    /// Example: hevy-bridge workouts import workouts.json --dry-run
+    Import {
+        /// File to read.
+        file: PathBuf,
+
+        /// Format of the file.
+        #[arg(long, value_enum, default_value_t)]
+        input_format: export::ImportFormat,
+
+        /// Check every workout can be converted without creating anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Pause between create requests, in milliseconds.
+        #[arg(long)]
+        delay_ms: Option<u64>,
+    },
+    /// Back up the whole account into a directory.
+    ///
+    /// Fetches every workout, routine, exercise template and routine folder
+    /// and writes each to its own JSON file under workouts/, routines/,
+    /// exercise_templates/ and routine_folders/, named by ID. manifest.json
+    /// records the account, when the backup was taken and how many items of
+    /// each kind it holds. The directory must be new or empty.
+    ///
+    /// Example: hevy-bridge workouts backup ./hevy-backup
+    Backup {
+        /// Directory to write the backup to.
+        output_dir: PathBuf,
+    },
+
+    /// Re-create an account from a `workouts backup` directory.
+    ///
+    /// Creates the routine folders first, then the routines (in the new
+    /// copies of their folders), then the workouts, oldest first. Items that
+    /// fail are skipped and the rest are still restored. Prints a JSON
+    /// summary per kind: { total, restored, failed: [{ id, title, error }] },
+    /// and exits non-zero if anything failed.
+    ///
+    /// Custom exercise templates are kept in the backup for reference but
+    /// not re-created. The API cannot delete anything, so restoring into an
+    /// account that still has the data creates duplicates.
+    ///
+    /// Example: hevy-bridge workouts restore ./hevy-backup --dry-run
+    Restore {
+        /// Directory holding the backup.
+        input_dir: PathBuf,
+
+        /// Check every item can be converted without creating anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Options for `workouts list`.
+#[derive(Args, Debug, Default)]
+struct ListWorkoutsArgs {
+    /// Page number (1-based).
+    #[arg(long, default_value_t = 1)]
+    page: u32,
+
+    /// Items per page (max 10; default 5 or `config set-page-size`).
+    #[arg(long)]
+    page_size: Option<u32>,
+
+    /// Fetch all pages (ignores --page and --page-size).
+    #[arg(long)]
+    all: bool,
+
+    /// Only workouts performed from this routine (implies --all).
+    #[arg(long, conflicts_with = "routine_title")]
+    routine_id: Option<String>,
+
+    /// Only workouts whose routine title contains this text (implies --all).
+    #[arg(long)]
+    routine_title: Option<String>,
+
+    /// Only workouts containing any of these exercise template IDs
+    /// (repeatable or comma-separated; implies --all).
+    #[arg(long, value_name = "TEMPLATE_ID", value_delimiter = ',')]
+    exercises_include: Vec<String>,
+
+    /// Skip workouts containing any of these exercise template IDs
+    /// (repeatable or comma-separated; implies --all).
+    #[arg(long, value_name = "TEMPLATE_ID", value_delimiter = ',')]
+    exercises_exclude: Vec<String>,
+}
+
+// ── Routines ──────────────────────────────────────────
+
+#[derive(Subcommand, Debug)]
+enum RoutineCommands {
+    /// List routines (paginated).
+    ///
+    /// Returns: page, page_count, routines[]
+    /// Each routine includes exercises with target sets and optional rep_range.
+    ///
+    /// With --all (implied by the folder filters) every page is fetched and
+    /// a plain JSON array of routines is printed instead.
+    ///
+    /// The API reports folder_id as a number (a float in the JSON model);
+    /// --folder-id takes it as an integer, as shown by `folders list`.
+    ///
+    /// Example: hevy-bridge routines list --page 1 --page-size 5
+    /// Example: hevy-bridge routines list --all --ndjson
+    /// Example: hevy-bridge routines list --folder-id 42
+    List {
+        /// Page number (1-based).
+        #[arg(long, default_value_t = 1)]
+        page: u32,
+
+        /// Items per page (max 10; default 5 or `config set-page-size`).
+        #[arg(long)]
+        page_size: Option<u32>,
+
+        /// Fetch all pages (ignores --page and --page-size).
+        #[arg(long)]
+        all: bool,
+
+        /// Only routines in this folder (implies --all).
+        #[arg(long, conflicts_with = "no_folder")]
+        folder_id: Option<u64>,
+
+        /// Only routines that are not in any folder (implies --all).
+        #[arg(long)]
+        no_folder: bool,
+    },
+
+    /// Get a single routine by its ID, or by its title.
+    ///
+    /// The first few characters of an ID will do when only one routine ID
+    /// starts with them. The API cannot look IDs up by prefix, so this
+    /// lists every routine (--no-scan skips it).
+    ///
+    /// With --title, several matches fail the command (listing them on
+    /// stderr) unless --latest (most recently updated) or --all-matches
+    /// is given.
+    ///
+    /// Example: hevy-bridge routines get <ROUTINE_ID>
+    /// Example: hevy-bridge routines get --title "push day" --exact
+    Get {
+        /// The routine ID, or the start of one.
+        #[arg(required_unless_present_any = ["ids_from_stdin", "title"])]
+        id: Option<String>,
+        /// Send an ID shorter than a full UUID to the API as it is, instead
+        /// of listing every routine to find the one it starts.
+        #[arg(long, requires = "id")]
+        no_scan: bool,
+        /// Read routine IDs from stdin, one per line, and print one JSON line
+        /// per ID in input order. IDs that cannot be fetched print
+        /// {"id": ..., "error": ...} instead of stopping the batch.
+        #[arg(long, conflicts_with = "id")]
+        ids_from_stdin: bool,
+        /// With --ids-from-stdin, stop at the first ID that cannot be fetched.
+        #[arg(long, requires = "ids_from_stdin")]
+        fail_fast: bool,
+        /// Get the routine whose title contains TITLE (ignoring case)
+        /// instead of one by ID.
+        #[arg(long, conflicts_with_all = ["id", "ids_from_stdin"])]
+        title: Option<String>,
+        /// With --title, match the whole title (ignoring case).
+        #[arg(long, requires = "title")]
+        exact: bool,
+        /// With --title, print the most recent match when several match.
+        #[arg(long, requires = "title", conflicts_with = "all_matches")]
+        latest: bool,
+        /// With --title, print every match as a JSON array.
+        #[arg(long, requires = "title")]
+        all_matches: bool,
+    },
+
+    /// Create a new routine.
+    ///
+    /// JSON schema (PostRoutinesRequestBody):
+    ///
+    ///   {
+    ///     "routine": {
+    ///       "title": "Push Day",
     ///       "folder_id": null,
     ///       "notes": "Focus on form",
     ///       "exercises": [
@@ -417,11 +1341,28 @@ enum RoutineCommands {
     ///     }
     ///   }
     ///
+    /// --generate-warmups adds warm-up sets as for `workouts create`.
+    ///
     /// Example: hevy-bridge routines create --json '{"routine":{...}}'
+    /// Example: hevy-bridge routines create --json '{"routine":{...}}' --generate-warmups 50%x5,75%x3
     Create {
         /// Raw JSON body (PostRoutinesRequestBody).
         #[arg(long)]
         json: String,
+
+        /// Put warm-up sets before each exercise's first working set, e.g.
+        /// "40%x5,60%x3,80%x1" (PERCENT%xREPS steps).
+        #[arg(long, value_name = "SPEC", value_parser = warmup::parse_spec)]
+        generate_warmups: Option<warmup::Spec>,
+
+        /// Round warm-up weights to this increment, in --units [default: 5
+        /// lbs or 2.5 kg].
+        #[arg(long, requires = "generate_warmups")]
+        round_to: Option<f64>,
+
+        /// Print the body that would be sent instead of sending it.
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Update an existing routine.
@@ -432,7 +1373,13 @@ enum RoutineCommands {
     ///     "routine": {
     ///       "title": "Updated Push Day",
     ///       "notes": "...",
-    ///       "exercises": [...]
+    ///       "exercises": [
+    ///         {
+    ///           "exercise_template_id": "D04AC939",
+    ///           "notes": "Pause at the bottom",
+    ///           "sets": [...]
+    ///         }
+    ///       ]
     ///     }
     ///   }
     ///
@@ -445,6 +1392,41 @@ enum RoutineCommands {
         #[arg(long)]
         json: String,
     },
+
+    /// Move a routine into a different folder (or out of any folder).
+    ///
+    /// The Hevy API does not accept folder_id on PUT /v1/routines, so a
+    /// routine cannot be moved in place. With --via-recreate the routine is
+    /// copied into the target folder via POST /v1/routines instead.
+    ///
+    /// WARNING: the copy gets a NEW routine ID. The public API has no
+    /// endpoint for deleting routines, so the original must be removed in
+    /// the Hevy app afterwards.
+    ///
+    /// The target folder is verified to exist before anything is changed.
+    ///
+    /// Example: hevy-bridge routines move <ROUTINE_ID> --folder-id 42 --via-recreate
+    #[command(visible_alias = "move-to-folder")]
+    Move {
+        /// The routine ID to move.
+        id: String,
+
+        /// Destination folder ID.
+        #[arg(
+            long,
+            conflicts_with = "no_folder",
+            required_unless_present = "no_folder"
+        )]
+        folder_id: Option<u64>,
+
+        /// Move the routine out of any folder.
+        #[arg(long)]
+        no_folder: bool,
+
+        /// Acknowledge that the move is done by creating a copy with a new ID.
+        #[arg(long)]
+        via_recreate: bool,
+    },
 }
 
 // ── Exercises ─────────────────────────────────────────
@@ -457,17 +1439,52 @@ enum ExerciseCommands {
     /// Each template: id, title, type, primary_muscle_group,
     /// secondary_muscle_groups, is_custom.
     ///
-    /// TIP: Use --page-size 100 (max) to fetch many at once.
+    /// TIP: Use --page-size 100 (max) to fetch many at once, or --all to
+    /// fetch every template as a JSON array. --all results are cached on
+    /// disk for 24 hours (see `hevy-bridge cache --help`).
     ///
     /// Example: hevy-bridge exercises list --page-size 100
+    /// --custom-only and --builtin-only filter on is_custom; on a single
+    /// page they filter that page only.
+    ///
+    /// Example: hevy-bridge exercises list --all
+    /// Example: hevy-bridge exercises list --all --custom-only
+    /// Example: hevy-bridge exercises list --all --muscle-group chest --include-secondary
+    /// Example: hevy-bridge exercises list --all --format table
     List {
         /// Page number (1-based).
         #[arg(long, default_value_t = 1)]
         page: u32,
 
-        /// Items per page (max 100).
-        #[arg(long, default_value_t = 5)]
-        page_size: u32,
+        /// Items per page (max 100; default 5 or `config set-page-size`).
+        #[arg(long)]
+        page_size: Option<u32>,
+
+        /// Fetch all pages (ignores --page and --page-size).
+        #[arg(long)]
+        all: bool,
+
+        /// With --all, use the cached templates regardless of their age
+        /// (the global --no-cache bypasses the cache entirely).
+        #[arg(long, conflicts_with = "no_cache")]
+        cached: bool,
+
+        /// Only custom exercise templates.
+        #[arg(long, conflicts_with = "builtin_only")]
+        custom_only: bool,
+
+        /// Only Hevy's built-in exercise templates.
+        #[arg(long)]
+        builtin_only: bool,
+
+        /// Only templates whose primary muscle group is this one, e.g.
+        /// chest (case-insensitive).
+        #[arg(long, value_name = "GROUP")]
+        muscle_group: Option<String>,
+
+        /// With --muscle-group, also match the secondary muscle groups.
+        #[arg(long, requires = "muscle_group")]
+        include_secondary: bool,
     },
 
     /// Get a single exercise template by ID.
@@ -475,7 +1492,16 @@ enum ExerciseCommands {
     /// Example: hevy-bridge exercises get D04AC939
     Get {
         /// The exercise template ID.
-        id: String,
+        #[arg(required_unless_present = "ids_from_stdin")]
+        id: Option<String>,
+        /// Read exercise template IDs from stdin, one per line, and print one JSON line
+        /// per ID in input order. IDs that cannot be fetched print
+        /// {"id": ..., "error": ...} instead of stopping the batch.
+        #[arg(long, conflicts_with = "id")]
+        ids_from_stdin: bool,
+        /// With --ids-from-stdin, stop at the first ID that cannot be fetched.
+        #[arg(long, requires = "ids_from_stdin")]
+        fail_fast: bool,
     },
 
     /// Create a custom exercise template.
@@ -513,6 +1539,26 @@ enum ExerciseCommands {
         #[arg(long)]
         json: String,
     },
+
+    /// Map exercise names to template IDs.
+    ///
+    /// Each name is matched against every exercise template (cached like
+    /// `exercises list --all`): a template ID or exact title first, then
+    /// by similarity, ignoring case, word order, punctuation and plurals.
+    ///
+    /// Returns: an object keyed by input name, each value
+    /// { id, matched_title, score, ambiguous, alternatives[] }. id and
+    /// matched_title are null when no template scores at least 0.6, or when
+    /// the best two are too close to choose (ambiguous); alternatives then
+    /// lists the closest templates instead.
+    ///
+    /// Example: hevy-bridge exercises resolve --names "Bench Press (Barbell),Lat Pulldown (Cable)"
+    /// Example: cat names.txt | hevy-bridge exercises resolve
+    Resolve {
+        /// Comma-separated names; without it, one name per line on stdin.
+        #[arg(long, value_delimiter = ',')]
+        names: Option<Vec<String>>,
+    },
 }
 
 // ── Folders ───────────────────────────────────────────
@@ -524,23 +1570,70 @@ enum FolderCommands {
     /// Returns: page, page_count, routine_folders[]
     /// Each folder: id, index, title, updated_at, created_at.
     ///
+    /// With --with-routines, every folder and every routine is fetched
+    /// (--page and --page-size are ignored) and each folder gains
+    /// routine_count and routines[] ({id, title}). Routines without a
+    /// folder, or whose folder no longer exists, are collected in a
+    /// synthetic "No folder" entry (id null).
+    /// Supports --format table.
+    ///
+    /// --format table alone lists ID, index, title, routine count and
+    /// creation date; counting fetches every routine as well.
+    ///
     /// Example: hevy-bridge folders list
+    /// Example: hevy-bridge folders list --format table
+    /// Example: hevy-bridge folders list --with-routines --format table
     List {
         /// Page number (1-based).
         #[arg(long, default_value_t = 1)]
         page: u32,
 
-        /// Items per page (max 10).
-        #[arg(long, default_value_t = 5)]
-        page_size: u32,
+        /// Items per page (max 10; default 5 or `config set-page-size`).
+        #[arg(long)]
+        page_size: Option<u32>,
+
+        /// Include the routines contained in each folder (fetches all
+        /// folders; ignores --page and --page-size).
+        #[arg(long)]
+        with_routines: bool,
     },
 
-    /// Get a single routine folder by ID.
+    /// Get a single routine folder by ID, or by its title.
+    ///
+    /// With --title, several matches fail the command (listing them on
+    /// stderr) unless --latest (most recently updated) or --all-matches
+    /// is given.
     ///
     /// Example: hevy-bridge folders get 42
+    /// Example: hevy-bridge folders get --title strength
     Get {
         /// The folder ID.
-        id: String,
+        #[arg(
+            required_unless_present_any = ["ids_from_stdin", "title"],
+            value_parser = parse_folder_id
+        )]
+        id: Option<u64>,
+        /// Read folder IDs from stdin, one per line, and print one JSON line
+        /// per ID in input order. IDs that cannot be fetched print
+        /// {"id": ..., "error": ...} instead of stopping the batch.
+        #[arg(long, conflicts_with = "id")]
+        ids_from_stdin: bool,
+        /// With --ids-from-stdin, stop at the first ID that cannot be fetched.
+        #[arg(long, requires = "ids_from_stdin")]
+        fail_fast: bool,
+        /// Get the folder whose title contains TITLE (ignoring case)
+        /// instead of one by ID.
+        #[arg(long, conflicts_with_all = ["id", "ids_from_stdin"])]
+        title: Option<String>,
+        /// With --title, match the whole title (ignoring case).
+        #[arg(long, requires = "title")]
+        exact: bool,
+        /// With --title, print the most recent match when several match.
+        #[arg(long, requires = "title", conflicts_with = "all_matches")]
+        latest: bool,
+        /// With --title, print every match as a JSON array.
+        #[arg(long, requires = "title")]
+        all_matches: bool,
     },
 
     /// Create a new routine folder.
@@ -585,444 +1678,3533 @@ enum HistoryCommands {
         #[arg(long)]
         end: Option<String>,
     },
-}
-
-// ─────────────────────────────────────────────────────
-// Entrypoint
-// ─────────────────────────────────────────────────────
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    /// Show how an exercise's sets spread over rep ranges and intensities.
+    ///
+    /// Buckets every set by reps (1-3, 4-6, 7-10, 11-15, 16+) and by weight
+    /// as a share of the best estimated one-rep max in the exercise's
+    /// whole history (<60%, 60-70%, 70-80%, 80-90%, 90%+), with the number
+    /// of sets and their tonnage (weight × reps) per bucket. The e1RM uses
+    /// Epley's formula, weight × (1 + reps / 30). Sets without weight or
+    /// reps are counted under unclassified. Without any set having both,
+    /// there is no e1RM and intensity is null.
+    ///
+    /// Returns JSON; --format table draws the buckets as bar charts.
+    ///
+    /// Dates accept YYYY-MM-DD or ISO 8601; --end is exclusive, and a
+    /// plain date covers that whole day. They limit the sets bucketed, not
+    /// the history the e1RM is taken from.
+    ///
+    /// Example: hevy-bridge history distribution D04AC939 --start 2024-01-01 --format table
+    Distribution {
+        /// The exercise template ID.
+        exercise_template_id: String,
 
-    match cli.command {
-        // ── Config ─────────────────────────
-        Commands::Config(cmd) => match cmd {
-            ConfigCommands::SetKey { key } => {
-                store_api_key(&key)?;
-                eprintln!("✓ API key saved to {}", config_path().display());
-            }
-            ConfigCommands::Path => {
-                println!("{}", config_path().display());
-            }
-        },
+        /// Only sets on or after this date.
+        #[arg(long)]
+        start: Option<String>,
 
-        // ── User ───────────────────────────
-        Commands::User(cmd) => {
-            let api_key = resolve_api_key(&cli.api_key)?;
-            let client = HevyClient::new(api_key);
-            match cmd {
-                UserCommands::Info => {
-                    let info = client.user_info().await?;
-                    println!("{}", serde_json::to_string_pretty(&info)?);
-                }
-            }
-        }
+        /// Only sets before this date.
+        #[arg(long)]
+        end: Option<String>,
+    },
 
-        // ── Workouts ───────────────────────
-        Commands::Workouts(cmd) => {
-            let api_key = resolve_api_key(&cli.api_key)?;
-            let client = HevyClient::new(api_key);
-            match cmd {
-                WorkoutCommands::List { page, page_size } => {
-                    let data = client.list_workouts(page, page_size).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                WorkoutCommands::Get { id } => {
-                    let data = client.get_workout(&id).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                WorkoutCommands::Count => {
-                    let data = client.workout_count().await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                WorkoutCommands::Events {
-                    page,
-                    page_size,
-                    since,
-                } => {
-                    let data = client
-                        .workout_events(page, page_size, since.as_deref())
-                        .await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                WorkoutCommands::Create { json } => {
-                    let body: PostWorkoutBody = serde_json::from_str(&json)
-                        .context("Invalid JSON for workout body. See `hevy-bridge workouts create --help` for the expected schema.")?;
-                    let data = client.create_workout(&body).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                WorkoutCommands::Update { id, json } => {
-                    let body: PostWorkoutBody = serde_json::from_str(&json)
-                        .context("Invalid JSON for workout body. See `hevy-bridge workouts update --help` for the expected schema.")?;
-                    let data = client.update_workout(&id, &body).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-            }
-        }
+    /// Look for plateaus, regressions and deloads in an exercise's history.
+    ///
+    /// Groups the exercise's sets into sessions (one per workout) and takes
+    /// each session's best estimated one-rep max (Epley: weight × (1 +
+    /// reps / 30)) and volume (weight × reps). It then flags:
+    ///
+    ///   plateau     no new best e1RM in the last --plateau-sessions sessions
+    ///   regression  the mean best e1RM of the last --window sessions is
+    ///               more than --regression-pct below the same mean
+    ///               --lookback sessions earlier
+    ///   deload      a week whose volume is more than --deload-drop below
+    ///               the mean of the training weeks before and after it
+    ///
+    /// Returns JSON with each verdict and its numbers, the session series,
+    /// and a one-line summary, e.g. "No e1RM PR in 9 sessions; last PR
+    /// 2024-03-02 at 142.5 kg".
+    ///
+    /// Example: hevy-bridge history analyze D04AC939 --plateau-sessions 8
+    Analyze {
+        /// The exercise template ID.
+        exercise_template_id: String,
 
-        // ── Routines ──────────────────────
-        Commands::Routines(cmd) => {
-            let api_key = resolve_api_key(&cli.api_key)?;
-            let client = HevyClient::new(api_key);
-            match cmd {
-                RoutineCommands::List { page, page_size } => {
-                    let data = client.list_routines(page, page_size).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                RoutineCommands::Get { id } => {
-                    let data = client.get_routine(&id).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                RoutineCommands::Create { json } => {
-                    let body: PostRoutineBody = serde_json::from_str(&json)
-                        .context("Invalid JSON for routine body. See `hevy-bridge routines create --help` for the expected schema.")?;
-                    let data = client.create_routine(&body).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                RoutineCommands::Update { id, json } => {
-                    let body: PutRoutineBody = serde_json::from_str(&json)
-                        .context("Invalid JSON for routine body. See `hevy-bridge routines update --help` for the expected schema.")?;
-                    let data = client.update_routine(&id, &body).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-            }
-        }
+        /// Sessions without a new best e1RM that count as a plateau.
+        #[arg(long, default_value_t = 6)]
+        plateau_sessions: usize,
 
-        // ── Exercises ─────────────────────
-        Commands::Exercises(cmd) => {
-            let api_key = resolve_api_key(&cli.api_key)?;
-            let client = HevyClient::new(api_key);
-            match cmd {
-                ExerciseCommands::List { page, page_size } => {
-                    let data = client.list_exercise_templates(page, page_size).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                ExerciseCommands::Get { id } => {
-                    let data = client.get_exercise_template(&id).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                ExerciseCommands::Create { json } => {
-                    let body: CreateExerciseBody = serde_json::from_str(&json)
-                        .context("Invalid JSON for exercise body. See `hevy-bridge exercises create --help` for the expected schema.")?;
-                    let data = client.create_exercise_template(&body).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-            }
-        }
+        /// Sessions in each rolling average of best e1RM.
+        #[arg(long, default_value_t = 3, value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+        window: usize,
 
-        // ── Folders ───────────────────────
-        Commands::Folders(cmd) => {
-            let api_key = resolve_api_key(&cli.api_key)?;
-            let client = HevyClient::new(api_key);
-            match cmd {
-                FolderCommands::List { page, page_size } => {
-                    let data = client.list_routine_folders(page, page_size).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                FolderCommands::Get { id } => {
-                    let data = client.get_routine_folder(&id).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-                FolderCommands::Create { json } => {
-                    let body: PostRoutineFolderBody = serde_json::from_str(&json)
-                        .context("Invalid JSON for folder body. See `hevy-bridge folders create --help` for the expected schema.")?;
-                    let data = client.create_routine_folder(&body).await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-            }
-        }
+        /// How many sessions earlier the rolling average compared against ends.
+        #[arg(long, default_value_t = 6)]
+        lookback: usize,
 
-        // ── History ───────────────────────
-        Commands::History(cmd) => {
-            let api_key = resolve_api_key(&cli.api_key)?;
-            let client = HevyClient::new(api_key);
-            match cmd {
-                HistoryCommands::Get {
-                    exercise_template_id,
-                    start,
-                    end,
-                } => {
-                    let data = client
-                        .exercise_history(
-                            &exercise_template_id,
-                            start.as_deref(),
-                            end.as_deref(),
-                        )
-                        .await?;
-                    println!("{}", serde_json::to_string_pretty(&data)?);
-                }
-            }
-        }
+        /// Drop in the rolling average, in percent, that counts as a regression.
+        #[arg(long, default_value_t = 5.0)]
+        regression_pct: f64,
 
-        // ── Process Workout ───────────────
-        Commands::ProcessWorkout { json } => {
-            let payload: WebhookPayload = serde_json::from_str(&json)
-                .context("Invalid webhook JSON. Expected: {\"workoutId\":\"<UUID>\"}")?;
+        /// Drop in a week's volume, in percent, that marks a likely deload.
+        #[arg(long, default_value_t = 40.0)]
+        deload_drop: f64,
+    },
 
-            let api_key = resolve_api_key(&cli.api_key)?;
-            let client = HevyClient::new(api_key);
-            let workout = client.get_workout(&payload.workout_id).await?;
+    /// Compare the history of two exercises day by day.
+    ///
+    /// Meant for unilateral movements tracked as separate templates (e.g.
+    /// left and right single-arm rows). Both histories are fetched
+    /// concurrently and grouped by the UTC calendar day of the workout.
+    ///
+    /// Returns a JSON array, oldest first, of { date, a_max_weight_kg,
+    /// b_max_weight_kg, a_max_reps, b_max_reps, imbalance_pct }, where
+    /// imbalance_pct is |a - b| / max(a, b) * 100 of the max weights (or of
+    /// the max reps when neither side used weight). Days on which only one
+    /// exercise was trained have nulls for the other side.
+    ///
+    /// Dates accept YYYY-MM-DD or ISO 8601; --until is exclusive, and a
+    /// plain date covers that whole day.
+    ///
+    /// Example: hevy-bridge history compare <TEMPLATE_ID_A> <TEMPLATE_ID_B> --since 2024-01-01
+    Compare {
+        /// First exercise template ID.
+        id_a: String,
 
-            // If the workout is based on a routine, fetch it for per-set targets
-            let routine = if let Some(ref routine_id) = workout.routine_id {
-                client.get_routine(routine_id).await.ok().map(|r| r.routine)
-            } else {
-                None
-            };
+        /// Second exercise template ID.
+        id_b: String,
 
-            // Build a per-set lookup: (exercise_template_id, set_index) -> (lo, hi)
-            let mut set_targets: std::collections::HashMap<(String, usize), (i64, i64)> =
-                std::collections::HashMap::new();
-            if let Some(ref r) = routine {
-                for ex in &r.exercises {
-                    if let Some(ref tmpl_id) = ex.exercise_template_id {
-                        for (i, s) in ex.sets.iter().enumerate() {
-                            let (lo, hi) = if let Some(ref range) = s.rep_range {
-                                let lo = range.start.map(|v| v as i64).unwrap_or(8);
-                                let hi = range.end.map(|v| v as i64).unwrap_or(lo);
-                                (lo, hi)
-                            } else {
-                                let r = s.reps.map(|v| v as i64).unwrap_or(10);
-                                (r.saturating_sub(1), r + 1)
-                            };
-                            set_targets.insert((tmpl_id.clone(), i), (lo, hi));
-                        }
-                    }
-                }
-            }
+        /// Only sets on or after this date.
+        #[arg(long)]
+        since: Option<String>,
 
-            let title = workout.title.as_deref().unwrap_or("Untitled Workout");
-            println!();
-            println!("  {title}");
-            println!("  {}", "─".repeat(title.len()));
-            if let Some(ref routine_id) = workout.routine_id {
-                println!("  Routine ID: {routine_id}");
-            }
-            println!();
+        /// Only sets before this date.
+        #[arg(long)]
+        until: Option<String>,
+    },
+}
 
-            // ── Routine table (printed first when available) ──
-            if let Some(ref routine) = routine {
-                let routine_title = routine.title.as_deref().unwrap_or("Untitled Routine");
+#[derive(Subcommand, Debug)]
+enum ReportCommands {
+    /// Summarize a calendar month or year of training.
+    ///
+    /// Returns JSON with the workout count, total and average session
+    /// duration, total volume (weight × reps of working sets, in kg), sets
+    /// per muscle group, the top 5 exercises by volume, and new PRs: each
+    /// exercise's heaviest set in the period that beats every earlier set
+    /// of it. --format table prints a compact summary instead.
+    ///
+    /// The period starts and ends at midnight in the local time zone, or
+    /// in --tz. Checking PRs needs the whole workout history, so every
+    /// workout on the account is fetched; the number of requests this
+    /// takes is printed on stderr first.
+    ///
+    /// Example: hevy-bridge report period --month 2024-06
+    /// Example: hevy-bridge report period --year 2024 --tz Europe/Berlin --format table
+    Period {
+        /// Calendar month, YYYY-MM.
+        #[arg(
+            long,
+            value_parser = report::Period::parse_month,
+            required_unless_present = "year",
+            conflicts_with = "year"
+        )]
+        month: Option<report::Period>,
 
-                println!("  Routine: {routine_title}");
-                println!("  {}", "─".repeat(routine_title.len() + 10));
-                println!();
+        /// Calendar year, e.g. 2024.
+        #[arg(long)]
+        year: Option<i32>,
 
-                println!(
-                    "  {:<35} {:>5} {:>18} {:>12} {:>12}   {}",
-                    "Exercise", "Sets", "Target Wt (lbs)", "Target Reps", "Rest (s)", "Notes"
-                );
-                println!("  {}", "─".repeat(120));
+        /// IANA time zone the period's days start in, e.g. Europe/Berlin
+        /// [default: the local time zone].
+        #[arg(long, value_parser = dates::parse_time_zone)]
+        tz: Option<chrono_tz::Tz>,
+    },
 
-                for exercise in &routine.exercises {
-                    let ex_title = exercise
-                        .title
-                        .as_deref()
-                        .unwrap_or("Unknown Exercise");
-                    let notes = exercise.notes.as_deref().unwrap_or("");
-                    let num_sets = exercise.sets.len();
-
-                    let rest = exercise
-                        .rest_seconds
-                        .as_ref()
-                        .and_then(|v| v.as_f64())
-                        .map(|v| format!("{}", v as i64))
-                        .unwrap_or_else(|| "—".to_string());
-
-                    // Show the heaviest target weight and its rep range
-                    let (best_kg, reps_display) = exercise
-                        .sets
-                        .iter()
-                        .map(|s| {
-                            let w = s.weight_kg.unwrap_or(0.0);
-                            let rep_str = if let Some(ref range) = s.rep_range {
-                                let lo = range.start.map(|v| v as i64);
-                                let hi = range.end.map(|v| v as i64);
-                                match (lo, hi) {
-                                    (Some(l), Some(h)) => format!("{l}-{h}"),
-                                    (Some(l), None) => format!("{l}+"),
-                                    _ => s.reps.map(|r| format!("{}", r as i64)).unwrap_or_else(|| "—".to_string()),
-                                }
-                            } else {
-                                s.reps.map(|r| format!("{}", r as i64)).unwrap_or_else(|| "—".to_string())
-                            };
-                            (w, rep_str)
+    /// Show a year of training days as a contribution graph.
+    ///
+    /// Draws a grid like GitHub's contributions graph: one column per
+    /// week, one row per weekday, with month names along the top. Each day
+    /// is shaded by how many workouts started on it (or their volume with
+    /// --metric volume), relative to the busiest day. Without color, days
+    /// are drawn as · (none), ▪ and ■.
+    ///
+    /// With --json, prints each day of the year (YYYY-MM-DD) mapped to its
+    /// value instead, for drawing elsewhere.
+    ///
+    /// Example: hevy-bridge report calendar
+    /// Example: hevy-bridge report calendar --year 2024 --metric volume --week-start sunday
+    Calendar {
+        /// Year to show [default: the current year].
+        #[arg(long)]
+        year: Option<i32>,
+
+        /// What to shade each day by.
+        #[arg(long, value_enum, default_value_t)]
+        metric: report::Metric,
+
+        /// Day each week (column) starts on.
+        #[arg(long, value_enum, default_value_t)]
+        week_start: report::WeekStart,
+
+        /// IANA time zone the days are counted in, e.g. Europe/Berlin
+        /// [default: the local time zone].
+        #[arg(long, value_parser = dates::parse_time_zone)]
+        tz: Option<chrono_tz::Tz>,
+
+        /// Print the date-to-value map as JSON instead of the graph.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show which routines are actually followed.
+    ///
+    /// Lists every routine with the number of workouts started from it
+    /// (by routine_id) in the last --weeks, the average days between those
+    /// sessions, the date of the latest one, and whether it is abandoned:
+    /// not performed in the last --abandoned-after weeks. Workouts not
+    /// started from a routine are counted as freestyle. Routines that have
+    /// since been deleted show as "(deleted routine <id>)".
+    ///
+    /// Returns JSON; --format table prints a table instead.
+    ///
+    /// Example: hevy-bridge report adherence
+    /// Example: hevy-bridge report adherence --weeks 26 --abandoned-after 8 --format table
+    Adherence {
+        /// Weeks back from now to count workouts in.
+        #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(1..))]
+        weeks: u32,
+
+        /// Weeks without a session after which a routine counts as
+        /// abandoned. At most --weeks.
+        #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u32).range(1..))]
+        abandoned_after: u32,
+    },
+
+    /// Compare training in two windows of time side by side.
+    ///
+    /// For each of --a and --b, counts the workouts, total volume (weight
+    /// × reps, in kg), sets per muscle group (warmups aside), average
+    /// session duration and distinct exercises, then the change from A to
+    /// B, absolute and as a percentage of A.
+    ///
+    /// Each window is START..END. Either side takes a date (YYYY-MM-DD or
+    /// ISO 8601; a plain END date covers that whole day), `now`, or a time
+    /// before now in days or weeks, e.g. 10d or 4w.
+    ///
+    /// Returns JSON with both windows and the deltas; --format table
+    /// prints them as one table.
+    ///
+    /// Example: hevy-bridge report compare --a 2024-01-01..2024-02-28 --b 2024-03-01..2024-04-30
+    /// Example: hevy-bridge report compare --a 8w..4w --b 4w..now --format table
+    Compare {
+        /// First window, START..END.
+        #[arg(long, value_parser = dates::parse_range)]
+        a: dates::DateRange,
+
+        /// Second window, START..END.
+        #[arg(long, value_parser = dates::parse_range)]
+        b: dates::DateRange,
+    },
+
+    /// Score your squat, bench and deadlift total with Wilks and DOTS.
+    ///
+    /// Takes each lift's best estimated one-rep max (Epley: weight × (1 +
+    /// reps / 30)) over its whole history, or with --singles-only its
+    /// heaviest single, sums the three, and scores the total for the body
+    /// weight given. The lifts are the templates titled "Squat (Barbell)",
+    /// "Bench Press (Barbell)" and "Deadlift (Barbell)" unless --squat,
+    /// --bench or --deadlift name others (by template ID, alias or title).
+    ///
+    /// The body weight defaults to `config set bodyweight_kg`. Fails if a
+    /// lift has no set with both weight and reps.
+    ///
+    /// Returns JSON with the three lifts, the total and both scores.
+    ///
+    /// Example: hevy-bridge report strength-score --bodyweight-kg 82
+    /// Example: hevy-bridge report strength-score --bodyweight-kg 63 --sex f --date 2024-06-30
+    StrengthScore {
+        /// Body weight in kg [default: the bodyweight_kg config default].
+        #[arg(long)]
+        bodyweight_kg: Option<f64>,
+
+        /// Sex the coefficients are taken for.
+        #[arg(long, value_enum, default_value_t)]
+        sex: report::Sex,
+
+        /// Only count sets up to the end of this day (YYYY-MM-DD or ISO 8601).
+        #[arg(long)]
+        date: Option<String>,
+
+        /// Squat template (ID, alias or title).
+        #[arg(long, default_value = "Squat (Barbell)")]
+        squat: String,
+
+        /// Bench press template (ID, alias or title).
+        #[arg(long, default_value = "Bench Press (Barbell)")]
+        bench: String,
+
+        /// Deadlift template (ID, alias or title).
+        #[arg(long, default_value = "Deadlift (Barbell)")]
+        deadlift: String,
+
+        /// Use the heaviest single (1 rep) of each lift instead of the best e1RM.
+        #[arg(long)]
+        singles_only: bool,
+    },
+
+    /// Chart how long sessions last and how far apart they are.
+    ///
+    /// Over the workouts started in the last --weeks, reports the average,
+    /// median, longest and shortest session (end_time - start_time) and
+    /// the average days between consecutive workouts. Workouts without an
+    /// end_time, or ending before they start, are left out of the session
+    /// lengths and counted under skipped.
+    ///
+    /// Returns JSON with every session, oldest first; --format table
+    /// prints the summary with a sparkline of session length over time.
+    ///
+    /// Example: hevy-bridge report duration --weeks 26 --format table
+    Duration {
+        /// Weeks back from now to include.
+        #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u32).range(1..))]
+        weeks: u32,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ProgramCommands {
+    /// Create the routines of a whole 5/3/1 or linear progression cycle.
+    ///
+    /// 531 builds one routine per lift for each of Week 1 (65/75/85% of the
+    /// training max for 5, 5 and 5+), Week 2 (70/80/90% for 3, 3, 3+),
+    /// Week 3 (75/85/95% for 5, 3, 1+) and the Deload (40/50/60% for 5s):
+    /// 16 routines. lp builds Workout A (squat, bench, deadlift) and
+    /// Workout B (squat, press, deadlift), to alternate, each 3x5 at the
+    /// --tm weight (1x5 for the deadlift).
+    ///
+    /// Weights are in --units and rounded to --round-to. Every set gets a
+    /// rep range, open-ended for the + sets, so `process-workout` can
+    /// classify the sets logged against it. The lifts are the templates
+    /// named by --squat, --bench, --deadlift and --press (template ID,
+    /// alias or title).
+    ///
+    /// --assistance-file adds the exercises in a JSON array, in the
+    /// `routines create` exercise schema, to the end of every routine.
+    /// --folder creates a routine folder for the cycle first.
+    ///
+    /// Returns JSON with the created routines, or with --dry-run the
+    /// bodies that would be sent.
+    ///
+    /// Example: hevy-bridge --units kg program generate --template 531 --tm squat=140,bench=100,deadlift=170,press=60 --folder "531 Cycle 3"
+    /// Example: hevy-bridge program generate --template lp --tm squat=225,bench=185,deadlift=275,press=115 --dry-run
+    Generate {
+        /// Program to build.
+        #[arg(long, value_enum)]
+        template: program::Template,
+
+        /// Training max of each lift (the working weight for lp), e.g.
+        /// squat=140,bench=100,deadlift=170,press=60.
+        #[arg(long, value_name = "LIFT=WEIGHT,...", value_parser = program::parse_training_maxes)]
+        tm: program::TrainingMaxes,
+
+        /// Create a routine folder with this title and put the routines in it.
+        #[arg(long)]
+        folder: Option<String>,
+
+        /// Round weights to this increment, in --units [default: 5 lbs or
+        /// 2.5 kg].
+        #[arg(long)]
+        round_to: Option<f64>,
+
+        /// JSON array of exercises to add to every routine.
+        #[arg(long)]
+        assistance_file: Option<PathBuf>,
+
+        /// Print the routine bodies instead of creating anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Squat template (ID, alias or title).
+        #[arg(long, default_value = "Squat (Barbell)")]
+        squat: String,
+
+        /// Bench press template (ID, alias or title).
+        #[arg(long, default_value = "Bench Press (Barbell)")]
+        bench: String,
+
+        /// Deadlift template (ID, alias or title).
+        #[arg(long, default_value = "Deadlift (Barbell)")]
+        deadlift: String,
+
+        /// Overhead press template (ID, alias or title).
+        #[arg(long, default_value = "Overhead Press (Barbell)")]
+        press: String,
+    },
+}
+
+// ─────────────────────────────────────────────────────
+// Entrypoint
+// ─────────────────────────────────────────────────────
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+    let error_format = cli.error_format;
+    let (api_key, api_key_file, profile) = (
+        cli.api_key.clone(),
+        cli.api_key_file.clone(),
+        cli.profile.clone(),
+    );
+    if let Err(e) = run(cli).await {
+        let key_args = config::KeyArgs {
+            api_key: api_key.as_deref(),
+            api_key_file: api_key_file.as_deref(),
+            profile: profile.as_deref(),
+        };
+        std::process::exit(failure::report(&e, error_format, &key_args));
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    init_logging(&cli);
+    let printer =
+        output::Printer::new(cli.fields.clone(), cli.output.as_deref())?.compact(cli.compact);
+    let key_args = config::KeyArgs {
+        api_key: cli.api_key.as_deref(),
+        api_key_file: cli.api_key_file.as_deref(),
+        profile: cli.profile.as_deref(),
+    };
+
+    if let Err(e) = config::migrate_config_if_needed(&config::config_path()) {
+        eprintln!("Warning: could not migrate the config file: {e:#}");
+    }
+
+    // Explicit flags (and their environment variables) win over stored defaults.
+    let stored = config::Config::load();
+    let defaults = &stored.defaults;
+    let format = if cli.ndjson {
+        OutputFormat::Ndjson
+    } else {
+        cli.format.or(defaults.format).unwrap_or_default()
+    };
+    // Only export writes iCalendar; config commands stay usable to change
+    // a stored ics default.
+    if format == OutputFormat::Ics
+        && !matches!(
+            cli.command,
+            Commands::Workouts(WorkoutCommands::Export { .. }) | Commands::Config(_)
+        )
+    {
+        anyhow::bail!(
+            "--format ics is only supported by `workouts export`; use json, table or ndjson"
+        );
+    }
+    let ndjson = format == OutputFormat::Ndjson;
+    let color_choice = if cli.no_color {
+        table::ColorChoice::Never
+    } else {
+        cli.color.or(defaults.color).unwrap_or_default()
+    };
+    let units = cli.units.or(defaults.units).unwrap_or_default();
+    let http_cache = !cli.no_cache && (cli.cache || defaults.cache.unwrap_or(false));
+    let max_rps = cli
+        .max_rps
+        .or(defaults.max_rps)
+        .unwrap_or(client::DEFAULT_MAX_RPS);
+    let style = table::Style::new(
+        color_choice.enabled(printer.is_terminal()),
+        cli.width,
+        printer.is_terminal(),
+    )
+    .units(units)
+    .columns(cli.columns.clone())
+    .aliases(if cli.use_aliases {
+        stored.aliases_by_template()
+    } else {
+        Default::default()
+    });
+    // API commands build their client with the global network flags applied.
+    let client_builder = |api_key: String| {
+        let mut builder = HevyClient::builder(api_key)
+            .max_retries(cli.max_retries)
+            .timeout(std::time::Duration::from_secs(cli.timeout))
+            .connect_timeout(std::time::Duration::from_secs(cli.connect_timeout))
+            .concurrency(cli.concurrency)
+            .max_rps(max_rps)
+            .http_cache(http_cache)
+            .strictness(match cli.strict {
+                None => Strictness::Lenient,
+                Some(StrictMode::Warn) => Strictness::Warn,
+                Some(StrictMode::Error) => Strictness::Error,
+            })
+            .exercise_cache_policy(if cli.no_cache {
+                CachePolicy::Never
+            } else {
+                CachePolicy::default()
+            });
+        if let Some(dir) = &cli.record {
+            builder = builder.record(dir.clone());
+        }
+        if let Some(dir) = &cli.replay {
+            builder = builder.replay(dir.clone());
+        }
+        builder
+    };
+    // Replayed responses need no real key, so demos work without one.
+    let resolve_api_key = || match config::resolve_api_key(&key_args) {
+        Err(_) if cli.replay.is_some() => Ok(String::new()),
+        result => result,
+    };
+
+    match cli.command {
+        // ── Config ─────────────────────────
+        Commands::Config(cmd) => match cmd {
+            ConfigCommands::SetKey {
+                key,
+                keyring,
+                no_check,
+            } => {
+                if !no_check {
+                    validate_api_key(
+                        &client_builder(key.clone()).http_cache(false).build()?,
+                        true,
+                        "Could not check the API key; pass --no-check to store it anyway",
+                    )
+                    .await?;
+                }
+                let mut config = config::Config::load();
+                if keyring {
+                    let profile = config.set_api_key(cli.profile.as_deref(), None);
+                    config::keyring::set(&profile, &key)?;
+                    config.save()?;
+                    eprintln!("✓ API key for profile \"{profile}\" saved to the OS keyring");
+                } else {
+                    let profile = config.set_api_key(cli.profile.as_deref(), Some(&key));
+                    config.save()?;
+                    eprintln!(
+                        "✓ API key for profile \"{profile}\" saved to {}",
+                        config::config_path().display()
+                    );
+                }
+            }
+            ConfigCommands::Validate { key } => {
+                let given = key.is_some();
+                let key = match key {
+                    Some(key) => key,
+                    None => resolve_api_key()?,
+                };
+                validate_api_key(
+                    &client_builder(key).http_cache(false).build()?,
+                    given,
+                    "Could not check the API key",
+                )
+                .await?;
+            }
+            ConfigCommands::Show { reveal, yes } => {
+                if reveal && !yes && !printer.is_terminal() {
+                    anyhow::bail!(
+                        "--reveal prints the full API key; run it in a terminal or add --yes"
+                    );
+                }
+                let config = config::Config::load();
+                let key = config::find_api_key(&key_args).ok();
+                printer.json(&serde_json::json!({
+                    "config_path": config::config_path(),
+                    "profile": cli.profile.as_deref().unwrap_or(config.active_profile()),
+                    "profiles": config.profiles.keys().collect::<Vec<_>>(),
+                    "api_key": key.as_ref().map(|k| if reveal { k.key.clone() } else { k.masked() }),
+                    "api_key_source": key.as_ref().map(|k| k.source),
+                    "defaults": config.defaults,
+                }))?;
+            }
+            ConfigCommands::UnsetKey { yes } => {
+                let mut config = config::Config::load();
+                let profile = cli
+                    .profile
+                    .clone()
+                    .unwrap_or_else(|| config.active_profile().to_string());
+                let Some(stored) = config::stored_key(&config, &profile) else {
+                    eprintln!("No API key stored for profile \"{profile}\".");
+                    return printer.finish();
+                };
+                let prompt = format!(
+                    "Delete the API key ending in {} for profile \"{profile}\"?",
+                    stored.masked()
+                );
+                if !yes && !confirm(&prompt)? {
+                    eprintln!("Aborted.");
+                    return printer.finish();
+                }
+                let mut removed_from = Vec::new();
+                if config::keyring::delete(&profile)? {
+                    removed_from.push("the OS keyring".to_string());
+                }
+                if config.remove_api_key(&profile) {
+                    config.save()?;
+                    removed_from.push(config::config_path().display().to_string());
+                }
+                eprintln!(
+                    "✓ Removed the API key for profile \"{profile}\" from {}",
+                    removed_from.join(" and ")
+                );
+            }
+            ConfigCommands::SetPageSize { size } => {
+                let mut config = config::Config::load();
+                config.defaults.page_size = Some(size);
+                config.save()?;
+                eprintln!("✓ Default page size set to {size}");
+            }
+            ConfigCommands::Set { key, value } => {
+                let mut config = config::Config::load();
+                config.set_default(key, &value)?;
+                config.save()?;
+                eprintln!(
+                    "✓ Default {} set to {}",
+                    config::value_name(&key),
+                    config.get_default(key).unwrap_or(value)
+                );
+            }
+            ConfigCommands::Get { key } => {
+                if let Some(value) = config::Config::load().get_default(key) {
+                    writeln!(printer.out(), "{value}")?;
+                }
+            }
+            ConfigCommands::ListProfiles => {
+                let config = config::Config::load();
+                let profiles: Vec<_> = config
+                    .profiles
+                    .keys()
+                    .map(|name| {
+                        serde_json::json!({
+                            "name": name,
+                            "default": name == config.active_profile(),
+                            "api_key_source": config::stored_key(&config, name).map(|k| k.source),
                         })
-                        .fold((0.0_f64, "—".to_string()), |(bw, br), (w, r)| {
-                            if w > bw { (w, r) } else { (bw, br) }
+                    })
+                    .collect();
+                printer.json(&profiles)?;
+            }
+            ConfigCommands::Use { name } => {
+                let mut config = config::Config::load();
+                config.use_profile(&name)?;
+                config.save()?;
+                eprintln!("✓ Default profile set to \"{name}\"");
+            }
+            ConfigCommands::Path => {
+                println!("{}", config::config_path().display());
+            }
+        },
+
+        // ── Alias ──────────────────────────
+        Commands::Alias(cmd) => match cmd {
+            AliasCommands::Set { name, template_id } => {
+                let mut config = config::Config::load();
+                config.set_alias(&name, &template_id)?;
+                config.save()?;
+                eprintln!(
+                    "✓ Alias \"{}\" set to {template_id}",
+                    name.trim().to_lowercase()
+                );
+            }
+            AliasCommands::List => {
+                printer.json(&config::Config::load().aliases)?;
+            }
+            AliasCommands::Rm { name } => {
+                let mut config = config::Config::load();
+                if !config.remove_alias(&name) {
+                    anyhow::bail!("No alias named \"{name}\"");
+                }
+                config.save()?;
+                eprintln!("✓ Removed alias \"{name}\"");
+            }
+        },
+
+        // ── Cache ──────────────────────────
+        Commands::Cache(cmd) => match cmd {
+            CacheCommands::Show => {
+                // Templates are cached per API key; without one, show the
+                // directory holding them all.
+                let key = resolve_api_key().ok();
+                let path = key.as_deref().map_or_else(
+                    cache::exercise_templates_dir,
+                    cache::exercise_templates_path,
+                );
+                let mut info = match key.as_deref().and_then(cache::load_exercise_templates) {
+                    Some(cached) => serde_json::json!({
+                        "path": path,
+                        "cached_at": cached.cached_at,
+                        "age_seconds": cached.age().as_secs(),
+                        "template_count": cached.exercise_templates.len(),
+                    }),
+                    None => serde_json::json!({ "path": path, "cached_at": null }),
+                };
+                info["http_path"] = serde_json::json!(cache::http_dir());
+                info["http_entries"] = serde_json::json!(cache::http_entry_count());
+                printer.json(&info)?;
+            }
+            CacheCommands::Clear => {
+                let templates = cache::clear()?;
+                if templates {
+                    eprintln!("✓ Cleared {}", cache::exercise_templates_dir().display());
+                }
+                let responses = cache::clear_http()?;
+                if responses > 0 {
+                    eprintln!("✓ Cleared {responses} cached response(s)");
+                }
+                if !templates && responses == 0 {
+                    eprintln!("Cache is already empty.");
+                }
+            }
+        },
+
+        // ── Plates ─────────────────────────
+        Commands::Plates {
+            weight,
+            bar,
+            available,
+            routine,
+        } => {
+            let bar = bar.unwrap_or(plates::default_bar(units));
+            let available = available
+                .or_else(|| stored.defaults.plates.clone())
+                .unwrap_or_else(|| plates::default_plates(units));
+            if let Some(id) = routine {
+                let api_key = resolve_api_key()?;
+                let client = client_builder(api_key).build()?;
+                let routine = client.get_routine(&id).await?.routine;
+                let sets = plates::routine_loadings(&routine, bar, &available, units);
+                if sets.is_empty() {
+                    eprintln!("Warning: routine {id} has no weighted barbell sets");
+                }
+                match format {
+                    OutputFormat::Table => {
+                        plates::print_routine(&mut *printer.out(), &sets, units)?
+                    }
+                    _ => printer.json(&sets)?,
+                }
+            } else if let Some(weight) = weight {
+                let loading = plates::solve(weight, bar, &available);
+                match format {
+                    OutputFormat::Table => {
+                        plates::print_loading(&mut *printer.out(), &loading, units)?
+                    }
+                    _ => printer.json(&loading)?,
+                }
+            }
+        }
+
+        // ── Completions ────────────────────
+        Commands::Completions { shell } => {
+            let mut cmd = Cli::command();
+            let name = cmd.get_name().to_string();
+            clap_complete::generate(shell, &mut cmd, name, &mut *printer.out());
+        }
+
+        // ── User ───────────────────────────
+        Commands::User(cmd) => {
+            let api_key = resolve_api_key()?;
+            let client = client_builder(api_key).build()?;
+            match cmd {
+                UserCommands::Info => {
+                    let info = client.user_info().await?;
+                    printer.json(&info)?;
+                }
+            }
+        }
+
+        // ── Workouts ───────────────────────
+        Commands::Workouts(cmd) => {
+            let api_key = resolve_api_key()?;
+            let client = client_builder(api_key).build()?;
+            match cmd {
+                WorkoutCommands::List(args) => {
+                    list_workouts(&client, &printer, format, &style, args).await?
+                }
+                WorkoutCommands::Recent { n } => {
+                    let workouts = if n <= client::MAX_PAGE_SIZE {
+                        client.list_workouts(1, n).await?.workouts
+                    } else {
+                        api::dedup_stream_by_id(client.workouts_stream())
+                            .take(n as usize)
+                            .try_collect()
+                            .await?
+                    };
+                    if ndjson {
+                        printer.ndjson(&workouts)?;
+                    } else if format == OutputFormat::Table {
+                        print_workouts_table(&mut *printer.out(), &workouts, &style)?;
+                    } else {
+                        printer.json(&workouts)?;
+                    }
+                }
+                WorkoutCommands::Latest => {
+                    let workout = client
+                        .list_workouts(1, 1)
+                        .await?
+                        .workouts
+                        .into_iter()
+                        .next()
+                        .context("No workouts yet")?;
+                    if format == OutputFormat::Table {
+                        print_workouts_table(&mut *printer.out(), &[workout], &style)?;
+                    } else {
+                        printer.json(&workout)?;
+                    }
+                }
+                WorkoutCommands::Dedupe { since } => {
+                    let since = since.as_deref().map(dates::parse_start).transpose()?;
+                    let workouts = workouts_in_range(&client, since, None).await?;
+                    let report = dedupe::find(&api::dedup_by_id(workouts));
+                    match format {
+                        OutputFormat::Table => {
+                            dedupe::print_report(&mut *printer.out(), &report, &style)?
+                        }
+                        _ => printer.json(&report)?,
+                    }
+                }
+                WorkoutCommands::Search {
+                    query,
+                    since,
+                    regex,
+                } => {
+                    let since = since.as_deref().map(dates::parse_start).transpose()?;
+                    let pattern = if regex {
+                        regex::RegexBuilder::new(&query)
+                            .case_insensitive(true)
+                            .build()
+                            .with_context(|| format!("Invalid regular expression \"{query}\""))?
+                    } else {
+                        regex::RegexBuilder::new(&regex::escape(&query))
+                            .case_insensitive(true)
+                            .build()?
+                    };
+
+                    let mut matches = Vec::new();
+                    let mut scanned = 0;
+                    let pages = client.workout_pages();
+                    tokio::pin!(pages);
+                    'pages: while let Some(page) = pages.try_next().await? {
+                        scanned += 1;
+                        for w in page.items {
+                            if let (Some(since), Some(start)) = (since, w.start_time)
+                                && start < since
+                            {
+                                // Newest first: everything after this is older.
+                                break 'pages;
+                            }
+                            let hit = [&w.title, &w.description]
+                                .into_iter()
+                                .flatten()
+                                .any(|text| pattern.is_match(text));
+                            if hit {
+                                matches.push(w);
+                            }
+                        }
+                    }
+                    eprintln!("Scanned {scanned} page(s), {} match(es).", matches.len());
+
+                    match format {
+                        OutputFormat::Json => printer.json(&matches)?,
+                        OutputFormat::Ndjson => printer.ndjson(&matches)?,
+                        OutputFormat::Table => {
+                            let mut table = Table::new(vec![
+                                Column::left("ID", 36),
+                                Column::left("Title", 40).flex(),
+                                Column::free("Date"),
+                            ])
+                            .gap("  ");
+                            for w in &matches {
+                                table.row(vec![
+                                    w.id.as_deref().unwrap_or("—").into(),
+                                    w.title.as_deref().unwrap_or("Untitled").into(),
+                                    w.start_time
+                                        .map_or("—".to_string(), |t| {
+                                            t.format("%Y-%m-%d").to_string()
+                                        })
+                                        .into(),
+                                ]);
+                            }
+                            let mut out = printer.out();
+                            writeln!(out)?;
+                            table.render(&mut *out, &style)?;
+                            writeln!(out)?;
+                        }
+                        OutputFormat::Ics => unreachable!("ics is rejected up front"),
+                    }
+                }
+                WorkoutCommands::Get {
+                    id: Some(id),
+                    no_scan,
+                    ..
+                } => get_workout(&client, &printer, id, no_scan).await?,
+                WorkoutCommands::Get {
+                    title: Some(title),
+                    exact,
+                    latest,
+                    all_matches,
+                    since,
+                    ..
+                } => {
+                    let since = since.as_deref().map(dates::parse_start).transpose()?;
+                    let mut matches = Vec::new();
+                    let workouts = client.workouts_stream();
+                    tokio::pin!(workouts);
+                    while let Some(w) = workouts.try_next().await? {
+                        if let (Some(since), Some(start)) = (since, w.start_time)
+                            && start < since
+                        {
+                            // Newest first: everything after this is older.
+                            break;
+                        }
+                        if title_matches(w.title.as_deref(), &title, exact) {
+                            matches.push(w);
+                        }
+                    }
+                    print_title_match(
+                        &printer,
+                        "workout",
+                        &title,
+                        &matches,
+                        latest,
+                        all_matches,
+                        |w| candidate(w.id.clone(), w.start_time, w.title.as_deref()),
+                    )?;
+                }
+                WorkoutCommands::Get { fail_fast, .. } => {
+                    let ids = ids_from_stdin()?;
+                    get_each(&printer, &ids, fail_fast, |id| client.get_workout(id)).await?;
+                }
+                WorkoutCommands::Find {
+                    exercise,
+                    since,
+                    limit,
+                    full,
+                } => {
+                    let template = resolve_exercise_template(&client, &stored, &exercise).await?;
+                    let history = client
+                        .exercise_history(&template, since.as_deref(), None)
+                        .await?;
+                    let mut found = workouts_from_history(history.exercise_history);
+                    if let Some(limit) = limit {
+                        found.truncate(limit);
+                    }
+
+                    if full {
+                        let workouts: Vec<Workout> = stream::iter(&found)
+                            .map(|f| client.get_workout(&f.workout_id))
+                            .buffered(FETCH_CONCURRENCY)
+                            .try_collect()
+                            .await?;
+                        printer.json(&workouts)?;
+                    } else {
+                        printer.json(&found)?;
+                    }
+                }
+                WorkoutCommands::Count { by_routine: false } => {
+                    let data = client.workout_count().await?;
+                    printer.json(&data)?;
+                }
+                WorkoutCommands::Count { by_routine: true } => {
+                    let (workouts, routines) = tokio::try_join!(
+                        ProgressReporter::new("workouts").collect(client.workout_pages()),
+                        client.all_routines().err_into(),
+                    )?;
+                    let counts = count_by_routine(&api::dedup_by_id(workouts), &routines);
+                    if format == OutputFormat::Table {
+                        print_routine_counts(&mut *printer.out(), &counts, &style)?;
+                    } else {
+                        printer.json(&counts)?;
+                    }
+                }
+                WorkoutCommands::Today | WorkoutCommands::Yesterday => {
+                    let days_back = if matches!(cmd, WorkoutCommands::Today) {
+                        0
+                    } else {
+                        1
+                    };
+                    let (start, end) = dates::utc_day_bounds(days_back);
+                    let workouts = workouts_in_range(&client, Some(start), Some(end)).await?;
+                    if workouts.is_empty() {
+                        eprintln!(
+                            "No workouts logged {}.",
+                            if days_back == 0 { "today" } else { "yesterday" }
+                        );
+                    }
+                    printer.json(&workouts)?;
+                }
+                WorkoutCommands::Duration { since, until } => {
+                    let since = since.as_deref().map(dates::parse_start).transpose()?;
+                    let until = until.as_deref().map(dates::parse_end).transpose()?;
+                    let workouts = workouts_in_range(&client, since, until).await?;
+                    let report = workout_durations(&workouts, since, until);
+                    printer.json(&report)?;
+                }
+                WorkoutCommands::Export { since, until } => {
+                    let since = since.as_deref().map(dates::parse_start).transpose()?;
+                    let until = until.as_deref().map(dates::parse_end).transpose()?;
+                    let mut workouts = workouts_in_range(&client, since, until).await?;
+                    workouts.reverse();
+                    match format {
+                        OutputFormat::Ics => export::IcsExporter::new(&mut *printer.out(), units)
+                            .export(&workouts)?,
+                        OutputFormat::Ndjson => printer.ndjson(&workouts)?,
+                        OutputFormat::Json | OutputFormat::Table => printer.json(&workouts)?,
+                    }
+                    eprintln!("Exported {} workout(s).", workouts.len());
+                }
+                WorkoutCommands::Import {
+                    file,
+                    input_format,
+                    dry_run,
+                    delay_ms,
+                } => {
+                    let text = std::fs::read_to_string(&file)
+                        .with_context(|| format!("Failed to read {}", file.display()))?;
+                    let workouts: Vec<Workout> = match input_format {
+                        export::ImportFormat::Json => {
+                            serde_json::from_str(&text).with_context(|| {
+                                format!("{} is not a JSON array of workouts", file.display())
+                            })?
+                        }
+                    };
+                    let report = import_workouts(&client, &workouts, dry_run, delay_ms).await;
+                    printer.json(&report)?;
+                    if !report.failed.is_empty() {
+                        printer.finish()?;
+                        anyhow::bail!(
+                            "{} of {} workout(s) failed to import",
+                            report.failed.len(),
+                            report.total
+                        );
+                    }
+                }
+                WorkoutCommands::Backup { output_dir } => {
+                    let manifest = backup::backup(&client, &output_dir).await?;
+                    eprintln!("Backed up to {}.", output_dir.display());
+                    printer.json(&manifest)?;
+                }
+                WorkoutCommands::Restore { input_dir, dry_run } => {
+                    let report = backup::restore(&client, &input_dir, dry_run).await?;
+                    printer.json(&report)?;
+                    if report.failed() > 0 {
+                        printer.finish()?;
+                        anyhow::bail!("{} item(s) failed to restore", report.failed());
+                    }
+                }
+                WorkoutCommands::Diff { id_a, id_b } => {
+                    let (a, b) =
+                        tokio::try_join!(client.get_workout(&id_a), client.get_workout(&id_b))?;
+                    let diff = diff_workouts(&a, &b);
+                    printer.json(&diff)?;
+                }
+                WorkoutCommands::Events {
+                    follow: true,
+                    since,
+                    interval,
+                    state_file,
+                    event_type,
+                    ..
+                } => {
+                    events::follow(&client, &printer, since, interval, state_file, event_type)
+                        .await?;
+                }
+                WorkoutCommands::Events {
+                    all: true,
+                    since,
+                    event_type,
+                    ..
+                } if ndjson => {
+                    events::stream_all(&client, &printer, since, event_type).await?;
+                }
+                WorkoutCommands::Events {
+                    all: true,
+                    since,
+                    event_type,
+                    ..
+                } => {
+                    let mut events = ProgressReporter::new("events")
+                        .collect(client.workout_event_pages(since))
+                        .await?;
+                    events.retain(|e| events::matches_type(e, event_type));
+                    let out = serde_json::json!({ "events": events });
+                    printer.json(&out)?;
+                }
+                WorkoutCommands::Events {
+                    page,
+                    page_size,
+                    since,
+                    event_type,
+                    ..
+                } => {
+                    let mut data = client
+                        .workout_events(
+                            page,
+                            config::resolve_page_size(page_size, client::MAX_PAGE_SIZE),
+                            since,
+                        )
+                        .await?;
+                    data.events.retain(|e| events::matches_type(e, event_type));
+                    if ndjson {
+                        printer.ndjson(&data.events)?;
+                    } else {
+                        printer.json(&data)?;
+                    }
+                }
+                WorkoutCommands::Create {
+                    from_routine: Some(routine_id),
+                    title,
+                    start,
+                    end,
+                    generate_warmups,
+                    round_to,
+                    dry_run,
+                    ..
+                } => {
+                    let routine = client.get_routine(&routine_id).await?.routine;
+                    let start = match start {
+                        Some(s) => dates::parse_start(&s)?,
+                        None => Utc::now(),
+                    };
+                    let end = match end {
+                        Some(s) => dates::parse_start(&s)?,
+                        None => start + chrono::TimeDelta::hours(1),
+                    };
+                    let mut builder = WorkoutBuilder::try_from(&routine)
+                        .context("Failed to build a workout from the routine")?
+                        .start(start)
+                        .end(end);
+                    if let Some(title) = title {
+                        builder = builder.title(title);
+                    }
+                    let mut body = builder.build()?;
+                    if let Some(spec) = generate_warmups {
+                        warmup::Warmups::new(spec, units, round_to)?.add_to_workout(&mut body)?;
+                    }
+                    if dry_run {
+                        printer.json(&body)?;
+                    } else {
+                        let data = client.create_workout(&body).await?;
+                        printer.json(&data)?;
+                    }
+                }
+                WorkoutCommands::Create {
+                    json,
+                    file,
+                    generate_warmups,
+                    round_to,
+                    dry_run,
+                    ..
+                } => {
+                    let json = match (json, file) {
+                        (Some(json), _) => json,
+                        (None, Some(file)) => std::fs::read_to_string(&file)
+                            .with_context(|| format!("Failed to read {}", file.display()))?,
+                        // clap requires --json, --file or --from-routine.
+                        (None, None) => unreachable!(),
+                    };
+                    let mut body: PostWorkoutBody = serde_json::from_str(&json)
+                        .context("Invalid JSON for workout body. See `hevy-bridge workouts create --help` for the expected schema.")?;
+                    if let Some(spec) = generate_warmups {
+                        warmup::Warmups::new(spec, units, round_to)?.add_to_workout(&mut body)?;
+                    }
+                    body.validate()?;
+                    if dry_run {
+                        printer.json(&body)?;
+                    } else {
+                        let data = client.create_workout(&body).await?;
+                        printer.json(&data)?;
+                    }
+                }
+                WorkoutCommands::Update { id, json } => {
+                    let body: PostWorkoutBody = serde_json::from_str(&json)
+                        .context("Invalid JSON for workout body. See `hevy-bridge workouts update --help` for the expected schema.")?;
+                    body.validate()?;
+                    let data = client.update_workout(&id, &body).await?;
+                    printer.json(&data)?;
+                }
+            }
+        }
+
+        // ── Routines ──────────────────────
+        Commands::Routines(cmd) => {
+            let api_key = resolve_api_key()?;
+            let client = client_builder(api_key).build()?;
+            match cmd {
+                RoutineCommands::List {
+                    page,
+                    page_size,
+                    all,
+                    folder_id,
+                    no_folder,
+                } => {
+                    let keep = |r: &Routine| match (folder_id, no_folder) {
+                        (Some(id), _) => r.folder_id == Some(id),
+                        (None, true) => r.folder_id.is_none(),
+                        (None, false) => true,
+                    };
+                    let all = all || folder_id.is_some() || no_folder;
+                    if all && ndjson {
+                        let mut progress = ProgressReporter::new("routines");
+                        let pages = client.routine_pages();
+                        tokio::pin!(pages);
+                        while let Some(page) = pages.try_next().await? {
+                            progress.page(&page);
+                            let mut routines = page.items;
+                            routines.retain(|r| keep(r));
+                            printer.ndjson(&routines)?;
+                        }
+                        progress.finish();
+                    } else if all {
+                        let routines = ProgressReporter::new("routines")
+                            .collect(client.routine_pages())
+                            .await?;
+                        let mut routines = api::dedup_by_id(routines);
+                        routines.retain(|r| keep(r));
+                        if format == OutputFormat::Table {
+                            let folders = client.all_routine_folders().await?;
+                            print_routines_table(&mut *printer.out(), &routines, &folders, &style)?;
+                        } else {
+                            printer.json(&routines)?;
+                        }
+                    } else {
+                        let data = client
+                            .list_routines(
+                                page,
+                                config::resolve_page_size(page_size, client::MAX_PAGE_SIZE),
+                            )
+                            .await?;
+                        if ndjson {
+                            printer.ndjson(&data.routines)?;
+                        } else if format == OutputFormat::Table {
+                            let folders = client.all_routine_folders().await?;
+                            print_routines_table(
+                                &mut *printer.out(),
+                                &data.routines,
+                                &folders,
+                                &style,
+                            )?;
+                            eprintln!("Page {} of {}", data.page, data.page_count);
+                        } else {
+                            printer.json(&data)?;
+                        }
+                    }
+                }
+                RoutineCommands::Get {
+                    id: Some(id),
+                    no_scan,
+                    ..
+                } => {
+                    let id = if id.len() < UUID_LEN && !no_scan {
+                        let routines = client.all_routines().await?;
+                        expand_id_prefix(
+                            "routine",
+                            &id,
+                            &routines,
+                            |r| r.id.as_deref(),
+                            |r| candidate(r.id.clone(), r.updated_at, r.title.as_deref()),
+                        )?
+                    } else {
+                        id
+                    };
+                    let data = client.get_routine(&id).await?;
+                    printer.json(&data)?;
+                }
+                RoutineCommands::Get {
+                    title: Some(title),
+                    exact,
+                    latest,
+                    all_matches,
+                    ..
+                } => {
+                    let mut matches: Vec<Routine> = client
+                        .all_routines()
+                        .await?
+                        .into_iter()
+                        .filter(|r| title_matches(r.title.as_deref(), &title, exact))
+                        .collect();
+                    matches.sort_by_key(|r| std::cmp::Reverse(r.updated_at));
+                    print_title_match(
+                        &printer,
+                        "routine",
+                        &title,
+                        &matches,
+                        latest,
+                        all_matches,
+                        |r| candidate(r.id.clone(), r.updated_at, r.title.as_deref()),
+                    )?;
+                }
+                RoutineCommands::Get { fail_fast, .. } => {
+                    let ids = ids_from_stdin()?;
+                    get_each(&printer, &ids, fail_fast, |id| client.get_routine(id)).await?;
+                }
+                RoutineCommands::Create {
+                    json,
+                    generate_warmups,
+                    round_to,
+                    dry_run,
+                } => {
+                    let warmups = generate_warmups
+                        .map(|spec| warmup::Warmups::new(spec, units, round_to))
+                        .transpose()?;
+                    create_routine(&client, &printer, &json, warmups.as_ref(), dry_run).await?
+                }
+                RoutineCommands::Update { id, json } => {
+                    update_routine(&client, &printer, &id, &json).await?
+                }
+                RoutineCommands::Move {
+                    id,
+                    folder_id,
+                    no_folder: _,
+                    via_recreate,
+                } => {
+                    if let Some(folder_id) = folder_id {
+                        client
+                            .get_routine_folder(&folder_id.to_string())
+                            .await
+                            .with_context(|| format!("Target folder {folder_id} not found"))?;
+                    }
+                    if !via_recreate {
+                        anyhow::bail!(
+                            "The Hevy API does not allow changing folder_id on an existing routine.\n  \
+                             Re-run with --via-recreate to copy the routine into the folder \
+                             (the copy gets a new routine ID)."
+                        );
+                    }
+
+                    let routine = client.get_routine(&id).await?.routine;
+                    let body = routine_to_post_body(&routine, folder_id)?;
+                    let data = client.create_routine(&body).await?;
+                    eprintln!(
+                        "⚠ Routine recreated with a new ID ({}). The original ({id}) still exists — \
+                         the API cannot delete routines, so remove it in the Hevy app.",
+                        data.id.as_deref().unwrap_or("unknown")
+                    );
+                    printer.json(&data)?;
+                }
+            }
+        }
+
+        // ── Exercises ─────────────────────
+        Commands::Exercises(cmd) => {
+            let api_key = resolve_api_key()?;
+            let policy = match cmd {
+                _ if cli.no_cache => CachePolicy::Never,
+                ExerciseCommands::List { cached: true, .. } => CachePolicy::Always,
+                _ => CachePolicy::default(),
+            };
+            let client = client_builder(api_key)
+                .exercise_cache_policy(policy)
+                .build()?;
+            match cmd {
+                ExerciseCommands::List {
+                    page,
+                    page_size,
+                    all,
+                    custom_only,
+                    builtin_only,
+                    muscle_group,
+                    include_secondary,
+                    ..
+                } => {
+                    let is_custom = match (custom_only, builtin_only) {
+                        (true, _) => Some(true),
+                        (_, true) => Some(false),
+                        _ => None,
+                    };
+                    let in_group = |t: &ExerciseTemplate, group: &str| {
+                        let matches = |g: &str| g.eq_ignore_ascii_case(group);
+                        t.primary_muscle_group.as_deref().is_some_and(matches)
+                            || include_secondary
+                                && t.secondary_muscle_groups
+                                    .iter()
+                                    .flatten()
+                                    .any(|g| matches(g))
+                    };
+                    let keep = |t: &ExerciseTemplate| {
+                        is_custom.is_none_or(|c| t.is_custom == Some(c))
+                            && muscle_group.as_deref().is_none_or(|g| in_group(t, g))
+                    };
+                    if all && ndjson {
+                        // Stream straight from the API unless the cache can
+                        // answer; the streamed pages are not written back.
+                        if let Some(mut templates) = client.cached_exercise_templates() {
+                            templates.retain(|t| keep(t));
+                            printer.ndjson(&templates)?;
+                        } else {
+                            let mut progress = ProgressReporter::new("exercise templates");
+                            let pages = client.exercise_template_pages();
+                            tokio::pin!(pages);
+                            while let Some(page) = pages.try_next().await? {
+                                progress.page(&page);
+                                let mut templates = page.items;
+                                templates.retain(|t| keep(t));
+                                printer.ndjson(&templates)?;
+                            }
+                            progress.finish();
+                        }
+                    } else if all {
+                        let mut data = client.all_exercise_templates().await?;
+                        data.retain(|t| keep(t));
+                        if format == OutputFormat::Table {
+                            print_exercise_templates_table(&mut *printer.out(), &data, &style)?;
+                        } else {
+                            printer.json(&data)?;
+                        }
+                    } else {
+                        let mut data = client
+                            .list_exercise_templates(
+                                page,
+                                config::resolve_page_size(
+                                    page_size,
+                                    client::MAX_EXERCISE_TEMPLATE_PAGE_SIZE,
+                                ),
+                            )
+                            .await?;
+                        data.exercise_templates.retain(|t| keep(t));
+                        if ndjson {
+                            printer.ndjson(&data.exercise_templates)?;
+                        } else if format == OutputFormat::Table {
+                            print_exercise_templates_table(
+                                &mut *printer.out(),
+                                &data.exercise_templates,
+                                &style,
+                            )?;
+                            eprintln!("Page {} of {}", data.page, data.page_count);
+                        } else {
+                            printer.json(&data)?;
+                        }
+                    }
+                }
+                ExerciseCommands::Get { id: Some(id), .. } => {
+                    let data = client.get_exercise_template(&id).await?;
+                    printer.json(&data)?;
+                }
+                ExerciseCommands::Get { fail_fast, .. } => {
+                    let ids = ids_from_stdin()?;
+                    get_each(&printer, &ids, fail_fast, |id| {
+                        client.get_exercise_template(id)
+                    })
+                    .await?;
+                }
+                ExerciseCommands::Create { json } => {
+                    let body: CreateExerciseBody = serde_json::from_str(&json)
+                        .context("Invalid JSON for exercise body. See `hevy-bridge exercises create --help` for the expected schema.")?;
+                    let data = client.create_exercise_template(&body).await?;
+                    printer.json(&data)?;
+                }
+                ExerciseCommands::Resolve { names } => {
+                    let names = match names {
+                        Some(names) => names,
+                        None if std::io::stdin().is_terminal() => {
+                            anyhow::bail!("Pass --names, or pipe names in on stdin, one per line")
+                        }
+                        None => std::io::read_to_string(std::io::stdin())
+                            .context("Failed to read names from stdin")?
+                            .lines()
+                            .map(str::to_string)
+                            .collect(),
+                    };
+                    let templates = client.all_exercise_templates().await?;
+                    let mut resolved = serde_json::Map::new();
+                    for name in names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+                        let resolution = resolver::resolve(name, &templates, &stored.aliases);
+                        resolved.insert(name.to_string(), serde_json::to_value(resolution)?);
+                    }
+                    printer.json(&resolved)?;
+                }
+            }
+        }
+
+        // ── Folders ───────────────────────
+        Commands::Folders(cmd) => {
+            let api_key = resolve_api_key()?;
+            let client = client_builder(api_key).build()?;
+            match cmd {
+                FolderCommands::List {
+                    page,
+                    page_size,
+                    with_routines,
+                } => {
+                    if with_routines {
+                        let (folders, routines) =
+                            tokio::try_join!(client.all_routine_folders(), client.all_routines())?;
+                        let folders = group_routines_by_folder(&folders, &routines);
+                        match format {
+                            OutputFormat::Json => printer.json(&folders)?,
+                            OutputFormat::Ndjson => printer.ndjson(&folders)?,
+                            OutputFormat::Table => {
+                                print_folders_table(&mut *printer.out(), &folders, &style)?
+                            }
+                            OutputFormat::Ics => unreachable!("ics is rejected up front"),
+                        }
+                    } else {
+                        let data = client
+                            .list_routine_folders(
+                                page,
+                                config::resolve_page_size(page_size, client::MAX_PAGE_SIZE),
+                            )
+                            .await?;
+                        if ndjson {
+                            printer.ndjson(&data.routine_folders)?;
+                        } else if format == OutputFormat::Table {
+                            let routines = client.all_routines().await?;
+                            print_folder_list_table(
+                                &mut *printer.out(),
+                                &data.routine_folders,
+                                &routines,
+                                &style,
+                            )?;
+                            eprintln!("Page {} of {}", data.page, data.page_count);
+                        } else {
+                            printer.json(&data)?;
+                        }
+                    }
+                }
+                FolderCommands::Get { id: Some(id), .. } => {
+                    let data = client.get_routine_folder(&id.to_string()).await?;
+                    printer.json(&data)?;
+                }
+                FolderCommands::Get {
+                    title: Some(title),
+                    exact,
+                    latest,
+                    all_matches,
+                    ..
+                } => {
+                    let mut matches: Vec<RoutineFolder> = client
+                        .all_routine_folders()
+                        .await?
+                        .into_iter()
+                        .filter(|f| title_matches(f.title.as_deref(), &title, exact))
+                        .collect();
+                    matches.sort_by_key(|f| std::cmp::Reverse(f.updated_at));
+                    print_title_match(
+                        &printer,
+                        "folder",
+                        &title,
+                        &matches,
+                        latest,
+                        all_matches,
+                        |f| {
+                            candidate(
+                                f.id.map(|id| id.to_string()),
+                                f.updated_at,
+                                f.title.as_deref(),
+                            )
+                        },
+                    )?;
+                }
+                FolderCommands::Get { fail_fast, .. } => {
+                    let ids = ids_from_stdin()?;
+                    get_each(&printer, &ids, fail_fast, |id| {
+                        client.get_routine_folder(id)
+                    })
+                    .await?;
+                }
+                FolderCommands::Create { json } => {
+                    let body: PostRoutineFolderBody = serde_json::from_str(&json)
+                        .context("Invalid JSON for folder body. See `hevy-bridge folders create --help` for the expected schema.")?;
+                    let data = client.create_routine_folder(&body).await?;
+                    printer.json(&data)?;
+                }
+            }
+        }
+
+        // ── History ───────────────────────
+        Commands::History(cmd) => {
+            let api_key = resolve_api_key()?;
+            let client = client_builder(api_key).build()?;
+            match cmd {
+                HistoryCommands::Get {
+                    exercise_template_id,
+                    start,
+                    end,
+                } => {
+                    let data = client
+                        .exercise_history(&exercise_template_id, start.as_deref(), end.as_deref())
+                        .await?;
+                    if ndjson {
+                        printer.ndjson(&data.exercise_history)?;
+                    } else {
+                        printer.json(&data)?;
+                    }
+                }
+                HistoryCommands::Distribution {
+                    exercise_template_id,
+                    start,
+                    end,
+                } => {
+                    let since = start.as_deref().map(dates::parse_start).transpose()?;
+                    let until = end.as_deref().map(dates::parse_end).transpose()?;
+                    let data = client
+                        .exercise_history(&exercise_template_id, None, None)
+                        .await?;
+                    let distribution = analysis::distribution(
+                        exercise_template_id,
+                        &data.exercise_history,
+                        since,
+                        until,
+                    );
+                    match format {
+                        OutputFormat::Table => analysis::print_distribution(
+                            &mut *printer.out(),
+                            &distribution,
+                            &style,
+                        )?,
+                        _ => printer.json(&distribution)?,
+                    }
+                }
+                HistoryCommands::Analyze {
+                    exercise_template_id,
+                    plateau_sessions,
+                    window,
+                    lookback,
+                    regression_pct,
+                    deload_drop,
+                } => {
+                    let data = client
+                        .exercise_history(&exercise_template_id, None, None)
+                        .await?;
+                    let analysis = analysis::analyze(
+                        exercise_template_id,
+                        &data.exercise_history,
+                        analysis::Thresholds {
+                            plateau_sessions,
+                            window,
+                            lookback,
+                            regression_pct,
+                            deload_drop_pct: deload_drop,
+                        },
+                    );
+                    printer.json(&analysis)?;
+                }
+                HistoryCommands::Compare {
+                    id_a,
+                    id_b,
+                    since,
+                    until,
+                } => {
+                    let since = since
+                        .as_deref()
+                        .map(dates::parse_start)
+                        .transpose()?
+                        .map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, true));
+                    let until = until
+                        .as_deref()
+                        .map(dates::parse_end)
+                        .transpose()?
+                        .map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, true));
+                    let (a, b) = tokio::try_join!(
+                        client.exercise_history(&id_a, since.as_deref(), until.as_deref()),
+                        client.exercise_history(&id_b, since.as_deref(), until.as_deref()),
+                    )?;
+                    let rows = compare_histories(&a.exercise_history, &b.exercise_history);
+                    printer.json(&rows)?;
+                }
+            }
+        }
+
+        // ── Report ────────────────────────
+        Commands::Report(cmd) => {
+            let api_key = resolve_api_key()?;
+            let client = client_builder(api_key).build()?;
+            match cmd {
+                ReportCommands::Period { month, year, tz } => {
+                    // clap guarantees one of --month or --year.
+                    let period = month.or(year.map(report::Period::Year)).unwrap();
+                    let bounds = period.bounds(tz)?;
+                    let count = client.workout_count().await?.workout_count;
+                    eprintln!(
+                        "Warning: fetching all {count} workout(s) to compare against earlier \
+                         bests (about {} request(s))",
+                        (count.max(0) as u64).div_ceil(client::MAX_PAGE_SIZE.into())
+                    );
+                    let (workouts, templates) = tokio::try_join!(
+                        client.workouts_stream().try_collect::<Vec<_>>(),
+                        client.all_exercise_templates(),
+                    )?;
+                    let time_zone = tz.map_or("local".to_string(), |tz| tz.name().to_string());
+                    let report = report::period_report(
+                        period,
+                        time_zone,
+                        bounds,
+                        &api::dedup_by_id(workouts),
+                        &templates,
+                    );
+                    match format {
+                        OutputFormat::Table => {
+                            report::print_table(&mut *printer.out(), &report, &style)?
+                        }
+                        _ => printer.json(&report)?,
+                    }
+                }
+                ReportCommands::Calendar {
+                    year,
+                    metric,
+                    week_start,
+                    tz,
+                    json,
+                } => {
+                    let year = year.unwrap_or_else(|| match tz {
+                        Some(tz) => Utc::now().with_timezone(&tz).year(),
+                        None => chrono::Local::now().year(),
+                    });
+                    let (since, until) = report::Period::Year(year).bounds(tz)?;
+                    let workouts = workouts_in_range(&client, Some(since), Some(until)).await?;
+                    let days = report::calendar_days(year, &workouts, metric, tz)?;
+                    if json {
+                        printer.json(&report::calendar_json(&days, metric))?;
+                    } else {
+                        report::print_calendar(&mut *printer.out(), &days, week_start, &style)?;
+                    }
+                }
+                ReportCommands::Adherence {
+                    weeks,
+                    abandoned_after,
+                } => {
+                    if abandoned_after > weeks {
+                        anyhow::bail!(
+                            "--abandoned-after ({abandoned_after}) cannot be more than --weeks ({weeks})"
+                        );
+                    }
+                    let now = Utc::now();
+                    let since = now - chrono::TimeDelta::weeks(weeks.into());
+                    let (workouts, routines) = tokio::try_join!(
+                        workouts_in_range(&client, Some(since), None),
+                        client.all_routines().err_into(),
+                    )?;
+                    let report = report::adherence_report(
+                        now,
+                        weeks,
+                        abandoned_after,
+                        &api::dedup_by_id(workouts),
+                        &routines,
+                    );
+                    match format {
+                        OutputFormat::Table => {
+                            report::print_adherence(&mut *printer.out(), &report, &style)?
+                        }
+                        _ => printer.json(&report)?,
+                    }
+                }
+                ReportCommands::Compare { a, b } => {
+                    let (workouts, templates) = tokio::try_join!(
+                        workouts_in_range(
+                            &client,
+                            Some(a.start.min(b.start)),
+                            Some(a.end.max(b.end))
+                        ),
+                        client.all_exercise_templates().err_into(),
+                    )?;
+                    let comparison = report::compare(a, b, &api::dedup_by_id(workouts), &templates);
+                    match format {
+                        OutputFormat::Table => {
+                            report::print_comparison(&mut *printer.out(), &comparison, &style)?
+                        }
+                        _ => printer.json(&comparison)?,
+                    }
+                }
+                ReportCommands::StrengthScore {
+                    bodyweight_kg,
+                    sex,
+                    date,
+                    squat,
+                    bench,
+                    deadlift,
+                    singles_only,
+                } => {
+                    let bodyweight_kg = bodyweight_kg
+                        .or(stored.defaults.bodyweight_kg)
+                        .filter(|kg| kg.is_finite() && *kg > 0.0)
+                        .context(
+                            "Give a body weight with --bodyweight-kg, or store one with \
+                             `hevy-bridge config set bodyweight_kg <KG>`",
+                        )?;
+                    let until = date.as_deref().map(dates::parse_end).transpose()?;
+                    let end = until.map(|d| d.to_rfc3339_opts(SecondsFormat::Secs, true));
+                    let lifts = [("squat", squat), ("bench", bench), ("deadlift", deadlift)];
+                    let mut ids = Vec::new();
+                    for (_, query) in &lifts {
+                        ids.push(resolve_exercise_template(&client, &stored, query).await?);
+                    }
+                    let histories = futures::future::try_join_all(
+                        ids.iter()
+                            .map(|id| client.exercise_history(id, None, end.as_deref())),
+                    )
+                    .await?;
+                    let mut bests = Vec::new();
+                    let mut missing = Vec::new();
+                    for (((lift, query), id), history) in lifts.iter().zip(&ids).zip(&histories) {
+                        match report::best_lift(id, &history.exercise_history, singles_only) {
+                            Some(best) => bests.push(best),
+                            None if query == id => missing.push(format!("{lift} ({id})")),
+                            None => missing.push(format!("{lift} ({query}, {id})")),
+                        }
+                    }
+                    let Ok(bests) = <[report::LiftBest; 3]>::try_from(bests) else {
+                        anyhow::bail!(
+                            "No {} recorded for: {}",
+                            if singles_only {
+                                "singles with weight"
+                            } else {
+                                "sets with both weight and reps"
+                            },
+                            missing.join(", ")
+                        );
+                    };
+                    let as_of = until.map(|d| (d - chrono::TimeDelta::seconds(1)).date_naive());
+                    printer.json(&report::strength_score(
+                        sex,
+                        bodyweight_kg,
+                        as_of,
+                        singles_only,
+                        bests,
+                    ))?;
+                }
+                ReportCommands::Duration { weeks } => {
+                    let now = Utc::now();
+                    let since = now - chrono::TimeDelta::weeks(weeks.into());
+                    let workouts = workouts_in_range(&client, Some(since), None).await?;
+                    let trend = report::duration_trend(now, weeks, &api::dedup_by_id(workouts));
+                    match format {
+                        OutputFormat::Table => {
+                            report::print_duration(&mut *printer.out(), &trend, &style)?
+                        }
+                        _ => printer.json(&trend)?,
+                    }
+                }
+            }
+        }
+
+        // ── Program ───────────────────────
+        Commands::Program(cmd) => {
+            let api_key = resolve_api_key()?;
+            let client = client_builder(api_key).build()?;
+            match cmd {
+                ProgramCommands::Generate {
+                    template,
+                    tm,
+                    folder,
+                    round_to,
+                    assistance_file,
+                    dry_run,
+                    squat,
+                    bench,
+                    deadlift,
+                    press,
+                } => {
+                    let round_to = round_to.unwrap_or(plates::default_round_to(units));
+                    if !(round_to.is_finite() && round_to > 0.0) {
+                        anyhow::bail!("Invalid --round-to {round_to}: expected a positive weight");
+                    }
+                    let assistance: Vec<PostRoutineExercise> = match assistance_file {
+                        Some(file) => {
+                            let json = std::fs::read_to_string(&file)
+                                .with_context(|| format!("Failed to read {}", file.display()))?;
+                            serde_json::from_str(&json).with_context(|| {
+                                format!(
+                                    "{} is not a JSON array of routine exercises. See \
+                                     `hevy-bridge routines create --help` for the schema.",
+                                    file.display()
+                                )
+                            })?
+                        }
+                        None => Vec::new(),
+                    };
+                    let templates = client.all_exercise_templates().await?;
+                    let [squat, bench, deadlift, press] =
+                        [squat, bench, deadlift, press].map(|query| {
+                            resolver::resolve(&query, &templates, &stored.aliases)
+                                .id
+                                .with_context(|| {
+                                    format!(
+                                        "No single exercise template matches \"{query}\". \
+                                         Check it with `hevy-bridge exercises resolve --names`"
+                                    )
+                                })
                         });
+                    let program = program::Program {
+                        template,
+                        maxes: tm,
+                        template_ids: [squat?, bench?, deadlift?, press?],
+                        units,
+                        round_to,
+                        assistance: &assistance,
+                    };
+                    let mut routines = program.routines()?;
+                    if dry_run {
+                        printer.json(&routines)?;
+                    } else {
+                        if let Some(title) = folder {
+                            let body = PostRoutineFolderBody {
+                                routine_folder: PostRoutineFolderInner { title },
+                            };
+                            let folder = client.create_routine_folder(&body).await?;
+                            eprintln!(
+                                "✓ Created folder {}",
+                                folder.id.map_or("?".to_string(), |id| id.to_string())
+                            );
+                            for body in &mut routines {
+                                body.routine.folder_id = folder.id;
+                            }
+                        }
+                        let mut created = Vec::new();
+                        for (i, body) in routines.iter().enumerate() {
+                            let title = &body.routine.title;
+                            eprintln!(
+                                "[{}/{}] Creating routine \"{title}\"...",
+                                i + 1,
+                                routines.len()
+                            );
+                            let routine = client
+                                .create_routine(body)
+                                .await
+                                .with_context(|| format!("Failed to create routine \"{title}\""))?;
+                            created.push(routine);
+                        }
+                        printer.json(&created)?;
+                    }
+                }
+            }
+        }
+
+        // ── Process Workout ───────────────
+        Commands::ProcessWorkout {
+            json,
+            stdin,
+            webhook_secret,
+            signature_header,
+        } => {
+            let input = match json {
+                Some(json) if !stdin && json != "-" => json,
+                // clap guarantees either --stdin or `--json -` here.
+                _ => read_stdin_with_timeout(STDIN_TIMEOUT)?,
+            };
+            let json = match webhook_secret {
+                Some(secret) => {
+                    let (headers, body) = webhook::split_http_message(&input);
+                    let signature = headers
+                        .iter()
+                        .find(|(name, _)| name.eq_ignore_ascii_case(&signature_header))
+                        .map(|(_, value)| *value)
+                        .with_context(|| {
+                            format!("Missing {signature_header} header in the webhook input")
+                        })?;
+                    if !webhook::verify_webhook_signature(body.as_bytes(), signature, &secret) {
+                        anyhow::bail!("Invalid webhook signature in {signature_header} header");
+                    }
+                    body.to_string()
+                }
+                None => input,
+            };
+            let payload: WebhookPayload = serde_json::from_str(&json)
+                .context("Invalid webhook JSON. Expected: {\"workoutId\":\"<UUID>\"}")?;
+
+            let api_key = resolve_api_key()?;
+            let client = client_builder(api_key).build()?;
+            let summary = process::summarize(&client, &payload.workout_id, &style).await?;
+            write!(printer.out(), "{summary}")?;
+        }
+
+        // ── Webhook Server ────────────────
+        Commands::WebhookServer {
+            bind,
+            port,
+            secret,
+            signature_header,
+        } => {
+            let api_key = resolve_api_key()?;
+            let client = client_builder(api_key).build()?;
+            let addr = if bind.contains(':') {
+                format!("[{bind}]:{port}")
+            } else {
+                format!("{bind}:{port}")
+            };
+            let signing = secret.map(|secret| webhook::Signing {
+                secret,
+                header: signature_header,
+            });
+            // Summaries go straight to stdout, not through --output.
+            let is_terminal = std::io::stdout().is_terminal();
+            let style =
+                table::Style::new(color_choice.enabled(is_terminal), cli.width, is_terminal)
+                    .units(units)
+                    .columns(cli.columns.clone());
+            webhook::serve(&addr, client, signing, style).await?;
+        }
+    }
+
+    printer.finish()
+}
+
+/// `workouts list`: one page, or with --all or a filter every matching
+/// workout.
+async fn list_workouts(
+    client: &impl HevyApi,
+    printer: &output::Printer,
+    format: OutputFormat,
+    style: &table::Style,
+    args: ListWorkoutsArgs,
+) -> Result<()> {
+    let ListWorkoutsArgs {
+        page,
+        page_size,
+        all,
+        routine_id,
+        routine_title,
+        exercises_include,
+        exercises_exclude,
+    } = args;
+    let ndjson = format == OutputFormat::Ndjson;
+    let keep = |w: &Workout| {
+        routine_id
+            .as_ref()
+            .is_none_or(|id| w.routine_id.as_ref() == Some(id))
+            && (exercises_include.is_empty() || has_any_exercise(w, &exercises_include))
+            && !has_any_exercise(w, &exercises_exclude)
+    };
+    let filtered =
+        routine_id.is_some() || !exercises_include.is_empty() || !exercises_exclude.is_empty();
+    if ndjson && routine_title.is_none() && (all || filtered) {
+        let mut progress = ProgressReporter::new("workouts");
+        let pages = client.workout_pages();
+        tokio::pin!(pages);
+        while let Some(page) = pages.try_next().await? {
+            progress.page(&page);
+            let mut workouts = page.items;
+            workouts.retain(|w| keep(w));
+            printer.ndjson(&workouts)?;
+        }
+        progress.finish();
+    } else if all || filtered || routine_title.is_some() {
+        let workouts = ProgressReporter::new("workouts")
+            .collect(client.workout_pages())
+            .await?;
+        let mut workouts = api::dedup_by_id(workouts);
+        workouts.retain(|w| keep(w));
+        if let Some(ref needle) = routine_title {
+            let matching = routine_ids_matching_title(client, &workouts, needle).await;
+            workouts.retain(|w| {
+                w.routine_id
+                    .as_ref()
+                    .is_some_and(|id| matching.contains(id))
+            });
+        }
+        if ndjson {
+            printer.ndjson(&workouts)?;
+        } else if format == OutputFormat::Table {
+            print_workouts_table(&mut *printer.out(), &workouts, style)?;
+        } else {
+            printer.json(&workouts)?;
+        }
+    } else {
+        let data = client
+            .list_workouts(
+                page,
+                config::resolve_page_size(page_size, client::MAX_PAGE_SIZE),
+            )
+            .await?;
+        if ndjson {
+            printer.ndjson(&data.workouts)?;
+        } else if format == OutputFormat::Table {
+            print_workouts_table(&mut *printer.out(), &data.workouts, style)?;
+            eprintln!("Page {} of {}", data.page, data.page_count);
+        } else {
+            printer.json(&data)?;
+        }
+    }
+    Ok(())
+}
+
+/// `workouts get <ID>`, expanding a shorter ID to the one full ID starting
+/// with it unless `no_scan`.
+async fn get_workout(
+    client: &impl HevyApi,
+    printer: &output::Printer,
+    id: String,
+    no_scan: bool,
+) -> Result<()> {
+    let id = if id.len() < UUID_LEN && !no_scan {
+        let workouts: Vec<Workout> = client.workouts_stream().try_collect().await?;
+        expand_id_prefix(
+            "workout",
+            &id,
+            &workouts,
+            |w| w.id.as_deref(),
+            |w| candidate(w.id.clone(), w.start_time, w.title.as_deref()),
+        )?
+    } else {
+        id
+    };
+    let data = client.get_workout(&id).await?;
+    printer.json(&data)?;
+    Ok(())
+}
+
+/// `routines create`: post the routine in `json`, with `warmups` added, or
+/// with `dry_run` print the body that would be sent.
+async fn create_routine(
+    client: &impl HevyApi,
+    printer: &output::Printer,
+    json: &str,
+    warmups: Option<&warmup::Warmups>,
+    dry_run: bool,
+) -> Result<()> {
+    let mut body: PostRoutineBody = serde_json::from_str(json)
+        .context("Invalid JSON for routine body. See `hevy-bridge routines create --help` for the expected schema.")?;
+    if let Some(warmups) = warmups {
+        warmups.add_to_routine(&mut body)?;
+    }
+    if dry_run {
+        printer.json(&body)?;
+    } else {
+        let data = client.create_routine(&body).await?;
+        printer.json(&data)?;
+    }
+    Ok(())
+}
+
+/// `routines update`: replace routine `id` with the one in `json`.
+async fn update_routine(
+    client: &impl HevyApi,
+    printer: &output::Printer,
+    id: &str,
+    json: &str,
+) -> Result<()> {
+    let body: PutRoutineBody = serde_json::from_str(json)
+        .context("Invalid JSON for routine body. See `hevy-bridge routines update --help` for the expected schema.")?;
+    let data = client.update_routine(id, &body).await?;
+    printer.json(&data)?;
+    Ok(())
+}
+
+/// Check an API key by fetching the user's profile with `client`. A key the
+/// API rejects (401 or 403) fails with [`failure::InvalidKey`], which `given`
+/// says came from the command line; other failures get `context`.
+async fn validate_api_key(client: &impl HevyApi, given: bool, context: &'static str) -> Result<()> {
+    match client.user_info().await {
+        Ok(info) => {
+            eprintln!(
+                "✓ API key is valid for user: {}",
+                info.data.name.as_deref().unwrap_or("(unnamed)")
+            );
+            Ok(())
+        }
+        Err(e) if e.as_api().is_some_and(|api| api.is_auth_failure()) => {
+            Err(anyhow::Error::new(e).context(failure::InvalidKey { given }))
+        }
+        Err(e) => Err(anyhow::Error::new(e).context(context)),
+    }
+}
 
-                    let best_lbs = best_kg * 2.20462;
-                    let weight_str = if best_kg > 0.0 {
-                        format!("{best_lbs:.1}")
-                    } else {
-                        "—".to_string()
-                    };
+/// A routine folder ID given on the command line.
+fn parse_folder_id(s: &str) -> Result<u64> {
+    s.trim().parse().with_context(|| {
+        format!("Invalid folder ID \"{s}\". Folder IDs are whole numbers, e.g. 42")
+    })
+}
 
-                    println!(
-                        "  {:<35} {:>5} {:>18} {:>12} {:>12}   {}",
-                        truncate_str(ex_title, 35),
-                        num_sets,
-                        weight_str,
-                        reps_display,
-                        rest,
-                        notes
-                    );
+/// A target or bar weight for `plates`: a number from 0 to
+/// [`plates::MAX_WEIGHT`].
+fn parse_plate_weight(s: &str) -> Result<f64> {
+    s.trim()
+        .parse()
+        .ok()
+        .filter(|w: &f64| (0.0..=plates::MAX_WEIGHT).contains(w))
+        .with_context(|| {
+            format!(
+                "Invalid weight \"{s}\": expected a number from 0 to {}",
+                plates::MAX_WEIGHT
+            )
+        })
+}
 
-                    // Indented per-set detail rows
-                    for (i, s) in exercise.sets.iter().enumerate() {
-                        let set_num = i + 1;
-                        let set_label = format!(
-                            "  Set {set_num}{}",
-                            s.set_type
-                                .as_ref()
-                                .map(|t| format!(" ({t})"))
-                                .unwrap_or_default()
-                        );
-                        let w_lbs = s.weight_kg.unwrap_or(0.0) * 2.20462;
-                        let rep_str = if let Some(ref range) = s.rep_range {
-                            let lo = range.start.map(|v| v as i64);
-                            let hi = range.end.map(|v| v as i64);
-                            match (lo, hi) {
-                                (Some(l), Some(h)) => format!("{l}-{h}"),
-                                (Some(l), None) => format!("{l}+"),
-                                _ => s.reps.map(|r| format!("{}", r as i64)).unwrap_or_else(|| "—".to_string()),
-                            }
-                        } else {
-                            s.reps.map(|r| format!("{}", r as i64)).unwrap_or_else(|| "—".to_string())
-                        };
-                        let w_str = if s.weight_kg.unwrap_or(0.0) > 0.0 {
-                            format!("{w_lbs:.1}")
-                        } else {
-                            "—".to_string()
-                        };
-                        println!(
-                            "  {:<35} {:>5} {:>18} {:>12} {:>12}   {}",
-                            set_label,
-                            "",
-                            w_str,
-                            rep_str,
-                            "",
-                            ""
-                        );
-                    }
+/// Whether `title` matches a `get --title` query, ignoring case: equal to
+/// it with `exact`, else containing it.
+fn title_matches(title: Option<&str>, query: &str, exact: bool) -> bool {
+    let Some(title) = title else {
+        return false;
+    };
+    let (title, query) = (title.to_lowercase(), query.to_lowercase());
+    if exact {
+        title == query
+    } else {
+        title.contains(&query)
+    }
+}
+
+/// Print the `get --title` match among `matches` (most recent first), or
+/// all of them with `all_matches`. Several matches without `latest` are
+/// listed on stderr, one `describe` line each, and fail the command.
+fn print_title_match<T: Serialize>(
+    printer: &output::Printer,
+    kind: &str,
+    query: &str,
+    matches: &[T],
+    latest: bool,
+    all_matches: bool,
+    describe: impl Fn(&T) -> String,
+) -> Result<()> {
+    if all_matches {
+        return printer.json(&matches);
+    }
+    match matches {
+        [] => anyhow::bail!("No {kind} title matches \"{query}\""),
+        [only] => printer.json(only),
+        [newest, ..] if latest => printer.json(newest),
+        _ => {
+            eprintln!("{} {kind}s match \"{query}\":", matches.len());
+            for item in matches {
+                eprintln!("  {}", describe(item));
+            }
+            anyhow::bail!(
+                "Several {kind}s match \"{query}\"; pass --latest or --all-matches, \
+                 or get one by ID"
+            )
+        }
+    }
+}
+
+/// The ID among `items` that starts with `prefix` (ignoring case), for
+/// `get` with a short ID. None or several matching fail the command;
+/// several are listed on stderr, one `describe` line each.
+fn expand_id_prefix<T>(
+    kind: &str,
+    prefix: &str,
+    items: &[T],
+    id: impl Fn(&T) -> Option<&str>,
+    describe: impl Fn(&T) -> String,
+) -> Result<String> {
+    let prefix = prefix.to_lowercase();
+    let matches: Vec<&T> = items
+        .iter()
+        .filter(|item| id(item).is_some_and(|id| id.to_lowercase().starts_with(&prefix)))
+        .collect();
+    match matches[..] {
+        [] => anyhow::bail!("No {kind} ID starts with \"{prefix}\""),
+        [only] => Ok(id(only).unwrap_or_default().to_string()),
+        _ => {
+            eprintln!("{} {kind} IDs start with \"{prefix}\":", matches.len());
+            for item in matches {
+                eprintln!("  {}", describe(item));
+            }
+            anyhow::bail!("Several {kind} IDs start with \"{prefix}\"; give more of the ID")
+        }
+    }
+}
+
+/// A `get --title` or short ID candidate line: ID, date and title.
+fn candidate(id: Option<String>, date: Option<DateTime<Utc>>, title: Option<&str>) -> String {
+    format!(
+        "{}  {}  {}",
+        id.as_deref().unwrap_or("—"),
+        date.map_or("—".to_string(), |d| d.format("%Y-%m-%d").to_string()),
+        title.unwrap_or("Untitled")
+    )
+}
+
+/// The non-blank lines of stdin, for `get --ids-from-stdin`.
+fn ids_from_stdin() -> Result<Vec<String>> {
+    if std::io::stdin().is_terminal() {
+        anyhow::bail!("--ids-from-stdin needs IDs piped in on stdin, one per line");
+    }
+    Ok(std::io::read_to_string(std::io::stdin())
+        .context("Failed to read IDs from stdin")?
+        .lines()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Fetch each of `ids`, up to [`FETCH_CONCURRENCY`] at a time, and print
+/// one JSON line per ID in the order given. A failed fetch prints an
+/// `{"id", "error"}` line, or with `fail_fast` fails the command.
+async fn get_each<'a, T, Fut>(
+    printer: &output::Printer,
+    ids: &'a [String],
+    fail_fast: bool,
+    fetch: impl Fn(&'a str) -> Fut,
+) -> Result<()>
+where
+    T: Serialize,
+    Fut: Future<Output = hevy_bridge::Result<T>> + 'a,
+{
+    let mut results = stream::iter(ids)
+        .map(|id| fetch(id))
+        .buffered(FETCH_CONCURRENCY);
+    let mut failed = 0;
+    for id in ids {
+        let Some(result) = results.next().await else {
+            break;
+        };
+        match result {
+            Ok(item) => printer.write_line(&mut *printer.out(), &item)?,
+            Err(e) if fail_fast => return Err(e).with_context(|| format!("Failed to get {id}")),
+            Err(e) => {
+                failed += 1;
+                let line = serde_json::json!({ "id": id, "error": format!("{e:#}") });
+                let mut out = printer.out();
+                writeln!(out, "{line}")?;
+                out.flush()?;
+            }
+        }
+    }
+    if failed > 0 {
+        eprintln!("{failed} of {} ID(s) could not be fetched.", ids.len());
+    }
+    Ok(())
+}
+
+/// Ask a yes/no question on stderr and read the answer from stdin.
+/// Without a terminal to ask on, fails and points to `--yes`.
+fn confirm(prompt: &str) -> Result<bool> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("{prompt} Pass --yes to confirm when not running interactively.");
+    }
+    eprint!("{prompt} [y/N] ");
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Read all of stdin, failing if no input arrives within `timeout`.
+///
+/// The read happens on a detached thread so a stuck terminal cannot keep
+/// the process alive after the timeout fires.
+fn read_stdin_with_timeout(timeout: std::time::Duration) -> Result<String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let mut input = String::new();
+        let result = std::io::Read::read_to_string(&mut std::io::stdin(), &mut input);
+        let _ = tx.send(result.map(|_| input));
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result.context("Failed to read payload from stdin"),
+        Err(_) => anyhow::bail!(
+            "No input on stdin after {}s. Pipe the webhook JSON in, or pass it with --json.",
+            timeout.as_secs()
+        ),
+    }
+}
+
+/// Resolve every distinct routine_id referenced by `workouts` and return the
+/// ids whose routine title contains `needle` (case-insensitive).
+async fn routine_ids_matching_title(
+    client: &impl HevyApi,
+    workouts: &[Workout],
+    needle: &str,
+) -> std::collections::HashSet<String> {
+    let needle = needle.to_lowercase();
+    let ids: std::collections::BTreeSet<&String> = workouts
+        .iter()
+        .filter_map(|w| w.routine_id.as_ref())
+        .collect();
+
+    let mut matching = std::collections::HashSet::new();
+    for id in ids {
+        match client.get_routine(id).await {
+            Ok(r) => {
+                let title = r.routine.title.unwrap_or_default().to_lowercase();
+                if title.contains(&needle) {
+                    matching.insert(id.clone());
                 }
+            }
+            Err(e) => eprintln!("Warning: could not resolve routine {id}: {e:#}"),
+        }
+    }
+    matching
+}
+
+/// Resolve an exercise alias, template ID or title to a template ID,
+/// using the (cached) exercise template list for the latter two.
+async fn resolve_exercise_template(
+    client: &impl HevyApi,
+    config: &config::Config,
+    query: &str,
+) -> Result<String> {
+    if let Some(id) = config.alias(query) {
+        return Ok(id.to_string());
+    }
+    let templates = client.all_exercise_templates().await?;
+    if templates.iter().any(|t| t.id.as_deref() == Some(query)) {
+        return Ok(query.to_string());
+    }
+
+    let needle = query.to_lowercase();
+    let title_of = |t: &ExerciseTemplate| t.title.as_deref().unwrap_or("").to_lowercase();
+    let id_of = |t: &ExerciseTemplate| {
+        t.id.clone().with_context(|| {
+            format!(
+                "Exercise template \"{}\" matching \"{query}\" has no ID",
+                t.title.as_deref().unwrap_or("")
+            )
+        })
+    };
+    if let Some(t) = templates.iter().find(|t| title_of(t) == needle) {
+        return id_of(t);
+    }
+
+    let candidates: Vec<&ExerciseTemplate> = templates
+        .iter()
+        .filter(|t| title_of(t).contains(&needle))
+        .collect();
+    match candidates.as_slice() {
+        [t] => id_of(t),
+        [] => anyhow::bail!("No exercise template matches \"{query}\""),
+        many => {
+            let list = many
+                .iter()
+                .map(|t| {
+                    format!(
+                        "  {} {}",
+                        t.id.as_deref().unwrap_or("?"),
+                        t.title.as_deref().unwrap_or("")
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            anyhow::bail!("\"{query}\" matches several exercise templates:\n{list}")
+        }
+    }
+}
+
+/// One workout containing a searched-for exercise.
+#[derive(Debug, Serialize)]
+struct FoundWorkout {
+    workout_id: String,
+    title: Option<String>,
+    date: Option<DateTime<Utc>>,
+    sets: Vec<ExerciseHistoryEntry>,
+    best_set: Option<ExerciseHistoryEntry>,
+}
 
-                println!();
+/// Collapse set-level history into one entry per workout, newest first.
+fn workouts_from_history(history: Vec<ExerciseHistoryEntry>) -> Vec<FoundWorkout> {
+    let mut found: Vec<FoundWorkout> = Vec::new();
+    for entry in history {
+        let Some(workout_id) = entry.workout_id.clone() else {
+            continue;
+        };
+        match found.iter_mut().find(|f| f.workout_id == workout_id) {
+            Some(f) => f.sets.push(entry),
+            None => found.push(FoundWorkout {
+                workout_id,
+                title: entry.workout_title.clone(),
+                date: entry.workout_start_time,
+                sets: vec![entry],
+                best_set: None,
+            }),
+        }
+    }
+
+    for f in &mut found {
+        f.best_set = f
+            .sets
+            .iter()
+            .max_by(|a, b| {
+                let key =
+                    |s: &ExerciseHistoryEntry| (s.weight_kg.unwrap_or(0.0), s.reps.unwrap_or(0));
+                key(a)
+                    .partial_cmp(&key(b))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .cloned();
+    }
+    found.sort_by_key(|f| std::cmp::Reverse(f.date));
+    found
+}
+
+/// Progress bar on stderr for commands that walk every page of a list.
+///
+/// Hidden unless stdout is a terminal, so piped and scripted runs stay
+/// quiet.
+struct ProgressReporter {
+    noun: &'static str,
+    enabled: bool,
+    bar: Option<ProgressBar>,
+    pages: u64,
+    items: usize,
+}
+
+impl ProgressReporter {
+    fn new(noun: &'static str) -> Self {
+        ProgressReporter {
+            noun,
+            enabled: std::io::stdout().is_terminal(),
+            bar: None,
+            pages: 0,
+            items: 0,
+        }
+    }
+
+    /// Count a fetched page; the first one sizes the bar from its page count.
+    fn page<T>(&mut self, page: &api::Page<T>) {
+        self.pages += 1;
+        self.items += page.items.len();
+        if !self.enabled {
+            return;
+        }
+        let noun = self.noun;
+        let bar = self.bar.get_or_insert_with(|| {
+            let bar = ProgressBar::new(page.page_count.max(1) as u64)
+                .with_finish(ProgressFinish::AndClear);
+            if let Ok(style) =
+                ProgressStyle::with_template("Fetching {msg} [{bar:30}] {pos}/{len} pages")
+            {
+                bar.set_style(style.progress_chars("=> "));
             }
+            bar.set_message(noun);
+            bar
+        });
+        bar.inc(1);
+    }
+
+    /// Clear the bar and print a one-line summary in its place.
+    fn finish(self) {
+        if let Some(bar) = self.bar {
+            bar.finish_and_clear();
+            eprintln!(
+                "✓ {} pages fetched ({} {})",
+                self.pages, self.items, self.noun
+            );
+        }
+    }
+
+    /// Collect every page of `pages`, showing progress as they arrive.
+    async fn collect<T>(
+        mut self,
+        pages: impl futures::Stream<Item = hevy_bridge::Result<api::Page<T>>>,
+    ) -> Result<Vec<T>> {
+        tokio::pin!(pages);
+        let mut items = Vec::new();
+        while let Some(page) = pages.try_next().await? {
+            self.page(&page);
+            items.extend(page.items);
+        }
+        self.finish();
+        Ok(items)
+    }
+}
+
+/// Send HTTP logs to stderr at the level chosen by `-v`/`--debug-http`.
+/// Logging from dependencies stays off.
+fn init_logging(cli: &Cli) {
+    use tracing_subscriber::filter::{LevelFilter, Targets};
+    use tracing_subscriber::prelude::*;
+
+    let targets = |level| Targets::new().with_target(env!("CARGO_CRATE_NAME"), level);
+    let layer = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_target(false);
+    let level = match (cli.debug_http, cli.verbose) {
+        (false, 0) => {
+            // Only the library's warnings, printed like the CLI's own.
+            tracing_subscriber::registry()
+                .with(layer.event_format(WarningFormat))
+                .with(targets(LevelFilter::WARN))
+                .init();
+            return;
+        }
+        (false, 1) => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    tracing_subscriber::registry()
+        .with(layer)
+        .with(targets(level))
+        .init();
+}
+
+/// Formats log events as `Warning: <message>`, matching the warnings the
+/// CLI prints itself.
+struct WarningFormat;
+
+impl<S, N> tracing_subscriber::fmt::FormatEvent<S, N> for WarningFormat
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &tracing_subscriber::fmt::FmtContext<'_, S, N>,
+        mut writer: tracing_subscriber::fmt::format::Writer<'_>,
+        event: &tracing::Event<'_>,
+    ) -> std::fmt::Result {
+        write!(writer, "Warning: ")?;
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// Fetch the workouts starting within `[since, until)`.
+///
+/// Workouts are listed newest first, so paging stops at the first page
+/// that reaches back before `since`.
+async fn workouts_in_range(
+    client: &impl HevyApi,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Result<Vec<Workout>> {
+    let mut workouts = Vec::new();
+    let stream = client.workouts_stream();
+    tokio::pin!(stream);
+    while let Some(w) = stream.try_next().await? {
+        let Some(start) = w.start_time else {
+            continue;
+        };
+        if since.is_some_and(|s| start < s) {
+            // Newest first: everything after this is older.
+            break;
+        }
+        if until.is_none_or(|u| start < u) {
+            workouts.push(w);
+        }
+    }
+    Ok(workouts)
+}
+
+/// Key `workouts count --by-routine` counts workouts without a routine under.
+const AD_HOC: &str = "ad_hoc";
+
+/// A JSON object that keeps its keys in this order.
+struct OrderedMap<V>(Vec<(String, V)>);
+
+impl<V: Serialize> Serialize for OrderedMap<V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(key, value)| (key, value)))
+    }
+}
+
+/// Workout counts keyed by routine ID (or [`AD_HOC`]), most first, and the
+/// title of each routine under the same keys: null for [`AD_HOC`] and for
+/// deleted routines.
+#[derive(Serialize)]
+struct RoutineCounts {
+    counts: OrderedMap<usize>,
+    titles: OrderedMap<Option<String>>,
+}
+
+/// Workouts per routine, most first, then by ID, titled from `routines`.
+fn count_by_routine(workouts: &[Workout], routines: &[Routine]) -> RoutineCounts {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for w in workouts {
+        *counts
+            .entry(w.routine_id.as_deref().unwrap_or(AD_HOC))
+            .or_default() += 1;
+    }
+    let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    let title = |id: &str| {
+        routines
+            .iter()
+            .find(|r| r.id.as_deref() == Some(id))
+            .and_then(|r| r.title.clone())
+    };
+    RoutineCounts {
+        titles: OrderedMap(
+            counts
+                .iter()
+                .map(|(id, _)| (id.to_string(), title(id)))
+                .collect(),
+        ),
+        counts: OrderedMap(
+            counts
+                .into_iter()
+                .map(|(id, count)| (id.to_string(), count))
+                .collect(),
+        ),
+    }
+}
+
+/// Print `counts` as a table of routine title, ID and workout count.
+fn print_routine_counts(
+    out: &mut impl Write,
+    counts: &RoutineCounts,
+    style: &table::Style,
+) -> Result<()> {
+    let mut table = Table::new(vec![
+        Column::left("Routine", 30).flex(),
+        Column::left("ID", 36),
+        Column::right("Workouts", 8),
+    ]);
+    for ((id, count), (_, title)) in counts.counts.0.iter().zip(&counts.titles.0) {
+        let title = match title {
+            Some(title) => title,
+            None if id == AD_HOC => "(no routine)",
+            None => "(deleted routine)",
+        };
+        table.row(vec![
+            title.into(),
+            id.as_str().into(),
+            count.to_string().into(),
+        ]);
+    }
+    writeln!(out)?;
+    table.render(out, style)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[derive(Debug, Serialize)]
+struct WorkoutDuration {
+    workout_id: Option<String>,
+    title: Option<String>,
+    date: String,
+    duration_minutes: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct DurationReport {
+    workouts: Vec<WorkoutDuration>,
+    avg_duration_minutes: Option<f64>,
+    min_duration_minutes: Option<i64>,
+    max_duration_minutes: Option<i64>,
+}
 
-            // ── Workout results table ──
-            println!(
-                "  {:<35} {:>5} {:>18} {:>13} {:>12}   {}",
-                "Exercise", "Sets", "Weight (lbs)", "Reps", "Result", "Notes"
+/// Compute per-workout durations for workouts starting within `[since, until)`.
+fn workout_durations(
+    workouts: &[Workout],
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> DurationReport {
+    let mut rows: Vec<(DateTime<Utc>, WorkoutDuration)> = Vec::new();
+    for w in workouts {
+        let Some(start) = w.start_time else {
+            continue;
+        };
+        if since.is_some_and(|s| start < s) || until.is_some_and(|u| start >= u) {
+            continue;
+        }
+        let Some(end) = w.end_time else {
+            eprintln!(
+                "Warning: skipping workout {} (no end_time)",
+                w.id.as_deref().unwrap_or("?")
             );
-            println!("  {}", "─".repeat(120));
+            continue;
+        };
+        rows.push((
+            start,
+            WorkoutDuration {
+                workout_id: w.id.clone(),
+                title: w.title.clone(),
+                date: start.to_rfc3339_opts(SecondsFormat::Secs, true),
+                duration_minutes: (end - start).num_minutes(),
+            },
+        ));
+    }
+    rows.sort_by_key(|(start, _)| *start);
+
+    let minutes: Vec<i64> = rows.iter().map(|(_, r)| r.duration_minutes).collect();
+    let avg = (!minutes.is_empty())
+        .then(|| (minutes.iter().sum::<i64>() as f64 / minutes.len() as f64 * 10.0).round() / 10.0);
+    DurationReport {
+        avg_duration_minutes: avg,
+        min_duration_minutes: minutes.iter().min().copied(),
+        max_duration_minutes: minutes.iter().max().copied(),
+        workouts: rows.into_iter().map(|(_, r)| r).collect(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ExerciseDiff {
+    exercise_template_id: Option<String>,
+    title: Option<String>,
+    sets: Vec<SetDiff>,
+}
+
+#[derive(Debug, Serialize)]
+struct SetDiff {
+    set_a: Option<SetSummary>,
+    set_b: Option<SetSummary>,
+    weight_delta_kg: Option<f64>,
+    reps_delta: Option<i64>,
+}
 
-            for exercise in &workout.exercises {
-                let ex_title = exercise
+#[derive(Debug, Serialize)]
+struct SetSummary {
+    weight_kg: Option<f64>,
+    reps: Option<i64>,
+}
+
+impl From<&Set> for SetSummary {
+    fn from(set: &Set) -> Self {
+        SetSummary {
+            weight_kg: set.weight_kg,
+            reps: set.reps,
+        }
+    }
+}
+
+/// Pair up the exercises of two workouts by template ID and compare their sets.
+///
+/// Follows A's exercise order, then appends exercises only found in B. A
+/// template repeated within a workout pairs with its next unused occurrence
+/// on the other side.
+fn diff_workouts(a: &Workout, b: &Workout) -> Vec<ExerciseDiff> {
+    let mut used_b = vec![false; b.exercises.len()];
+    let mut diffs = Vec::new();
+
+    for ex_a in &a.exercises {
+        let matched = (0..b.exercises.len()).find(|&i| {
+            !used_b[i] && b.exercises[i].exercise_template_id == ex_a.exercise_template_id
+        });
+        if let Some(i) = matched {
+            used_b[i] = true;
+        }
+        diffs.push(diff_exercise(Some(ex_a), matched.map(|i| &b.exercises[i])));
+    }
+    for (ex_b, used) in b.exercises.iter().zip(used_b) {
+        if !used {
+            diffs.push(diff_exercise(None, Some(ex_b)));
+        }
+    }
+    diffs
+}
+
+fn diff_exercise(a: Option<&Exercise>, b: Option<&Exercise>) -> ExerciseDiff {
+    let either = a.or(b).expect("at least one side is present");
+    let sets_a = a.map(|e| e.sets.as_slice()).unwrap_or_default();
+    let sets_b = b.map(|e| e.sets.as_slice()).unwrap_or_default();
+
+    let sets = (0..sets_a.len().max(sets_b.len()))
+        .map(|i| {
+            let set_a = sets_a.get(i).map(SetSummary::from);
+            let set_b = sets_b.get(i).map(SetSummary::from);
+            let (weight_delta_kg, reps_delta) = match (&set_a, &set_b) {
+                (Some(sa), Some(sb)) => (
+                    sa.weight_kg
+                        .zip(sb.weight_kg)
+                        .map(|(wa, wb)| ((wb - wa) * 100.0).round() / 100.0),
+                    sa.reps.zip(sb.reps).map(|(ra, rb)| rb - ra),
+                ),
+                _ => (None, None),
+            };
+            SetDiff {
+                set_a,
+                set_b,
+                weight_delta_kg,
+                reps_delta,
+            }
+        })
+        .collect();
+
+    ExerciseDiff {
+        exercise_template_id: either.exercise_template_id.clone(),
+        title: either.title.clone(),
+        sets,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct HistoryComparison {
+    date: NaiveDate,
+    a_max_weight_kg: Option<f64>,
+    b_max_weight_kg: Option<f64>,
+    a_max_reps: Option<i64>,
+    b_max_reps: Option<i64>,
+    imbalance_pct: Option<f64>,
+}
+
+/// Heaviest weight and most reps per UTC calendar day.
+fn daily_maxima(
+    history: &[ExerciseHistoryEntry],
+) -> BTreeMap<NaiveDate, (Option<f64>, Option<i64>)> {
+    let mut days: BTreeMap<_, (Option<f64>, Option<i64>)> = BTreeMap::new();
+    for entry in history {
+        let Some(start) = entry.workout_start_time else {
+            continue;
+        };
+        let (weight, reps) = days.entry(start.date_naive()).or_default();
+        if let Some(w) = entry.weight_kg {
+            *weight = Some(weight.map_or(w, |m| m.max(w)));
+        }
+        if let Some(r) = entry.reps {
+            *reps = Some(reps.map_or(r, |m| m.max(r)));
+        }
+    }
+    days
+}
+
+/// Align two exercise histories by day and compute the imbalance between them.
+fn compare_histories(
+    a: &[ExerciseHistoryEntry],
+    b: &[ExerciseHistoryEntry],
+) -> Vec<HistoryComparison> {
+    let days_a = daily_maxima(a);
+    let days_b = daily_maxima(b);
+    let days: BTreeSet<_> = days_a.keys().chain(days_b.keys()).collect();
+
+    days.into_iter()
+        .map(|date| {
+            let (a_weight, a_reps) = days_a.get(date).copied().unwrap_or_default();
+            let (b_weight, b_reps) = days_b.get(date).copied().unwrap_or_default();
+            let imbalance = |x: f64, y: f64| {
+                let max = x.max(y);
+                (max > 0.0).then(|| ((x - y).abs() / max * 1000.0).round() / 10.0)
+            };
+            let imbalance_pct = match (a_weight, b_weight) {
+                (Some(x), Some(y)) if x > 0.0 || y > 0.0 => imbalance(x, y),
+                _ => a_reps
+                    .zip(b_reps)
+                    .and_then(|(x, y)| imbalance(x as f64, y as f64)),
+            };
+            HistoryComparison {
+                date: *date,
+                a_max_weight_kg: a_weight,
+                b_max_weight_kg: b_weight,
+                a_max_reps: a_reps,
+                b_max_reps: b_reps,
+                imbalance_pct,
+            }
+        })
+        .collect()
+}
+
+/// A routine folder together with the routines it contains.
+#[derive(Debug, Serialize)]
+struct FolderWithRoutines {
+    id: Option<u64>,
+    index: Option<u64>,
+    title: String,
+    routine_count: usize,
+    routines: Vec<RoutineSummary>,
+}
+
+#[derive(Debug, Serialize)]
+struct RoutineSummary {
+    id: Option<String>,
+    title: Option<String>,
+}
+
+/// Group routines under `folders` (every folder of the account), appending
+/// a "No folder" bucket for routines whose folder_id is null or points at a
+/// folder that no longer exists.
+fn group_routines_by_folder(
+    folders: &[RoutineFolder],
+    routines: &[Routine],
+) -> Vec<FolderWithRoutines> {
+    let summary = |r: &Routine| RoutineSummary {
+        id: r.id.clone(),
+        title: r.title.clone(),
+    };
+
+    let mut grouped: Vec<FolderWithRoutines> = folders
+        .iter()
+        .map(|f| {
+            let routines: Vec<RoutineSummary> = routines
+                .iter()
+                .filter(|r| f.id.is_some() && r.folder_id == f.id)
+                .map(summary)
+                .collect();
+            FolderWithRoutines {
+                id: f.id,
+                index: f.index,
+                title: f
                     .title
-                    .as_deref()
-                    .unwrap_or("Unknown Exercise");
-                let notes = exercise.notes.as_deref().unwrap_or("");
-                let num_sets = exercise.sets.len();
-
-                // Compute an overall result: worst individual set classification wins
-                let mut has_struggled = false;
-                let mut all_exceeded = true;
-                for (i, s) in exercise.sets.iter().enumerate() {
-                    let reps = s.reps.map(|v| v as i64).unwrap_or(0);
-                    let (lo, hi) = exercise
-                        .exercise_template_id
-                        .as_ref()
-                        .and_then(|id| set_targets.get(&(id.clone(), i)))
-                        .copied()
-                        .unwrap_or((8, 10));
-                    if reps < lo {
-                        has_struggled = true;
-                        all_exceeded = false;
-                    } else if reps <= hi {
-                        all_exceeded = false;
-                    }
-                }
-                let overall = if has_struggled {
-                    "\x1b[33mStruggled\x1b[0m"
-                } else if all_exceeded {
-                    "\x1b[36mExceeded\x1b[0m"
-                } else {
-                    "\x1b[32mSucceeded\x1b[0m"
-                };
+                    .clone()
+                    .unwrap_or_else(|| "Untitled Folder".to_string()),
+                routine_count: routines.len(),
+                routines,
+            }
+        })
+        .collect();
 
-                // Exercise summary row (no weight/reps — those are on the set rows)
-                println!(
-                    "  {:<35} {:>5} {:>18} {:>13} {:>21}   {}",
-                    truncate_str(ex_title, 35),
-                    num_sets,
-                    "",
-                    "",
-                    overall,
-                    notes
-                );
+    let unfiled: Vec<RoutineSummary> = routines
+        .iter()
+        .filter(|r| {
+            r.folder_id
+                .is_none_or(|id| !folders.iter().any(|f| f.id == Some(id)))
+        })
+        .map(summary)
+        .collect();
+    grouped.push(FolderWithRoutines {
+        id: None,
+        index: None,
+        title: "No folder".to_string(),
+        routine_count: unfiled.len(),
+        routines: unfiled,
+    });
 
-                // Indented per-set detail rows with individual results
-                for (i, s) in exercise.sets.iter().enumerate() {
-                    let set_num = i + 1;
-                    let set_label = format!(
-                        "  Set {set_num}{}",
-                        s.set_type
-                            .as_ref()
-                            .map(|t| format!(" ({t})"))
-                            .unwrap_or_default()
-                    );
-                    let w_lbs = s.weight_kg.unwrap_or(0.0) * 2.20462;
-                    let reps = s.reps.map(|v| v as i64);
-
-                    let (lo, hi) = exercise
-                        .exercise_template_id
-                        .as_ref()
-                        .and_then(|id| set_targets.get(&(id.clone(), i)))
-                        .copied()
-                        .unwrap_or((8, 10));
-
-                    let r = reps.unwrap_or(0);
-                    let result = if r < lo {
-                        "\x1b[33mStruggled\x1b[0m"
-                    } else if r <= hi {
-                        "\x1b[32mSucceeded\x1b[0m"
-                    } else {
-                        "\x1b[36mExceeded\x1b[0m"
-                    };
+    grouped
+}
 
-                    let rpe_str = s
-                        .rpe
-                        .map(|v| format!("RPE {v}"))
-                        .unwrap_or_default();
-
-                    println!(
-                        "  {:<35} {:>5} {:>18.1} {:>13} {:>21}   {}",
-                        set_label,
-                        "",
-                        w_lbs,
-                        reps.map(|v| v.to_string()).unwrap_or_else(|| "—".to_string()),
-                        result,
-                        rpe_str
-                    );
+fn print_folders_table(
+    out: &mut impl Write,
+    folders: &[FolderWithRoutines],
+    style: &table::Style,
+) -> Result<()> {
+    let mut table = Table::new(vec![
+        Column::left("Folder", 30).flex(),
+        Column::right("Index", 5),
+        Column::right("Count", 8),
+        Column::free("Routines"),
+    ]);
+    for f in folders {
+        let index = f
+            .index
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "—".to_string());
+        let preview = f
+            .routines
+            .iter()
+            .map(|r| r.title.as_deref().unwrap_or("Untitled Routine"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        table.row(vec![
+            f.title.as_str().into(),
+            index.into(),
+            f.routine_count.to_string().into(),
+            preview.into(),
+        ]);
+    }
+    writeln!(out)?;
+    table.render(out, style)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Render folders with how many routines each holds, for
+/// `folders list --format table`.
+fn print_folder_list_table(
+    out: &mut impl Write,
+    folders: &[RoutineFolder],
+    routines: &[Routine],
+    style: &table::Style,
+) -> Result<()> {
+    let mut table = Table::new(vec![
+        Column::right("ID", 10),
+        Column::right("Index", 5),
+        Column::left("Title", 30).flex(),
+        Column::right("Routines", 8),
+        Column::left("Created", 10),
+    ]);
+    for f in folders {
+        let count = routines
+            .iter()
+            .filter(|r| f.id.is_some() && r.folder_id == f.id)
+            .count();
+        let created = f
+            .created_at
+            .map_or("—".to_string(), |t| t.format("%Y-%m-%d").to_string());
+        table.row(vec![
+            f.id.map_or("—".to_string(), |v| v.to_string()).into(),
+            f.index.map_or("—".to_string(), |v| v.to_string()).into(),
+            f.title.as_deref().unwrap_or("Untitled Folder").into(),
+            count.to_string().into(),
+            created.into(),
+        ]);
+    }
+    writeln!(out)?;
+    table.render(out, style)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct ImportReport {
+    total: usize,
+    imported: usize,
+    dry_run: bool,
+    failed: Vec<ImportFailure>,
+}
+
+#[derive(Serialize)]
+struct ImportFailure {
+    /// 1-based position in the import file.
+    index: usize,
+    title: String,
+    error: String,
+}
+
+/// Create each workout in turn, continuing past failures. With `dry_run`
+/// the bodies are only built, not sent.
+async fn import_workouts(
+    client: &impl HevyApi,
+    workouts: &[Workout],
+    dry_run: bool,
+    delay_ms: Option<u64>,
+) -> ImportReport {
+    let total = workouts.len();
+    let mut report = ImportReport {
+        total,
+        imported: 0,
+        dry_run,
+        failed: Vec::new(),
+    };
+    for (i, w) in workouts.iter().enumerate() {
+        let title = w.title.as_deref().unwrap_or("Untitled");
+        eprintln!("[{}/{total}] Importing \"{title}\"...", i + 1);
+        let result = match workout_to_post_body(w) {
+            Ok(_) if dry_run => Ok(()),
+            Ok(body) => {
+                if let Some(ms) = delay_ms
+                    && i > 0
+                {
+                    tokio::time::sleep(std::time::Duration::from_millis(ms)).await;
                 }
+                client
+                    .create_workout(&body)
+                    .await
+                    .map(|_| ())
+                    .map_err(Into::into)
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => report.imported += 1,
+            Err(e) => {
+                eprintln!("  Failed: {e:#}");
+                report.failed.push(ImportFailure {
+                    index: i + 1,
+                    title: title.to_string(),
+                    error: format!("{e:#}"),
+                });
             }
-
-            println!();
         }
     }
+    report
+}
+
+/// Whether `workout` contains any exercise from `template_ids`.
+fn has_any_exercise(workout: &Workout, template_ids: &[String]) -> bool {
+    workout.exercises.iter().any(|e| {
+        e.exercise_template_id
+            .as_ref()
+            .is_some_and(|id| template_ids.contains(id))
+    })
+}
+
+/// Convert a fetched workout back into a POST body.
+fn workout_to_post_body(workout: &Workout) -> Result<PostWorkoutBody> {
+    Ok(PostWorkoutBody {
+        workout: PostWorkoutInner::try_from(workout)?,
+    })
+}
+
+/// Routines as a table with their folder title and exercise and set counts.
+/// Characters of a workout ID shown in the workouts table.
+const SHORT_ID_LEN: usize = 8;
+
+/// Exercise titles named in the workouts table before "+N more".
+const EXERCISE_PREVIEW_COUNT: usize = 3;
+
+/// Render workouts as a table for `workouts list --format table`, with a
+/// preview of each workout's exercises.
+fn print_workouts_table(
+    out: &mut impl Write,
+    workouts: &[Workout],
+    style: &table::Style,
+) -> Result<()> {
+    let mut table = Table::new(vec![
+        Column::left("ID", SHORT_ID_LEN),
+        Column::left("Title", 30).flex(),
+        Column::left("Date", 10),
+        Column::right("Duration", 8),
+        Column::left("Exercises", 40).flex(),
+    ]);
+    for w in workouts {
+        let id = w.id.as_deref().map_or("—".to_string(), |id| {
+            id.chars().take(SHORT_ID_LEN).collect()
+        });
+        let date = w
+            .start_time
+            .map_or("—".to_string(), |t| t.format("%Y-%m-%d").to_string());
+        let duration = w
+            .start_time
+            .zip(w.end_time)
+            .map_or("—".to_string(), |(start, end)| {
+                let minutes = (end - start).num_minutes();
+                format!("{}h{:02}m", minutes / 60, minutes % 60)
+            });
+        table.row(vec![
+            id.into(),
+            w.title.as_deref().unwrap_or("Untitled").into(),
+            date.into(),
+            duration.into(),
+            exercise_preview(w, style).into(),
+        ]);
+    }
+    writeln!(out)?;
+    table.render(out, style)?;
+    writeln!(out)?;
+    Ok(())
+}
 
+fn print_exercise_templates_table(
+    out: &mut impl Write,
+    templates: &[ExerciseTemplate],
+    style: &table::Style,
+) -> Result<()> {
+    let mut table = Table::new(vec![
+        Column::left("ID", SHORT_ID_LEN),
+        Column::left("Title", 30).flex(),
+        Column::left("Type", 16),
+        Column::left("Primary", 16),
+        Column::left("Secondary Muscles", 30),
+        Column::left("Custom", 6),
+    ]);
+    for t in templates {
+        let secondary = t
+            .secondary_muscle_groups
+            .as_deref()
+            .filter(|groups| !groups.is_empty())
+            .map_or("—".to_string(), |groups| {
+                table::truncate(&groups.join(", "), 30)
+            });
+        table.row(vec![
+            t.id.as_deref().unwrap_or("—").into(),
+            t.title.as_deref().unwrap_or("Untitled").into(),
+            t.exercise_type.as_deref().unwrap_or("—").into(),
+            t.primary_muscle_group.as_deref().unwrap_or("—").into(),
+            secondary.into(),
+            match t.is_custom {
+                Some(true) => "yes",
+                _ => "no",
+            }
+            .into(),
+        ]);
+    }
+    writeln!(out)?;
+    table.render(out, style)?;
+    writeln!(out)?;
     Ok(())
 }
 
-/// Truncate a string to `max` characters, appending "…" if shortened.
-fn truncate_str(s: &str, max: usize) -> String {
-    if s.chars().count() <= max {
-        s.to_string()
-    } else {
-        let truncated: String = s.chars().take(max - 1).collect();
-        format!("{truncated}…")
+/// The first few exercise titles of `workout`, e.g.
+/// `Bench Press, Squat, Deadlift, +3 more`.
+fn exercise_preview(workout: &Workout, style: &table::Style) -> String {
+    let names: Vec<&str> = workout
+        .exercises
+        .iter()
+        .take(EXERCISE_PREVIEW_COUNT)
+        .map(|e| {
+            style.exercise_title(
+                e.exercise_template_id.as_deref(),
+                e.title.as_deref().unwrap_or("Unknown exercise"),
+            )
+        })
+        .collect();
+    let mut preview = names.join(", ");
+    let hidden = workout
+        .exercises
+        .len()
+        .saturating_sub(EXERCISE_PREVIEW_COUNT);
+    if hidden > 0 {
+        preview.push_str(&format!(", +{hidden} more"));
+    }
+    preview
+}
+
+fn print_routines_table(
+    out: &mut impl Write,
+    routines: &[Routine],
+    folders: &[RoutineFolder],
+    style: &table::Style,
+) -> Result<()> {
+    let folder_titles: BTreeMap<u64, &str> = folders
+        .iter()
+        .filter_map(|f| Some((f.id?, f.title.as_deref().unwrap_or("Untitled Folder"))))
+        .collect();
+    let mut table = Table::new(vec![
+        Column::left("ID", 36),
+        Column::left("Title", 30).flex(),
+        Column::left("Folder", 20).flex(),
+        Column::right("Exercises", 9),
+        Column::right("Sets", 4),
+    ]);
+    for r in routines {
+        let folder = match r.folder_id {
+            Some(id) => folder_titles.get(&id).copied().unwrap_or("—"),
+            None => "—",
+        };
+        let sets: usize = r.exercises.iter().map(|e| e.sets.len()).sum();
+        table.row(vec![
+            r.id.as_deref().unwrap_or("—").into(),
+            r.title.as_deref().unwrap_or("Untitled Routine").into(),
+            folder.into(),
+            r.exercises.len().to_string().into(),
+            sets.to_string().into(),
+        ]);
+    }
+    writeln!(out)?;
+    table.render(out, style)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Convert a fetched routine back into a POST body, targeting `folder_id`.
+fn routine_to_post_body(routine: &Routine, folder_id: Option<u64>) -> Result<PostRoutineBody> {
+    let mut routine = PostRoutineInner::try_from(routine)?;
+    routine.folder_id = folder_id;
+    Ok(PostRoutineBody { routine })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::{Value, json};
+
+    use super::*;
+    use crate::mock::{Captured, MockHevyClient, from_json};
+
+    const W1: &str = "8a3f0c7e-1111-4c2a-9d7e-000000000001";
+    const W2: &str = "8a3f0c7e-2222-4c2a-9d7e-000000000002";
+    const W3: &str = "c41d9b20-3333-4c2a-9d7e-000000000003";
+
+    fn workout(id: &str, routine_id: &str, template_id: &str) -> Value {
+        json!({
+            "id": id,
+            "title": format!("Workout {id}"),
+            "routine_id": routine_id,
+            "start_time": "2026-03-02T18:00:00Z",
+            "exercises": [{
+                "index": 0,
+                "title": "Bench Press (Barbell)",
+                "exercise_template_id": template_id,
+                "sets": [{ "index": 0, "type": "normal", "weight_kg": 80, "reps": 5 }]
+            }]
+        })
+    }
+
+    /// Two pages of workouts: W1 and W2 from routine r1, then W3 from r2.
+    fn two_pages() -> MockHevyClient {
+        MockHevyClient::default().on_list_workouts(|page, _| {
+            let workouts = match page {
+                1 => vec![workout(W1, "r1", "D04AC939"), workout(W2, "r2", "79D0BB3A")],
+                2 => vec![workout(W3, "r1", "79D0BB3A")],
+                _ => vec![],
+            };
+            Ok(from_json(
+                json!({ "page": page, "page_count": 2, "workouts": workouts }),
+            ))
+        })
+    }
+
+    fn style() -> table::Style {
+        table::Style::new(false, Some(120), false)
+    }
+
+    fn ids(output: &Value) -> Vec<&str> {
+        output
+            .as_array()
+            .expect("expected a JSON array")
+            .iter()
+            .map(|w| w["id"].as_str().unwrap())
+            .collect()
+    }
+
+    fn routine_json(title: &str) -> String {
+        json!({
+            "routine": {
+                "title": title,
+                "exercises": [{
+                    "exercise_template_id": "D04AC939",
+                    "sets": [{ "type": "normal", "weight_kg": 100, "reps": 5 }]
+                }]
+            }
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn workouts_list_prints_the_requested_page() {
+        let client = MockHevyClient::default().on_list_workouts(|page, page_size| {
+            assert_eq!((page, page_size), (2, 3));
+            Ok(from_json(json!({
+                "page": 2,
+                "page_count": 4,
+                "workouts": [workout(W1, "r1", "D04AC939")]
+            })))
+        });
+        let captured = Captured::new();
+        let printer = captured.printer();
+        let args = ListWorkoutsArgs {
+            page: 2,
+            page_size: Some(3),
+            ..Default::default()
+        };
+        list_workouts(&client, &printer, OutputFormat::Json, &style(), args)
+            .await
+            .unwrap();
+
+        let output: Value = serde_json::from_str(&captured.text(printer)).unwrap();
+        assert_eq!(output["page"], 2);
+        assert_eq!(output["page_count"], 4);
+        assert_eq!(output["workouts"][0]["id"], W1);
+    }
+
+    #[tokio::test]
+    async fn workouts_list_filters_across_every_page() {
+        let captured = Captured::new();
+        let printer = captured.printer();
+        let args = ListWorkoutsArgs {
+            routine_id: Some("r1".to_string()),
+            exercises_exclude: vec!["D04AC939".to_string()],
+            ..Default::default()
+        };
+        list_workouts(&two_pages(), &printer, OutputFormat::Json, &style(), args)
+            .await
+            .unwrap();
+
+        let output: Value = serde_json::from_str(&captured.text(printer)).unwrap();
+        assert_eq!(ids(&output), [W3]);
+    }
+
+    #[tokio::test]
+    async fn workouts_get_fetches_a_full_id_directly() {
+        // Listing is not mocked: a full ID must not trigger a scan.
+        let client = MockHevyClient::default().on_get_workout(|id| {
+            assert_eq!(id, W3);
+            Ok(from_json(workout(W3, "r1", "D04AC939")))
+        });
+        let captured = Captured::new();
+        let printer = captured.printer();
+        get_workout(&client, &printer, W3.to_string(), false)
+            .await
+            .unwrap();
+
+        let output: Value = serde_json::from_str(&captured.text(printer)).unwrap();
+        assert_eq!(output["id"], W3);
+    }
+
+    #[tokio::test]
+    async fn workouts_get_expands_a_unique_prefix() {
+        let client = two_pages().on_get_workout(|id| {
+            assert_eq!(id, W3);
+            Ok(from_json(workout(W3, "r1", "D04AC939")))
+        });
+        let captured = Captured::new();
+        let printer = captured.printer();
+        get_workout(&client, &printer, "c41d".to_string(), false)
+            .await
+            .unwrap();
+        assert!(captured.text(printer).contains(W3));
+
+        // "8a3f" starts both W1 and W2.
+        let printer = captured.printer();
+        let err = get_workout(&client, &printer, "8a3f".to_string(), false)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("Several workout IDs start with \"8a3f\"")
+        );
+    }
+
+    #[tokio::test]
+    async fn routines_create_posts_the_body_with_warmups() {
+        let posted = Arc::new(Mutex::new(None));
+        let client = MockHevyClient::default().on_create_routine({
+            let posted = Arc::clone(&posted);
+            move |body| {
+                *posted.lock().unwrap() = Some(serde_json::to_value(body).unwrap());
+                Ok(from_json(
+                    json!({ "id": "r9", "title": body.routine.title }),
+                ))
+            }
+        });
+        let warmups =
+            warmup::Warmups::new(warmup::parse_spec("50%x5").unwrap(), table::Units::Kg, None)
+                .unwrap();
+        let captured = Captured::new();
+        let printer = captured.printer();
+        create_routine(
+            &client,
+            &printer,
+            &routine_json("Push"),
+            Some(&warmups),
+            false,
+        )
+        .await
+        .unwrap();
+
+        let output: Value = serde_json::from_str(&captured.text(printer)).unwrap();
+        assert_eq!(output["id"], "r9");
+        let posted = posted.lock().unwrap().take().expect("nothing was posted");
+        let sets = &posted["routine"]["exercises"][0]["sets"];
+        assert_eq!(sets[0]["type"], "warmup");
+        assert_eq!(sets[0]["weight_kg"], 50.0);
+        assert_eq!(sets[1]["type"], "normal");
+    }
+
+    #[tokio::test]
+    async fn routines_create_dry_run_sends_nothing() {
+        // create_routine is not mocked: calling it fails the test.
+        let client = MockHevyClient::default();
+        let captured = Captured::new();
+        let printer = captured.printer();
+        create_routine(&client, &printer, &routine_json("Push"), None, true)
+            .await
+            .unwrap();
+
+        let output: Value = serde_json::from_str(&captured.text(printer)).unwrap();
+        assert_eq!(output["routine"]["title"], "Push");
+
+        let printer = captured.printer();
+        let err = create_routine(&client, &printer, "{not json", None, true)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid JSON for routine body"));
+    }
+
+    #[tokio::test]
+    async fn routines_update_puts_to_the_given_id() {
+        let client = MockHevyClient::default().on_update_routine(|id, body| {
+            assert_eq!(id, "r1");
+            Ok(from_json(json!({ "id": id, "title": body.routine.title })))
+        });
+        let captured = Captured::new();
+        let printer = captured.printer();
+        update_routine(&client, &printer, "r1", &routine_json("Push v2"))
+            .await
+            .unwrap();
+
+        let output: Value = serde_json::from_str(&captured.text(printer)).unwrap();
+        assert_eq!(output["id"], "r1");
+        assert_eq!(output["title"], "Push v2");
+    }
+
+    #[test]
+    fn only_null_or_dangling_folder_ids_are_unfiled() {
+        let folders: Vec<RoutineFolder> =
+            from_json(json!([{ "id": 1, "title": "Push" }, { "id": 12, "title": "Pull" }]));
+        let routines: Vec<Routine> = from_json(json!([
+            { "id": "r1", "title": "Bench", "folder_id": 12 },
+            { "id": "r2", "title": "Old", "folder_id": 99 },
+            { "id": "r3", "title": "Loose", "folder_id": null },
+        ]));
+        let grouped = group_routines_by_folder(&folders, &routines);
+        let titles = |f: &FolderWithRoutines| -> Vec<_> {
+            f.routines
+                .iter()
+                .map(|r| r.title.clone().unwrap())
+                .collect()
+        };
+        assert_eq!(grouped.len(), 3);
+        assert!(titles(&grouped[0]).is_empty());
+        assert_eq!(titles(&grouped[1]), ["Bench"]);
+        assert_eq!(
+            (grouped[2].id, grouped[2].title.as_str()),
+            (None, "No folder")
+        );
+        assert_eq!(titles(&grouped[2]), ["Old", "Loose"]);
+    }
+
+    #[test]
+    fn routine_counts_map_ids_to_counts_most_first_and_to_titles() {
+        let workouts: Vec<Workout> = [("w1", "r2"), ("w2", "r1"), ("w3", "r2")]
+            .iter()
+            .map(|(id, routine)| from_json(workout(id, routine, "D04AC939")))
+            .chain([from_json(json!({ "id": "w4", "routine_id": null }))])
+            .collect();
+        let routines: Vec<Routine> = vec![from_json(json!({ "id": "r2", "title": "Push" }))];
+        let counts = serde_json::to_string(&count_by_routine(&workouts, &routines)).unwrap();
+        assert_eq!(
+            counts,
+            r#"{"counts":{"r2":2,"ad_hoc":1,"r1":1},"titles":{"r2":"Push","ad_hoc":null,"r1":null}}"#
+        );
     }
 }
@@ -0,0 +1,127 @@
+//! A stand-in for the Hevy API in handler tests.
+//!
+//! Each endpoint answers through a closure set with the matching `on_*`
+//! method; endpoints left alone fail with an error naming them, so a test
+//! notices requests it did not expect.
+
+use std::path::PathBuf;
+
+use hevy_bridge::api::HevyApi;
+use hevy_bridge::models::*;
+use hevy_bridge::{Error, Result};
+use tempfile::TempDir;
+
+use crate::output::Printer;
+
+macro_rules! mock_api {
+    ($($name:ident / $setter:ident ($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        /// A [`HevyApi`] serving whatever its closures return.
+        pub struct MockHevyClient {
+            $($name: Box<dyn Fn($($ty),*) -> Result<$ret> + Send + Sync>,)*
+        }
+
+        impl Default for MockHevyClient {
+            fn default() -> Self {
+                MockHevyClient {
+                    $($name: Box::new(|$(_: $ty),*| {
+                        Err(Error::Invalid(format!(
+                            "MockHevyClient: unexpected call to {}",
+                            stringify!($name)
+                        )))
+                    }),)*
+                }
+            }
+        }
+
+        // Each test sets only the endpoints it expects.
+        #[allow(dead_code)]
+        impl MockHevyClient {
+            $(
+                pub fn $setter(
+                    mut self,
+                    respond: impl Fn($($ty),*) -> Result<$ret> + Send + Sync + 'static,
+                ) -> Self {
+                    self.$name = Box::new(respond);
+                    self
+                }
+            )*
+        }
+
+        impl HevyApi for MockHevyClient {
+            $(
+                async fn $name(&self, $($arg: $ty),*) -> Result<$ret> {
+                    (self.$name)($($arg),*)
+                }
+            )*
+        }
+    };
+}
+
+mock_api! {
+    list_workouts / on_list_workouts(page: u32, page_size: u32) -> WorkoutsPage;
+    get_workout / on_get_workout(workout_id: &str) -> Workout;
+    create_workout / on_create_workout(body: &PostWorkoutBody) -> Workout;
+    update_workout / on_update_workout(workout_id: &str, body: &PostWorkoutBody) -> Workout;
+    workout_count / on_workout_count() -> WorkoutCountResponse;
+    workout_events / on_workout_events(
+        page: u32,
+        page_size: u32,
+        since: Option<Timestamp>
+    ) -> PaginatedWorkoutEvents;
+    list_routines / on_list_routines(page: u32, page_size: u32) -> RoutinesPage;
+    get_routine / on_get_routine(routine_id: &str) -> SingleRoutineResponse;
+    create_routine / on_create_routine(body: &PostRoutineBody) -> Routine;
+    update_routine / on_update_routine(routine_id: &str, body: &PutRoutineBody) -> Routine;
+    list_exercise_templates / on_list_exercise_templates(
+        page: u32,
+        page_size: u32
+    ) -> ExerciseTemplatesPage;
+    get_exercise_template / on_get_exercise_template(template_id: &str) -> ExerciseTemplate;
+    create_exercise_template / on_create_exercise_template(
+        body: &CreateExerciseBody
+    ) -> CreateExerciseResponse;
+    list_routine_folders / on_list_routine_folders(
+        page: u32,
+        page_size: u32
+    ) -> RoutineFoldersPage;
+    get_routine_folder / on_get_routine_folder(folder_id: &str) -> RoutineFolder;
+    create_routine_folder / on_create_routine_folder(
+        body: &PostRoutineFolderBody
+    ) -> RoutineFolder;
+    exercise_history / on_exercise_history(
+        template_id: &str,
+        start_date: Option<&str>,
+        end_date: Option<&str>
+    ) -> ExerciseHistoryResponse;
+    user_info / on_user_info() -> UserInfoResponse;
+}
+
+/// Build a model from JSON, as the API would send it.
+pub fn from_json<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+    serde_json::from_value(value).expect("fixture does not match the model")
+}
+
+/// Where a handler's output goes, to be read back after it finishes.
+pub struct Captured {
+    _dir: TempDir,
+    path: PathBuf,
+}
+
+impl Captured {
+    pub fn new() -> Self {
+        let dir = tempfile::tempdir().expect("failed to create a temporary directory");
+        let path = dir.path().join("out");
+        Captured { _dir: dir, path }
+    }
+
+    /// A printer writing to the capture file.
+    pub fn printer(&self) -> Printer {
+        Printer::new(None, Some(&self.path)).expect("failed to create the printer")
+    }
+
+    /// Everything `printer` wrote.
+    pub fn text(&self, printer: Printer) -> String {
+        printer.finish().expect("failed to finish the output");
+        std::fs::read_to_string(&self.path).expect("nothing was written")
+    }
+}
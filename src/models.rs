@@ -1,4 +1,187 @@
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::{Error, Result};
+
+/// Deserialize `null` as the type's default, e.g. an empty list, so a null
+/// where a list was expected does not fail the whole response.
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Deserialize an optional integer the API may send as `42`, `42.0` or
+/// `"42"`. Fractional values are rounded to the nearest integer.
+fn lenient_int<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        Int(i64),
+        Float(f64),
+        Text(String),
+    }
+
+    let float = |v: f64| {
+        if v.is_finite() {
+            Ok(v.round() as i64)
+        } else {
+            Err(serde::de::Error::custom(format!("{v} is not an integer")))
+        }
+    };
+    match Option::<Number>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Number::Int(v)) => Ok(Some(v)),
+        Some(Number::Float(v)) => float(v).map(Some),
+        Some(Number::Text(text)) => {
+            let text = text.trim();
+            if text.is_empty() {
+                return Ok(None);
+            }
+            match text.parse::<i64>() {
+                Ok(v) => Ok(Some(v)),
+                Err(_) => match text.parse::<f64>() {
+                    Ok(v) => float(v).map(Some),
+                    Err(_) => Err(serde::de::Error::custom(format!(
+                        "\"{text}\" is not an integer"
+                    ))),
+                },
+            }
+        }
+    }
+}
+
+/// Deserialize an optional count or position the API may send as `90`,
+/// `90.0` or `"90"`, as [`lenient_int`] does, rejecting negative values.
+fn lenient_u32<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+    lenient_unsigned(deserializer)
+}
+
+/// Deserialize an optional ID or position the API may send as `1`, `1.0`
+/// or `"1"`, as [`lenient_int`] does, rejecting negative values.
+fn lenient_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    lenient_unsigned(deserializer)
+}
+
+fn lenient_unsigned<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<i64>,
+{
+    lenient_int(deserializer)?
+        .map(|v| {
+            T::try_from(v)
+                .map_err(|_| serde::de::Error::custom(format!("{v} is not a non-negative integer")))
+        })
+        .transpose()
+}
+
+/// Format a timestamp the way the API writes them, e.g. `2024-08-14T12:00:00Z`.
+///
+/// ```
+/// use hevy_bridge::models::{Workout, format_timestamp};
+///
+/// let w: Workout = serde_json::from_str(r#"{"start_time": "2024-08-14 14:00:00+02:00"}"#)?;
+/// assert_eq!(format_timestamp(&w.start_time.unwrap()), "2024-08-14T12:00:00Z");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+pub fn format_timestamp(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// A timestamp sent to the API, in request bodies and query parameters.
+/// Parsing one from a string requires RFC 3339; it serializes as
+/// [`format_timestamp`] writes it.
+///
+/// ```
+/// use hevy_bridge::models::Timestamp;
+///
+/// let t = Timestamp::try_from("2024-08-14T14:00:00+02:00")?;
+/// assert_eq!(serde_json::to_value(t).unwrap(), "2024-08-14T12:00:00Z");
+/// assert!(Timestamp::try_from("2024-08-14").is_err());
+/// # Ok::<(), hevy_bridge::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Timestamp(pub DateTime<Utc>);
+
+impl TryFrom<&str> for Timestamp {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|time| Timestamp(time.with_timezone(&Utc)))
+            .map_err(|_| Error::Invalid(format!("\"{value}\" is not an RFC 3339 timestamp")))
+    }
+}
+
+impl TryFrom<String> for Timestamp {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        Timestamp::try_from(value.as_str())
+    }
+}
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(time: DateTime<Utc>) -> Self {
+        Timestamp(time)
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&format_timestamp(&self.0))
+    }
+}
+
+fn timestamp<S: Serializer>(time: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format_timestamp(time))
+}
+
+fn optional_timestamp<S: Serializer>(
+    time: &Option<DateTime<Utc>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match time {
+        Some(time) => timestamp(time, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Deserialize an optional timestamp. Besides RFC 3339 this accepts the
+/// same without an offset (taken as UTC), with a space for the `T`. A value
+/// that still does not parse is logged and dropped rather than failing the
+/// whole response.
+fn lenient_timestamp<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DateTime<Utc>>, D::Error> {
+    let Some(text) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(Some(time.with_timezone(&Utc)));
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(&text.replacen(' ', "T", 1)) {
+        return Ok(Some(time.with_timezone(&Utc)));
+    }
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(text, format) {
+            return Ok(Some(time.and_utc()));
+        }
+    }
+    tracing::warn!("ignoring malformed timestamp \"{text}\"");
+    Ok(None)
+}
 
 // ──────────────────────────────────────────────
 // Sets
@@ -7,13 +190,17 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Set {
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub index: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_u32")]
+    pub index: Option<u32>,
     #[serde(rename = "type")]
     pub set_type: Option<String>,
     pub weight_kg: Option<f64>,
-    pub reps: Option<f64>,
-    pub distance_meters: Option<f64>,
-    pub duration_seconds: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub reps: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub distance_meters: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub duration_seconds: Option<i64>,
     pub rpe: Option<f64>,
     pub custom_metric: Option<f64>,
 }
@@ -39,14 +226,18 @@ pub struct PostSet {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutineSet {
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub index: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub index: Option<i64>,
     #[serde(rename = "type")]
     pub set_type: Option<String>,
     pub weight_kg: Option<f64>,
-    pub reps: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub reps: Option<i64>,
     pub rep_range: Option<RepRange>,
-    pub distance_meters: Option<f64>,
-    pub duration_seconds: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub distance_meters: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub duration_seconds: Option<i64>,
     pub rpe: Option<f64>,
     pub custom_metric: Option<f64>,
 }
@@ -71,8 +262,10 @@ pub struct PostRoutineSet {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RepRange {
-    pub start: Option<f64>,
-    pub end: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub start: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub end: Option<i64>,
 }
 
 // ──────────────────────────────────────────────
@@ -81,15 +274,18 @@ pub struct RepRange {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Exercise {
-    pub index: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_u32")]
+    pub index: Option<u32>,
     pub title: Option<String>,
     pub notes: Option<String>,
     pub exercise_template_id: Option<String>,
-    pub supersets_id: Option<f64>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub supersets_id: Option<i64>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub sets: Vec<Set>,
 }
 
+/// An exercise of a workout being created or updated, notes included.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostExercise {
     pub exercise_template_id: String,
@@ -100,18 +296,35 @@ pub struct PostExercise {
     pub sets: Vec<PostSet>,
 }
 
+/// An exercise of a [`Routine`].
+///
+/// `rest_seconds` is read from a number, a numeric string or null:
+///
+/// ```
+/// use hevy_bridge::models::RoutineExercise;
+///
+/// for (json, rest) in [(r#""90""#, Some(90)), ("90", Some(90)), ("null", None)] {
+///     let ex: RoutineExercise = serde_json::from_str(&format!(r#"{{"rest_seconds": {json}}}"#))?;
+///     assert_eq!(ex.rest_seconds, rest);
+/// }
+/// # Ok::<(), serde_json::Error>(())
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutineExercise {
-    pub index: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub index: Option<i64>,
     pub title: Option<String>,
-    pub rest_seconds: Option<serde_json::Value>,
+    #[serde(default, deserialize_with = "lenient_u32")]
+    pub rest_seconds: Option<u32>,
     pub notes: Option<String>,
     pub exercise_template_id: Option<String>,
-    pub supersets_id: Option<f64>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "lenient_int")]
+    pub supersets_id: Option<i64>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub sets: Vec<RoutineSet>,
 }
 
+/// An exercise of a routine being created or updated, notes included.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostRoutineExercise {
     pub exercise_template_id: String,
@@ -124,6 +337,11 @@ pub struct PostRoutineExercise {
     pub sets: Vec<PostRoutineSet>,
 }
 
+/// A resource with an API-assigned string ID.
+pub trait HasId {
+    fn id(&self) -> Option<&str>;
+}
+
 // ──────────────────────────────────────────────
 // Workouts
 // ──────────────────────────────────────────────
@@ -134,21 +352,85 @@ pub struct Workout {
     pub title: Option<String>,
     pub routine_id: Option<String>,
     pub description: Option<String>,
-    pub start_time: Option<String>,
-    pub end_time: Option<String>,
-    pub updated_at: Option<String>,
-    pub created_at: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub start_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub end_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub exercises: Vec<Exercise>,
 }
 
+impl HasId for Workout {
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+}
+
+/// `Leg Day (2024-08-14T12:00:00Z)`, or just the title without a start time.
+///
+/// ```
+/// use hevy_bridge::models::Workout;
+///
+/// let w: Workout =
+///     serde_json::from_str(r#"{"title": "Leg Day", "start_time": "2024-08-14T12:00:00Z"}"#)?;
+/// assert_eq!(w.to_string(), "Leg Day (2024-08-14T12:00:00Z)");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+impl fmt::Display for Workout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.title.as_deref().unwrap_or("Untitled"))?;
+        if let Some(start) = &self.start_time {
+            write!(f, " ({})", format_timestamp(start))?;
+        }
+        Ok(())
+    }
+}
+
+impl Workout {
+    /// Total weight moved, in kg: `weight_kg * reps` summed over every
+    /// working set. Warmup, failure and drop sets are left out; a set
+    /// without a type counts as normal, and a missing weight or rep count
+    /// as zero.
+    pub fn total_volume_kg(&self) -> f64 {
+        self.exercises.iter().map(Exercise::volume_kg).sum()
+    }
+
+    /// Each exercise's volume as in [`total_volume_kg`](Self::total_volume_kg),
+    /// paired with its `exercise_template_id`, in workout order. Exercises
+    /// without a template ID are skipped.
+    pub fn per_exercise_volume(&self) -> Vec<(String, f64)> {
+        self.exercises
+            .iter()
+            .filter_map(|e| Some((e.exercise_template_id.clone()?, e.volume_kg())))
+            .collect()
+    }
+}
+
+impl Exercise {
+    fn volume_kg(&self) -> f64 {
+        self.sets
+            .iter()
+            .filter(|s| s.set_type.as_deref().unwrap_or("normal") == "normal")
+            .map(|s| s.weight_kg.unwrap_or(0.0) * s.reps.unwrap_or(0) as f64)
+            .sum()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostWorkoutInner {
     pub title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
-    pub start_time: String,
-    pub end_time: String,
+    pub start_time: Timestamp,
+    pub end_time: Timestamp,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_private: Option<bool>,
     pub exercises: Vec<PostExercise>,
@@ -159,10 +441,38 @@ pub struct PostWorkoutBody {
     pub workout: PostWorkoutInner,
 }
 
+impl PostWorkoutBody {
+    /// Check rules the API enforces that the types alone do not: a
+    /// non-empty title, and an end time not before the start time.
+    ///
+    /// A `superset_id` used by only one exercise pairs it with nothing and
+    /// is likely a mistake, but the API allows it, so it is only warned
+    /// about.
+    pub fn validate(&self) -> Result<()> {
+        let workout = &self.workout;
+        check_title("Workout", &workout.title)?;
+        if workout.end_time < workout.start_time {
+            return Err(Error::Invalid(format!(
+                "Workout end_time {} is before start_time {}",
+                workout.end_time, workout.start_time
+            )));
+        }
+        let mut supersets = BTreeMap::new();
+        for id in workout.exercises.iter().filter_map(|e| e.superset_id) {
+            *supersets.entry(id).or_insert(0) += 1;
+        }
+        for (id, _) in supersets.into_iter().filter(|&(_, count)| count == 1) {
+            tracing::warn!("superset_id {id} appears only once in exercise list");
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkoutsPage {
     pub page: i64,
     pub page_count: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub workouts: Vec<Workout>,
 }
 
@@ -175,6 +485,19 @@ pub struct WorkoutCountResponse {
 // Workout Events
 // ──────────────────────────────────────────────
 
+/// A change to a workout, told apart by its `type` field:
+///
+/// ```
+/// use hevy_bridge::models::WorkoutEvent;
+///
+/// let event: WorkoutEvent = serde_json::from_str(
+///     r#"{ "type": "deleted", "id": "b459cba5", "deleted_at": "2024-08-15T09:30:00Z" }"#,
+/// )?;
+/// if let WorkoutEvent::Deleted { id, .. } = event {
+///     println!("workout {id} was deleted");
+/// }
+/// # Ok::<(), serde_json::Error>(())
+/// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
 pub enum WorkoutEvent {
@@ -183,14 +506,18 @@ pub enum WorkoutEvent {
     #[serde(rename = "deleted")]
     Deleted {
         id: String,
-        deleted_at: Option<String>,
+        #[serde(default, deserialize_with = "lenient_timestamp")]
+        #[serde(serialize_with = "optional_timestamp")]
+        deleted_at: Option<DateTime<Utc>>,
     },
 }
 
+/// A page of workout events, updates and deletions mixed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PaginatedWorkoutEvents {
     pub page: i64,
     pub page_count: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub events: Vec<WorkoutEvent>,
 }
 
@@ -202,18 +529,51 @@ pub struct PaginatedWorkoutEvents {
 pub struct Routine {
     pub id: Option<String>,
     pub title: Option<String>,
-    pub folder_id: Option<f64>,
-    pub updated_at: Option<String>,
-    pub created_at: Option<String>,
-    #[serde(default)]
+    #[serde(default, deserialize_with = "lenient_u64")]
+    pub folder_id: Option<u64>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub exercises: Vec<RoutineExercise>,
 }
 
+impl HasId for Routine {
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+}
+
+/// `Push Day (5 exercises)`.
+///
+/// ```
+/// use hevy_bridge::models::Routine;
+///
+/// let r: Routine = serde_json::from_str(
+///     r#"{"title": "Push Day", "exercises": [{"title": "Bench Press"}, {"title": "Dips"}]}"#,
+/// )?;
+/// assert_eq!(r.to_string(), "Push Day (2 exercises)");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+impl fmt::Display for Routine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} exercises)",
+            self.title.as_deref().unwrap_or("Untitled Routine"),
+            self.exercises.len()
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostRoutineInner {
     pub title: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub folder_id: Option<f64>,
+    pub folder_id: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub notes: Option<String>,
     pub exercises: Vec<PostRoutineExercise>,
@@ -232,6 +592,10 @@ pub struct PutRoutineInner {
     pub exercises: Vec<PostRoutineExercise>,
 }
 
+/// The body of [`update_routine`]. The API replaces the routine with it,
+/// so notes left out are cleared.
+///
+/// [`update_routine`]: crate::HevyApi::update_routine
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PutRoutineBody {
     pub routine: PutRoutineInner,
@@ -241,6 +605,7 @@ pub struct PutRoutineBody {
 pub struct RoutinesPage {
     pub page: i64,
     pub page_count: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub routines: Vec<Routine>,
 }
 
@@ -264,10 +629,41 @@ pub struct ExerciseTemplate {
     pub is_custom: Option<bool>,
 }
 
+impl HasId for ExerciseTemplate {
+    fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+}
+
+/// `Bench Press (Barbell) [weight_reps/chest]`, with `?` for a missing
+/// type or muscle group.
+///
+/// ```
+/// use hevy_bridge::models::ExerciseTemplate;
+///
+/// let t: ExerciseTemplate = serde_json::from_str(
+///     r#"{"title": "Bench Press (Barbell)", "type": "weight_reps", "primary_muscle_group": "chest"}"#,
+/// )?;
+/// assert_eq!(t.to_string(), "Bench Press (Barbell) [weight_reps/chest]");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+impl fmt::Display for ExerciseTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}/{}]",
+            self.title.as_deref().unwrap_or("Untitled"),
+            self.exercise_type.as_deref().unwrap_or("?"),
+            self.primary_muscle_group.as_deref().unwrap_or("?")
+        )
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExerciseTemplatesPage {
     pub page: i64,
     pub page_count: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub exercise_templates: Vec<ExerciseTemplate>,
 }
 
@@ -297,17 +693,43 @@ pub struct CreateExerciseResponse {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutineFolder {
-    pub id: Option<f64>,
-    pub index: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_u64")]
+    pub id: Option<u64>,
+    #[serde(default, deserialize_with = "lenient_u64")]
+    pub index: Option<u64>,
     pub title: Option<String>,
-    pub updated_at: Option<String>,
-    pub created_at: Option<String>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// `Strength (#42)`, or just the title without an ID.
+///
+/// ```
+/// use hevy_bridge::models::RoutineFolder;
+///
+/// let f: RoutineFolder = serde_json::from_str(r#"{"id": 42, "title": "Strength"}"#)?;
+/// assert_eq!(f.to_string(), "Strength (#42)");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+impl fmt::Display for RoutineFolder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.title.as_deref().unwrap_or("Untitled Folder"))?;
+        if let Some(id) = self.id {
+            write!(f, " (#{id})")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutineFoldersPage {
     pub page: i64,
     pub page_count: i64,
+    #[serde(default, deserialize_with = "null_as_default")]
     pub routine_folders: Vec<RoutineFolder>,
 }
 
@@ -325,16 +747,35 @@ pub struct PostRoutineFolderBody {
 // Exercise History
 // ──────────────────────────────────────────────
 
+/// One set from an exercise's history, with the workout it belongs to.
+/// `workout_updated_at` tells a local copy whether the entry is stale.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExerciseHistoryEntry {
     pub workout_id: Option<String>,
     pub workout_title: Option<String>,
-    pub workout_start_time: Option<String>,
-    pub workout_end_time: Option<String>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub workout_start_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub workout_end_time: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub workout_created_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "lenient_timestamp")]
+    #[serde(serialize_with = "optional_timestamp")]
+    pub workout_updated_at: Option<DateTime<Utc>>,
     pub exercise_template_id: Option<String>,
+    pub exercise_notes: Option<String>,
+    /// Position of the exercise within its workout, from 0.
+    #[serde(default, deserialize_with = "lenient_u32")]
+    pub exercise_index: Option<u32>,
     pub weight_kg: Option<f64>,
+    #[serde(default, deserialize_with = "lenient_int")]
     pub reps: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_int")]
     pub distance_meters: Option<i64>,
+    #[serde(default, deserialize_with = "lenient_int")]
     pub duration_seconds: Option<i64>,
     pub rpe: Option<f64>,
     pub custom_metric: Option<f64>,
@@ -343,6 +784,7 @@ pub struct ExerciseHistoryEntry {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExerciseHistoryResponse {
+    #[serde(default, deserialize_with = "null_as_default")]
     pub exercise_history: Vec<ExerciseHistoryEntry>,
 }
 
@@ -371,3 +813,1603 @@ pub struct WebhookPayload {
     #[serde(alias = "workoutId")]
     pub workout_id: String,
 }
+
+// ──────────────────────────────────────────────
+// Builders
+// ──────────────────────────────────────────────
+
+/// Set types accepted by the API.
+pub const SET_TYPES: &[&str] = &["normal", "warmup", "failure", "dropset"];
+
+/// RPE values accepted by the API.
+pub const RPE_VALUES: &[f64] = &[6.0, 7.0, 7.5, 8.0, 8.5, 9.0, 9.5, 10.0];
+
+/// `exercise_type` values accepted for custom exercises.
+pub const EXERCISE_TYPES: &[&str] = &[
+    "weight_reps",
+    "reps_only",
+    "bodyweight_reps",
+    "bodyweight_assisted_reps",
+    "duration",
+    "weight_duration",
+    "distance_duration",
+    "short_distance_weight",
+];
+
+/// `equipment_category` values accepted for custom exercises.
+pub const EQUIPMENT_CATEGORIES: &[&str] = &[
+    "none",
+    "barbell",
+    "dumbbell",
+    "kettlebell",
+    "machine",
+    "plate",
+    "resistance_band",
+    "suspension",
+    "other",
+];
+
+/// Muscle groups accepted for custom exercises.
+pub const MUSCLE_GROUPS: &[&str] = &[
+    "abdominals",
+    "shoulders",
+    "biceps",
+    "triceps",
+    "forearms",
+    "quadriceps",
+    "hamstrings",
+    "calves",
+    "glutes",
+    "abductors",
+    "adductors",
+    "lats",
+    "upper_back",
+    "traps",
+    "lower_back",
+    "chest",
+    "cardio",
+    "neck",
+    "full_body",
+    "other",
+];
+
+fn check_one_of(what: &str, value: &str, allowed: &[&str]) -> Result<()> {
+    if allowed.contains(&value) {
+        Ok(())
+    } else {
+        Err(Error::Invalid(format!(
+            "Unknown {what} \"{value}\"; expected one of: {}",
+            allowed.join(", ")
+        )))
+    }
+}
+
+fn check_rpe(rpe: f64) -> Result<()> {
+    if RPE_VALUES.contains(&rpe) {
+        Ok(())
+    } else {
+        Err(Error::Invalid(format!(
+            "Invalid RPE {rpe}; expected one of: 6, 7, 7.5, 8, 8.5, 9, 9.5, 10"
+        )))
+    }
+}
+
+fn check_title(what: &str, title: &str) -> Result<()> {
+    if title.trim().is_empty() {
+        return Err(Error::Invalid(format!("{what} title must not be empty")));
+    }
+    Ok(())
+}
+
+fn check_template_id(id: &str) -> Result<()> {
+    if id.trim().is_empty() {
+        return Err(Error::Invalid(
+            "exercise_template_id must not be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Prefix a validation error with where it happened, e.g. `Exercise 2`.
+fn in_context<T>(result: Result<T>, context: impl FnOnce() -> String) -> Result<T> {
+    result.map_err(|e| match e {
+        Error::Invalid(message) => Error::Invalid(format!("{}: {message}", context())),
+        other => other,
+    })
+}
+
+/// Builds one set of a workout exercise.
+#[derive(Debug, Clone)]
+pub struct SetBuilder {
+    set: PostSet,
+}
+
+impl SetBuilder {
+    /// A set of `set_type`, one of [`SET_TYPES`].
+    pub fn new(set_type: impl Into<String>) -> Self {
+        SetBuilder {
+            set: PostSet {
+                set_type: set_type.into(),
+                weight_kg: None,
+                reps: None,
+                distance_meters: None,
+                duration_seconds: None,
+                custom_metric: None,
+                rpe: None,
+            },
+        }
+    }
+
+    pub fn normal() -> Self {
+        Self::new("normal")
+    }
+
+    pub fn warmup() -> Self {
+        Self::new("warmup")
+    }
+
+    pub fn failure() -> Self {
+        Self::new("failure")
+    }
+
+    pub fn dropset() -> Self {
+        Self::new("dropset")
+    }
+
+    pub fn weight_kg(mut self, weight_kg: f64) -> Self {
+        self.set.weight_kg = Some(weight_kg);
+        self
+    }
+
+    pub fn reps(mut self, reps: i64) -> Self {
+        self.set.reps = Some(reps);
+        self
+    }
+
+    pub fn distance_meters(mut self, meters: i64) -> Self {
+        self.set.distance_meters = Some(meters);
+        self
+    }
+
+    pub fn duration_seconds(mut self, seconds: i64) -> Self {
+        self.set.duration_seconds = Some(seconds);
+        self
+    }
+
+    pub fn custom_metric(mut self, value: f64) -> Self {
+        self.set.custom_metric = Some(value);
+        self
+    }
+
+    /// Rate of perceived exertion, one of [`RPE_VALUES`].
+    pub fn rpe(mut self, rpe: f64) -> Self {
+        self.set.rpe = Some(rpe);
+        self
+    }
+
+    /// Check the set type and RPE and return the set.
+    pub fn build(self) -> Result<PostSet> {
+        check_one_of("set type", &self.set.set_type, SET_TYPES)?;
+        if let Some(rpe) = self.set.rpe {
+            check_rpe(rpe)?;
+        }
+        Ok(self.set)
+    }
+}
+
+/// Builds one exercise of a workout from an exercise template ID.
+#[derive(Debug, Clone)]
+pub struct ExerciseBuilder {
+    exercise_template_id: String,
+    superset_id: Option<i64>,
+    notes: Option<String>,
+    sets: Vec<SetBuilder>,
+}
+
+impl ExerciseBuilder {
+    pub fn new(exercise_template_id: impl Into<String>) -> Self {
+        ExerciseBuilder {
+            exercise_template_id: exercise_template_id.into(),
+            superset_id: None,
+            notes: None,
+            sets: Vec::new(),
+        }
+    }
+
+    pub fn superset_id(mut self, superset_id: i64) -> Self {
+        self.superset_id = Some(superset_id);
+        self
+    }
+
+    pub fn notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = Some(notes.into());
+        self
+    }
+
+    /// Append a set; sets are performed in the order added.
+    pub fn set(mut self, set: SetBuilder) -> Self {
+        self.sets.push(set);
+        self
+    }
+
+    pub fn build(self) -> Result<PostExercise> {
+        check_template_id(&self.exercise_template_id)?;
+        let sets = self
+            .sets
+            .into_iter()
+            .enumerate()
+            .map(|(i, set)| in_context(set.build(), || format!("Set {}", i + 1)))
+            .collect::<Result<_>>()?;
+        Ok(PostExercise {
+            exercise_template_id: self.exercise_template_id,
+            superset_id: self.superset_id,
+            notes: self.notes,
+            sets,
+        })
+    }
+}
+
+/// Builds a [`PostWorkoutBody`] for [`create_workout`] and
+/// [`update_workout`], checking it against the API's rules first.
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use hevy_bridge::models::{ExerciseBuilder, SetBuilder, WorkoutBuilder};
+///
+/// let body = WorkoutBuilder::new("Leg Day")
+///     .start(Utc.with_ymd_and_hms(2024, 8, 14, 12, 0, 0).unwrap())
+///     .end(Utc.with_ymd_and_hms(2024, 8, 14, 12, 30, 0).unwrap())
+///     .exercise(ExerciseBuilder::new("D04AC939").set(SetBuilder::normal().weight_kg(100.0).reps(10)))
+///     .build()?;
+/// assert_eq!(body.workout.exercises[0].sets[0].reps, Some(10));
+/// let json = serde_json::to_value(&body).unwrap();
+/// assert_eq!(json["workout"]["start_time"], "2024-08-14T12:00:00Z");
+/// # Ok::<(), hevy_bridge::Error>(())
+/// ```
+///
+/// [`create_workout`]: crate::HevyApi::create_workout
+/// [`update_workout`]: crate::HevyApi::update_workout
+#[derive(Debug, Clone)]
+pub struct WorkoutBuilder {
+    title: String,
+    description: Option<String>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    is_private: Option<bool>,
+    exercises: Vec<ExerciseBuilder>,
+}
+
+impl WorkoutBuilder {
+    pub fn new(title: impl Into<String>) -> Self {
+        WorkoutBuilder {
+            title: title.into(),
+            description: None,
+            start_time: None,
+            end_time: None,
+            is_private: None,
+            exercises: Vec::new(),
+        }
+    }
+
+    /// Replace the title given to [`new`](Self::new).
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Required.
+    pub fn start(mut self, start_time: DateTime<Utc>) -> Self {
+        self.start_time = Some(start_time);
+        self
+    }
+
+    /// Not before the start. Required.
+    pub fn end(mut self, end_time: DateTime<Utc>) -> Self {
+        self.end_time = Some(end_time);
+        self
+    }
+
+    pub fn is_private(mut self, is_private: bool) -> Self {
+        self.is_private = Some(is_private);
+        self
+    }
+
+    /// Append an exercise; exercises appear in the order added.
+    pub fn exercise(mut self, exercise: ExerciseBuilder) -> Self {
+        self.exercises.push(exercise);
+        self
+    }
+
+    pub fn build(self) -> Result<PostWorkoutBody> {
+        let start_time = self
+            .start_time
+            .ok_or_else(|| Error::Invalid("Workout is missing start_time".to_string()))?;
+        let end_time = self
+            .end_time
+            .ok_or_else(|| Error::Invalid("Workout is missing end_time".to_string()))?;
+        let exercises = self
+            .exercises
+            .into_iter()
+            .enumerate()
+            .map(|(i, ex)| in_context(ex.build(), || format!("Exercise {}", i + 1)))
+            .collect::<Result<_>>()?;
+        let body = PostWorkoutBody {
+            workout: PostWorkoutInner {
+                title: self.title,
+                description: self.description,
+                start_time: start_time.into(),
+                end_time: end_time.into(),
+                is_private: self.is_private,
+                exercises,
+            },
+        };
+        body.validate()?;
+        Ok(body)
+    }
+}
+
+/// Builds one set of a routine exercise.
+#[derive(Debug, Clone)]
+pub struct RoutineSetBuilder {
+    set: PostRoutineSet,
+}
+
+impl RoutineSetBuilder {
+    /// A set of `set_type`, one of [`SET_TYPES`].
+    pub fn new(set_type: impl Into<String>) -> Self {
+        RoutineSetBuilder {
+            set: PostRoutineSet {
+                set_type: set_type.into(),
+                weight_kg: None,
+                reps: None,
+                distance_meters: None,
+                duration_seconds: None,
+                custom_metric: None,
+                rep_range: None,
+            },
+        }
+    }
+
+    pub fn normal() -> Self {
+        Self::new("normal")
+    }
+
+    pub fn warmup() -> Self {
+        Self::new("warmup")
+    }
+
+    pub fn failure() -> Self {
+        Self::new("failure")
+    }
+
+    pub fn dropset() -> Self {
+        Self::new("dropset")
+    }
+
+    pub fn weight_kg(mut self, weight_kg: f64) -> Self {
+        self.set.weight_kg = Some(weight_kg);
+        self
+    }
+
+    pub fn reps(mut self, reps: i64) -> Self {
+        self.set.reps = Some(reps);
+        self
+    }
+
+    /// Target rep range, e.g. 8 to 12 reps as `rep_range(8, 12)`.
+    pub fn rep_range(mut self, start: i64, end: i64) -> Self {
+        self.set.rep_range = Some(RepRange {
+            start: Some(start),
+            end: Some(end),
+        });
+        self
+    }
+
+    /// Target of at least `start` reps with no upper bound, e.g. an
+    /// as-many-reps-as-possible set of 5+.
+    pub fn min_reps(mut self, start: i64) -> Self {
+        self.set.rep_range = Some(RepRange {
+            start: Some(start),
+            end: None,
+        });
+        self
+    }
+
+    pub fn distance_meters(mut self, meters: i64) -> Self {
+        self.set.distance_meters = Some(meters);
+        self
+    }
+
+    pub fn duration_seconds(mut self, seconds: i64) -> Self {
+        self.set.duration_seconds = Some(seconds);
+        self
+    }
+
+    pub fn custom_metric(mut self, value: f64) -> Self {
+        self.set.custom_metric = Some(value);
+        self
+    }
+
+    /// Check the set type and rep range and return the set.
+    pub fn build(self) -> Result<PostRoutineSet> {
+        check_one_of("set type", &self.set.set_type, SET_TYPES)?;
+        if let Some(RepRange {
+            start: Some(start),
+            end: Some(end),
+        }) = self.set.rep_range
+            && start > end
+        {
+            return Err(Error::Invalid(format!(
+                "Rep range start {start} is above its end {end}"
+            )));
+        }
+        Ok(self.set)
+    }
+}
+
+/// Builds one exercise of a routine from an exercise template ID.
+#[derive(Debug, Clone)]
+pub struct RoutineExerciseBuilder {
+    exercise_template_id: String,
+    superset_id: Option<i64>,
+    rest_seconds: Option<i64>,
+    notes: Option<String>,
+    sets: Vec<RoutineSetBuilder>,
+}
+
+impl RoutineExerciseBuilder {
+    pub fn new(exercise_template_id: impl Into<String>) -> Self {
+        RoutineExerciseBuilder {
+            exercise_template_id: exercise_template_id.into(),
+            superset_id: None,
+            rest_seconds: None,
+            notes: None,
+            sets: Vec::new(),
+        }
+    }
+
+    pub fn superset_id(mut self, superset_id: i64) -> Self {
+        self.superset_id = Some(superset_id);
+        self
+    }
+
+    pub fn rest_seconds(mut self, seconds: i64) -> Self {
+        self.rest_seconds = Some(seconds);
+        self
+    }
+
+    pub fn notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = Some(notes.into());
+        self
+    }
+
+    /// Append a set; sets are performed in the order added.
+    pub fn set(mut self, set: RoutineSetBuilder) -> Self {
+        self.sets.push(set);
+        self
+    }
+
+    pub fn build(self) -> Result<PostRoutineExercise> {
+        check_template_id(&self.exercise_template_id)?;
+        if let Some(seconds) = self.rest_seconds
+            && seconds < 0
+        {
+            return Err(Error::Invalid(format!(
+                "rest_seconds must not be negative, got {seconds}"
+            )));
+        }
+        let sets = self
+            .sets
+            .into_iter()
+            .enumerate()
+            .map(|(i, set)| in_context(set.build(), || format!("Set {}", i + 1)))
+            .collect::<Result<_>>()?;
+        Ok(PostRoutineExercise {
+            exercise_template_id: self.exercise_template_id,
+            superset_id: self.superset_id,
+            rest_seconds: self.rest_seconds,
+            notes: self.notes,
+            sets,
+        })
+    }
+}
+
+/// Builds a [`PostRoutineBody`] for [`create_routine`], or with
+/// [`build_update`](Self::build_update) a [`PutRoutineBody`] for
+/// [`update_routine`].
+///
+/// [`create_routine`]: crate::HevyApi::create_routine
+/// [`update_routine`]: crate::HevyApi::update_routine
+#[derive(Debug, Clone)]
+pub struct RoutineBuilder {
+    title: String,
+    folder_id: Option<u64>,
+    notes: Option<String>,
+    exercises: Vec<RoutineExerciseBuilder>,
+}
+
+impl RoutineBuilder {
+    pub fn new(title: impl Into<String>) -> Self {
+        RoutineBuilder {
+            title: title.into(),
+            folder_id: None,
+            notes: None,
+            exercises: Vec::new(),
+        }
+    }
+
+    /// Folder to create the routine in. Ignored by `build_update`, as the
+    /// API cannot move routines between folders.
+    pub fn folder_id(mut self, folder_id: u64) -> Self {
+        self.folder_id = Some(folder_id);
+        self
+    }
+
+    pub fn notes(mut self, notes: impl Into<String>) -> Self {
+        self.notes = Some(notes.into());
+        self
+    }
+
+    /// Append an exercise; exercises appear in the order added.
+    pub fn exercise(mut self, exercise: RoutineExerciseBuilder) -> Self {
+        self.exercises.push(exercise);
+        self
+    }
+
+    pub fn build(self) -> Result<PostRoutineBody> {
+        let folder_id = self.folder_id;
+        let (title, notes, exercises) = self.validate()?;
+        Ok(PostRoutineBody {
+            routine: PostRoutineInner {
+                title,
+                folder_id,
+                notes,
+                exercises,
+            },
+        })
+    }
+
+    pub fn build_update(self) -> Result<PutRoutineBody> {
+        let (title, notes, exercises) = self.validate()?;
+        Ok(PutRoutineBody {
+            routine: PutRoutineInner {
+                title,
+                notes,
+                exercises,
+            },
+        })
+    }
+
+    fn validate(self) -> Result<(String, Option<String>, Vec<PostRoutineExercise>)> {
+        check_title("Routine", &self.title)?;
+        let exercises = self
+            .exercises
+            .into_iter()
+            .enumerate()
+            .map(|(i, ex)| in_context(ex.build(), || format!("Exercise {}", i + 1)))
+            .collect::<Result<_>>()?;
+        Ok((self.title, self.notes, exercises))
+    }
+}
+
+/// Builds a [`CreateExerciseBody`] for a custom exercise template, checking
+/// the type, equipment and muscle groups against the values the API knows.
+#[derive(Debug, Clone)]
+pub struct CustomExerciseBuilder {
+    exercise: CreateExerciseInner,
+}
+
+impl CustomExerciseBuilder {
+    /// `exercise_type` is one of [`EXERCISE_TYPES`], `equipment_category`
+    /// one of [`EQUIPMENT_CATEGORIES`] and `muscle_group` one of
+    /// [`MUSCLE_GROUPS`].
+    pub fn new(
+        title: impl Into<String>,
+        exercise_type: impl Into<String>,
+        equipment_category: impl Into<String>,
+        muscle_group: impl Into<String>,
+    ) -> Self {
+        CustomExerciseBuilder {
+            exercise: CreateExerciseInner {
+                title: title.into(),
+                exercise_type: exercise_type.into(),
+                equipment_category: equipment_category.into(),
+                muscle_group: muscle_group.into(),
+                other_muscles: None,
+            },
+        }
+    }
+
+    /// Add a secondary muscle group.
+    pub fn other_muscle(mut self, muscle_group: impl Into<String>) -> Self {
+        self.exercise
+            .other_muscles
+            .get_or_insert_with(Vec::new)
+            .push(muscle_group.into());
+        self
+    }
+
+    pub fn build(self) -> Result<CreateExerciseBody> {
+        let e = &self.exercise;
+        check_title("Exercise", &e.title)?;
+        check_one_of("exercise type", &e.exercise_type, EXERCISE_TYPES)?;
+        check_one_of(
+            "equipment category",
+            &e.equipment_category,
+            EQUIPMENT_CATEGORIES,
+        )?;
+        check_one_of("muscle group", &e.muscle_group, MUSCLE_GROUPS)?;
+        for muscle in e.other_muscles.iter().flatten() {
+            check_one_of("muscle group", muscle, MUSCLE_GROUPS)?;
+        }
+        Ok(CreateExerciseBody {
+            exercise: self.exercise,
+        })
+    }
+}
+
+// ──────────────────────────────────────────────
+// Conversions
+// ──────────────────────────────────────────────
+
+/// A workout as fetched, ready to create again. A missing title becomes
+/// "Untitled" and a missing set type "normal".
+impl TryFrom<&Workout> for PostWorkoutInner {
+    type Error = Error;
+
+    fn try_from(workout: &Workout) -> Result<Self> {
+        let mut builder = WorkoutBuilder::new(workout.title.as_deref().unwrap_or("Untitled"));
+        if let Some(description) = &workout.description {
+            builder = builder.description(description);
+        }
+        if let Some(start) = workout.start_time {
+            builder = builder.start(start);
+        }
+        if let Some(end) = workout.end_time {
+            builder = builder.end(end);
+        }
+        for (i, ex) in workout.exercises.iter().enumerate() {
+            let template_id = template_id(i, ex.title.as_deref(), &ex.exercise_template_id)?;
+            let mut exercise = ExerciseBuilder::new(template_id);
+            if let Some(superset_id) = ex.supersets_id {
+                exercise = exercise.superset_id(superset_id);
+            }
+            if let Some(notes) = &ex.notes {
+                exercise = exercise.notes(notes);
+            }
+            for s in &ex.sets {
+                exercise = exercise.set(set_builder(s));
+            }
+            builder = builder.exercise(exercise);
+        }
+        Ok(builder.build()?.workout)
+    }
+}
+
+/// A routine as fetched, ready to create again in the same folder.
+impl TryFrom<&Routine> for PostRoutineInner {
+    type Error = Error;
+
+    fn try_from(routine: &Routine) -> Result<Self> {
+        let mut builder = routine_builder(routine)?;
+        if let Some(folder_id) = routine.folder_id {
+            builder = builder.folder_id(folder_id);
+        }
+        Ok(builder.build()?.routine)
+    }
+}
+
+/// A routine as fetched, ready to send back as an update.
+impl TryFrom<&Routine> for PutRoutineInner {
+    type Error = Error;
+
+    fn try_from(routine: &Routine) -> Result<Self> {
+        Ok(routine_builder(routine)?.build_update()?.routine)
+    }
+}
+
+/// A workout following a routine: its title and exercises, with each set
+/// pre-filled with the routine's target weight, reps (the low end of the
+/// rep range when there is no fixed target), distance and duration. Start
+/// and end times are left for the caller to set.
+impl TryFrom<&Routine> for WorkoutBuilder {
+    type Error = Error;
+
+    fn try_from(routine: &Routine) -> Result<Self> {
+        let mut builder = WorkoutBuilder::new(routine.title.as_deref().unwrap_or("Untitled"));
+        for (i, ex) in routine.exercises.iter().enumerate() {
+            let template_id = template_id(i, ex.title.as_deref(), &ex.exercise_template_id)?;
+            let mut exercise = ExerciseBuilder::new(template_id);
+            if let Some(superset_id) = ex.supersets_id {
+                exercise = exercise.superset_id(superset_id);
+            }
+            if let Some(notes) = &ex.notes {
+                exercise = exercise.notes(notes);
+            }
+            for s in &ex.sets {
+                let mut set = SetBuilder::new(s.set_type.as_deref().unwrap_or("normal"));
+                if let Some(v) = s.weight_kg {
+                    set = set.weight_kg(v);
+                }
+                if let Some(v) = s.reps.or(s.rep_range.as_ref().and_then(|r| r.start)) {
+                    set = set.reps(v);
+                }
+                if let Some(v) = s.distance_meters {
+                    set = set.distance_meters(v);
+                }
+                if let Some(v) = s.duration_seconds {
+                    set = set.duration_seconds(v);
+                }
+                if let Some(v) = s.custom_metric {
+                    set = set.custom_metric(v);
+                }
+                exercise = exercise.set(set);
+            }
+            builder = builder.exercise(exercise);
+        }
+        Ok(builder)
+    }
+}
+
+fn set_builder(s: &Set) -> SetBuilder {
+    let mut set = SetBuilder::new(s.set_type.as_deref().unwrap_or("normal"));
+    if let Some(v) = s.weight_kg {
+        set = set.weight_kg(v);
+    }
+    if let Some(v) = s.reps {
+        set = set.reps(v);
+    }
+    if let Some(v) = s.distance_meters {
+        set = set.distance_meters(v);
+    }
+    if let Some(v) = s.duration_seconds {
+        set = set.duration_seconds(v);
+    }
+    if let Some(v) = s.custom_metric {
+        set = set.custom_metric(v);
+    }
+    if let Some(v) = s.rpe {
+        set = set.rpe(v);
+    }
+    set
+}
+
+fn routine_builder(routine: &Routine) -> Result<RoutineBuilder> {
+    let mut builder = RoutineBuilder::new(routine.title.as_deref().unwrap_or("Untitled Routine"));
+    for (i, ex) in routine.exercises.iter().enumerate() {
+        let template_id = template_id(i, ex.title.as_deref(), &ex.exercise_template_id)?;
+        let mut exercise = RoutineExerciseBuilder::new(template_id);
+        if let Some(superset_id) = ex.supersets_id {
+            exercise = exercise.superset_id(superset_id);
+        }
+        if let Some(rest) = ex.rest_seconds {
+            exercise = exercise.rest_seconds(rest.into());
+        }
+        if let Some(notes) = &ex.notes {
+            exercise = exercise.notes(notes);
+        }
+        for s in &ex.sets {
+            let mut set = RoutineSetBuilder::new(s.set_type.as_deref().unwrap_or("normal"));
+            if let Some(v) = s.weight_kg {
+                set = set.weight_kg(v);
+            }
+            if let Some(v) = s.reps {
+                set = set.reps(v);
+            }
+            match s.rep_range.as_ref().map(|r| (r.start, r.end)) {
+                Some((Some(start), Some(end))) => set = set.rep_range(start, end),
+                Some((Some(start), None)) => set = set.min_reps(start),
+                _ => {}
+            }
+            if let Some(v) = s.distance_meters {
+                set = set.distance_meters(v);
+            }
+            if let Some(v) = s.duration_seconds {
+                set = set.duration_seconds(v);
+            }
+            if let Some(v) = s.custom_metric {
+                set = set.custom_metric(v);
+            }
+            exercise = exercise.set(set);
+        }
+        builder = builder.exercise(exercise);
+    }
+    Ok(builder)
+}
+
+/// The template ID of the `index`th exercise, which a write body requires.
+fn template_id<'a>(index: usize, title: Option<&str>, id: &'a Option<String>) -> Result<&'a str> {
+    id.as_deref().ok_or_else(|| {
+        Error::Invalid(format!(
+            "Exercise {} ({}) is missing exercise_template_id",
+            index + 1,
+            title.unwrap_or("untitled")
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 8, 14, hour, 0, 0).unwrap()
+    }
+
+    fn workout() -> WorkoutBuilder {
+        WorkoutBuilder::new("Leg Day").start(at(12)).end(at(13))
+    }
+
+    /// The message of an [`Error::Invalid`], failing the test on success or
+    /// any other error.
+    fn invalid<T: std::fmt::Debug>(result: Result<T>) -> String {
+        match result {
+            Err(Error::Invalid(message)) => message,
+            other => panic!("expected an invalid-input error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn workout_needs_start_and_end() {
+        let no_start = WorkoutBuilder::new("Leg Day").end(at(13)).build();
+        assert_eq!(invalid(no_start), "Workout is missing start_time");
+        let no_end = WorkoutBuilder::new("Leg Day").start(at(12)).build();
+        assert_eq!(invalid(no_end), "Workout is missing end_time");
+    }
+
+    #[test]
+    fn workout_needs_a_title_and_an_end_after_the_start() {
+        assert_eq!(
+            invalid(workout().title("  ").build()),
+            "Workout title must not be empty"
+        );
+        let backwards = WorkoutBuilder::new("Leg Day")
+            .start(at(13))
+            .end(at(12))
+            .build();
+        assert!(invalid(backwards).contains("is before start_time"));
+        // An empty workout is allowed; the API accepts it too.
+        assert!(workout().build().unwrap().workout.exercises.is_empty());
+    }
+
+    #[test]
+    fn exercise_needs_a_template_id() {
+        let result = workout().exercise(ExerciseBuilder::new("")).build();
+        assert_eq!(
+            invalid(result),
+            "Exercise 1: exercise_template_id must not be empty"
+        );
+    }
+
+    #[test]
+    fn set_type_must_be_known() {
+        for set_type in SET_TYPES {
+            assert!(SetBuilder::new(*set_type).build().is_ok(), "{set_type}");
+        }
+        let message = invalid(SetBuilder::new("amrap").build());
+        assert!(
+            message.starts_with("Unknown set type \"amrap\""),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn rpe_must_be_one_the_api_accepts() {
+        for rpe in RPE_VALUES {
+            assert!(SetBuilder::normal().rpe(*rpe).build().is_ok(), "{rpe}");
+        }
+        for rpe in [5.0, 6.5, 10.5, 8.25] {
+            let message = invalid(SetBuilder::normal().rpe(rpe).build());
+            assert!(
+                message.starts_with(&format!("Invalid RPE {rpe};")),
+                "{message}"
+            );
+        }
+        assert_eq!(SetBuilder::normal().build().unwrap().rpe, None);
+    }
+
+    #[test]
+    fn errors_name_the_exercise_and_set() {
+        let result = workout()
+            .exercise(ExerciseBuilder::new("D04AC939").set(SetBuilder::normal()))
+            .exercise(
+                ExerciseBuilder::new("79D0BB3A")
+                    .set(SetBuilder::normal())
+                    .set(SetBuilder::warmup().rpe(11.0)),
+            )
+            .build();
+        assert!(invalid(result).starts_with("Exercise 2: Set 2: Invalid RPE 11;"));
+    }
+
+    #[test]
+    fn workout_keeps_exercises_and_sets_in_order() {
+        let body = workout()
+            .is_private(true)
+            .exercise(
+                ExerciseBuilder::new("D04AC939")
+                    .superset_id(1)
+                    .set(SetBuilder::warmup().weight_kg(60.0).reps(5))
+                    .set(SetBuilder::normal().weight_kg(100.0).reps(8).rpe(8.5)),
+            )
+            .exercise(ExerciseBuilder::new("79D0BB3A").superset_id(1))
+            .build()
+            .unwrap();
+        let w = &body.workout;
+        assert_eq!(w.is_private, Some(true));
+        let ids: Vec<_> = w
+            .exercises
+            .iter()
+            .map(|e| e.exercise_template_id.as_str())
+            .collect();
+        assert_eq!(ids, ["D04AC939", "79D0BB3A"]);
+        let sets = &w.exercises[0].sets;
+        assert_eq!(sets[0].set_type, "warmup");
+        assert_eq!(
+            (sets[1].weight_kg, sets[1].reps, sets[1].rpe),
+            (Some(100.0), Some(8), Some(8.5))
+        );
+    }
+
+    #[test]
+    fn workout_exercise_notes_reach_the_body() {
+        let body = workout()
+            .exercise(
+                ExerciseBuilder::new("D04AC939")
+                    .notes("Felt good")
+                    .set(SetBuilder::normal().weight_kg(100.0).reps(10)),
+            )
+            .exercise(ExerciseBuilder::new("79D0BB3A").set(SetBuilder::normal().reps(10)))
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&body).unwrap();
+        assert_eq!(json["workout"]["exercises"][0]["notes"], "Felt good");
+        assert!(json["workout"]["exercises"][1].get("notes").is_none());
+    }
+
+    #[test]
+    fn routine_needs_a_title_and_valid_sets() {
+        assert_eq!(
+            invalid(RoutineBuilder::new("").build()),
+            "Routine title must not be empty"
+        );
+        assert_eq!(
+            invalid(RoutineBuilder::new(" ").build_update()),
+            "Routine title must not be empty"
+        );
+        let bad_type = RoutineBuilder::new("Push")
+            .exercise(RoutineExerciseBuilder::new("D04AC939").set(RoutineSetBuilder::new("rest")))
+            .build();
+        assert!(invalid(bad_type).starts_with("Exercise 1: Set 1: Unknown set type \"rest\""));
+        let no_template = RoutineBuilder::new("Push")
+            .exercise(RoutineExerciseBuilder::new(" "))
+            .build();
+        assert_eq!(
+            invalid(no_template),
+            "Exercise 1: exercise_template_id must not be empty"
+        );
+    }
+
+    #[test]
+    fn routine_rejects_negative_rest_and_reversed_rep_ranges() {
+        let rest = RoutineExerciseBuilder::new("D04AC939")
+            .rest_seconds(-30)
+            .build();
+        assert_eq!(invalid(rest), "rest_seconds must not be negative, got -30");
+        let range = RoutineSetBuilder::normal().rep_range(12, 8).build();
+        assert_eq!(invalid(range), "Rep range start 12 is above its end 8");
+        let open = RoutineSetBuilder::normal().min_reps(5).build().unwrap();
+        let range = open.rep_range.unwrap();
+        assert_eq!((range.start, range.end), (Some(5), None));
+    }
+
+    #[test]
+    fn routine_update_leaves_out_the_folder() {
+        let builder = RoutineBuilder::new("Push").folder_id(7).exercise(
+            RoutineExerciseBuilder::new("D04AC939")
+                .rest_seconds(90)
+                .set(RoutineSetBuilder::normal().weight_kg(80.0).rep_range(8, 12)),
+        );
+        let created = builder.clone().build().unwrap();
+        assert_eq!(created.routine.folder_id, Some(7));
+        let updated = serde_json::to_value(builder.build_update().unwrap()).unwrap();
+        assert!(updated["routine"].get("folder_id").is_none());
+        assert_eq!(updated["routine"]["exercises"][0]["rest_seconds"], 90);
+    }
+
+    #[test]
+    fn routine_exercise_carries_notes() {
+        let exercise = RoutineExerciseBuilder::new("D04AC939")
+            .notes("Slow and controlled")
+            .set(RoutineSetBuilder::normal().reps(10))
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&exercise).unwrap();
+        assert_eq!(json["notes"], "Slow and controlled");
+        let without = RoutineExerciseBuilder::new("D04AC939")
+            .set(RoutineSetBuilder::normal().reps(10))
+            .build()
+            .unwrap();
+        assert!(
+            serde_json::to_value(&without)
+                .unwrap()
+                .get("notes")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn routine_update_carries_notes() {
+        let with_notes = RoutineBuilder::new("Push Day")
+            .notes("Focus on form")
+            .build_update()
+            .unwrap();
+        let json = serde_json::to_value(&with_notes).unwrap();
+        assert_eq!(json["routine"]["notes"], "Focus on form");
+        let without = RoutineBuilder::new("Push Day").build_update().unwrap();
+        let json = serde_json::to_value(&without).unwrap();
+        assert!(json["routine"].get("notes").is_none());
+    }
+
+    #[test]
+    fn custom_exercise_checks_each_category() {
+        let ok = CustomExerciseBuilder::new("Sled Push", "weight_reps", "machine", "quadriceps")
+            .other_muscle("glutes")
+            .build()
+            .unwrap();
+        assert_eq!(ok.exercise.other_muscles, Some(vec!["glutes".to_string()]));
+
+        let cases = [
+            (
+                ("", "weight_reps", "machine", "quadriceps", "glutes"),
+                "Exercise title must not be empty",
+            ),
+            (
+                ("Sled", "laps", "machine", "quadriceps", "glutes"),
+                "Unknown exercise type \"laps\"",
+            ),
+            (
+                ("Sled", "weight_reps", "sled", "quadriceps", "glutes"),
+                "Unknown equipment category \"sled\"",
+            ),
+            (
+                ("Sled", "weight_reps", "machine", "quads", "glutes"),
+                "Unknown muscle group \"quads\"",
+            ),
+            (
+                ("Sled", "weight_reps", "machine", "quadriceps", "toes"),
+                "Unknown muscle group \"toes\"",
+            ),
+        ];
+        for ((title, kind, equipment, muscle, other), expected) in cases {
+            let result = CustomExerciseBuilder::new(title, kind, equipment, muscle)
+                .other_muscle(other)
+                .build();
+            let message = invalid(result);
+            assert!(message.starts_with(expected), "{message}");
+        }
+    }
+
+    fn fetched<T: serde::de::DeserializeOwned>(value: serde_json::Value) -> T {
+        serde_json::from_value(value).expect("fixture does not match the model")
+    }
+
+    /// Serialize `body`, read it back and serialize it again, checking
+    /// nothing changed on the way, and return the JSON.
+    fn round_trip<T: Serialize + serde::de::DeserializeOwned>(body: &T) -> serde_json::Value {
+        let json = serde_json::to_value(body).unwrap();
+        let again: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&again).unwrap(), json);
+        json
+    }
+
+    fn workouts() -> Vec<serde_json::Value> {
+        vec![
+            serde_json::json!({
+                "id": "8a3f6c1e",
+                "title": "Leg Day",
+                "description": "Heavy",
+                "start_time": "2024-08-14T12:00:00Z",
+                "end_time": "2024-08-14T13:10:00Z",
+                "exercises": [
+                    {
+                        "index": 0,
+                        "title": "Squat (Barbell)",
+                        "notes": "Belt on top sets",
+                        "exercise_template_id": "D04AC939",
+                        "supersets_id": null,
+                        "sets": [
+                            {"index": 0, "type": "warmup", "weight_kg": 60, "reps": 5, "rpe": null},
+                            {"index": 1, "type": "normal", "weight_kg": 142.5, "reps": 5.0, "rpe": 8.5},
+                            {"index": 2, "type": "failure", "weight_kg": 120, "reps": "7.6", "rpe": 10}
+                        ]
+                    },
+                    {
+                        "title": "Leg Curl",
+                        "exercise_template_id": "B8127AD1",
+                        "supersets_id": "3",
+                        "sets": [{"type": "dropset", "weight_kg": 40.5, "reps": 12}]
+                    },
+                    {
+                        "title": "Sled Push",
+                        "exercise_template_id": "0EB695C9",
+                        "supersets_id": 3,
+                        "sets": [
+                            {"type": null, "distance_meters": 20.0, "duration_seconds": "35"},
+                            {"custom_metric": 4.5}
+                        ]
+                    }
+                ]
+            }),
+            serde_json::json!({
+                "start_time": "2024-08-15T07:00:00+02:00",
+                "end_time": "2024-08-15T07:30:00+02:00",
+                "exercises": null
+            }),
+        ]
+    }
+
+    fn routines() -> Vec<serde_json::Value> {
+        vec![
+            serde_json::json!({
+                "id": "b459cba5",
+                "title": "Push Day",
+                "folder_id": 42.0,
+                "exercises": [
+                    {
+                        "title": "Bench Press (Barbell)",
+                        "exercise_template_id": "79D0BB3A",
+                        "rest_seconds": "90",
+                        "notes": "Pause the first rep",
+                        "sets": [
+                            {"type": "warmup", "weight_kg": 40, "reps": 10},
+                            {"type": "normal", "weight_kg": 80, "rep_range": {"start": 8, "end": 12}},
+                            {"type": "normal", "weight_kg": 70, "rep_range": {"start": "5", "end": null}}
+                        ]
+                    },
+                    {
+                        "title": "Dips",
+                        "exercise_template_id": "6FCD7755",
+                        "supersets_id": 1.0,
+                        "rest_seconds": 60.0,
+                        "sets": [{"type": "failure", "reps": 15.4}]
+                    },
+                    {
+                        "title": "Plank",
+                        "exercise_template_id": "C6C9B8A0",
+                        "supersets_id": 1,
+                        "rest_seconds": null,
+                        "sets": [{"type": null, "duration_seconds": 60}]
+                    }
+                ]
+            }),
+            serde_json::json!({"title": null, "exercises": []}),
+        ]
+    }
+
+    /// Check the type and each of `fields` of a fetched set survived in a
+    /// write set, with a missing type written as "normal".
+    fn assert_set_kept(fetched: &impl Serialize, written: &impl Serialize, fields: &[&str]) {
+        let fetched = serde_json::to_value(fetched).unwrap();
+        let written = serde_json::to_value(written).unwrap();
+        assert_eq!(
+            written["type"],
+            fetched["type"].as_str().unwrap_or("normal")
+        );
+        for field in fields {
+            assert_eq!(written[field].as_f64(), fetched[field].as_f64(), "{field}");
+        }
+    }
+
+    #[test]
+    fn workout_round_trips_through_the_write_body() {
+        for fixture in workouts() {
+            let workout: Workout = fetched(fixture.clone());
+            let body = PostWorkoutInner::try_from(&workout).unwrap();
+            let json = round_trip(&body);
+
+            assert_eq!(
+                json["title"],
+                fixture["title"].as_str().unwrap_or("Untitled")
+            );
+            assert_eq!(json["description"], fixture["description"]);
+            assert_eq!(
+                json["start_time"],
+                format_timestamp(&workout.start_time.unwrap())
+            );
+            assert_eq!(
+                json["end_time"],
+                format_timestamp(&workout.end_time.unwrap())
+            );
+            assert_eq!(
+                json["exercises"].as_array().unwrap().len(),
+                workout.exercises.len()
+            );
+            for (ex, written) in workout.exercises.iter().zip(&body.exercises) {
+                assert_eq!(
+                    Some(&written.exercise_template_id),
+                    ex.exercise_template_id.as_ref()
+                );
+                assert_eq!(written.superset_id, ex.supersets_id);
+                assert_eq!(written.notes, ex.notes);
+                assert_eq!(written.sets.len(), ex.sets.len());
+                for (set, post) in ex.sets.iter().zip(&written.sets) {
+                    assert_set_kept(set, post, &["weight_kg", "rpe", "custom_metric"]);
+                    assert_eq!(
+                        (post.reps, post.distance_meters, post.duration_seconds),
+                        (set.reps, set.distance_meters, set.duration_seconds)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn workout_numbers_are_rounded_and_supersets_renamed() {
+        let workout: Workout = fetched(workouts().remove(0));
+        let json = round_trip(&PostWorkoutInner::try_from(&workout).unwrap());
+        let squat = &json["exercises"][0]["sets"];
+        assert_eq!(squat[1]["reps"], 5);
+        assert_eq!(squat[2]["reps"], 8);
+        assert_eq!(json["exercises"][1]["superset_id"], 3);
+        assert!(json["exercises"][0].get("superset_id").is_none());
+        assert!(json["exercises"][0].get("supersets_id").is_none());
+        let sled = &json["exercises"][2]["sets"][0];
+        assert_eq!(
+            (&sled["distance_meters"], &sled["duration_seconds"]),
+            (&20.into(), &35.into())
+        );
+    }
+
+    #[test]
+    fn routine_round_trips_through_the_write_bodies() {
+        for fixture in routines() {
+            let routine: Routine = fetched(fixture.clone());
+            let post = PostRoutineInner::try_from(&routine).unwrap();
+            let put = PutRoutineInner::try_from(&routine).unwrap();
+            let post_json = round_trip(&post);
+            let put_json = round_trip(&put);
+
+            assert_eq!(post.folder_id, routine.folder_id);
+            assert_eq!(post_json["exercises"], put_json["exercises"]);
+            assert_eq!(
+                post.title,
+                routine.title.as_deref().unwrap_or("Untitled Routine")
+            );
+            for (ex, written) in routine.exercises.iter().zip(&post.exercises) {
+                assert_eq!(
+                    Some(&written.exercise_template_id),
+                    ex.exercise_template_id.as_ref()
+                );
+                assert_eq!(written.superset_id, ex.supersets_id);
+                assert_eq!(written.rest_seconds, ex.rest_seconds.map(i64::from));
+                assert_eq!(written.notes, ex.notes);
+                for (set, post) in ex.sets.iter().zip(&written.sets) {
+                    assert_set_kept(set, post, &["weight_kg", "custom_metric"]);
+                    assert_eq!(post.reps, set.reps);
+                    let range = |r: &Option<RepRange>| r.as_ref().map(|r| (r.start, r.end));
+                    assert_eq!(range(&post.rep_range), range(&set.rep_range));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn routine_keeps_open_ended_rep_ranges() {
+        let routine: Routine = fetched(routines().remove(0));
+        let json = round_trip(&PutRoutineInner::try_from(&routine).unwrap());
+        let bench = &json["exercises"][0];
+        assert_eq!(bench["rest_seconds"], 90);
+        assert_eq!(
+            bench["sets"][1]["rep_range"],
+            serde_json::json!({"start": 8, "end": 12})
+        );
+        assert_eq!(
+            bench["sets"][2]["rep_range"],
+            serde_json::json!({"start": 5, "end": null})
+        );
+        assert_eq!(json["exercises"][1]["sets"][0]["reps"], 15);
+    }
+
+    #[test]
+    fn conversion_names_the_exercise_without_a_template_id() {
+        let workout: Workout = fetched(serde_json::json!({
+            "title": "Push",
+            "start_time": "2024-08-14T12:00:00Z",
+            "end_time": "2024-08-14T13:00:00Z",
+            "exercises": [
+                {"title": "Bench", "exercise_template_id": "79D0BB3A"},
+                {"title": "Mystery Machine"}
+            ]
+        }));
+        assert_eq!(
+            invalid(PostWorkoutInner::try_from(&workout)),
+            "Exercise 2 (Mystery Machine) is missing exercise_template_id"
+        );
+        let routine: Routine = fetched(serde_json::json!({"exercises": [{"sets": []}]}));
+        assert_eq!(
+            invalid(PutRoutineInner::try_from(&routine)),
+            "Exercise 1 (untitled) is missing exercise_template_id"
+        );
+    }
+
+    #[test]
+    fn conversion_rejects_what_the_api_would() {
+        let workout: Workout = fetched(serde_json::json!({"title": "No times"}));
+        assert_eq!(
+            invalid(PostWorkoutInner::try_from(&workout)),
+            "Workout is missing start_time"
+        );
+        let rest = serde_json::from_value::<Routine>(serde_json::json!({
+            "exercises": [{"exercise_template_id": "79D0BB3A", "rest_seconds": "a minute"}]
+        }));
+        assert!(
+            rest.unwrap_err()
+                .to_string()
+                .contains("\"a minute\" is not an integer")
+        );
+        let negative = serde_json::from_value::<Routine>(serde_json::json!({
+            "exercises": [{"exercise_template_id": "79D0BB3A", "rest_seconds": -5}]
+        }));
+        assert!(
+            negative
+                .unwrap_err()
+                .to_string()
+                .contains("-5 is not a non-negative integer")
+        );
+    }
+
+    /// The same captured payload with integers sent as integers, and as
+    /// floats or numeric strings, must read the same and write integers.
+    fn assert_same_shape<T: Serialize + serde::de::DeserializeOwned>(
+        integers: &str,
+        floats: &str,
+    ) -> serde_json::Value {
+        let integers: T = serde_json::from_str(integers).unwrap();
+        let floats: T = serde_json::from_str(floats).unwrap();
+        let json = serde_json::to_value(&integers).unwrap();
+        assert_eq!(serde_json::to_value(&floats).unwrap(), json);
+        json
+    }
+
+    #[test]
+    fn folder_ids_read_from_either_shape_and_write_as_integers() {
+        let json = assert_same_shape::<RoutineFoldersPage>(
+            include_str!("../tests/fixtures/api/routine_folders_integers.json"),
+            include_str!("../tests/fixtures/api/routine_folders_floats.json"),
+        );
+        let folders = &json["routine_folders"];
+        assert!(folders[0]["id"].is_u64() && folders[1]["index"].is_u64());
+        let text = serde_json::to_string(&folders[0]).unwrap();
+        assert!(text.contains(r#""id":42,"index":0,"#), "{text}");
+    }
+
+    #[test]
+    fn routine_integers_read_from_either_shape() {
+        let json = assert_same_shape::<SingleRoutineResponse>(
+            include_str!("../tests/fixtures/api/routine_integers.json"),
+            include_str!("../tests/fixtures/api/routine_floats.json"),
+        );
+        let routine = &json["routine"];
+        assert_eq!(routine["folder_id"], 42);
+        let bench = &routine["exercises"][0];
+        assert_eq!(
+            (&bench["index"], &bench["rest_seconds"]),
+            (&0.into(), &90.into())
+        );
+        assert_eq!(bench["sets"][0]["reps"], 8);
+        assert_eq!(
+            bench["sets"][0]["rep_range"],
+            serde_json::json!({"start": 8, "end": 12})
+        );
+        let plank = &routine["exercises"][1];
+        assert_eq!(
+            (&plank["supersets_id"], &plank["rest_seconds"]),
+            (&1.into(), &60.into())
+        );
+        assert_eq!(plank["sets"][0]["duration_seconds"], 60);
+        assert!(plank["sets"][0]["distance_meters"].is_i64());
+    }
+
+    #[test]
+    fn workout_integers_read_from_either_shape() {
+        let json = assert_same_shape::<WorkoutsPage>(
+            include_str!("../tests/fixtures/api/workouts_integers.json"),
+            include_str!("../tests/fixtures/api/workouts_floats.json"),
+        );
+        let squat = &json["workouts"][0]["exercises"][0];
+        assert_eq!(
+            (&squat["index"], &squat["supersets_id"]),
+            (&0.into(), &0.into())
+        );
+        assert_eq!(
+            (&squat["sets"][0]["index"], &squat["sets"][0]["reps"]),
+            (&0.into(), &5.into())
+        );
+        assert_eq!(squat["sets"][0]["weight_kg"], 140.0);
+    }
+
+    #[test]
+    fn fractional_and_malformed_integers() {
+        let set: Set =
+            serde_json::from_str(r#"{"reps": 7.5, "duration_seconds": " 30 "}"#).unwrap();
+        assert_eq!((set.reps, set.duration_seconds), (Some(8), Some(30)));
+        let set: Set = serde_json::from_str(r#"{"reps": "", "distance_meters": null}"#).unwrap();
+        assert_eq!((set.reps, set.distance_meters), (None, None));
+        assert!(serde_json::from_str::<Set>(r#"{"reps": "eight"}"#).is_err());
+        assert!(serde_json::from_str::<RoutineFolder>(r#"{"id": -1}"#).is_err());
+    }
+
+    #[test]
+    fn exercise_history_carries_workout_timestamps_and_exercise_details() {
+        let history: ExerciseHistoryResponse =
+            serde_json::from_str(include_str!("../tests/fixtures/api/exercise_history.json"))
+                .unwrap();
+        let [edited, fresh, old] = &history.exercise_history[..] else {
+            panic!("expected three entries");
+        };
+        assert_eq!(
+            (edited.workout_created_at, edited.workout_updated_at),
+            (
+                Some(Utc.with_ymd_and_hms(2024, 8, 14, 13, 1, 0).unwrap()),
+                Some(Utc.with_ymd_and_hms(2024, 8, 15, 9, 30, 0).unwrap())
+            )
+        );
+        assert_eq!(fresh.workout_created_at, fresh.workout_updated_at);
+        assert_eq!(
+            (old.workout_created_at, old.workout_updated_at),
+            (None, None)
+        );
+
+        assert_eq!(edited.exercise_notes.as_deref(), Some("Paused reps"));
+        assert_eq!((&fresh.exercise_notes, &old.exercise_notes), (&None, &None));
+        // The index arrives as an integer, a string and a float alike.
+        let indexes: Vec<_> = history
+            .exercise_history
+            .iter()
+            .map(|e| e.exercise_index)
+            .collect();
+        assert_eq!(indexes, [Some(0), Some(2), Some(1)]);
+        assert!(serde_json::from_str::<ExerciseHistoryEntry>(r#"{"exercise_index": -1}"#).is_err());
+    }
+
+    #[test]
+    fn updated_event_carries_the_full_workout() {
+        let event: WorkoutEvent = serde_json::from_value(serde_json::json!({
+            "type": "updated",
+            "workout": {
+                "id": "b459cba5",
+                "title": "Push",
+                "start_time": "2024-08-14T12:00:00Z",
+                "end_time": "2024-08-14T13:00:00Z",
+                "exercises": [{
+                    "index": 0,
+                    "title": "Bench Press (Barbell)",
+                    "exercise_template_id": "D04AC939",
+                    "sets": [{ "index": 0, "type": "normal", "weight_kg": 100, "reps": 5 }]
+                }]
+            }
+        }))
+        .unwrap();
+        let WorkoutEvent::Updated { workout } = &event else {
+            panic!("expected an update, got {event:?}");
+        };
+        assert_eq!(workout.title.as_deref(), Some("Push"));
+        assert_eq!(
+            (workout.start_time, workout.end_time),
+            (Some(at(12)), Some(at(13)))
+        );
+        let set = &workout.exercises[0].sets[0];
+        assert_eq!(set.set_type.as_deref(), Some("normal"));
+        assert_eq!((set.weight_kg, set.reps), (Some(100.0), Some(5)));
+
+        // Written back, it reads the same.
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["type"], "updated");
+        let again: WorkoutEvent = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&again).unwrap(), json);
+    }
+
+    #[test]
+    fn deleted_event_carries_id_and_time() {
+        let event: WorkoutEvent = serde_json::from_str(
+            r#"{ "type": "deleted", "id": "b459cba5", "deleted_at": "2024-08-15T09:30:00Z" }"#,
+        )
+        .unwrap();
+        let WorkoutEvent::Deleted { id, deleted_at } = &event else {
+            panic!("expected a deletion, got {event:?}");
+        };
+        assert_eq!(id, "b459cba5");
+        assert_eq!(
+            *deleted_at,
+            Some(Utc.with_ymd_and_hms(2024, 8, 15, 9, 30, 0).unwrap())
+        );
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["deleted_at"], "2024-08-15T09:30:00Z");
+
+        let untimed: WorkoutEvent =
+            serde_json::from_str(r#"{ "type": "deleted", "id": "b459cba5" }"#).unwrap();
+        assert!(matches!(
+            untimed,
+            WorkoutEvent::Deleted {
+                deleted_at: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn events_without_a_known_type_are_errors() {
+        // Rejected rather than guessed at, and without panicking.
+        for json in [
+            r#"{ "id": "b459cba5" }"#,
+            r#"{ "type": "archived", "id": "b459cba5" }"#,
+            r#"{ "type": "deleted" }"#,
+        ] {
+            assert!(
+                serde_json::from_str::<WorkoutEvent>(json).is_err(),
+                "{json}"
+            );
+        }
+    }
+
+    #[test]
+    fn event_page_mixes_updates_and_deletions() {
+        let page: PaginatedWorkoutEvents = serde_json::from_str(
+            r#"{
+                "page": 1,
+                "page_count": 3,
+                "events": [
+                    { "type": "updated", "workout": { "id": "b459cba5", "title": "Push" } },
+                    { "type": "deleted", "id": "7c1e2f90", "deleted_at": "2024-08-15T09:30:00Z" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!((page.page, page.page_count), (1, 3));
+        assert!(matches!(page.events[0], WorkoutEvent::Updated { .. }));
+        assert!(matches!(page.events[1], WorkoutEvent::Deleted { .. }));
+        let empty: PaginatedWorkoutEvents =
+            serde_json::from_str(r#"{ "page": 1, "page_count": 0, "events": null }"#).unwrap();
+        assert!(empty.events.is_empty());
+    }
+
+    fn mixed_sets() -> Workout {
+        serde_json::from_str(include_str!(
+            "../tests/fixtures/api/workout_mixed_sets.json"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn volume_counts_working_sets_only() {
+        // Bench: 100 x 5, with the warmup, the failure set and the set
+        // without reps adding nothing. Pull ups: bodyweight, so 0. Landmine
+        // press: a set without a type counts as normal, 25 x 12.
+        assert_eq!(mixed_sets().total_volume_kg(), 500.0 + 0.0 + 300.0);
+    }
+
+    #[test]
+    fn per_exercise_volume_skips_exercises_without_a_template() {
+        assert_eq!(
+            mixed_sets().per_exercise_volume(),
+            [
+                ("79D0BB3A".to_string(), 500.0),
+                ("1B2B1E7C".to_string(), 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_workout_has_no_volume() {
+        let workout: Workout = serde_json::from_str(r#"{"exercises": null}"#).unwrap();
+        assert_eq!(workout.total_volume_kg(), 0.0);
+        assert!(workout.per_exercise_volume().is_empty());
+    }
+}
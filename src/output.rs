@@ -0,0 +1,440 @@
+use std::cell::{Cell, RefCell, RefMut};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Output format for data commands (`--format`). Commands without a table
+/// view print JSON for it; only `workouts export` accepts ics.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Pretty-printed JSON (default).
+    #[default]
+    Json,
+    /// Human-readable table.
+    Table,
+    /// One JSON object per line for list results; `--all` streams page by
+    /// page.
+    Ndjson,
+    /// iCalendar (RFC 5545), written by `workouts export`.
+    Ics,
+}
+
+/// Where command output goes: stdout, or a temporary file that replaces
+/// the `--output` target once the command succeeds.
+pub enum Sink {
+    Stdout(std::io::Stdout),
+    File {
+        writer: BufWriter<File>,
+        tmp_path: PathBuf,
+        path: PathBuf,
+    },
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Sink::Stdout(out) => out.write(buf),
+            Sink::File { writer, .. } => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Sink::Stdout(out) => out.flush(),
+            Sink::File { writer, .. } => writer.flush(),
+        }
+    }
+}
+
+impl Drop for Sink {
+    /// A sink that was never committed leaves the target file untouched.
+    fn drop(&mut self) {
+        if let Sink::File { tmp_path, .. } = self {
+            let _ = std::fs::remove_file(tmp_path);
+        }
+    }
+}
+
+/// Renders command results as JSON, applying the global `--fields` projection
+/// and writing to the global `--output` destination.
+pub struct Printer {
+    fields: Option<Vec<String>>,
+    compact: bool,
+    /// Unknown fields are reported once, not for every NDJSON line.
+    warned: Cell<bool>,
+    sink: RefCell<Sink>,
+}
+
+impl Printer {
+    /// `output` of `None` or `-` means stdout. Otherwise output is staged in
+    /// a temporary file next to `output` until [`Printer::finish`].
+    pub fn new(fields: Option<Vec<String>>, output: Option<&Path>) -> Result<Self> {
+        let sink = match output {
+            Some(path) if path != Path::new("-") => {
+                let file_name = path
+                    .file_name()
+                    .with_context(|| format!("Invalid output path {}", path.display()))?;
+                let mut tmp_name = std::ffi::OsString::from(".");
+                tmp_name.push(file_name);
+                tmp_name.push(format!(".{}.tmp", std::process::id()));
+                let tmp_path = path.with_file_name(tmp_name);
+                let file = File::create(&tmp_path)
+                    .with_context(|| format!("Failed to create {}", tmp_path.display()))?;
+                Sink::File {
+                    writer: BufWriter::new(file),
+                    tmp_path,
+                    path: path.to_path_buf(),
+                }
+            }
+            _ => Sink::Stdout(std::io::stdout()),
+        };
+        Ok(Printer {
+            fields,
+            compact: false,
+            warned: Cell::new(false),
+            sink: RefCell::new(sink),
+        })
+    }
+
+    /// Print JSON on a single line instead of pretty-printing it.
+    pub fn compact(mut self, compact: bool) -> Self {
+        self.compact = compact;
+        self
+    }
+
+    /// Whether output goes to an interactive terminal (for color decisions).
+    pub fn is_terminal(&self) -> bool {
+        matches!(&*self.sink.borrow(), Sink::Stdout(out) if out.is_terminal())
+    }
+
+    /// The output destination, for commands that render their own text.
+    pub fn out(&self) -> RefMut<'_, Sink> {
+        self.sink.borrow_mut()
+    }
+
+    /// Commit the output: flush stdout, or move the temporary file into place.
+    pub fn finish(self) -> Result<()> {
+        let mut sink = self.sink.into_inner();
+        sink.flush()?;
+        if let Sink::File {
+            writer,
+            tmp_path,
+            path,
+        } = &sink
+        {
+            writer.get_ref().sync_all()?;
+            // Once renamed, the sink's cleanup on drop finds nothing to remove.
+            std::fs::rename(tmp_path, path).with_context(|| {
+                format!("Failed to move output into place at {}", path.display())
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Print `value` as pretty JSON, or on one line when compact.
+    pub fn json<T: Serialize>(&self, value: &T) -> Result<()> {
+        let value = self.prepare(value)?;
+        let json = if self.compact {
+            serde_json::to_string(&value)?
+        } else {
+            serde_json::to_string_pretty(&value)?
+        };
+        writeln!(self.out(), "{json}")?;
+        Ok(())
+    }
+
+    /// Print each item of `items` as one line of JSON.
+    pub fn ndjson<T: Serialize>(&self, items: &[T]) -> Result<()> {
+        let mut out = self.out();
+        for item in items {
+            self.write_line(&mut *out, item)?;
+        }
+        Ok(())
+    }
+
+    /// Write `item` as a single line of JSON and flush, so consumers see it immediately.
+    pub fn write_line<T: Serialize>(&self, out: &mut impl Write, item: &T) -> Result<()> {
+        let value = self.prepare(item)?;
+        writeln!(out, "{}", serde_json::to_string(&value)?)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    fn prepare<T: Serialize>(&self, value: &T) -> Result<Value> {
+        let value = serde_json::to_value(value)?;
+        let Some(fields) = &self.fields else {
+            return Ok(value);
+        };
+        let (projected, unknown) = project(&value, fields);
+        if !unknown.is_empty() && !self.warned.replace(true) {
+            for path in unknown {
+                eprintln!("Warning: field \"{path}\" not found in output");
+            }
+        }
+        Ok(projected)
+    }
+}
+
+/// The part of a value selected by a `--fields` projection.
+enum Selection {
+    /// The whole value.
+    All,
+    /// Only the named keys, each with its own selection.
+    Fields(BTreeMap<String, Selection>),
+}
+
+impl Selection {
+    fn insert(&mut self, segments: &[&str]) {
+        // A shorter path already selected everything beneath it.
+        let Selection::Fields(children) = self else {
+            return;
+        };
+        let Some((first, rest)) = segments.split_first() else {
+            *self = Selection::All;
+            return;
+        };
+        children
+            .entry(first.to_string())
+            .or_insert_with(|| Selection::Fields(BTreeMap::new()))
+            .insert(rest);
+    }
+}
+
+/// Keep only the dot-separated `paths` of `value`, preserving its structure.
+///
+/// Arrays are traversed implicitly, so `exercises.title` selects the title
+/// of every exercise. Returns the projected value together with the paths
+/// that matched nothing.
+pub fn project(value: &Value, paths: &[String]) -> (Value, Vec<String>) {
+    let mut selection = Selection::Fields(BTreeMap::new());
+    let mut unknown = Vec::new();
+    for path in paths {
+        let segments: Vec<&str> = path.split('.').collect();
+        if !path_exists(value, &segments) {
+            unknown.push(path.clone());
+        }
+        selection.insert(&segments);
+    }
+    let projected = apply(value, &selection).unwrap_or(Value::Null);
+    (projected, unknown)
+}
+
+fn apply(value: &Value, selection: &Selection) -> Option<Value> {
+    let Selection::Fields(children) = selection else {
+        return Some(value.clone());
+    };
+    match value {
+        Value::Array(items) => Some(Value::Array(
+            items.iter().filter_map(|v| apply(v, selection)).collect(),
+        )),
+        Value::Object(map) => Some(Value::Object(
+            map.iter()
+                .filter_map(|(k, v)| Some((k.clone(), apply(v, children.get(k)?)?)))
+                .collect(),
+        )),
+        _ => None,
+    }
+}
+
+fn path_exists(value: &Value, segments: &[&str]) -> bool {
+    let Some((first, rest)) = segments.split_first() else {
+        return true;
+    };
+    match value {
+        // An empty array gives no evidence either way.
+        Value::Array(items) => items.is_empty() || items.iter().any(|v| path_exists(v, segments)),
+        Value::Object(map) => map.get(*first).is_some_and(|v| path_exists(v, rest)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn workout() -> Value {
+        json!({
+            "id": "w1",
+            "title": "Push",
+            "exercises": [
+                {
+                    "title": "Bench Press",
+                    "notes": "Paused",
+                    "sets": [{ "reps": 5, "weight_kg": 100 }, { "reps": 3, "weight_kg": 105 }]
+                },
+                { "title": "Dips", "sets": [{ "reps": 12, "weight_kg": null }] }
+            ]
+        })
+    }
+
+    fn paths(paths: &[&str]) -> Vec<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn top_level_fields() {
+        let (projected, unknown) = project(&workout(), &paths(&["id", "title"]));
+        assert_eq!(projected, json!({ "id": "w1", "title": "Push" }));
+        assert!(unknown.is_empty());
+    }
+
+    #[test]
+    fn nested_arrays_are_traversed() {
+        let (projected, unknown) = project(&workout(), &paths(&["exercises.sets.reps"]));
+        assert_eq!(
+            projected,
+            json!({
+                "exercises": [
+                    { "sets": [{ "reps": 5 }, { "reps": 3 }] },
+                    { "sets": [{ "reps": 12 }] }
+                ]
+            })
+        );
+        assert!(unknown.is_empty());
+
+        // A list result is projected item by item.
+        let list = json!([workout(), { "id": "w2", "title": "Pull", "exercises": [] }]);
+        let (projected, _) = project(&list, &paths(&["id", "exercises.title"]));
+        assert_eq!(
+            projected,
+            json!([
+                { "id": "w1", "exercises": [{ "title": "Bench Press" }, { "title": "Dips" }] },
+                { "id": "w2", "exercises": [] }
+            ])
+        );
+    }
+
+    #[test]
+    fn missing_fields_are_reported_and_skipped() {
+        let (projected, unknown) = project(&workout(), &paths(&["id", "tempo", "exercises.rpe"]));
+        assert_eq!(projected, json!({ "id": "w1", "exercises": [{}, {}] }));
+        assert_eq!(unknown, ["tempo", "exercises.rpe"]);
+
+        // A field present in some array items only is known, and kept where present.
+        let (projected, unknown) = project(&workout(), &paths(&["exercises.notes"]));
+        assert_eq!(
+            projected,
+            json!({ "exercises": [{ "notes": "Paused" }, {}] })
+        );
+        assert!(unknown.is_empty());
+
+        // Selecting beneath a scalar finds nothing.
+        let (_, unknown) = project(&workout(), &paths(&["title.text"]));
+        assert_eq!(unknown, ["title.text"]);
+    }
+
+    #[test]
+    fn overlapping_selections_keep_the_wider_one() {
+        let expected = json!({ "exercises": workout()["exercises"].clone() });
+        for order in [
+            ["exercises.sets.reps", "exercises"],
+            ["exercises", "exercises.sets.reps"],
+        ] {
+            let (projected, unknown) = project(&workout(), &paths(&order));
+            assert_eq!(projected, expected, "selected in order {order:?}");
+            assert!(unknown.is_empty());
+        }
+
+        let (projected, _) = project(
+            &workout(),
+            &paths(&["exercises.title", "exercises.sets.reps", "exercises.title"]),
+        );
+        assert_eq!(
+            projected,
+            json!({
+                "exercises": [
+                    { "title": "Bench Press", "sets": [{ "reps": 5 }, { "reps": 3 }] },
+                    { "title": "Dips", "sets": [{ "reps": 12 }] }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn null_values_are_kept_when_selected() {
+        let (projected, unknown) = project(&workout(), &paths(&["exercises.sets.weight_kg"]));
+        assert_eq!(
+            projected["exercises"][1],
+            json!({ "sets": [{ "weight_kg": null }] })
+        );
+        assert!(unknown.is_empty());
+    }
+
+    /// Names of the files left in `dir`.
+    fn files_in(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn unfinished_output_leaves_the_target_untouched() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("workouts.json");
+        std::fs::write(&target, b"[\"previous run\"]\n").unwrap();
+
+        let printer = Printer::new(None, Some(&target)).unwrap();
+        printer.json(&workout()).unwrap();
+        // The command fails before finish().
+        drop(printer);
+
+        assert_eq!(std::fs::read(&target).unwrap(), b"[\"previous run\"]\n");
+        assert_eq!(files_in(dir.path()), ["workouts.json"]);
+    }
+
+    #[test]
+    fn finish_replaces_the_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("workouts.json");
+        std::fs::write(&target, b"[\"previous run\"]\n").unwrap();
+
+        let printer = Printer::new(Some(paths(&["id"])), Some(&target))
+            .unwrap()
+            .compact(true);
+        printer.json(&workout()).unwrap();
+        // Nothing reaches the target before finish().
+        assert_eq!(std::fs::read(&target).unwrap(), b"[\"previous run\"]\n");
+        printer.finish().unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&target).unwrap(),
+            "{\"id\":\"w1\"}\n"
+        );
+        assert_eq!(files_in(dir.path()), ["workouts.json"]);
+    }
+
+    #[test]
+    fn finish_creates_a_missing_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("new.ndjson");
+
+        let printer = Printer::new(None, Some(&target)).unwrap();
+        printer
+            .ndjson(&[json!({ "id": 1 }), json!({ "id": 2 })])
+            .unwrap();
+        printer.finish().unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&target).unwrap(),
+            "{\"id\":1}\n{\"id\":2}\n"
+        );
+    }
+
+    #[test]
+    fn dash_means_stdout() {
+        let printer = Printer::new(None, Some(Path::new("-"))).unwrap();
+        assert!(matches!(*printer.out(), Sink::Stdout(_)));
+    }
+}
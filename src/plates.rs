@@ -0,0 +1,338 @@
+//! Working out which plates load a barbell to a target weight.
+
+use std::io::Write;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::models::Routine;
+use crate::table::Units;
+
+/// Plates of a typical kg gym, heaviest first.
+const KG_PLATES: [f64; 7] = [25.0, 20.0, 15.0, 10.0, 5.0, 2.5, 1.25];
+/// Plates of a typical lbs gym, heaviest first.
+const LBS_PLATES: [f64; 6] = [45.0, 35.0, 25.0, 10.0, 5.0, 2.5];
+
+/// Weights are solved in thousandths, so sums of plates such as 1.25 stay
+/// exact.
+const SCALE: f64 = 1000.0;
+
+/// Largest number of amounts the exact search walks before giving up on
+/// improving the greedy loading.
+const MAX_STATES: u64 = 1_000_000;
+
+/// Heaviest target or bar worth solving for, in either unit. Anything
+/// heavier is a typo, and would take the solver a very long time.
+pub const MAX_WEIGHT: f64 = 10_000.0;
+
+/// The standard bar in `units`: 20 kg or 45 lbs.
+pub fn default_bar(units: Units) -> f64 {
+    match units {
+        Units::Kg => 20.0,
+        Units::Lbs => 45.0,
+    }
+}
+
+/// The smallest jump in weight worth planning with: 5 lbs or 2.5 kg, a
+/// pair of the smallest common plates.
+pub fn default_round_to(units: Units) -> f64 {
+    match units {
+        Units::Kg => 2.5,
+        Units::Lbs => 5.0,
+    }
+}
+
+/// The plates of a typical gym in `units`.
+pub fn default_plates(units: Units) -> Vec<f64> {
+    match units {
+        Units::Kg => KG_PLATES.to_vec(),
+        Units::Lbs => LBS_PLATES.to_vec(),
+    }
+}
+
+/// How to load the bar for one target weight. All weights are in the
+/// units the target was given in.
+#[derive(Debug, Serialize)]
+pub struct Loading {
+    pub target: f64,
+    pub bar: f64,
+    /// Plates on each side, heaviest first.
+    pub per_side: Vec<f64>,
+    /// The bar and both sides.
+    pub loaded: f64,
+    /// How far `loaded` falls short of `target`: 0 when the target can be
+    /// loaded exactly.
+    pub remainder: f64,
+}
+
+/// Load `bar` as close to `target` as `plates` allow without going over.
+/// Plates go on in pairs, and any number of each size can be used.
+///
+/// Heaviest-first is tried first. When that misses the target (say 30 a
+/// side from 20s and 15s), every reachable amount is searched for the
+/// closest one, with as few plates as possible. Plates too light to count
+/// in thousandths are ignored. `target` should be finite and at most
+/// [`MAX_WEIGHT`].
+pub fn solve(target: f64, bar: f64, plates: &[f64]) -> Loading {
+    let mut sizes: Vec<u64> = plates
+        .iter()
+        .filter(|p| p.is_finite())
+        .map(|p| units(*p))
+        .filter(|size| *size > 0)
+        .collect();
+    sizes.sort_unstable_by(|a, b| b.cmp(a));
+    sizes.dedup();
+    let side = units(((target - bar) / 2.0).max(0.0));
+
+    let mut per_side = greedy(side, &sizes);
+    if per_side.iter().sum::<u64>() < side
+        && let Some(closest) = closest(side, &sizes)
+        && closest.iter().sum::<u64>() > per_side.iter().sum::<u64>()
+    {
+        per_side = closest;
+    }
+    let loaded = bar + 2.0 * per_side.iter().sum::<u64>() as f64 / SCALE;
+    Loading {
+        target,
+        bar,
+        per_side: per_side.into_iter().map(|p| p as f64 / SCALE).collect(),
+        loaded: tidy(loaded),
+        remainder: tidy((target - loaded).max(0.0)),
+    }
+}
+
+fn units(weight: f64) -> u64 {
+    (weight * SCALE).round() as u64
+}
+
+/// Round away float noise, e.g. 97.49999 to 97.5.
+fn tidy(weight: f64) -> f64 {
+    (weight * SCALE).round() / SCALE
+}
+
+/// As many of each size as fit, heaviest first.
+fn greedy(side: u64, sizes: &[u64]) -> Vec<u64> {
+    let mut left = side;
+    let mut plates = Vec::new();
+    for &size in sizes {
+        while size <= left {
+            plates.push(size);
+            left -= size;
+        }
+    }
+    plates
+}
+
+/// The fewest plates making the largest amount up to `side`. None when
+/// the search would be too large.
+fn closest(side: u64, sizes: &[u64]) -> Option<Vec<u64>> {
+    // Every sum of plates is a multiple of their greatest common divisor.
+    let step = sizes.iter().copied().reduce(gcd)?;
+    let steps = side / step;
+    if steps > MAX_STATES {
+        return None;
+    }
+    // fewest[n]: the fewest plates summing to n steps, and the last one.
+    let mut fewest: Vec<Option<(usize, u64)>> = vec![None; steps as usize + 1];
+    fewest[0] = Some((0, 0));
+    for n in 1..=steps as usize {
+        fewest[n] = sizes
+            .iter()
+            .filter_map(|&size| {
+                let before = n.checked_sub((size / step) as usize)?;
+                let (count, _) = fewest[before]?;
+                Some((count + 1, size))
+            })
+            .min_by_key(|(count, _)| *count);
+    }
+    let mut n = (0..=steps as usize).rev().find(|n| fewest[*n].is_some())?;
+    let mut plates = Vec::new();
+    while n > 0 {
+        let (_, size) = fewest[n]?;
+        plates.push(size);
+        n -= (size / step) as usize;
+    }
+    plates.sort_unstable_by(|a, b| b.cmp(a));
+    Some(plates)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+/// `bar 20 + 25 15 per side`, with how far the loading falls short when it
+/// cannot reach the target.
+pub fn describe(loading: &Loading, units: Units) -> String {
+    let unit = unit(units);
+    let plates = if loading.per_side.is_empty() {
+        "no plates".to_string()
+    } else {
+        let plates: Vec<String> = loading.per_side.iter().map(f64::to_string).collect();
+        format!("{} per side", plates.join(" "))
+    };
+    let mut line = format!("bar {} + {plates}", loading.bar);
+    if loading.loaded > loading.target {
+        line.push_str(&format!(
+            " = {} {unit} ({} {unit} over)",
+            loading.loaded,
+            tidy(loading.loaded - loading.target)
+        ));
+    } else if loading.remainder > 0.0 {
+        line.push_str(&format!(
+            " = {} {unit} ({} {unit} short)",
+            loading.loaded, loading.remainder
+        ));
+    }
+    line
+}
+
+fn unit(units: Units) -> &'static str {
+    match units {
+        Units::Kg => "kg",
+        Units::Lbs => "lbs",
+    }
+}
+
+/// Print one loading as `100 kg: bar 20 + 25 15 per side`.
+pub fn print_loading(out: &mut impl Write, loading: &Loading, units: Units) -> Result<()> {
+    writeln!(
+        out,
+        "{} {}: {}",
+        loading.target,
+        unit(units),
+        describe(loading, units)
+    )?;
+    Ok(())
+}
+
+/// One weighted set of a barbell exercise in a routine, with its loading.
+#[derive(Debug, Serialize)]
+pub struct RoutineSetLoading {
+    pub exercise_template_id: Option<String>,
+    pub title: String,
+    /// Position of the set within its exercise, from 1.
+    pub set: usize,
+    pub loading: Loading,
+}
+
+/// Solve every weighted set of the barbell exercises in `routine`, with the
+/// set weights converted to `units`. Templates carry no equipment, so an
+/// exercise counts as barbell work when its title says "Barbell".
+pub fn routine_loadings(
+    routine: &Routine,
+    bar: f64,
+    plates: &[f64],
+    units: Units,
+) -> Vec<RoutineSetLoading> {
+    let mut sets = Vec::new();
+    for exercise in &routine.exercises {
+        let Some(title) = exercise
+            .title
+            .as_deref()
+            .filter(|t| t.to_lowercase().contains("barbell"))
+        else {
+            continue;
+        };
+        for (i, set) in exercise.sets.iter().enumerate() {
+            let Some(kg) = set
+                .weight_kg
+                .filter(|kg| *kg > 0.0 && units.convert(*kg) <= MAX_WEIGHT)
+            else {
+                continue;
+            };
+            sets.push(RoutineSetLoading {
+                exercise_template_id: exercise.exercise_template_id.clone(),
+                title: title.to_string(),
+                set: i + 1,
+                // To a tenth, as tables show weights.
+                loading: solve((units.convert(kg) * 10.0).round() / 10.0, bar, plates),
+            });
+        }
+    }
+    sets
+}
+
+/// Print `sets` grouped under their exercises.
+pub fn print_routine(out: &mut impl Write, sets: &[RoutineSetLoading], units: Units) -> Result<()> {
+    let mut title = None;
+    for s in sets {
+        if title != Some(&s.title) {
+            writeln!(out, "{}", s.title)?;
+            title = Some(&s.title);
+        }
+        writeln!(
+            out,
+            "  Set {:<3} {:>8} {}  {}",
+            s.set,
+            s.loading.target,
+            unit(units),
+            describe(&s.loading, units)
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_plates_load_exactly() {
+        let loading = solve(100.0, 20.0, &KG_PLATES);
+        assert_eq!(loading.per_side, [25.0, 15.0]);
+        assert_eq!((loading.loaded, loading.remainder), (100.0, 0.0));
+
+        let loading = solve(225.0, 45.0, &LBS_PLATES);
+        assert_eq!(loading.per_side, [45.0, 45.0]);
+        let loading = solve(102.5, 20.0, &KG_PLATES);
+        assert_eq!(loading.per_side, [25.0, 15.0, 1.25]);
+        assert_eq!(loading.loaded, 102.5);
+    }
+
+    #[test]
+    fn greedy_misses_fall_back_to_the_exact_search() {
+        // Bumpers with no 2.5s or 5s: heaviest first takes a 25 and is
+        // 5 short a side; the search finds 20 + 10.
+        let bumpers = [25.0, 20.0, 15.0, 10.0];
+        let loading = solve(80.0, 20.0, &bumpers);
+        assert_eq!(loading.per_side, [20.0, 10.0]);
+        assert_eq!((loading.loaded, loading.remainder), (80.0, 0.0));
+        // Without 2.5s, 1.25s still make up 2.5 a side.
+        let no_2_5 = [20.0, 10.0, 5.0, 1.25];
+        assert_eq!(
+            solve(47.5, 20.0, &no_2_5).per_side,
+            [10.0, 1.25, 1.25, 1.25]
+        );
+        // 7.5 a side from 5s and 3s: greedy loads 5 (2.5 short a side),
+        // the search 3 + 3 (1.5 short).
+        let loading = solve(35.0, 20.0, &[5.0, 3.0]);
+        assert_eq!(loading.per_side, [3.0, 3.0]);
+        assert_eq!((loading.loaded, loading.remainder), (32.0, 3.0));
+        assert!(describe(&loading, Units::Kg).ends_with("(3 kg short)"));
+    }
+
+    #[test]
+    fn a_target_at_or_below_the_bar_takes_no_plates() {
+        for target in [20.0, 15.0, 0.0] {
+            let loading = solve(target, 20.0, &KG_PLATES);
+            assert!(loading.per_side.is_empty());
+            assert_eq!((loading.loaded, loading.remainder), (20.0, 0.0));
+        }
+        let below = solve(15.0, 20.0, &KG_PLATES);
+        assert_eq!(
+            describe(&below, Units::Kg),
+            "bar 20 + no plates = 20 kg (5 kg over)"
+        );
+    }
+
+    #[test]
+    fn unusable_plates_are_ignored() {
+        // Zero, negative, NaN and plates too light to count would
+        // otherwise never fill a side.
+        let plates = [25.0, 0.0001, 0.0, -5.0, f64::NAN, f64::INFINITY];
+        let loading = solve(100.0, 20.0, &plates);
+        assert_eq!(loading.per_side, [25.0]);
+        assert_eq!(loading.remainder, 30.0);
+        assert!(solve(100.0, 20.0, &[]).per_side.is_empty());
+    }
+}
@@ -0,0 +1,424 @@
+use std::io::Write;
+
+use anyhow::Result;
+use unicode_width::UnicodeWidthStr;
+
+use crate::analysis::{self, SetResult};
+use crate::api::HevyApi;
+use crate::models::*;
+use crate::table::{Cell, Column, Style, Table, Units};
+
+/// Fetch the routine a workout was performed from, if any.
+///
+/// A routine that cannot be fetched (e.g. deleted since) is treated as
+/// absent so the summary still renders with default rep targets.
+pub async fn fetch_routine(client: &impl HevyApi, workout: &Workout) -> Option<Routine> {
+    let routine_id = workout.routine_id.as_ref()?;
+    client.get_routine(routine_id).await.ok().map(|r| r.routine)
+}
+
+/// Fetch a workout and the routine it was performed from, and render its
+/// summary into a string, so concurrent requests never interleave their
+/// output.
+pub async fn summarize(client: &impl HevyApi, workout_id: &str, style: &Style) -> Result<String> {
+    let workout = client.get_workout(workout_id).await?;
+    let routine = fetch_routine(client, &workout).await;
+    let mut buf = Vec::new();
+    render_summary(&mut buf, &workout, routine.as_ref(), style)?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Render the `process-workout` summary: the routine's targets (when
+/// known) followed by each exercise's results against them.
+///
+/// `style` decides colors for the Struggled/Succeeded/Exceeded results, the
+/// width the tables fit in, and which of their columns are shown.
+pub fn render_summary(
+    out: &mut impl Write,
+    workout: &Workout,
+    routine: Option<&Routine>,
+    style: &Style,
+) -> Result<()> {
+    let set_targets = routine
+        .map(analysis::set_targets_from_routine)
+        .unwrap_or_default();
+
+    let title = workout.title.as_deref().unwrap_or("Untitled Workout");
+    writeln!(out)?;
+    writeln!(out, "  {title}")?;
+    writeln!(out, "  {}", "─".repeat(title.width()))?;
+    if let Some(ref routine_id) = workout.routine_id {
+        writeln!(out, "  Routine ID: {routine_id}")?;
+    }
+    writeln!(out)?;
+
+    // ── Routine table (printed first when available) ──
+    if let Some(routine) = routine {
+        let routine_title = routine.title.as_deref().unwrap_or("Untitled Routine");
+
+        writeln!(out, "  Routine: {routine_title}")?;
+        writeln!(out, "  {}", "─".repeat(routine_title.width() + 10))?;
+        writeln!(out)?;
+
+        let mut table = Table::new(vec![
+            Column::left("Exercise", 35).key("exercise").flex(),
+            Column::right("Sets", 5).key("sets"),
+            Column::right(
+                match style.units {
+                    Units::Lbs => "Target Wt (lbs)",
+                    Units::Kg => "Target Wt (kg)",
+                },
+                18,
+            )
+            .key("weight"),
+            Column::right("Target Reps", 12).key("reps"),
+            Column::right("Rest (s)", 12).key("rest"),
+            Column::free("Notes").key("notes"),
+        ]);
+
+        for exercise in &routine.exercises {
+            let ex_title = style.exercise_title(
+                exercise.exercise_template_id.as_deref(),
+                exercise.title.as_deref().unwrap_or("Unknown Exercise"),
+            );
+            let notes = exercise.notes.as_deref().unwrap_or("");
+
+            let rest = exercise
+                .rest_seconds
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "—".to_string());
+
+            // Show the heaviest target weight and its rep range
+            let (best_kg, reps_display) = exercise
+                .sets
+                .iter()
+                .map(|s| (s.weight_kg.unwrap_or(0.0), target_reps(s)))
+                .fold((0.0_f64, "—".to_string()), |(bw, br), (w, r)| {
+                    if w > bw { (w, r) } else { (bw, br) }
+                });
+
+            table.row(vec![
+                ex_title.into(),
+                exercise.sets.len().to_string().into(),
+                style.units.weight(best_kg).into(),
+                reps_display.into(),
+                rest.into(),
+                notes.into(),
+            ]);
+
+            // Indented per-set detail rows
+            for (i, s) in exercise.sets.iter().enumerate() {
+                table.row(vec![
+                    set_label(i, s.set_type.as_deref()).into(),
+                    "".into(),
+                    style.units.weight(s.weight_kg.unwrap_or(0.0)).into(),
+                    target_reps(s).into(),
+                ]);
+            }
+        }
+
+        table.render(out, style)?;
+        writeln!(out)?;
+    }
+
+    // ── Workout results table ──
+    let mut table = Table::new(vec![
+        Column::left("Exercise", 35).key("exercise").flex(),
+        Column::right("Sets", 5).key("sets"),
+        Column::right(
+            match style.units {
+                Units::Lbs => "Weight (lbs)",
+                Units::Kg => "Weight (kg)",
+            },
+            18,
+        )
+        .key("weight"),
+        Column::right("Reps", 13).key("reps"),
+        Column::right("Result", 12).key("result"),
+        Column::free("Notes").key("notes"),
+    ]);
+
+    for group in analysis::group_by_superset(&workout.exercises) {
+        let superset = group.superset_id.is_some();
+        if superset {
+            table.row(vec![SUPERSET_START.into()]);
+        }
+        for exercise in group.exercises {
+            let ex_title = style.exercise_title(
+                exercise.exercise_template_id.as_deref(),
+                exercise.title.as_deref().unwrap_or("Unknown Exercise"),
+            );
+            let notes = exercise.notes.as_deref().unwrap_or("");
+            let targets = |i: usize| {
+                exercise
+                    .exercise_template_id
+                    .as_ref()
+                    .and_then(|id| set_targets.get(&(id.clone(), i)))
+                    .copied()
+                    .unwrap_or((8, 10))
+            };
+
+            let results: Vec<SetResult> = exercise
+                .sets
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    let (lo, hi) = targets(i);
+                    analysis::classify_set_result(s.reps.unwrap_or(0), lo, hi)
+                })
+                .collect();
+            // Compute an overall result: worst individual set classification wins
+            let overall = if results.contains(&SetResult::Struggled) {
+                SetResult::Struggled
+            } else if results.iter().all(|r| *r == SetResult::Exceeded) {
+                SetResult::Exceeded
+            } else {
+                SetResult::Succeeded
+            };
+
+            // Exercise summary row (no weight/reps — those are on the set rows)
+            table.row(vec![
+                ex_title.into(),
+                exercise.sets.len().to_string().into(),
+                "".into(),
+                "".into(),
+                result_cell(overall),
+                notes.into(),
+            ]);
+
+            // Indented per-set detail rows with individual results
+            for (i, (s, result)) in exercise.sets.iter().zip(&results).enumerate() {
+                let reps = s.reps;
+                let rpe_str = s.rpe.map(|v| format!("RPE {v}")).unwrap_or_default();
+                // Number sets as the Hevy app does, by their own index.
+                let index = s.index.map_or(i, |index| index as usize);
+
+                table.row(vec![
+                    set_label(index, s.set_type.as_deref()).into(),
+                    "".into(),
+                    format!("{:.1}", style.units.convert(s.weight_kg.unwrap_or(0.0))).into(),
+                    reps.map(|v| v.to_string())
+                        .unwrap_or_else(|| "—".to_string())
+                        .into(),
+                    result_cell(*result),
+                    rpe_str.into(),
+                ]);
+            }
+        }
+        if superset {
+            table.row(vec![SUPERSET_END.into()]);
+        }
+    }
+
+    table.render(out, style)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Rows bracketing the exercises of a superset in the results table.
+const SUPERSET_START: &str = "┌─ Superset ─┐";
+const SUPERSET_END: &str = "└────────────┘";
+
+fn result_cell(result: SetResult) -> Cell {
+    Cell::colored(result.to_string(), result.color())
+}
+
+/// "  Set 2 (warmup)" style label for the indented per-set rows.
+fn set_label(index: usize, set_type: Option<&str>) -> String {
+    format!(
+        "  Set {}{}",
+        index + 1,
+        set_type.map(|t| format!(" ({t})")).unwrap_or_default()
+    )
+}
+
+/// A routine set's target reps: its rep range when set, else its fixed reps.
+fn target_reps(s: &RoutineSet) -> String {
+    let fixed = || {
+        s.reps
+            .map(|r| r.to_string())
+            .unwrap_or_else(|| "—".to_string())
+    };
+    match s.rep_range.as_ref().map(|r| (r.start, r.end)) {
+        Some((Some(l), Some(h))) => format!("{l}-{h}"),
+        Some((Some(l), None)) => format!("{l}+"),
+        _ => fixed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::mock::{MockHevyClient, from_json};
+
+    fn workout() -> Workout {
+        serde_json::from_value(json!({
+            "id": "w1",
+            "title": "Push",
+            "routine_id": "r1",
+            "exercises": [{
+                "index": 0,
+                "title": "Bench Press (Barbell)",
+                "exercise_template_id": "D04AC939",
+                "sets": [
+                    { "index": 0, "type": "normal", "weight_kg": 100, "reps": 8 },
+                    { "index": 1, "type": "normal", "weight_kg": 100, "reps": 6 }
+                ]
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn routine() -> Routine {
+        serde_json::from_value(json!({
+            "id": "r1",
+            "title": "Push Day",
+            "exercises": [{
+                "title": "Bench Press (Barbell)",
+                "exercise_template_id": "D04AC939",
+                "sets": [
+                    { "type": "normal", "weight_kg": 100, "rep_range": { "start": 8, "end": 10 } },
+                    { "type": "normal", "weight_kg": 100, "rep_range": { "start": 8, "end": 10 } }
+                ]
+            }]
+        }))
+        .unwrap()
+    }
+
+    fn summary(workout: &Workout, routine: Option<&Routine>, color: bool) -> String {
+        let mut out = Vec::new();
+        render_summary(
+            &mut out,
+            workout,
+            routine,
+            &Style::new(color, Some(120), false),
+        )
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn summary_has_no_escape_bytes_without_color() {
+        let plain = summary(&workout(), Some(&routine()), false);
+        assert!(!plain.contains('\x1b'), "escape bytes in:\n{plain}");
+        assert!(plain.contains("Succeeded") && plain.contains("Struggled"));
+
+        assert!(summary(&workout(), Some(&routine()), true).contains("\x1b[33mStruggled"));
+    }
+
+    #[tokio::test]
+    async fn summarize_fetches_the_workout_and_its_routine() {
+        let client = MockHevyClient::default()
+            .on_get_workout(|id| {
+                assert_eq!(id, "w1");
+                Ok(workout())
+            })
+            .on_get_routine(|id| {
+                assert_eq!(id, "r1");
+                Ok(from_json(json!({ "routine": routine() })))
+            });
+        let text = summarize(&client, "w1", &Style::new(false, Some(120), false))
+            .await
+            .unwrap();
+        assert!(text.contains("Routine: Push Day"), "{text}");
+        assert!(text.contains("Succeeded") && text.contains("Struggled"));
+    }
+
+    #[tokio::test]
+    async fn summarize_renders_without_a_routine_that_cannot_be_fetched() {
+        // get_routine is not mocked, so fetching the routine fails.
+        let client = MockHevyClient::default().on_get_workout(|_| Ok(workout()));
+        let text = summarize(&client, "w1", &Style::new(false, Some(120), false))
+            .await
+            .unwrap();
+        assert!(
+            text.contains("Push") && !text.contains("Routine: "),
+            "{text}"
+        );
+    }
+
+    #[tokio::test]
+    async fn summarize_fails_when_the_workout_cannot_be_fetched() {
+        let err = summarize(
+            &MockHevyClient::default(),
+            "w1",
+            &Style::new(false, Some(120), false),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("get_workout"), "{err}");
+    }
+
+    #[test]
+    fn summary_shows_warmup_bodyweight_and_missing_reps() {
+        let workout: Workout = serde_json::from_str(include_str!(
+            "../tests/fixtures/api/workout_mixed_sets.json"
+        ))
+        .unwrap();
+        let text = summary(&workout, None, false);
+        let line = |label: &str, exercise: &str| {
+            text.lines()
+                .skip_while(|l| !l.contains(exercise))
+                .find(|l| l.contains(label))
+                .unwrap_or_else(|| panic!("no {label} row under {exercise} in:\n{text}"))
+                .to_string()
+        };
+
+        let warmup = line("Set 1 (warmup)", "Bench Press");
+        assert!(
+            warmup.contains("132.3") && warmup.contains("10"),
+            "{warmup}"
+        );
+        // A set logged without reps shows a dash and counts as short.
+        let no_reps = line("Set 3 (normal)", "Bench Press");
+        assert!(
+            no_reps.contains('—') && no_reps.contains("Struggled"),
+            "{no_reps}"
+        );
+        let failure = line("Set 4 (failure)", "Bench Press");
+        assert!(failure.contains("RPE 10"), "{failure}");
+        // Bodyweight sets show no added weight.
+        let bodyweight = line("Set 1 (normal)", "Pull Up");
+        assert!(
+            bodyweight.contains(" 0.0 ") && bodyweight.contains("Succeeded"),
+            "{bodyweight}"
+        );
+        // A set without a type is labelled by its number alone.
+        let untyped = line("Set 1", "Landmine Press");
+        assert!(
+            !untyped.contains('(') && untyped.contains("Exceeded"),
+            "{untyped}"
+        );
+    }
+
+    #[test]
+    fn summary_brackets_supersets() {
+        let workout: Workout = serde_json::from_value(json!({
+            "title": "Arms",
+            "exercises": [
+                { "title": "Bicep Curl (Dumbbell)", "supersets_id": 0,
+                  "sets": [{ "type": "normal", "weight_kg": 12, "reps": 10 }] },
+                { "title": "Triceps Pushdown", "supersets_id": 0,
+                  "sets": [{ "type": "normal", "weight_kg": 25, "reps": 10 }] },
+                { "title": "Plank", "supersets_id": null,
+                  "sets": [{ "type": "normal", "duration_seconds": 60 }] }
+            ]
+        }))
+        .unwrap();
+        let text = summary(&workout, None, false);
+        let rows = [
+            SUPERSET_START,
+            "Bicep Curl (Dumbbell)",
+            "Triceps Pushdown",
+            SUPERSET_END,
+            "Plank",
+        ];
+        let names: Vec<&str> = text
+            .lines()
+            .filter_map(|l| rows.iter().copied().find(|row| l.trim().starts_with(row)))
+            .collect();
+        assert_eq!(names, rows, "{text}");
+    }
+}
@@ -0,0 +1,425 @@
+//! Routines for a whole training cycle, built from training maxes.
+
+use anyhow::{Context, Result};
+
+use crate::models::{
+    PostRoutineBody, PostRoutineExercise, RoutineBuilder, RoutineExerciseBuilder, RoutineSetBuilder,
+};
+use crate::table::Units;
+
+/// A program `program generate` can build.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Template {
+    /// Wendler's 5/3/1: three weeks of 5s, 3s and 5/3/1 plus a deload,
+    /// one routine per lift each week.
+    #[value(name = "531", alias = "5/3/1")]
+    FiveThreeOne,
+    /// Linear progression: workouts A and B, alternated, of 3x5 at the
+    /// given weights (1x5 for the deadlift).
+    Lp,
+}
+
+/// A main lift of the programs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lift {
+    Squat,
+    Bench,
+    Deadlift,
+    Press,
+}
+
+impl Lift {
+    pub const ALL: [Lift; 4] = [Lift::Squat, Lift::Bench, Lift::Deadlift, Lift::Press];
+
+    /// The lift's key in `--tm`.
+    pub fn key(self) -> &'static str {
+        match self {
+            Lift::Squat => "squat",
+            Lift::Bench => "bench",
+            Lift::Deadlift => "deadlift",
+            Lift::Press => "press",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Lift::Squat => "Squat",
+            Lift::Bench => "Bench Press",
+            Lift::Deadlift => "Deadlift",
+            Lift::Press => "Overhead Press",
+        }
+    }
+}
+
+/// Weight of each lift in --units: the training max for 5/3/1, the
+/// working weight for linear progression. Indexed like [`Lift::ALL`].
+#[derive(Debug, Clone, Copy)]
+pub struct TrainingMaxes([f64; 4]);
+
+impl TrainingMaxes {
+    fn get(&self, lift: Lift) -> f64 {
+        self.0[lift as usize]
+    }
+}
+
+/// Parse `squat=140,bench=100,deadlift=170,press=60`. Every lift is
+/// required.
+pub fn parse_training_maxes(s: &str) -> Result<TrainingMaxes> {
+    let mut maxes = [None; 4];
+    for pair in s.split(',') {
+        let (key, value) = pair
+            .split_once('=')
+            .with_context(|| format!("Invalid training max \"{pair}\". Use LIFT=WEIGHT"))?;
+        let key = key.trim().to_lowercase();
+        let lift = Lift::ALL
+            .into_iter()
+            .find(|l| l.key() == key)
+            .with_context(|| {
+                format!("Unknown lift \"{key}\". Expected squat, bench, deadlift and press")
+            })?;
+        let weight: f64 = value
+            .trim()
+            .parse()
+            .ok()
+            .filter(|w: &f64| w.is_finite() && *w > 0.0)
+            .with_context(|| format!("Invalid weight \"{}\" for {key}", value.trim()))?;
+        maxes[lift as usize] = Some(weight);
+    }
+    let mut weights = [0.0; 4];
+    for lift in Lift::ALL {
+        weights[lift as usize] = maxes[lift as usize]
+            .with_context(|| format!("Missing a training max for {}", lift.key()))?;
+    }
+    Ok(TrainingMaxes(weights))
+}
+
+/// One set of a week: a share of the training max, its reps, and whether
+/// it is taken for as many reps as possible.
+type Step = (f64, i64, bool);
+
+/// The 5/3/1 weeks, by name, with their three main sets.
+const FIVE_THREE_ONE: [(&str, [Step; 3]); 4] = [
+    (
+        "Week 1",
+        [(0.65, 5, false), (0.75, 5, false), (0.85, 5, true)],
+    ),
+    (
+        "Week 2",
+        [(0.70, 3, false), (0.80, 3, false), (0.90, 3, true)],
+    ),
+    (
+        "Week 3",
+        [(0.75, 5, false), (0.85, 3, false), (0.95, 1, true)],
+    ),
+    (
+        "Deload",
+        [(0.40, 5, false), (0.50, 5, false), (0.60, 5, false)],
+    ),
+];
+
+/// The linear progression workouts and their lifts.
+const LINEAR_PROGRESSION: [(&str, [Lift; 3]); 2] = [
+    ("A", [Lift::Squat, Lift::Bench, Lift::Deadlift]),
+    ("B", [Lift::Squat, Lift::Press, Lift::Deadlift]),
+];
+
+/// What the generated routines are built from.
+pub struct Program<'a> {
+    pub template: Template,
+    pub maxes: TrainingMaxes,
+    /// Exercise template ID of each lift, indexed like [`Lift::ALL`].
+    pub template_ids: [String; 4],
+    pub units: Units,
+    /// Increment working weights are rounded to, in `units`.
+    pub round_to: f64,
+    /// Exercises appended to every routine.
+    pub assistance: &'a [PostRoutineExercise],
+}
+
+impl Program<'_> {
+    /// Build every routine of the cycle, in the order they are trained.
+    pub fn routines(&self) -> Result<Vec<PostRoutineBody>> {
+        let mut routines = Vec::new();
+        match self.template {
+            Template::FiveThreeOne => {
+                let unit = match self.units {
+                    Units::Lbs => "lbs",
+                    Units::Kg => "kg",
+                };
+                for (week, steps) in FIVE_THREE_ONE {
+                    for lift in Lift::ALL {
+                        let tm = self.maxes.get(lift);
+                        let mut exercise = RoutineExerciseBuilder::new(self.id(lift))
+                            .notes(format!("Training max {tm} {unit}"));
+                        for (share, reps, amrap) in steps {
+                            let set = RoutineSetBuilder::normal()
+                                .weight_kg(self.kg(tm * share))
+                                .reps(reps);
+                            exercise = exercise.set(if amrap {
+                                set.min_reps(reps)
+                            } else {
+                                set.rep_range(reps, reps)
+                            });
+                        }
+                        routines.push(
+                            self.routine(
+                                format!("5/3/1 {week} - {}", lift.name()),
+                                vec![exercise],
+                            )?,
+                        );
+                    }
+                }
+            }
+            Template::Lp => {
+                for (day, lifts) in LINEAR_PROGRESSION {
+                    let exercises = lifts
+                        .into_iter()
+                        .map(|lift| {
+                            let sets = if lift == Lift::Deadlift { 1 } else { 3 };
+                            let weight = self.kg(self.maxes.get(lift));
+                            (0..sets).fold(RoutineExerciseBuilder::new(self.id(lift)), |e, _| {
+                                e.set(
+                                    RoutineSetBuilder::normal()
+                                        .weight_kg(weight)
+                                        .reps(5)
+                                        .rep_range(5, 5),
+                                )
+                            })
+                        })
+                        .collect();
+                    routines.push(self.routine(format!("LP Workout {day}"), exercises)?);
+                }
+            }
+        }
+        Ok(routines)
+    }
+
+    fn id(&self, lift: Lift) -> &str {
+        &self.template_ids[lift as usize]
+    }
+
+    /// `weight` in `units` rounded to the nearest increment, in kg.
+    fn kg(&self, weight: f64) -> f64 {
+        let rounded = (weight / self.round_to).round() * self.round_to;
+        let kg = rounded / self.units.convert(1.0);
+        (kg * 1000.0).round() / 1000.0
+    }
+
+    fn routine(
+        &self,
+        title: String,
+        exercises: Vec<RoutineExerciseBuilder>,
+    ) -> Result<PostRoutineBody> {
+        let mut builder = RoutineBuilder::new(title);
+        for exercise in exercises {
+            builder = builder.exercise(exercise);
+        }
+        let mut body = builder.build()?;
+        body.routine
+            .exercises
+            .extend(self.assistance.iter().cloned());
+        Ok(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PostRoutineSet;
+
+    const IDS: [&str; 4] = ["D04AC939", "79D0BB3A", "C6272009", "7B8D84E8"];
+
+    fn program(template: Template, maxes: &str, units: Units, round_to: f64) -> Program<'static> {
+        Program {
+            template,
+            maxes: parse_training_maxes(maxes).unwrap(),
+            template_ids: IDS.map(String::from),
+            units,
+            round_to,
+            assistance: &[],
+        }
+    }
+
+    /// Weight in kg, and the rep range start and end, of each set.
+    fn sets(sets: &[PostRoutineSet]) -> Vec<(f64, Option<i64>, Option<i64>)> {
+        sets.iter()
+            .map(|s| {
+                let range = s.rep_range.as_ref().unwrap();
+                (s.weight_kg.unwrap(), range.start, range.end)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn five_three_one_weeks_take_their_percentages() {
+        let program = program(
+            Template::FiveThreeOne,
+            "squat=100,bench=80,deadlift=140,press=50",
+            Units::Kg,
+            2.5,
+        );
+        let routines = program.routines().unwrap();
+        assert_eq!(routines.len(), 16);
+        let titles: Vec<_> = routines.iter().map(|r| r.routine.title.as_str()).collect();
+        assert_eq!(
+            titles[..2],
+            ["5/3/1 Week 1 - Squat", "5/3/1 Week 1 - Bench Press"]
+        );
+        assert_eq!(titles[15], "5/3/1 Deload - Overhead Press");
+
+        let week_1_squat = &routines[0].routine.exercises[0];
+        assert_eq!(week_1_squat.exercise_template_id, "D04AC939");
+        assert_eq!(week_1_squat.notes.as_deref(), Some("Training max 100 kg"));
+        // The last set of each working week is for as many reps as
+        // possible: a minimum with no upper bound.
+        assert_eq!(
+            sets(&week_1_squat.sets),
+            [
+                (65.0, Some(5), Some(5)),
+                (75.0, Some(5), Some(5)),
+                (85.0, Some(5), None)
+            ]
+        );
+        // Week 2 deadlift: 70/80/90% of 140, 3s.
+        assert_eq!(
+            sets(&routines[6].routine.exercises[0].sets),
+            [
+                (97.5, Some(3), Some(3)),
+                (112.5, Some(3), Some(3)),
+                (125.0, Some(3), None)
+            ]
+        );
+        // Week 3 bench: 75/85/95% of 80 is 60, 68 and 76, to 2.5 kg.
+        assert_eq!(
+            sets(&routines[9].routine.exercises[0].sets),
+            [
+                (60.0, Some(5), Some(5)),
+                (67.5, Some(3), Some(3)),
+                (75.0, Some(1), None)
+            ]
+        );
+        // The deload has no AMRAP set.
+        assert_eq!(
+            sets(&routines[15].routine.exercises[0].sets),
+            [
+                (20.0, Some(5), Some(5)),
+                (25.0, Some(5), Some(5)),
+                (30.0, Some(5), Some(5))
+            ]
+        );
+    }
+
+    /// Template ID, set count and first set's weight in kg of each exercise.
+    fn shape(routine: &PostRoutineBody) -> Vec<(&str, usize, f64)> {
+        routine
+            .routine
+            .exercises
+            .iter()
+            .map(|e| {
+                let first = e.sets[0].weight_kg.unwrap();
+                (e.exercise_template_id.as_str(), e.sets.len(), first)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn linear_progression_is_3x5_with_a_1x5_deadlift() {
+        let program = program(
+            Template::Lp,
+            "squat=100,bench=60,deadlift=120,press=40",
+            Units::Kg,
+            2.5,
+        );
+        let routines = program.routines().unwrap();
+        let titles: Vec<_> = routines.iter().map(|r| r.routine.title.as_str()).collect();
+        assert_eq!(titles, ["LP Workout A", "LP Workout B"]);
+
+        assert_eq!(
+            shape(&routines[0]),
+            [
+                ("D04AC939", 3, 100.0),
+                ("79D0BB3A", 3, 60.0),
+                ("C6272009", 1, 120.0)
+            ]
+        );
+        assert_eq!(
+            shape(&routines[1]),
+            [
+                ("D04AC939", 3, 100.0),
+                ("7B8D84E8", 3, 40.0),
+                ("C6272009", 1, 120.0)
+            ]
+        );
+        assert_eq!(
+            sets(&routines[0].routine.exercises[0].sets),
+            [(100.0, Some(5), Some(5)); 3]
+        );
+    }
+
+    #[test]
+    fn weights_round_in_the_units_given() {
+        let kg = program(
+            Template::Lp,
+            "squat=1,bench=1,deadlift=1,press=1",
+            Units::Kg,
+            2.5,
+        );
+        assert_eq!(kg.kg(68.0), 67.5);
+        assert_eq!(kg.kg(68.8), 70.0);
+        let kg = program(
+            Template::Lp,
+            "squat=1,bench=1,deadlift=1,press=1",
+            Units::Kg,
+            1.0,
+        );
+        assert_eq!(kg.kg(68.4), 68.0);
+
+        // 187.4 lbs rounds to 185 lbs, sent as kg.
+        let lbs = program(
+            Template::Lp,
+            "squat=1,bench=1,deadlift=1,press=1",
+            Units::Lbs,
+            5.0,
+        );
+        assert_eq!(lbs.kg(187.4), 83.915);
+        assert_eq!(
+            (Units::Lbs.convert(lbs.kg(187.4)) * 10.0).round() / 10.0,
+            185.0
+        );
+    }
+
+    #[test]
+    fn training_maxes_need_every_lift_with_a_positive_weight() {
+        let maxes =
+            parse_training_maxes(" Squat = 140 ,bench=100,deadlift=170,press=60.5").unwrap();
+        assert_eq!(maxes.get(Lift::Squat), 140.0);
+        assert_eq!(maxes.get(Lift::Press), 60.5);
+
+        for (spec, error) in [
+            (
+                "squat=140,bench=100,deadlift=170",
+                "Missing a training max for press",
+            ),
+            (
+                "squat=140,bench=100,deadlift=170,curl=40",
+                "Unknown lift \"curl\"",
+            ),
+            (
+                "squat=0,bench=100,deadlift=170,press=60",
+                "Invalid weight \"0\" for squat",
+            ),
+            (
+                "squat=140,bench=-5,deadlift=170,press=60",
+                "Invalid weight \"-5\" for bench",
+            ),
+            (
+                "squat=140,bench=heavy,deadlift=170,press=60",
+                "Invalid weight \"heavy\"",
+            ),
+            ("squat 140", "Invalid training max \"squat 140\""),
+        ] {
+            let err = parse_training_maxes(spec).unwrap_err().to_string();
+            assert!(err.starts_with(error), "{spec}: {err}");
+        }
+    }
+}
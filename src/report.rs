@@ -0,0 +1,1315 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+
+use crate::analysis;
+use crate::dates::DateRange;
+use crate::models::{
+    ExerciseHistoryEntry, ExerciseTemplate, Routine, Set, Workout, format_timestamp,
+};
+use crate::table::{self, Column, Style, Table, Units};
+
+/// Exercises listed under `top_exercises`.
+const TOP_EXERCISES: usize = 5;
+
+/// A calendar month or year to report on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Month(NaiveDate),
+    Year(i32),
+}
+
+impl Period {
+    /// Parse a `--month` such as `2024-06`.
+    pub fn parse_month(s: &str) -> Result<Period> {
+        NaiveDate::parse_from_str(&format!("{}-01", s.trim()), "%Y-%m-%d")
+            .map(Period::Month)
+            .with_context(|| format!("Invalid month \"{s}\". Use YYYY-MM, e.g. 2024-06"))
+    }
+
+    /// First and last-plus-one days of the period.
+    fn days(self) -> Result<(NaiveDate, NaiveDate)> {
+        let (first, next) = match self {
+            Period::Month(first) => (
+                Some(first),
+                first.checked_add_months(chrono::Months::new(1)),
+            ),
+            Period::Year(year) => (
+                NaiveDate::from_ymd_opt(year, 1, 1),
+                NaiveDate::from_ymd_opt(year + 1, 1, 1),
+            ),
+        };
+        first.zip(next).context("Period out of range")
+    }
+
+    /// The period's `[start, end)` bounds, with days starting at midnight
+    /// in `tz`, or in the local time zone.
+    pub fn bounds(self, tz: Option<Tz>) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+        let (first, next) = self.days()?;
+        match tz {
+            Some(tz) => Ok((midnight(&tz, first)?, midnight(&tz, next)?)),
+            None => Ok((midnight(&Local, first)?, midnight(&Local, next)?)),
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Period::Month(first) => write!(f, "{:04}-{:02}", first.year(), first.month()),
+            Period::Year(year) => write!(f, "{year:04}"),
+        }
+    }
+}
+
+/// The start of `day` in `tz`. Where the clocks skip midnight, the day
+/// starts an hour later.
+fn midnight<Z: TimeZone>(tz: &Z, day: NaiveDate) -> Result<DateTime<Utc>> {
+    let midnight = day.and_time(NaiveTime::MIN);
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(midnight + TimeDelta::hours(1)))
+                .earliest()
+        })
+        .map(|t| t.with_timezone(&Utc))
+        .with_context(|| format!("{day} has no midnight in the time zone"))
+}
+
+/// `report period` output.
+#[derive(Debug, Serialize)]
+pub struct PeriodReport {
+    /// `2024-06` or `2024`.
+    pub period: String,
+    /// The `--tz` name, or `local`.
+    pub time_zone: String,
+    pub start: String,
+    /// Exclusive.
+    pub end: String,
+    pub workout_count: usize,
+    /// Summed over workouts with both a start and an end time.
+    pub total_duration_minutes: i64,
+    pub avg_duration_minutes: Option<f64>,
+    /// As [`Workout::total_volume_kg`] counts it.
+    pub total_volume_kg: f64,
+    /// Sets other than warmups, by the primary muscle group of their
+    /// exercise (`unknown` for exercises without a known template).
+    pub sets_per_muscle_group: BTreeMap<String, usize>,
+    pub top_exercises: Vec<ExerciseVolume>,
+    pub new_prs: Vec<PersonalRecord>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExerciseVolume {
+    pub exercise_template_id: String,
+    pub title: String,
+    pub volume_kg: f64,
+}
+
+/// An exercise's heaviest set in the period, when heavier than any set of
+/// it before. Exercises first done in the period have no earlier best to
+/// beat and are not listed.
+#[derive(Debug, Serialize)]
+pub struct PersonalRecord {
+    pub exercise_template_id: String,
+    pub title: String,
+    pub weight_kg: f64,
+    pub reps: Option<i64>,
+    pub previous_best_kg: f64,
+    pub workout_id: Option<String>,
+    pub date: String,
+}
+
+/// The heaviest set of an exercise in the period so far.
+struct Best<'a> {
+    set: &'a Set,
+    workout: &'a Workout,
+    start: DateTime<Utc>,
+}
+
+/// Summarize the workouts starting within `[start, end)`. `workouts` must
+/// reach back to the account's first workout, so PRs can be checked
+/// against every earlier set.
+pub fn period_report(
+    period: Period,
+    time_zone: String,
+    (start, end): (DateTime<Utc>, DateTime<Utc>),
+    workouts: &[Workout],
+    templates: &[ExerciseTemplate],
+) -> PeriodReport {
+    let templates: HashMap<&str, &ExerciseTemplate> = templates
+        .iter()
+        .filter_map(|t| Some((t.id.as_deref()?, t)))
+        .collect();
+    let mut titles: HashMap<&str, &str> = HashMap::new();
+
+    let mut workout_count = 0;
+    let mut minutes = Vec::new();
+    let mut total_volume_kg = 0.0;
+    let mut sets_per_muscle_group: BTreeMap<String, usize> = BTreeMap::new();
+    let mut volumes: HashMap<String, f64> = HashMap::new();
+    let mut best_before: HashMap<&str, f64> = HashMap::new();
+    let mut best_during: HashMap<&str, Best> = HashMap::new();
+
+    for w in workouts {
+        let Some(started) = w.start_time else {
+            continue;
+        };
+        if started >= end {
+            continue;
+        }
+        let during = started >= start;
+        if during {
+            workout_count += 1;
+            if let Some(ended) = w.end_time {
+                minutes.push((ended - started).num_minutes());
+            }
+            total_volume_kg += w.total_volume_kg();
+            for (id, volume) in w.per_exercise_volume() {
+                *volumes.entry(id).or_default() += volume;
+            }
+        }
+
+        for exercise in &w.exercises {
+            let id = exercise.exercise_template_id.as_deref();
+            let template = id.and_then(|id| templates.get(id));
+            let working_sets = exercise
+                .sets
+                .iter()
+                .filter(|s| s.set_type.as_deref() != Some("warmup"));
+            if during {
+                let muscle = template
+                    .and_then(|t| t.primary_muscle_group.clone())
+                    .unwrap_or_else(|| "unknown".to_string());
+                *sets_per_muscle_group.entry(muscle).or_default() += working_sets.clone().count();
+            }
+            let Some(id) = id else {
+                continue;
+            };
+            if let Some(title) = template
+                .and_then(|t| t.title.as_deref())
+                .or(exercise.title.as_deref())
+            {
+                titles.entry(id).or_insert(title);
+            }
+            for set in working_sets {
+                let weight = set.weight_kg.unwrap_or(0.0);
+                if weight <= 0.0 {
+                    continue;
+                }
+                if !during {
+                    let best = best_before.entry(id).or_default();
+                    *best = best.max(weight);
+                } else if best_during
+                    .get(id)
+                    .is_none_or(|b| weight > b.set.weight_kg.unwrap_or(0.0))
+                {
+                    best_during.insert(
+                        id,
+                        Best {
+                            set,
+                            workout: w,
+                            start: started,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    let title = |id: &str| {
+        titles
+            .get(id)
+            .copied()
+            .unwrap_or("Unknown Exercise")
+            .to_string()
+    };
+    let mut top_exercises: Vec<ExerciseVolume> = volumes
+        .into_iter()
+        .filter(|(_, volume)| *volume > 0.0)
+        .map(|(id, volume)| ExerciseVolume {
+            title: title(&id),
+            exercise_template_id: id,
+            volume_kg: round(volume),
+        })
+        .collect();
+    top_exercises.sort_by(|a, b| {
+        b.volume_kg
+            .total_cmp(&a.volume_kg)
+            .then_with(|| a.title.cmp(&b.title))
+    });
+    top_exercises.truncate(TOP_EXERCISES);
+
+    let mut new_prs: Vec<PersonalRecord> = best_during
+        .into_iter()
+        .filter_map(|(id, best)| {
+            let previous = *best_before.get(id)?;
+            let weight = best.set.weight_kg.unwrap_or(0.0);
+            (weight > previous).then(|| PersonalRecord {
+                exercise_template_id: id.to_string(),
+                title: title(id),
+                weight_kg: weight,
+                reps: best.set.reps,
+                previous_best_kg: previous,
+                workout_id: best.workout.id.clone(),
+                date: format_timestamp(&best.start),
+            })
+        })
+        .collect();
+    new_prs.sort_by(|a, b| a.title.cmp(&b.title));
+
+    let avg_duration_minutes = (!minutes.is_empty())
+        .then(|| round(minutes.iter().sum::<i64>() as f64 / minutes.len() as f64));
+    PeriodReport {
+        period: period.to_string(),
+        time_zone,
+        start: format_timestamp(&start),
+        end: format_timestamp(&end),
+        workout_count,
+        total_duration_minutes: minutes.iter().sum(),
+        avg_duration_minutes,
+        total_volume_kg: round(total_volume_kg),
+        sets_per_muscle_group,
+        top_exercises,
+        new_prs,
+    }
+}
+
+/// Round to one decimal place.
+fn round(v: f64) -> f64 {
+    (v * 10.0).round() / 10.0
+}
+
+/// Print `report` as a summary followed by tables of the top exercises,
+/// new PRs and sets per muscle group, each left out when empty.
+pub fn print_table(out: &mut impl Write, report: &PeriodReport, style: &Style) -> Result<()> {
+    let heading = format!("Training report: {} ({})", report.period, report.time_zone);
+    writeln!(out)?;
+    writeln!(out, "  {heading}")?;
+    writeln!(out, "  {}", "─".repeat(heading.chars().count()))?;
+    writeln!(out)?;
+
+    let units = match style.units {
+        Units::Lbs => "lbs",
+        Units::Kg => "kg",
+    };
+    let minutes = report.total_duration_minutes;
+    let avg = report
+        .avg_duration_minutes
+        .map_or("—".to_string(), |m| format!("{m:.0} min"));
+    writeln!(out, "  Workouts       {}", report.workout_count)?;
+    writeln!(
+        out,
+        "  Training time  {}h {:02}m",
+        minutes / 60,
+        minutes % 60
+    )?;
+    writeln!(out, "  Avg session    {avg}")?;
+    writeln!(
+        out,
+        "  Volume         {} {units}",
+        style.units.weight(report.total_volume_kg)
+    )?;
+    writeln!(out)?;
+
+    if !report.top_exercises.is_empty() {
+        let mut table = Table::new(vec![
+            Column::left("Top Exercise", 35).flex(),
+            Column::right(
+                match style.units {
+                    Units::Lbs => "Volume (lbs)",
+                    Units::Kg => "Volume (kg)",
+                },
+                14,
+            ),
+        ]);
+        for e in &report.top_exercises {
+            table.row(vec![
+                style
+                    .exercise_title(Some(&e.exercise_template_id), &e.title)
+                    .into(),
+                style.units.weight(e.volume_kg).into(),
+            ]);
+        }
+        table.render(out, style)?;
+        writeln!(out)?;
+    }
+
+    if !report.new_prs.is_empty() {
+        let mut table = Table::new(vec![
+            Column::left("New PR", 35).flex(),
+            Column::right(
+                match style.units {
+                    Units::Lbs => "Weight (lbs)",
+                    Units::Kg => "Weight (kg)",
+                },
+                13,
+            ),
+            Column::right("Reps", 5),
+            Column::right("Previous", 9),
+            Column::left("Date", 10),
+        ])
+        .gap("  ");
+        for pr in &report.new_prs {
+            table.row(vec![
+                style
+                    .exercise_title(Some(&pr.exercise_template_id), &pr.title)
+                    .into(),
+                style.units.weight(pr.weight_kg).into(),
+                pr.reps.map_or("—".to_string(), |r| r.to_string()).into(),
+                style.units.weight(pr.previous_best_kg).into(),
+                pr.date.get(..10).unwrap_or(&pr.date).into(),
+            ]);
+        }
+        table.render(out, style)?;
+        writeln!(out)?;
+    }
+
+    if !report.sets_per_muscle_group.is_empty() {
+        let mut table = Table::new(vec![
+            Column::left("Muscle Group", 18),
+            Column::right("Sets", 6),
+        ]);
+        let mut groups: Vec<_> = report.sets_per_muscle_group.iter().collect();
+        groups.sort_by_key(|(group, sets)| (std::cmp::Reverse(**sets), *group));
+        for (group, sets) in groups {
+            table.row(vec![group.as_str().into(), sets.to_string().into()]);
+        }
+        table.render(out, style)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// What `report calendar` shades each day by.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Metric {
+    /// Number of workouts started that day.
+    #[default]
+    Count,
+    /// Total volume of those workouts, in kg.
+    Volume,
+}
+
+/// The day the calendar's weeks (its columns) start on.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl WeekStart {
+    /// Row of `day` in its week's column.
+    fn row(self, day: NaiveDate) -> u32 {
+        match self {
+            WeekStart::Monday => day.weekday().num_days_from_monday(),
+            WeekStart::Sunday => day.weekday().num_days_from_sunday(),
+        }
+    }
+}
+
+/// Every day of `year` mapped to its `metric` value, zero on rest days.
+/// Workouts count on their start date in `tz`, or in the local time zone.
+pub fn calendar_days(
+    year: i32,
+    workouts: &[Workout],
+    metric: Metric,
+    tz: Option<Tz>,
+) -> Result<BTreeMap<NaiveDate, f64>> {
+    let (first, next) = Period::Year(year).days()?;
+    let mut days: BTreeMap<NaiveDate, f64> = first
+        .iter_days()
+        .take_while(|day| *day < next)
+        .map(|day| (day, 0.0))
+        .collect();
+    for w in workouts {
+        let Some(start) = w.start_time else {
+            continue;
+        };
+        let day = match tz {
+            Some(tz) => start.with_timezone(&tz).date_naive(),
+            None => start.with_timezone(&Local).date_naive(),
+        };
+        if let Some(value) = days.get_mut(&day) {
+            *value += match metric {
+                Metric::Count => 1.0,
+                Metric::Volume => w.total_volume_kg(),
+            };
+        }
+    }
+    Ok(days)
+}
+
+/// `days` for `report calendar --json`: workout counts as integers,
+/// volumes rounded to one decimal place.
+pub fn calendar_json(
+    days: &BTreeMap<NaiveDate, f64>,
+    metric: Metric,
+) -> BTreeMap<NaiveDate, serde_json::Value> {
+    days.iter()
+        .map(|(day, value)| {
+            let value = match metric {
+                Metric::Count => serde_json::json!(*value as u64),
+                Metric::Volume => serde_json::json!(round(*value)),
+            };
+            (*day, value)
+        })
+        .collect()
+}
+
+/// Shades of green for days with training, least to most.
+const SHADES: [u8; 4] = [28, 34, 40, 46];
+
+/// Draw `days` (one calendar year) like GitHub's contribution graph: a
+/// column per week, a row per weekday, month names along the top, and
+/// each day shaded by its value relative to the busiest day. Without
+/// color the shades are `·`, `▪` and `■`.
+pub fn print_calendar(
+    out: &mut impl Write,
+    days: &BTreeMap<NaiveDate, f64>,
+    week_start: WeekStart,
+    style: &Style,
+) -> Result<()> {
+    let (Some(first), Some(last)) = (days.keys().next(), days.keys().next_back()) else {
+        return Ok(());
+    };
+    let grid_start = *first - TimeDelta::days(week_start.row(*first).into());
+    let column = |day: NaiveDate| ((day - grid_start).num_days() / 7) as usize;
+    let columns = column(*last) + 1;
+    // Space the cells out when the line is wide enough.
+    let cell_width = if style.width >= LABEL_WIDTH + 2 * columns {
+        2
+    } else {
+        1
+    };
+    let max = days.values().copied().fold(0.0, f64::max);
+    let level = |value: f64| -> usize {
+        if value <= 0.0 {
+            0
+        } else {
+            ((value / max * 4.0).ceil() as usize).clamp(1, 4)
+        }
+    };
+    let glyph = |level: usize| -> String {
+        match (style.color, level) {
+            (true, 0) => "\x1b[2m·\x1b[0m".to_string(),
+            (true, l) => format!("\x1b[38;5;{}m■\x1b[0m", SHADES[l - 1]),
+            (false, 0) => "·".to_string(),
+            (false, 1 | 2) => "▪".to_string(),
+            (false, _) => "■".to_string(),
+        }
+    };
+
+    let mut months = " ".repeat(LABEL_WIDTH + cell_width * columns);
+    let mut free = 0;
+    for day in days.keys().filter(|day| day.day() == 1) {
+        let at = LABEL_WIDTH + cell_width * column(*day);
+        if at >= free && at + 3 <= months.len() {
+            months.replace_range(at..at + 3, &day.format("%b").to_string());
+            free = at + 4;
+        }
+    }
+    writeln!(out)?;
+    writeln!(out, "{}", months.trim_end())?;
+
+    let mut grid = vec![vec![None; columns]; 7];
+    for (day, value) in days {
+        grid[week_start.row(*day) as usize][column(*day)] = Some(level(*value));
+    }
+    let labelled = match week_start {
+        WeekStart::Monday => [0, 2, 4],
+        WeekStart::Sunday => [1, 3, 5],
+    };
+    for (row, cells) in grid.iter().enumerate() {
+        let label = match labelled.iter().position(|r| *r == row) {
+            Some(i) => ["Mon", "Wed", "Fri"][i],
+            None => "",
+        };
+        let mut line = format!("{label:<LABEL_WIDTH$}");
+        for cell in cells {
+            match cell {
+                Some(level) => line.push_str(&glyph(*level)),
+                None => line.push(' '),
+            }
+            if cell_width == 2 {
+                line.push(' ');
+            }
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+
+    let levels: &[usize] = if style.color {
+        &[0, 1, 2, 3, 4]
+    } else {
+        &[0, 1, 3]
+    };
+    let legend: Vec<String> = levels.iter().map(|l| glyph(*l)).collect();
+    let training_days = days.values().filter(|v| **v > 0.0).count();
+    writeln!(out)?;
+    writeln!(
+        out,
+        "{:LABEL_WIDTH$}Less {} More    {training_days} training day(s) in {}",
+        "",
+        legend.join(" "),
+        first.year()
+    )?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Width of the weekday labels left of the calendar grid.
+const LABEL_WIDTH: usize = 4;
+
+/// `report adherence` output.
+#[derive(Debug, Serialize)]
+pub struct AdherenceReport {
+    pub weeks: u32,
+    /// Start of the window; it ends now.
+    pub since: String,
+    /// Routines not performed for longer than this are flagged abandoned.
+    pub abandoned_after_weeks: u32,
+    /// Every routine, plus deleted ones that workouts in the window name,
+    /// most performed first.
+    pub routines: Vec<RoutineAdherence>,
+    /// Workouts in the window not started from a routine.
+    pub freestyle_workouts: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoutineAdherence {
+    pub routine_id: String,
+    /// `(deleted routine <id>)` when the routine no longer exists.
+    pub title: String,
+    pub deleted: bool,
+    pub workouts: usize,
+    /// Mean gap between consecutive sessions, null with fewer than two.
+    pub avg_days_between: Option<f64>,
+    /// Start of the latest session in the window.
+    pub last_performed: Option<String>,
+    /// Not performed within `abandoned_after_weeks`.
+    pub abandoned: bool,
+}
+
+/// Match the workouts started within `[since, now)` to `routines` by their
+/// `routine_id`. `abandoned_after` must not reach back past `since`, or
+/// routines last done before the window could not be told apart from
+/// abandoned ones.
+pub fn adherence_report(
+    now: DateTime<Utc>,
+    weeks: u32,
+    abandoned_after_weeks: u32,
+    workouts: &[Workout],
+    routines: &[Routine],
+) -> AdherenceReport {
+    let since = now - TimeDelta::weeks(weeks.into());
+    let abandoned_before = now - TimeDelta::weeks(abandoned_after_weeks.into());
+
+    let mut sessions: HashMap<&str, Vec<DateTime<Utc>>> = HashMap::new();
+    let mut freestyle_workouts = 0;
+    for w in workouts {
+        let Some(start) = w.start_time.filter(|s| (since..now).contains(s)) else {
+            continue;
+        };
+        match w.routine_id.as_deref() {
+            Some(id) if !id.is_empty() => sessions.entry(id).or_default().push(start),
+            _ => freestyle_workouts += 1,
+        }
+    }
+
+    let mut titles: BTreeMap<&str, Option<&str>> = routines
+        .iter()
+        .filter_map(|r| Some((r.id.as_deref()?, r.title.as_deref())))
+        .collect();
+    let known: HashSet<&str> = titles.keys().copied().collect();
+    for id in sessions.keys() {
+        titles.entry(id).or_insert(None);
+    }
+
+    let mut rows: Vec<RoutineAdherence> = titles
+        .into_iter()
+        .map(|(id, title)| {
+            let mut starts = sessions.remove(id).unwrap_or_default();
+            starts.sort();
+            let deleted = !known.contains(id);
+            let avg_days_between = (starts.len() >= 2).then(|| {
+                let span = *starts.last().unwrap() - starts[0];
+                round(span.num_minutes() as f64 / 1440.0 / (starts.len() - 1) as f64)
+            });
+            let last = starts.last().copied();
+            RoutineAdherence {
+                routine_id: id.to_string(),
+                title: match (deleted, title) {
+                    (true, _) => format!("(deleted routine {id})"),
+                    (false, Some(title)) => title.to_string(),
+                    (false, None) => "Untitled Routine".to_string(),
+                },
+                deleted,
+                workouts: starts.len(),
+                avg_days_between,
+                last_performed: last.as_ref().map(format_timestamp),
+                abandoned: last.is_none_or(|last| last < abandoned_before),
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.workouts
+            .cmp(&a.workouts)
+            .then_with(|| a.title.cmp(&b.title))
+    });
+
+    AdherenceReport {
+        weeks,
+        since: format_timestamp(&since),
+        abandoned_after_weeks,
+        routines: rows,
+        freestyle_workouts,
+    }
+}
+
+/// Print `report` as a table of routines followed by the freestyle count.
+pub fn print_adherence(
+    out: &mut impl Write,
+    report: &AdherenceReport,
+    style: &Style,
+) -> Result<()> {
+    let heading = format!("Routine adherence: last {} week(s)", report.weeks);
+    writeln!(out)?;
+    writeln!(out, "  {heading}")?;
+    writeln!(out, "  {}", "─".repeat(heading.chars().count()))?;
+    writeln!(out)?;
+
+    if !report.routines.is_empty() {
+        let mut table = Table::new(vec![
+            Column::left("Routine", 35).flex(),
+            Column::right("Workouts", 8),
+            Column::right("Avg Gap", 9),
+            Column::left("Last", 10),
+            Column::left("Abandoned", 9),
+        ])
+        .gap("  ");
+        for r in &report.routines {
+            table.row(vec![
+                r.title.as_str().into(),
+                r.workouts.to_string().into(),
+                r.avg_days_between
+                    .map_or("—".to_string(), |d| format!("{d:.1} d"))
+                    .into(),
+                r.last_performed
+                    .as_deref()
+                    .map_or("—", |d| d.get(..10).unwrap_or(d))
+                    .into(),
+                if r.abandoned { "yes" } else { "" }.into(),
+            ]);
+        }
+        table.render(out, style)?;
+        writeln!(out)?;
+    }
+    writeln!(out, "  Freestyle workouts  {}", report.freestyle_workouts)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// `report duration` output.
+#[derive(Debug, Serialize)]
+pub struct DurationTrend {
+    pub weeks: u32,
+    /// Start of the window; it ends now.
+    pub since: String,
+    /// Workouts in the window without an end time, or ending before they
+    /// start. They count toward the gaps, but not the session lengths.
+    pub skipped: usize,
+    pub avg_duration_minutes: Option<f64>,
+    pub median_duration_minutes: Option<f64>,
+    pub longest: Option<Session>,
+    pub shortest: Option<Session>,
+    /// Mean gap between the starts of consecutive workouts, in days.
+    pub avg_days_between: Option<f64>,
+    /// Oldest first.
+    pub sessions: Vec<Session>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Session {
+    pub workout_id: Option<String>,
+    pub title: Option<String>,
+    pub date: String,
+    pub duration_minutes: i64,
+}
+
+/// Session lengths and the gaps between workouts starting within
+/// `[since, now)`, where `since` is `weeks` before `now`.
+pub fn duration_trend(now: DateTime<Utc>, weeks: u32, workouts: &[Workout]) -> DurationTrend {
+    let since = now - TimeDelta::weeks(weeks.into());
+    let mut starts = Vec::new();
+    let mut sessions = Vec::new();
+    let mut skipped = 0;
+    for w in workouts {
+        let Some(start) = w.start_time.filter(|s| (since..now).contains(s)) else {
+            continue;
+        };
+        starts.push(start);
+        match w.end_time.filter(|end| *end > start) {
+            Some(end) => sessions.push((
+                start,
+                Session {
+                    workout_id: w.id.clone(),
+                    title: w.title.clone(),
+                    date: format_timestamp(&start),
+                    duration_minutes: (end - start).num_minutes(),
+                },
+            )),
+            None => skipped += 1,
+        }
+    }
+    sessions.sort_by_key(|(start, _)| *start);
+    let sessions: Vec<Session> = sessions.into_iter().map(|(_, s)| s).collect();
+    starts.sort();
+
+    let mut minutes: Vec<i64> = sessions.iter().map(|s| s.duration_minutes).collect();
+    minutes.sort();
+    let avg_duration_minutes = (!minutes.is_empty())
+        .then(|| round(minutes.iter().sum::<i64>() as f64 / minutes.len() as f64));
+    let median_duration_minutes = (!minutes.is_empty()).then(|| {
+        let mid = minutes.len() / 2;
+        match minutes.len() % 2 {
+            0 => (minutes[mid - 1] + minutes[mid]) as f64 / 2.0,
+            _ => minutes[mid] as f64,
+        }
+    });
+    // Ties go to the earliest session.
+    let longest = sessions
+        .iter()
+        .rev()
+        .max_by_key(|s| s.duration_minutes)
+        .cloned();
+    let shortest = sessions.iter().min_by_key(|s| s.duration_minutes).cloned();
+    let avg_days_between = (starts.len() >= 2).then(|| {
+        let span = *starts.last().unwrap() - starts[0];
+        round(span.num_minutes() as f64 / 1440.0 / (starts.len() - 1) as f64)
+    });
+
+    DurationTrend {
+        weeks,
+        since: format_timestamp(&since),
+        skipped,
+        avg_duration_minutes,
+        median_duration_minutes,
+        longest,
+        shortest,
+        avg_days_between,
+        sessions,
+    }
+}
+
+/// Print `trend` as a summary with a sparkline of session lengths.
+pub fn print_duration(out: &mut impl Write, trend: &DurationTrend, style: &Style) -> Result<()> {
+    let heading = format!("Session length: last {} week(s)", trend.weeks);
+    writeln!(out)?;
+    writeln!(out, "  {heading}")?;
+    writeln!(out, "  {}", "─".repeat(heading.chars().count()))?;
+    writeln!(out)?;
+
+    let session = |s: &Option<Session>| {
+        s.as_ref().map_or("—".to_string(), |s| {
+            format!(
+                "{} min  {}  {}",
+                s.duration_minutes,
+                s.date.get(..10).unwrap_or(&s.date),
+                s.title.as_deref().unwrap_or("Untitled")
+            )
+        })
+    };
+    let minutes = |m: Option<f64>| m.map_or("—".to_string(), |m| format!("{m:.0} min"));
+    writeln!(
+        out,
+        "  Sessions  {} ({} skipped)",
+        trend.sessions.len(),
+        trend.skipped
+    )?;
+    writeln!(out, "  Average   {}", minutes(trend.avg_duration_minutes))?;
+    writeln!(
+        out,
+        "  Median    {}",
+        minutes(trend.median_duration_minutes)
+    )?;
+    writeln!(out, "  Longest   {}", session(&trend.longest))?;
+    writeln!(out, "  Shortest  {}", session(&trend.shortest))?;
+    writeln!(
+        out,
+        "  Avg gap   {}",
+        trend
+            .avg_days_between
+            .map_or("—".to_string(), |d| format!("{d:.1} days"))
+    )?;
+
+    if trend.sessions.len() >= 2 {
+        let values: Vec<f64> = trend
+            .sessions
+            .iter()
+            .map(|s| s.duration_minutes as f64)
+            .collect();
+        let label = "  Trend     ";
+        writeln!(out)?;
+        writeln!(
+            out,
+            "{label}{}",
+            table::sparkline(&values, style.width.saturating_sub(label.len()).max(1))
+        )?;
+        let low = values.iter().copied().fold(f64::INFINITY, f64::min);
+        let high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        writeln!(
+            out,
+            "{:width$}{low}–{high} min, oldest to newest",
+            "",
+            width = label.len()
+        )?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// The aggregates `report compare` puts side by side for each window.
+#[derive(Debug, Serialize)]
+pub struct WindowMetrics {
+    pub start: String,
+    /// Exclusive.
+    pub end: String,
+    pub workout_count: usize,
+    /// As [`Workout::total_volume_kg`] counts it.
+    pub total_volume_kg: f64,
+    /// Sets other than warmups, by the primary muscle group of their
+    /// exercise (`unknown` for exercises without a known template).
+    pub sets_per_muscle_group: BTreeMap<String, usize>,
+    /// Over workouts with both a start and an end time.
+    pub avg_duration_minutes: Option<f64>,
+    /// Exercise templates done at least once.
+    pub distinct_exercises: usize,
+}
+
+/// `report compare` output: both windows and how B differs from A.
+#[derive(Debug, Serialize)]
+pub struct Comparison {
+    pub a: WindowMetrics,
+    pub b: WindowMetrics,
+    pub deltas: Deltas,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Deltas {
+    pub workout_count: Delta,
+    pub total_volume_kg: Delta,
+    /// Muscle groups trained in either window.
+    pub sets_per_muscle_group: BTreeMap<String, Delta>,
+    /// Null unless both windows have a duration.
+    pub avg_duration_minutes: Option<Delta>,
+    pub distinct_exercises: Delta,
+}
+
+/// B minus A.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Delta {
+    pub absolute: f64,
+    /// Relative to A, null when A is 0.
+    pub percent: Option<f64>,
+}
+
+impl Delta {
+    fn new(a: f64, b: f64) -> Self {
+        Delta {
+            absolute: round(b - a),
+            percent: (a != 0.0).then(|| round((b - a) / a * 100.0)),
+        }
+    }
+}
+
+/// Aggregate `workouts` within each of the windows `a` and `b`, and the
+/// differences between them.
+pub fn compare(
+    a: DateRange,
+    b: DateRange,
+    workouts: &[Workout],
+    templates: &[ExerciseTemplate],
+) -> Comparison {
+    let templates: HashMap<&str, &ExerciseTemplate> = templates
+        .iter()
+        .filter_map(|t| Some((t.id.as_deref()?, t)))
+        .collect();
+    let a = window_metrics(a, workouts, &templates);
+    let b = window_metrics(b, workouts, &templates);
+    let count = |n: usize| n as f64;
+    let groups: BTreeSet<&String> = a
+        .sets_per_muscle_group
+        .keys()
+        .chain(b.sets_per_muscle_group.keys())
+        .collect();
+    let deltas = Deltas {
+        workout_count: Delta::new(count(a.workout_count), count(b.workout_count)),
+        total_volume_kg: Delta::new(a.total_volume_kg, b.total_volume_kg),
+        sets_per_muscle_group: groups
+            .into_iter()
+            .map(|group| {
+                let sets = |m: &WindowMetrics| {
+                    count(m.sets_per_muscle_group.get(group).copied().unwrap_or(0))
+                };
+                (group.clone(), Delta::new(sets(&a), sets(&b)))
+            })
+            .collect(),
+        avg_duration_minutes: a
+            .avg_duration_minutes
+            .zip(b.avg_duration_minutes)
+            .map(|(a, b)| Delta::new(a, b)),
+        distinct_exercises: Delta::new(count(a.distinct_exercises), count(b.distinct_exercises)),
+    };
+    Comparison { a, b, deltas }
+}
+
+fn window_metrics(
+    range: DateRange,
+    workouts: &[Workout],
+    templates: &HashMap<&str, &ExerciseTemplate>,
+) -> WindowMetrics {
+    let mut workout_count = 0;
+    let mut minutes = Vec::new();
+    let mut total_volume_kg = 0.0;
+    let mut sets_per_muscle_group: BTreeMap<String, usize> = BTreeMap::new();
+    let mut exercises: HashSet<&str> = HashSet::new();
+    for w in workouts {
+        let Some(started) = w
+            .start_time
+            .filter(|s| (range.start..range.end).contains(s))
+        else {
+            continue;
+        };
+        workout_count += 1;
+        if let Some(ended) = w.end_time {
+            minutes.push((ended - started).num_minutes());
+        }
+        total_volume_kg += w.total_volume_kg();
+        for exercise in &w.exercises {
+            let id = exercise.exercise_template_id.as_deref();
+            exercises.extend(id);
+            let muscle = id
+                .and_then(|id| templates.get(id))
+                .and_then(|t| t.primary_muscle_group.clone())
+                .unwrap_or_else(|| "unknown".to_string());
+            *sets_per_muscle_group.entry(muscle).or_default() += exercise
+                .sets
+                .iter()
+                .filter(|s| s.set_type.as_deref() != Some("warmup"))
+                .count();
+        }
+    }
+    WindowMetrics {
+        start: format_timestamp(&range.start),
+        end: format_timestamp(&range.end),
+        workout_count,
+        total_volume_kg: round(total_volume_kg),
+        sets_per_muscle_group,
+        avg_duration_minutes: (!minutes.is_empty())
+            .then(|| round(minutes.iter().sum::<i64>() as f64 / minutes.len() as f64)),
+        distinct_exercises: exercises.len(),
+    }
+}
+
+/// Print `comparison` as one table: a row per metric with A, B and the
+/// change from A to B.
+pub fn print_comparison(
+    out: &mut impl Write,
+    comparison: &Comparison,
+    style: &Style,
+) -> Result<()> {
+    let (a, b, deltas) = (&comparison.a, &comparison.b, &comparison.deltas);
+    let window = |m: &WindowMetrics| {
+        format!(
+            "{} until {}",
+            m.start.get(..10).unwrap_or(&m.start),
+            m.end.get(..10).unwrap_or(&m.end)
+        )
+    };
+    writeln!(out)?;
+    writeln!(out, "  A  {}", window(a))?;
+    writeln!(out, "  B  {}", window(b))?;
+    writeln!(out)?;
+
+    let mut table = Table::new(vec![
+        Column::left("Metric", 24).flex(),
+        Column::right("A", 10),
+        Column::right("B", 10),
+        Column::right("Change", 10),
+        Column::right("%", 8),
+    ])
+    .gap("  ");
+    let mut row = |metric: String, a: String, b: String, delta: Option<Delta>, weight: bool| {
+        let change = delta.map_or("—".to_string(), |d| {
+            let absolute = if weight {
+                style.units.weight(d.absolute.abs())
+            } else {
+                d.absolute.abs().to_string()
+            };
+            match d.absolute {
+                v if v > 0.0 => format!("+{absolute}"),
+                v if v < 0.0 => format!("-{absolute}"),
+                _ => "0".to_string(),
+            }
+        });
+        let percent = delta
+            .and_then(|d| d.percent)
+            .map_or("—".to_string(), |p| format!("{p:+.1}%"));
+        table.row(vec![
+            metric.into(),
+            a.into(),
+            b.into(),
+            change.into(),
+            percent.into(),
+        ]);
+    };
+    row(
+        "Workouts".to_string(),
+        a.workout_count.to_string(),
+        b.workout_count.to_string(),
+        Some(deltas.workout_count),
+        false,
+    );
+    row(
+        match style.units {
+            Units::Lbs => "Volume (lbs)",
+            Units::Kg => "Volume (kg)",
+        }
+        .to_string(),
+        style.units.weight(a.total_volume_kg),
+        style.units.weight(b.total_volume_kg),
+        Some(deltas.total_volume_kg),
+        true,
+    );
+    let minutes = |m: Option<f64>| m.map_or("—".to_string(), |m| format!("{m:.0}"));
+    row(
+        "Avg session (min)".to_string(),
+        minutes(a.avg_duration_minutes),
+        minutes(b.avg_duration_minutes),
+        deltas.avg_duration_minutes,
+        false,
+    );
+    row(
+        "Distinct exercises".to_string(),
+        a.distinct_exercises.to_string(),
+        b.distinct_exercises.to_string(),
+        Some(deltas.distinct_exercises),
+        false,
+    );
+    for (group, delta) in &deltas.sets_per_muscle_group {
+        let sets = |m: &WindowMetrics| {
+            m.sets_per_muscle_group
+                .get(group)
+                .copied()
+                .unwrap_or(0)
+                .to_string()
+        };
+        row(
+            format!("Sets: {group}"),
+            sets(a),
+            sets(b),
+            Some(*delta),
+            false,
+        );
+    }
+    table.render(out, style)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Sex the strength score coefficients are taken for.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sex {
+    #[default]
+    #[value(alias = "male")]
+    M,
+    #[value(alias = "female")]
+    F,
+}
+
+/// Wilks score of `total_kg` lifted at `bodyweight_kg` (the 2006
+/// coefficients, with body weight held to the range they were fitted on).
+pub fn wilks(sex: Sex, bodyweight_kg: f64, total_kg: f64) -> f64 {
+    let (coefficients, lowest, highest) = match sex {
+        Sex::M => (
+            [
+                -216.0475144,
+                16.2606339,
+                -0.002388645,
+                -0.00113732,
+                7.01863e-6,
+                -1.291e-8,
+            ],
+            40.0,
+            201.9,
+        ),
+        Sex::F => (
+            [
+                594.31747775582,
+                -27.23842536447,
+                0.82112226871,
+                -0.00930733913,
+                4.731582e-5,
+                -9.054e-8,
+            ],
+            26.51,
+            154.53,
+        ),
+    };
+    total_kg * 500.0 / polynomial(&coefficients, bodyweight_kg.clamp(lowest, highest))
+}
+
+/// DOTS score of `total_kg` lifted at `bodyweight_kg`, with body weight
+/// held to the range the coefficients were fitted on.
+pub fn dots(sex: Sex, bodyweight_kg: f64, total_kg: f64) -> f64 {
+    let (coefficients, highest) = match sex {
+        Sex::M => (
+            [
+                -307.75076,
+                24.0900756,
+                -0.1918759221,
+                0.0007391293,
+                -0.000001093,
+            ],
+            210.0,
+        ),
+        Sex::F => (
+            [
+                -57.96288,
+                13.6175032,
+                -0.1126655495,
+                0.0005158568,
+                -0.0000010706,
+            ],
+            150.0,
+        ),
+    };
+    total_kg * 500.0 / polynomial(&coefficients, bodyweight_kg.clamp(40.0, highest))
+}
+
+/// `coefficients[0] + coefficients[1] * x + coefficients[2] * x² + ...`
+fn polynomial(coefficients: &[f64], x: f64) -> f64 {
+    coefficients.iter().rev().fold(0.0, |sum, c| sum * x + c)
+}
+
+/// `report strength-score` output.
+#[derive(Debug, Serialize)]
+pub struct StrengthScore {
+    pub bodyweight_kg: f64,
+    pub sex: Sex,
+    /// Only sets up to the end of this day count, when given.
+    pub as_of: Option<NaiveDate>,
+    /// Whether lifts are the heaviest singles rather than the best e1RM.
+    pub singles_only: bool,
+    pub squat: LiftBest,
+    pub bench: LiftBest,
+    pub deadlift: LiftBest,
+    pub total_kg: f64,
+    pub wilks: f64,
+    pub dots: f64,
+}
+
+/// The set a lift's one-rep max is taken from.
+#[derive(Debug, Serialize)]
+pub struct LiftBest {
+    pub exercise_template_id: String,
+    /// The e1RM (Epley) of the set, or its weight for a single.
+    pub one_rep_max_kg: f64,
+    pub weight_kg: f64,
+    pub reps: i64,
+    pub workout_id: Option<String>,
+    pub date: Option<String>,
+}
+
+/// The set of `history` with the highest one-rep max: its e1RM, or with
+/// `singles_only` the weight of sets of one rep. None without such a set.
+pub fn best_lift(
+    exercise_template_id: &str,
+    history: &[ExerciseHistoryEntry],
+    singles_only: bool,
+) -> Option<LiftBest> {
+    history
+        .iter()
+        .filter_map(|entry| Some((entry, analysis::weight_reps(entry)?)))
+        .filter(|(_, (_, reps))| !singles_only || *reps == 1)
+        .map(|(entry, (weight, reps))| (entry, weight, reps, analysis::e1rm(weight, reps)))
+        .max_by(|a, b| a.3.total_cmp(&b.3))
+        .map(|(entry, weight, reps, max)| LiftBest {
+            exercise_template_id: exercise_template_id.to_string(),
+            one_rep_max_kg: round(max),
+            weight_kg: weight,
+            reps,
+            workout_id: entry.workout_id.clone(),
+            date: entry.workout_start_time.as_ref().map(format_timestamp),
+        })
+}
+
+/// Total the three lifts and score it.
+pub fn strength_score(
+    sex: Sex,
+    bodyweight_kg: f64,
+    as_of: Option<NaiveDate>,
+    singles_only: bool,
+    [squat, bench, deadlift]: [LiftBest; 3],
+) -> StrengthScore {
+    let total_kg = squat.one_rep_max_kg + bench.one_rep_max_kg + deadlift.one_rep_max_kg;
+    StrengthScore {
+        bodyweight_kg,
+        sex,
+        as_of,
+        singles_only,
+        squat,
+        bench,
+        deadlift,
+        total_kg: round(total_kg),
+        wilks: round(wilks(sex, bodyweight_kg, total_kg)),
+        dots: round(dots(sex, bodyweight_kg, total_kg)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Whether `actual` rounds to `expected` at the 4 decimals tables give.
+    fn close(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 5e-5
+    }
+
+    #[test]
+    fn coefficients_match_reference_values() {
+        // The score of a 1 kg total is the coefficient itself.
+        let wilks_m = wilks(Sex::M, 82.5, 1.0);
+        let dots_m = dots(Sex::M, 82.5, 1.0);
+        let wilks_f = wilks(Sex::F, 60.0, 1.0);
+        assert!(close(wilks_m, 0.6699), "{wilks_m}");
+        assert!(close(dots_m, 0.6774), "{dots_m}");
+        assert!(close(wilks_f, 1.1149), "{wilks_f}");
+        // Scores scale with the total.
+        assert!(close(wilks(Sex::M, 82.5, 600.0), 600.0 * wilks_m));
+    }
+
+    #[test]
+    fn body_weight_is_held_to_the_fitted_range() {
+        for (sex, lowest, highest) in [(Sex::M, 40.0, 201.9), (Sex::F, 26.51, 154.53)] {
+            assert_eq!(wilks(sex, lowest - 10.0, 500.0), wilks(sex, lowest, 500.0));
+            assert_eq!(
+                wilks(sex, highest + 50.0, 500.0),
+                wilks(sex, highest, 500.0)
+            );
+            assert_ne!(wilks(sex, lowest + 10.0, 500.0), wilks(sex, lowest, 500.0));
+        }
+        for (sex, highest) in [(Sex::M, 210.0), (Sex::F, 150.0)] {
+            assert_eq!(dots(sex, 30.0, 500.0), dots(sex, 40.0, 500.0));
+            assert_eq!(dots(sex, highest + 50.0, 500.0), dots(sex, highest, 500.0));
+            assert_ne!(dots(sex, 60.0, 500.0), dots(sex, 40.0, 500.0));
+        }
+    }
+}
@@ -0,0 +1,368 @@
+//! Matching exercise names, as people and agents write them, to templates.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use crate::models::ExerciseTemplate;
+
+/// Lowest score a match needs to be picked.
+pub const MIN_SCORE: f64 = 0.6;
+
+/// Two candidates scoring within this of each other are too close to pick
+/// between, unless the better one matches exactly.
+const AMBIGUITY_MARGIN: f64 = 0.05;
+
+/// Candidates listed under `alternatives`.
+const MAX_ALTERNATIVES: usize = 5;
+
+/// The outcome of resolving one name.
+///
+/// `id` and `matched_title` are null when no template scores at least
+/// [`MIN_SCORE`], or when the best two are too close to tell apart
+/// (`ambiguous`). `score` is then the best candidate's score.
+#[derive(Debug, Clone, Serialize)]
+pub struct Resolution {
+    pub id: Option<String>,
+    pub matched_title: Option<String>,
+    pub score: f64,
+    pub ambiguous: bool,
+    /// The next-best candidates, or the best ones when nothing was picked.
+    pub alternatives: Vec<Candidate>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+    pub id: Option<String>,
+    pub title: String,
+    pub score: f64,
+}
+
+/// Resolve `name` against `templates`: an alias from `aliases` (lowercase
+/// name to template ID), a template ID or an exact title (ignoring case)
+/// scores 1, anything else a fuzzy score below that.
+pub fn resolve(
+    name: &str,
+    templates: &[ExerciseTemplate],
+    aliases: &BTreeMap<String, String>,
+) -> Resolution {
+    if let Some(id) = aliases.get(&name.trim().to_lowercase()) {
+        return Resolution {
+            id: Some(id.clone()),
+            matched_title: templates
+                .iter()
+                .find(|t| t.id.as_ref() == Some(id))
+                .and_then(|t| t.title.clone()),
+            score: 1.0,
+            ambiguous: false,
+            alternatives: Vec::new(),
+        };
+    }
+    let query = Normalized::new(name);
+    let mut candidates: Vec<Candidate> = templates
+        .iter()
+        .filter_map(|t| {
+            let title = t.title.as_deref()?;
+            let score = if t.id.as_deref() == Some(name.trim()) {
+                1.0
+            } else {
+                score(name, &query, title)
+            };
+            Some(Candidate {
+                id: t.id.clone(),
+                title: title.to_string(),
+                score,
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.title.cmp(&b.title))
+    });
+
+    let best_score = candidates.first().map_or(0.0, |c| c.score);
+    let ambiguous = (MIN_SCORE..1.0).contains(&best_score)
+        && candidates
+            .get(1)
+            .is_some_and(|second| best_score - second.score < AMBIGUITY_MARGIN);
+    if best_score < MIN_SCORE || ambiguous {
+        candidates.truncate(MAX_ALTERNATIVES);
+        return Resolution {
+            id: None,
+            matched_title: None,
+            score: round(best_score),
+            ambiguous,
+            alternatives: candidates.into_iter().map(rounded).collect(),
+        };
+    }
+    let best = candidates.remove(0);
+    candidates.truncate(MAX_ALTERNATIVES);
+    Resolution {
+        id: best.id,
+        matched_title: Some(best.title),
+        score: round(best.score),
+        ambiguous,
+        alternatives: candidates
+            .into_iter()
+            .filter(|c| c.score >= MIN_SCORE)
+            .map(rounded)
+            .collect(),
+    }
+}
+
+/// How well `name` matches `title`, from 0 to 1.
+///
+/// Equal titles (ignoring case) score 1. The same words in any order and
+/// with any punctuation, e.g. `Barbell Bench Press` for
+/// `Bench Press (Barbell)`, or the same letters spaced differently, e.g.
+/// `Pullups` for `Pull Up`, score 0.95. Otherwise the score averages how
+/// many words the two share (allowing a typo in longer words) with how
+/// many letter pairs they share, scaled to stay below 0.95.
+fn score(name: &str, query: &Normalized, title: &str) -> f64 {
+    if name.trim().eq_ignore_ascii_case(title.trim()) {
+        return 1.0;
+    }
+    let title = Normalized::new(title);
+    if query.words.is_empty() || title.words.is_empty() {
+        return 0.0;
+    }
+    let query_set: HashSet<&str> = query.words.iter().map(String::as_str).collect();
+    let title_set: HashSet<&str> = title.words.iter().map(String::as_str).collect();
+    if query_set == title_set || query.joined.replace(' ', "") == title.joined.replace(' ', "") {
+        return 0.95;
+    }
+    let shared = query
+        .words
+        .iter()
+        .filter(|q| title.words.iter().any(|t| words_match(q, t)))
+        .count();
+    let words = 2.0 * shared as f64 / (query.words.len() + title.words.len()) as f64;
+    let letters = dice(&query.joined, &title.joined);
+    0.9 * (words + letters) / 2.0
+}
+
+/// A name lowercased and split into words, with punctuation (such as the
+/// parentheses around equipment) dropped and plural words made singular.
+struct Normalized {
+    words: Vec<String>,
+    joined: String,
+}
+
+impl Normalized {
+    fn new(s: &str) -> Self {
+        let lower = s.to_lowercase();
+        let words: Vec<String> = lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(singular)
+            .collect();
+        let joined = words.join(" ");
+        Normalized { words, joined }
+    }
+}
+
+/// `curls` → `curl`, `flies` → `fly`, leaving `press` and `abs`-length
+/// words alone.
+fn singular(word: &str) -> String {
+    if let Some(stem) = word.strip_suffix("ies")
+        && stem.len() >= 2
+    {
+        return format!("{stem}y");
+    }
+    if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") && !word.ends_with("us") {
+        return word[..word.len() - 1].to_string();
+    }
+    word.to_string()
+}
+
+/// Equal, or one edit apart when both are at least five letters long.
+fn words_match(a: &str, b: &str) -> bool {
+    a == b || (a.len() >= 5 && b.len() >= 5 && edit_distance(a, b) <= 1)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Sørensen–Dice coefficient of the letter pairs of `a` and `b`.
+fn dice(a: &str, b: &str) -> f64 {
+    let pairs = |s: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let (a, mut b) = (pairs(a), pairs(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let total = a.len() + b.len();
+    let mut shared = 0;
+    for pair in &a {
+        if let Some(i) = b.iter().position(|p| p == pair) {
+            b.swap_remove(i);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+fn round(score: f64) -> f64 {
+    (score * 1000.0).round() / 1000.0
+}
+
+fn rounded(c: Candidate) -> Candidate {
+    Candidate {
+        score: round(c.score),
+        ..c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn templates() -> Vec<ExerciseTemplate> {
+        serde_json::from_value(json!([
+            { "id": "79D0BB3A", "title": "Bench Press (Barbell)" },
+            { "id": "3601968B", "title": "Bench Press (Dumbbell)" },
+            { "id": "50DFDFAB", "title": "Incline Bench Press (Barbell)" },
+            { "id": "1B2B1E7C", "title": "Pull Up" },
+            { "id": "37FCC2BB", "title": "Bicep Curl (Dumbbell)" },
+            { "id": "12345678", "title": "Chest Fly (Dumbbell)" },
+            { "id": "422B08F1", "title": "Lateral Raise (Dumbbell)" },
+            { "id": "D04AC939", "title": "Squat (Barbell)" },
+            { "id": "00000000", "title": null }
+        ]))
+        .unwrap()
+    }
+
+    fn resolve_name(name: &str) -> Resolution {
+        resolve(name, &templates(), &BTreeMap::new())
+    }
+
+    fn picked(name: &str) -> (Option<String>, f64) {
+        let r = resolve_name(name);
+        assert!(!r.ambiguous, "{name} is ambiguous: {r:?}");
+        (r.id, r.score)
+    }
+
+    #[test]
+    fn exact_titles_and_ids_score_one() {
+        assert_eq!(
+            picked("Bench Press (Barbell)"),
+            (Some("79D0BB3A".into()), 1.0)
+        );
+        assert_eq!(picked("  squat (BARBELL) "), (Some("D04AC939".into()), 1.0));
+        assert_eq!(picked("1B2B1E7C"), (Some("1B2B1E7C".into()), 1.0));
+    }
+
+    #[test]
+    fn equipment_may_come_first_or_lose_its_parentheses() {
+        for name in [
+            "Barbell Bench Press",
+            "bench press barbell",
+            "Bench-Press, Barbell",
+        ] {
+            assert_eq!(picked(name), (Some("79D0BB3A".into()), 0.95), "{name}");
+        }
+        assert_eq!(
+            picked("Dumbbell Bench Press"),
+            (Some("3601968B".into()), 0.95)
+        );
+    }
+
+    #[test]
+    fn plurals_match_the_singular_title() {
+        assert_eq!(
+            picked("Dumbbell Bicep Curls"),
+            (Some("37FCC2BB".into()), 0.95)
+        );
+        assert_eq!(
+            picked("Chest Flies (Dumbbell)"),
+            (Some("12345678".into()), 0.95)
+        );
+        assert_eq!(singular("press"), "press");
+        assert_eq!(singular("abs"), "abs");
+        assert_eq!(singular("biceps"), "bicep");
+    }
+
+    #[test]
+    fn spacing_differences_match() {
+        for name in ["Pullups", "pullup", "Pull-Up"] {
+            assert_eq!(picked(name).0.as_deref(), Some("1B2B1E7C"), "{name}");
+        }
+        assert_eq!(picked("Pullups").1, 0.95);
+    }
+
+    #[test]
+    fn a_typo_still_resolves_below_exact() {
+        let (id, score) = picked("Lateral Raize (Dumbbell)");
+        assert_eq!(id.as_deref(), Some("422B08F1"));
+        assert!((MIN_SCORE..0.95).contains(&score), "{score}");
+    }
+
+    #[test]
+    fn bench_press_alone_is_ambiguous() {
+        let r = resolve_name("Bench Press");
+        assert!(r.ambiguous, "{r:?}");
+        assert_eq!((r.id, r.matched_title), (None, None));
+        let titles: Vec<&str> = r.alternatives.iter().map(|c| c.title.as_str()).collect();
+        assert_eq!(
+            titles[..2],
+            ["Bench Press (Barbell)", "Bench Press (Dumbbell)"]
+        );
+        assert!(r.alternatives.len() <= MAX_ALTERNATIVES);
+        assert_eq!(r.score, r.alternatives[0].score);
+    }
+
+    #[test]
+    fn unknown_names_resolve_to_nothing() {
+        let r = resolve_name("Zercher Carry");
+        assert!(!r.ambiguous);
+        assert_eq!(r.id, None);
+        assert!(r.score < MIN_SCORE, "{r:?}");
+        assert!(r.alternatives.iter().all(|c| c.score < MIN_SCORE));
+
+        let r = resolve_name("");
+        assert_eq!((r.id, r.score), (None, 0.0));
+        assert_eq!(resolve("Squat", &[], &BTreeMap::new()).id, None);
+    }
+
+    #[test]
+    fn aliases_win_over_titles() {
+        let aliases = BTreeMap::from([("bench".to_string(), "3601968B".to_string())]);
+        let r = resolve(" Bench ", &templates(), &aliases);
+        assert_eq!(r.id.as_deref(), Some("3601968B"));
+        assert_eq!(r.matched_title.as_deref(), Some("Bench Press (Dumbbell)"));
+        assert_eq!(r.score, 1.0);
+    }
+
+    #[test]
+    fn alternatives_follow_the_pick() {
+        let r = resolve_name("Incline Bench Press (Barbell)");
+        assert_eq!(r.id.as_deref(), Some("50DFDFAB"));
+        assert!(
+            r.alternatives
+                .iter()
+                .all(|c| c.score >= MIN_SCORE && c.score < 1.0),
+            "{r:?}"
+        );
+        assert_eq!(
+            r.alternatives.first().map(|c| c.title.as_str()),
+            Some("Bench Press (Barbell)")
+        );
+    }
+}
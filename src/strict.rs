@@ -0,0 +1,96 @@
+//! Checking that the models capture every field of an API response.
+
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+/// How a [`HevyClient`](crate::HevyClient) treats response fields the
+/// models do not capture, such as fields the API has added since.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// Drop them silently (the default).
+    #[default]
+    Lenient,
+    /// Log a warning naming them, and carry on.
+    Warn,
+    /// Fail the request with [`Error::UnknownFields`](crate::Error::UnknownFields).
+    Error,
+}
+
+/// Paths of the fields in `raw` that are missing from `captured`, the
+/// parsed model serialized again, e.g. `workouts[].exercises[].tempo`.
+///
+/// Fields that are null in `raw` carry no data and are not reported.
+pub(crate) fn uncaptured_fields(raw: &Value, captured: &Value) -> Vec<String> {
+    let mut fields = BTreeSet::new();
+    walk(raw, captured, "", &mut fields);
+    fields.into_iter().collect()
+}
+
+fn walk(raw: &Value, captured: &Value, path: &str, fields: &mut BTreeSet<String>) {
+    match (raw, captured) {
+        (Value::Object(raw), Value::Object(captured)) => {
+            for (key, value) in raw {
+                if value.is_null() {
+                    continue;
+                }
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                match captured.get(key) {
+                    Some(captured) => walk(value, captured, &field, fields),
+                    None => {
+                        fields.insert(field);
+                    }
+                }
+            }
+        }
+        (Value::Array(raw), Value::Array(captured)) => {
+            let items = format!("{path}[]");
+            for (raw, captured) in raw.iter().zip(captured) {
+                walk(raw, captured, &items, fields);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn nested_fields_are_named_by_path() {
+        let raw = json!({
+            "page": 1,
+            "page_total": 3,
+            "workouts": [
+                { "id": "a", "tempo": "3-1-1", "sets": [{ "reps": 5, "hr": 120 }] },
+                { "id": "b", "sets": [{ "reps": 5, "hr": 131 }, { "reps": 3 }] }
+            ]
+        });
+        let captured = json!({
+            "page": 1,
+            "workouts": [
+                { "id": "a", "sets": [{ "reps": 5 }] },
+                { "id": "b", "sets": [{ "reps": 5 }, { "reps": 3 }] }
+            ]
+        });
+        assert_eq!(
+            uncaptured_fields(&raw, &captured),
+            ["page_total", "workouts[].sets[].hr", "workouts[].tempo"]
+        );
+    }
+
+    #[test]
+    fn nulls_and_captured_fields_are_not_reported() {
+        let raw = json!({ "id": "a", "location": null, "sets": [{ "rpe": null }] });
+        let captured = json!({ "id": "a", "sets": [{}] });
+        assert!(uncaptured_fields(&raw, &captured).is_empty());
+        assert!(uncaptured_fields(&raw, &raw).is_empty());
+    }
+}
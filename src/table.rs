@@ -0,0 +1,636 @@
+use std::collections::HashMap;
+use std::io::Write;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// When to use ANSI colors in table output (`--color`).
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Color when writing to a terminal and NO_COLOR is unset.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Resolve the choice for output that is (or is not) going to a terminal.
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                is_terminal && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+            }
+        }
+    }
+}
+
+const LBS_PER_KG: f64 = 2.20462;
+
+/// Unit weights are shown in by table views (`--units`). JSON output always
+/// keeps the API's kilograms.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Units {
+    #[default]
+    Lbs,
+    Kg,
+}
+
+impl Units {
+    /// Convert a weight in kg to these units.
+    pub fn convert(self, kg: f64) -> f64 {
+        match self {
+            Units::Lbs => kg * LBS_PER_KG,
+            Units::Kg => kg,
+        }
+    }
+
+    /// A weight in kg formatted in these units, or "—" when there is no
+    /// weight.
+    pub fn weight(self, kg: f64) -> String {
+        if kg > 0.0 {
+            format!("{:.1}", self.convert(kg))
+        } else {
+            "—".to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Green,
+    Yellow,
+    Cyan,
+}
+
+impl Color {
+    fn ansi(self) -> &'static str {
+        match self {
+            Color::Green => "\x1b[32m",
+            Color::Yellow => "\x1b[33m",
+            Color::Cyan => "\x1b[36m",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// Line width used when output is not a terminal and `--width` is unset.
+pub const DEFAULT_WIDTH: usize = 120;
+
+/// Free-form columns are never squeezed narrower than this.
+const MIN_FREE_WIDTH: usize = 10;
+
+/// How tables are rendered: colors, the line width to fit, and (for tables
+/// with keyed columns) which columns to show.
+#[derive(Debug, Clone)]
+pub struct Style {
+    pub color: bool,
+    pub width: usize,
+    pub units: Units,
+    /// Keys of the columns to show (`--columns`); `None` shows them all.
+    pub columns: Option<Vec<String>>,
+    /// Exercise aliases by template ID, shown instead of exercise titles
+    /// (`--use-aliases`).
+    pub aliases: HashMap<String, String>,
+}
+
+impl Style {
+    /// Resolve the line width: an explicit `--width`, else the terminal's
+    /// width when writing to one, else [`DEFAULT_WIDTH`].
+    pub fn new(color: bool, width: Option<usize>, is_terminal: bool) -> Self {
+        let width = width
+            .or_else(|| {
+                is_terminal
+                    .then(terminal_size::terminal_size)
+                    .flatten()
+                    .map(|(w, _)| w.0 as usize)
+            })
+            .unwrap_or(DEFAULT_WIDTH);
+        Style {
+            color,
+            width,
+            units: Units::default(),
+            columns: None,
+            aliases: HashMap::new(),
+        }
+    }
+
+    pub fn units(mut self, units: Units) -> Self {
+        self.units = units;
+        self
+    }
+
+    pub fn columns(mut self, columns: Option<Vec<String>>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    pub fn aliases(mut self, aliases: HashMap<String, String>) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// An exercise's alias when one is set for `template_id`, else `title`.
+    pub fn exercise_title<'a>(&'a self, template_id: Option<&str>, title: &'a str) -> &'a str {
+        template_id
+            .and_then(|id| self.aliases.get(id))
+            .map_or(title, String::as_str)
+    }
+
+    fn shows(&self, column: &Column) -> bool {
+        match (&self.columns, column.key) {
+            (Some(keys), Some(key)) => keys.iter().any(|k| k == key),
+            _ => true,
+        }
+    }
+}
+
+/// A table column. Columns without a width hold free-form trailing text
+/// (such as notes); they are not padded, and are truncated to whatever
+/// room the line has left.
+pub struct Column {
+    header: &'static str,
+    width: Option<usize>,
+    align: Align,
+    key: Option<&'static str>,
+    flex: bool,
+}
+
+impl Column {
+    pub fn left(header: &'static str, width: usize) -> Self {
+        Column {
+            header,
+            width: Some(width),
+            align: Align::Left,
+            key: None,
+            flex: false,
+        }
+    }
+
+    pub fn right(header: &'static str, width: usize) -> Self {
+        Column {
+            header,
+            width: Some(width),
+            align: Align::Right,
+            ..Column::left(header, width)
+        }
+    }
+
+    pub fn free(header: &'static str) -> Self {
+        Column {
+            width: None,
+            ..Column::left(header, 0)
+        }
+    }
+
+    /// Name for selecting this column with `--columns`. Unkeyed columns are
+    /// always shown.
+    pub fn key(mut self, key: &'static str) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    /// Size this column to its content instead of its fixed width, shrinking
+    /// it (down to its header) when the line would not fit.
+    pub fn flex(mut self) -> Self {
+        self.flex = true;
+        self
+    }
+}
+
+/// One table cell, optionally colored. Padding is computed on the plain
+/// text's display width so colors, emoji and wide characters never disturb
+/// alignment.
+pub struct Cell {
+    text: String,
+    color: Option<Color>,
+}
+
+impl Cell {
+    pub fn colored(text: impl Into<String>, color: Color) -> Self {
+        Cell {
+            text: text.into(),
+            color: Some(color),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Cell { text, color: None }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        Cell::from(text.to_string())
+    }
+}
+
+/// Plain-text table renderer shared by every `--format table` view and
+/// the `process-workout` summary.
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<Cell>>,
+    gap: &'static str,
+}
+
+/// A shown column's position in each row and its resolved width
+/// (`None` for unpadded free-form text limited to `max`).
+struct Slot {
+    index: usize,
+    width: Option<usize>,
+    max: usize,
+    align: Align,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Table {
+            columns,
+            rows: Vec::new(),
+            gap: " ",
+        }
+    }
+
+    /// Separator between fixed-width columns (default: one space).
+    pub fn gap(mut self, gap: &'static str) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn row(&mut self, cells: Vec<Cell>) {
+        self.rows.push(cells);
+    }
+
+    /// Write the header, a ruler as wide as the widest line, and every row.
+    pub fn render(&self, out: &mut impl Write, style: &Style) -> Result<()> {
+        let slots = self.layout(style);
+        let header: Vec<Cell> = self.columns.iter().map(|c| Cell::from(c.header)).collect();
+        let lines: Vec<(String, usize)> = std::iter::once(&header)
+            .chain(&self.rows)
+            .map(|cells| self.format_row(&slots, cells, style.color))
+            .collect();
+        let ruler = lines.iter().map(|(_, width)| *width).max().unwrap_or(0);
+
+        let mut lines = lines.into_iter();
+        if let Some((line, _)) = lines.next() {
+            writeln!(out, "  {line}")?;
+        }
+        writeln!(out, "  {}", "─".repeat(ruler))?;
+        for (line, _) in lines {
+            writeln!(out, "  {line}")?;
+        }
+        Ok(())
+    }
+
+    /// Resolve the shown columns and their widths for `style.width`.
+    fn layout(&self, style: &Style) -> Vec<Slot> {
+        let mut slots: Vec<Slot> = self
+            .columns
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| style.shows(c))
+            .map(|(index, c)| Slot {
+                index,
+                width: c
+                    .width
+                    .map(|w| if c.flex { self.content_width(index) } else { w }),
+                max: usize::MAX,
+                align: c.align,
+            })
+            .collect();
+
+        // Two leading spaces, then each fixed column after its separator.
+        let gap = self.gap.width();
+        let mut used = 2;
+        for (i, slot) in slots.iter().enumerate() {
+            if let Some(w) = slot.width {
+                used += w + if i == 0 { 0 } else { gap };
+            }
+        }
+
+        // Leave free-form text its minimum room before squeezing flex columns.
+        let reserved = if slots.iter().any(|s| s.width.is_none()) {
+            3 + MIN_FREE_WIDTH
+        } else {
+            0
+        };
+        let mut excess = (used + reserved).saturating_sub(style.width);
+        for slot in &mut slots {
+            let column = &self.columns[slot.index];
+            if let (Some(w), true) = (slot.width.as_mut(), column.flex) {
+                let cut = excess.min(w.saturating_sub(column.header.width()));
+                *w -= cut;
+                used -= cut;
+                excess -= cut;
+            }
+        }
+
+        let room = style.width.saturating_sub(used + 3).max(MIN_FREE_WIDTH);
+        for slot in &mut slots {
+            if slot.width.is_none() {
+                slot.max = room;
+            }
+        }
+        slots
+    }
+
+    /// The widest text (header included) in column `index`.
+    fn content_width(&self, index: usize) -> usize {
+        self.rows
+            .iter()
+            .filter_map(|cells| cells.get(index))
+            .map(|c| c.text.width())
+            .chain(std::iter::once(self.columns[index].header.width()))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Render one row, returning it with its visible width.
+    fn format_row(&self, slots: &[Slot], cells: &[Cell], color: bool) -> (String, usize) {
+        let mut line = String::new();
+        let mut plain = String::new();
+        for (i, slot) in slots.iter().enumerate() {
+            let text = cells.get(slot.index).map(|c| c.text.as_str()).unwrap_or("");
+            let cell = match slot.width {
+                Some(w) => pad(&truncate(text, w), w, slot.align),
+                None if text.is_empty() => continue,
+                None => truncate(text, slot.max),
+            };
+            let sep = match (i, slot.width) {
+                (0, _) => "",
+                // Free-form text sits a little apart from the aligned columns.
+                (_, None) => "   ",
+                _ => self.gap,
+            };
+            line.push_str(sep);
+            plain.push_str(sep);
+            plain.push_str(&cell);
+            match cells.get(slot.index).and_then(|c| c.color) {
+                Some(c) if color => {
+                    // Color only the text, not its padding.
+                    let start = cell.len() - cell.trim_start().len();
+                    let end = cell.trim_end().len();
+                    line.push_str(&cell[..start]);
+                    line.push_str(c.ansi());
+                    line.push_str(&cell[start..end]);
+                    line.push_str("\x1b[0m");
+                    line.push_str(&cell[end..]);
+                }
+                _ => line.push_str(&cell),
+            }
+        }
+        let width = plain.trim_end().width();
+        (line.trim_end().to_string(), width)
+    }
+}
+
+/// Pad `text` to `width` terminal columns. `format!` padding counts chars,
+/// which misaligns emoji and full-width text.
+fn pad(text: &str, width: usize, align: Align) -> String {
+    let fill = " ".repeat(width.saturating_sub(text.width()));
+    match align {
+        Align::Left => format!("{text}{fill}"),
+        Align::Right => format!("{fill}{text}"),
+    }
+}
+
+/// Truncate a string to `max` terminal columns, appending "…" if shortened.
+///
+/// Cuts only between grapheme clusters, so combining marks and emoji
+/// sequences are never split.
+pub fn truncate(s: &str, max: usize) -> String {
+    if s.width() <= max {
+        return s.to_string();
+    }
+    let budget = max.saturating_sub(1);
+    let mut truncated = String::new();
+    let mut width = 0;
+    for grapheme in s.graphemes(true) {
+        width += grapheme.width();
+        if width > budget {
+            break;
+        }
+        truncated.push_str(grapheme);
+    }
+    format!("{truncated}…")
+}
+
+/// Block characters of a sparkline, lowest to highest.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Draw `values` as a one-line chart of block characters scaled from the
+/// smallest value to the largest. A series longer than `max` is averaged
+/// down, in runs of neighbouring values, to fit that many characters.
+pub fn sparkline(values: &[f64], max: usize) -> String {
+    let points: Vec<f64> = if values.len() > max && max > 0 {
+        let run = values.len().div_ceil(max);
+        values
+            .chunks(run)
+            .map(|chunk| chunk.iter().sum::<f64>() / chunk.len() as f64)
+            .collect()
+    } else {
+        values.to_vec()
+    };
+    let low = points.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = points.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    points
+        .iter()
+        .map(|v| {
+            if high <= low {
+                return SPARKS[SPARKS.len() / 2];
+            }
+            let level = ((v - low) / (high - low) * (SPARKS.len() - 1) as f64).round();
+            SPARKS[level as usize]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(table: &Table, style: &Style) -> String {
+        let mut out = Vec::new();
+        table.render(&mut out, style).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn results() -> Table {
+        let mut table = Table::new(vec![
+            Column::left("Exercise", 20),
+            Column::right("Result", 12),
+            Column::free("Notes"),
+        ]);
+        for (exercise, result, color) in [
+            ("Bench Press", "Succeeded", Color::Green),
+            ("Squat", "Struggled", Color::Yellow),
+            ("Deadlift", "Exceeded", Color::Cyan),
+        ] {
+            table.row(vec![
+                exercise.into(),
+                Cell::colored(result, color),
+                "".into(),
+            ]);
+        }
+        table
+    }
+
+    #[test]
+    fn no_escape_bytes_without_color() {
+        let plain = render(&results(), &Style::new(false, Some(80), false));
+        assert!(!plain.contains('\x1b'), "escape bytes in:\n{plain}");
+        assert!(plain.contains("Succeeded") && plain.contains("Exceeded"));
+
+        let colored = render(&results(), &Style::new(true, Some(80), false));
+        assert!(colored.contains("\x1b[32mSucceeded\x1b[0m"));
+        // Colors wrap the text only; the layout is the same.
+        let stripped = regex::Regex::new("\x1b\\[[0-9;]*m")
+            .unwrap()
+            .replace_all(&colored, "");
+        assert_eq!(stripped, plain);
+    }
+
+    #[test]
+    fn wide_and_combining_text_stays_aligned() {
+        let mut table = Table::new(vec![Column::left("Title", 12), Column::right("Sets", 4)]);
+        for title in [
+            "Leg Day",
+            "Leg Day 🔥",
+            // "e" and a combining acute accent: one column wide.
+            "Cafe\u{301} Pump",
+            "脚の日",
+            // Full-width text wider than the column is cut to fit.
+            "脚の日と背中の日",
+            "👨‍👩‍👧 Family",
+        ] {
+            table.row(vec![title.into(), "3".into()]);
+        }
+        let text = render(&table, &Style::new(false, Some(80), false));
+        let widths: Vec<usize> = text
+            .lines()
+            .filter(|line| !line.contains('─'))
+            .map(|line| line.width())
+            .collect();
+        assert_eq!(widths.len(), 7);
+        assert!(
+            widths.iter().all(|w| *w == widths[0]),
+            "{widths:?} in:\n{text}"
+        );
+        assert!(text.contains("脚の日と背…"));
+    }
+
+    #[test]
+    fn truncate_keeps_grapheme_clusters_whole() {
+        assert_eq!(truncate("Leg Day 🔥", 20), "Leg Day 🔥");
+        // The emoji is two columns, so it does not fit before the ellipsis.
+        assert_eq!(truncate("Leg Day 🔥🔥", 9), "Leg Day …");
+        assert_eq!(truncate("Cafe\u{301}s", 5), "Cafe\u{301}s");
+        assert_eq!(truncate("Cafe\u{301}teria", 5), "Cafe\u{301}…");
+        assert_eq!(truncate("脚の日と背中", 7), "脚の日…");
+        assert_eq!(truncate("👨‍👩‍👧👨‍👩‍👧", 3), "👨‍👩‍👧…");
+    }
+
+    fn summary() -> Table {
+        let mut table = Table::new(vec![
+            Column::left("Exercise", 35).key("exercise").flex(),
+            Column::right("Sets", 5).key("sets"),
+            Column::right("Weight (kg)", 18).key("weight"),
+            Column::right("Reps", 13).key("reps"),
+            Column::right("Result", 12).key("result"),
+            Column::free("Notes").key("notes"),
+        ]);
+        table.row(vec![
+            "Romanian Deadlift (Barbell) with Straps".into(),
+            "3".into(),
+            "100.0".into(),
+            "8".into(),
+            "Succeeded".into(),
+            "Slow eccentric, pause below the knee, bar close to the legs the whole way down".into(),
+        ]);
+        table.row(vec!["Plank".into(), "2".into(), "—".into(), "—".into()]);
+        table
+    }
+
+    fn line_widths(text: &str) -> Vec<usize> {
+        text.lines().map(|line| line.width()).collect()
+    }
+
+    #[test]
+    fn fits_80_columns() {
+        let text = render(&summary(), &Style::new(false, Some(80), false));
+        assert!(
+            line_widths(&text).iter().all(|w| *w <= 80),
+            "too wide:\n{text}"
+        );
+        // The exercise column gives way first; long notes end in an ellipsis.
+        assert!(!text.contains("with Straps"));
+        assert!(text.contains("Slow ecc") && !text.contains("Slow eccentric,"));
+        assert!(text.contains('…'));
+    }
+
+    #[test]
+    fn fits_120_columns() {
+        let text = render(&summary(), &Style::new(false, Some(120), false));
+        assert!(
+            line_widths(&text).iter().all(|w| *w <= 120),
+            "too wide:\n{text}"
+        );
+        assert!(text.contains("Romanian Deadlift (Barbell) with Straps"));
+        assert!(text.contains('…'));
+    }
+
+    #[test]
+    fn fits_200_columns() {
+        let text = render(&summary(), &Style::new(false, Some(200), false));
+        assert!(
+            line_widths(&text).iter().all(|w| *w <= 200),
+            "too wide:\n{text}"
+        );
+        // Everything fits: nothing is cut.
+        assert!(!text.contains('…'));
+        assert!(text.contains("close to the legs the whole way down"));
+    }
+
+    #[test]
+    fn selected_columns_only() {
+        let style = Style::new(false, Some(120), false)
+            .columns(Some(vec!["exercise".to_string(), "result".to_string()]));
+        let text = render(&summary(), &style);
+        let header = text.lines().next().unwrap();
+        assert_eq!(
+            header.split_whitespace().collect::<Vec<_>>(),
+            ["Exercise", "Result"]
+        );
+        assert!(!text.contains("Slow eccentric"));
+    }
+
+    #[test]
+    fn width_falls_back_without_a_terminal() {
+        assert_eq!(Style::new(false, None, false).width, DEFAULT_WIDTH);
+        assert_eq!(Style::new(false, Some(80), true).width, 80);
+    }
+
+    #[test]
+    fn color_choice() {
+        for is_terminal in [false, true] {
+            assert!(!ColorChoice::Never.enabled(is_terminal));
+            assert!(ColorChoice::Always.enabled(is_terminal));
+        }
+        // Piped output is never colored automatically, whatever NO_COLOR says.
+        assert!(!ColorChoice::Auto.enabled(false));
+    }
+}
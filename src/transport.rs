@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::error::{Error, Result};
+
+/// A response read in full.
+pub struct RawResponse {
+    pub status: StatusCode,
+    /// Parsed `Retry-After` header, when the server sent one.
+    pub retry_after: Option<Duration>,
+    pub body: Vec<u8>,
+}
+
+/// How [`HevyClient`](crate::client::HevyClient) gets responses: over HTTP,
+/// over HTTP while recording cassettes (`--record`), or from recorded
+/// cassettes without touching the network (`--replay`).
+pub trait Transport: Send + Sync {
+    /// Send `req` and read the whole response. `endpoint` names the call in
+    /// error messages (e.g. `GET /workouts`).
+    fn send<'a>(
+        &'a self,
+        req: reqwest::Request,
+        endpoint: &'a str,
+    ) -> BoxFuture<'a, Result<RawResponse>>;
+}
+
+/// Sends requests to the API with reqwest.
+pub struct HttpTransport {
+    client: Client,
+    timeout: Duration,
+    connect_timeout: Duration,
+}
+
+impl HttpTransport {
+    /// `timeout` and `connect_timeout` must be the ones `client` was built
+    /// with; they only appear in error messages.
+    pub fn new(client: Client, timeout: Duration, connect_timeout: Duration) -> Self {
+        HttpTransport {
+            client,
+            timeout,
+            connect_timeout,
+        }
+    }
+
+    /// Describe a failed send or body read. Timeouts get a plain message
+    /// naming the limit; anything else keeps reqwest's cause chain under
+    /// `context`.
+    fn error(&self, err: reqwest::Error, endpoint: &str, context: String) -> Error {
+        if !err.is_timeout() {
+            return Error::http(context, err);
+        }
+        let connecting = err.is_connect();
+        Error::Timeout {
+            endpoint: endpoint.to_string(),
+            limit: if connecting {
+                self.connect_timeout
+            } else {
+                self.timeout
+            },
+            connecting,
+        }
+    }
+}
+
+impl Transport for HttpTransport {
+    fn send<'a>(
+        &'a self,
+        req: reqwest::Request,
+        endpoint: &'a str,
+    ) -> BoxFuture<'a, Result<RawResponse>> {
+        async move {
+            let resp = self.client.execute(req).await.map_err(|e| {
+                self.error(e, endpoint, format!("Failed to send request to {endpoint}"))
+            })?;
+            let status = resp.status();
+            let retry_after = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+                .map(Duration::from_secs);
+            let body = resp.bytes().await.map_err(|e| {
+                self.error(e, endpoint, format!("Failed to read {endpoint} response"))
+            })?;
+            Ok(RawResponse {
+                status,
+                retry_after,
+                body: body.to_vec(),
+            })
+        }
+        .boxed()
+    }
+}
+
+/// One recorded request/response pair, stored as a JSON file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Cassette {
+    method: String,
+    url: String,
+    /// Request headers, with the API key redacted.
+    request_headers: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    request_body: Option<Value>,
+    status: u16,
+    /// The response body: JSON as-is, anything else as a string.
+    response_body: Value,
+}
+
+/// Sends requests over HTTP and saves each exchange to a cassette in `dir`.
+pub struct RecordingTransport {
+    inner: HttpTransport,
+    dir: PathBuf,
+}
+
+impl RecordingTransport {
+    pub fn new(inner: HttpTransport, dir: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            Error::io(
+                format!("Failed to create cassette directory {}", dir.display()),
+                e,
+            )
+        })?;
+        Ok(RecordingTransport { inner, dir })
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn send<'a>(
+        &'a self,
+        req: reqwest::Request,
+        endpoint: &'a str,
+    ) -> BoxFuture<'a, Result<RawResponse>> {
+        async move {
+            let path = cassette_path(&self.dir, &req);
+            let request_headers = req
+                .headers()
+                .iter()
+                .map(|(name, value)| {
+                    let value = if name == "api-key" {
+                        "<redacted>"
+                    } else {
+                        value.to_str().unwrap_or("<binary>")
+                    };
+                    (name.to_string(), value.to_string())
+                })
+                .collect();
+            let request_body = req.body().and_then(|b| b.as_bytes()).map(body_value);
+            let method = req.method().to_string();
+            let url = req.url().to_string();
+
+            let resp = self.inner.send(req, endpoint).await?;
+            let cassette = Cassette {
+                method,
+                url,
+                request_headers,
+                request_body,
+                status: resp.status.as_u16(),
+                response_body: body_value(&resp.body),
+            };
+            let json = serde_json::to_string_pretty(&cassette)
+                .map_err(|e| Error::json("Failed to serialize cassette", e))?;
+            std::fs::write(&path, json).map_err(|e| {
+                Error::io(format!("Failed to write cassette {}", path.display()), e)
+            })?;
+            Ok(resp)
+        }
+        .boxed()
+    }
+}
+
+/// Answers requests from cassettes in `dir`; a request without one fails.
+pub struct ReplayTransport {
+    dir: PathBuf,
+}
+
+impl ReplayTransport {
+    pub fn new(dir: PathBuf) -> Self {
+        ReplayTransport { dir }
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send<'a>(
+        &'a self,
+        req: reqwest::Request,
+        endpoint: &'a str,
+    ) -> BoxFuture<'a, Result<RawResponse>> {
+        async move {
+            let path = cassette_path(&self.dir, &req);
+            let Ok(data) = std::fs::read_to_string(&path) else {
+                return Err(Error::NotRecorded {
+                    endpoint: endpoint.to_string(),
+                    url: req.url().to_string(),
+                    path,
+                });
+            };
+            let invalid = || format!("Invalid cassette {}", path.display());
+            let cassette: Cassette =
+                serde_json::from_str(&data).map_err(|e| Error::json(invalid(), e))?;
+            let status = StatusCode::from_u16(cassette.status).map_err(|_| {
+                let reason = format!("invalid status {}", cassette.status);
+                Error::json(invalid(), serde::de::Error::custom(reason))
+            })?;
+            let body = match cassette.response_body {
+                Value::String(text) => text.into_bytes(),
+                json => serde_json::to_vec(&json).map_err(|e| Error::json(invalid(), e))?,
+            };
+            Ok(RawResponse {
+                status,
+                retry_after: None,
+                body,
+            })
+        }
+        .boxed()
+    }
+}
+
+/// Where the cassette for `req` lives: a readable method and path, plus a
+/// hash of the method, full URL and body so distinct requests never share
+/// a file.
+fn cassette_path(dir: &Path, req: &reqwest::Request) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(req.method().as_str());
+    hasher.update(req.url().as_str());
+    if let Some(body) = req.body().and_then(|b| b.as_bytes()) {
+        hasher.update(body);
+    }
+    let hash = hex::encode(hasher.finalize());
+    let slug: String = req
+        .url()
+        .path()
+        .trim_matches('/')
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .take(60)
+        .collect();
+    dir.join(format!("{}_{slug}_{}.json", req.method(), &hash[..12]))
+}
+
+/// A body as stored in a cassette: parsed JSON, else the text.
+fn body_value(body: &[u8]) -> Value {
+    serde_json::from_slice(body)
+        .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(body).into_owned()))
+}
@@ -0,0 +1,277 @@
+//! Warm-up sets worked out from each exercise's first working set.
+
+use anyhow::{Context, Result};
+
+use crate::models::{PostRoutineBody, PostWorkoutBody, RoutineSetBuilder, SetBuilder};
+use crate::plates;
+use crate::table::Units;
+
+/// A `--generate-warmups` spec such as `40%x5,60%x3,80%x1`: one warm-up
+/// set per step, lightest first.
+#[derive(Debug, Clone)]
+pub struct Spec(Vec<Step>);
+
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    /// Share of the working weight, in percent.
+    percent: f64,
+    reps: i64,
+}
+
+/// Parse a spec of comma-separated `PERCENT%xREPS` steps.
+pub fn parse_spec(s: &str) -> Result<Spec> {
+    let steps = s
+        .split(',')
+        .map(|step| {
+            let step = step.trim();
+            let (percent, reps) = step
+                .split_once(['x', 'X'])
+                .and_then(|(percent, reps)| {
+                    let percent: f64 = percent.trim().strip_suffix('%')?.trim().parse().ok()?;
+                    let reps: i64 = reps.trim().parse().ok()?;
+                    Some((percent, reps))
+                })
+                .filter(|(percent, reps)| *percent > 0.0 && *percent < 100.0 && *reps > 0)
+                .with_context(|| {
+                    format!(
+                        "Invalid warm-up step \"{step}\" in \"{s}\". Use PERCENT%xREPS with a \
+                         percentage below 100, e.g. 40%x5,60%x3,80%x1"
+                    )
+                })?;
+            Ok(Step { percent, reps })
+        })
+        .collect::<Result<_>>()?;
+    Ok(Spec(steps))
+}
+
+/// Turns a working weight into warm-up sets.
+pub struct Warmups {
+    spec: Spec,
+    units: Units,
+    /// Increment weights are rounded to, in `units`.
+    round_to: f64,
+    /// Bar weight in `units`: the lightest warm-up, and the working weight
+    /// below which an exercise gets none.
+    bar: f64,
+}
+
+impl Warmups {
+    /// Weights are rounded to `round_to` in `units`, by default 5 lbs or
+    /// 2.5 kg, and the bar is the standard one for `units`.
+    pub fn new(spec: Spec, units: Units, round_to: Option<f64>) -> Result<Self> {
+        let round_to = round_to.unwrap_or(plates::default_round_to(units));
+        if !(round_to.is_finite() && round_to > 0.0) {
+            anyhow::bail!("Invalid --round-to {round_to}: expected a positive weight");
+        }
+        Ok(Warmups {
+            spec,
+            units,
+            round_to,
+            bar: plates::default_bar(units),
+        })
+    }
+
+    /// Weight in kg and reps of each warm-up set for `working_kg`. Empty
+    /// when the working weight is below the bar.
+    fn sets(&self, working_kg: f64) -> Vec<(f64, i64)> {
+        let per_kg = self.units.convert(1.0);
+        let working = working_kg * per_kg;
+        if working < self.bar {
+            return Vec::new();
+        }
+        self.spec
+            .0
+            .iter()
+            .map(|step| {
+                let weight =
+                    (working * step.percent / 100.0 / self.round_to).round() * self.round_to;
+                let kg = weight.max(self.bar) / per_kg;
+                ((kg * 1000.0).round() / 1000.0, step.reps)
+            })
+            .collect()
+    }
+
+    /// Insert warm-up sets just before the first working (non-warm-up) set
+    /// of every exercise of `body` whose first working set is weighted, so
+    /// warm-ups already there come first.
+    pub fn add_to_workout(&self, body: &mut PostWorkoutBody) -> Result<()> {
+        for exercise in &mut body.workout.exercises {
+            let Some(first) = exercise.sets.iter().position(|s| s.set_type != "warmup") else {
+                continue;
+            };
+            let Some(working_kg) = exercise.sets[first].weight_kg else {
+                continue;
+            };
+            let warmups = self
+                .sets(working_kg)
+                .into_iter()
+                .map(|(kg, reps)| SetBuilder::warmup().weight_kg(kg).reps(reps).build())
+                .collect::<hevy_bridge::Result<Vec<_>>>()?;
+            exercise.sets.splice(first..first, warmups);
+        }
+        Ok(())
+    }
+
+    /// As [`Warmups::add_to_workout`], for a routine.
+    pub fn add_to_routine(&self, body: &mut PostRoutineBody) -> Result<()> {
+        for exercise in &mut body.routine.exercises {
+            let Some(first) = exercise.sets.iter().position(|s| s.set_type != "warmup") else {
+                continue;
+            };
+            let Some(working_kg) = exercise.sets[first].weight_kg else {
+                continue;
+            };
+            let warmups = self
+                .sets(working_kg)
+                .into_iter()
+                .map(|(kg, reps)| RoutineSetBuilder::warmup().weight_kg(kg).reps(reps).build())
+                .collect::<hevy_bridge::Result<Vec<_>>>()?;
+            exercise.sets.splice(first..first, warmups);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn steps(spec: &Spec) -> Vec<(f64, i64)> {
+        spec.0.iter().map(|s| (s.percent, s.reps)).collect()
+    }
+
+    fn warmups(spec: &str, units: Units, round_to: Option<f64>) -> Warmups {
+        Warmups::new(parse_spec(spec).unwrap(), units, round_to).unwrap()
+    }
+
+    #[test]
+    fn specs_parse_in_order() {
+        let spec = parse_spec("40%x5,60%x3,80%x1").unwrap();
+        assert_eq!(steps(&spec), [(40.0, 5), (60.0, 3), (80.0, 1)]);
+        let spec = parse_spec(" 50 % X 8 , 72.5%x2 ").unwrap();
+        assert_eq!(steps(&spec), [(50.0, 8), (72.5, 2)]);
+    }
+
+    #[test]
+    fn bad_steps_are_rejected() {
+        for spec in [
+            "0%x5", "100%x1", "120%x1", "50%x0", "50%x-1", "50x5", "50%", "", "40%x5,",
+        ] {
+            let err = parse_spec(spec).unwrap_err().to_string();
+            assert!(err.starts_with("Invalid warm-up step"), "{spec}: {err}");
+        }
+        assert!(Warmups::new(parse_spec("50%x5").unwrap(), Units::Kg, Some(0.0)).is_err());
+    }
+
+    #[test]
+    fn kg_weights_round_to_the_increment() {
+        let kg = warmups("37%x5,60%x3,81%x1", Units::Kg, None);
+        assert_eq!(kg.sets(100.0), [(37.5, 5), (60.0, 3), (80.0, 1)]);
+        let kg = warmups("37%x5", Units::Kg, Some(1.0));
+        assert_eq!(kg.sets(100.0), [(37.0, 5)]);
+    }
+
+    #[test]
+    fn lbs_weights_round_to_the_increment_in_lbs() {
+        // 100 kg is 220.5 lbs: 40% is 88.2 lbs, 90 to the nearest 5.
+        let lbs = warmups("40%x5,60%x3", Units::Lbs, None);
+        let in_lbs: Vec<_> = lbs
+            .sets(100.0)
+            .iter()
+            .map(|(kg, reps)| ((Units::Lbs.convert(*kg) * 100.0).round() / 100.0, *reps))
+            .collect();
+        assert_eq!(in_lbs, [(90.0, 5), (130.0, 3)]);
+    }
+
+    #[test]
+    fn warmups_never_go_below_the_bar() {
+        // 30% of 40 kg is 12.5, held at the 20 kg bar.
+        let kg = warmups("30%x5,75%x3", Units::Kg, None);
+        assert_eq!(kg.sets(40.0), [(20.0, 5), (30.0, 3)]);
+        // A working weight below the bar gets no warm-ups at all.
+        assert!(kg.sets(15.0).is_empty());
+    }
+
+    #[test]
+    fn warmups_go_before_the_first_working_set() {
+        let mut body: PostWorkoutBody = serde_json::from_value(json!({
+            "workout": {
+                "title": "Push",
+                "start_time": "2024-08-14T12:00:00Z",
+                "end_time": "2024-08-14T13:00:00Z",
+                "exercises": [
+                    {
+                        "exercise_template_id": "79D0BB3A",
+                        "sets": [
+                            { "type": "warmup", "weight_kg": 20, "reps": 10 },
+                            { "type": "normal", "weight_kg": 100, "reps": 5 },
+                            { "type": "normal", "weight_kg": 100, "reps": 5 }
+                        ]
+                    },
+                    {
+                        "exercise_template_id": "1B2B1E7C",
+                        "sets": [{ "type": "normal", "reps": 10 }]
+                    }
+                ]
+            }
+        }))
+        .unwrap();
+        warmups("40%x5,60%x3", Units::Kg, None)
+            .add_to_workout(&mut body)
+            .unwrap();
+
+        let sets: Vec<_> = body.workout.exercises[0]
+            .sets
+            .iter()
+            .map(|s| (s.set_type.as_str(), s.weight_kg))
+            .collect();
+        assert_eq!(
+            sets,
+            [
+                ("warmup", Some(20.0)),
+                ("warmup", Some(40.0)),
+                ("warmup", Some(60.0)),
+                ("normal", Some(100.0)),
+                ("normal", Some(100.0))
+            ]
+        );
+        // Bodyweight work gets none.
+        assert_eq!(body.workout.exercises[1].sets.len(), 1);
+    }
+
+    #[test]
+    fn routine_warmups_go_before_the_first_working_set() {
+        let mut body: PostRoutineBody = serde_json::from_value(json!({
+            "routine": {
+                "title": "Push",
+                "exercises": [{
+                    "exercise_template_id": "79D0BB3A",
+                    "sets": [
+                        { "type": "warmup", "weight_kg": 20, "reps": 10 },
+                        { "type": "normal", "weight_kg": 60, "reps": 8 }
+                    ]
+                }]
+            }
+        }))
+        .unwrap();
+        warmups("50%x5", Units::Kg, None)
+            .add_to_routine(&mut body)
+            .unwrap();
+
+        let sets: Vec<_> = body.routine.exercises[0]
+            .sets
+            .iter()
+            .map(|s| (s.set_type.as_str(), s.weight_kg))
+            .collect();
+        assert_eq!(
+            sets,
+            [
+                ("warmup", Some(20.0)),
+                ("warmup", Some(30.0)),
+                ("normal", Some(60.0))
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::client::HevyClient;
+use crate::models::WebhookPayload;
+use crate::process;
+use crate::table::Style;
+
+/// Default header carrying the hex-encoded HMAC-SHA256 of the request body.
+pub const SIGNATURE_HEADER: &str = "X-Hevy-Signature";
+
+/// Shared secret and the header its signatures arrive in.
+pub struct Signing {
+    pub secret: String,
+    pub header: String,
+}
+
+/// Check `signature_hex` (optionally prefixed with `sha256=`) against the
+/// HMAC-SHA256 of `body` keyed with `secret`, in constant time.
+pub fn verify_webhook_signature(body: &[u8], signature_hex: &str, secret: &str) -> bool {
+    let signature_hex = signature_hex.trim();
+    let signature_hex = signature_hex
+        .strip_prefix("sha256=")
+        .unwrap_or(signature_hex);
+    let Ok(signature) = hex::decode(signature_hex) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+/// Split a relayed HTTP message into its headers and body.
+///
+/// The input is an optional request line, `Name: value` header lines, a
+/// blank line, and the body. Input that starts directly with the JSON body
+/// has no headers. Line endings may be `\n` or `\r\n`; the body is returned
+/// byte for byte so its signature can be checked.
+pub fn split_http_message(input: &str) -> (Vec<(&str, &str)>, &str) {
+    if input.trim_start().starts_with('{') {
+        return (Vec::new(), input);
+    }
+    let mut headers = Vec::new();
+    let mut rest = input;
+    while let Some(end) = rest.find('\n') {
+        let line = rest[..end].trim_end_matches('\r');
+        rest = &rest[end + 1..];
+        if line.is_empty() {
+            return (headers, rest);
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim(), value.trim()));
+        }
+    }
+    // No blank line: everything was headers.
+    (headers, rest)
+}
+
+struct ServerState {
+    client: HevyClient,
+    signing: Option<Signing>,
+    style: Style,
+}
+
+/// Serve `POST /webhook` on `addr` until Ctrl-C, printing a workout summary
+/// to stdout for every valid payload received.
+pub async fn serve(
+    addr: &str,
+    client: HevyClient,
+    signing: Option<Signing>,
+    style: Style,
+) -> Result<()> {
+    let state = Arc::new(ServerState {
+        client,
+        signing,
+        style,
+    });
+    let app = Router::new()
+        .route("/webhook", post(handle_webhook))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind {addr}"))?;
+    eprintln!("Listening for webhooks on http://{addr}/webhook");
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+            eprintln!("Shutting down.");
+        })
+        .await
+        .context("Webhook server failed")
+}
+
+async fn handle_webhook(
+    State(state): State<Arc<ServerState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> (StatusCode, String) {
+    if let Some(signing) = &state.signing {
+        let signature = headers
+            .get(signing.header.as_str())
+            .and_then(|v| v.to_str().ok());
+        let Some(signature) = signature else {
+            eprintln!("Rejected webhook: missing {} header", signing.header);
+            return (StatusCode::UNAUTHORIZED, "missing signature\n".into());
+        };
+        if !verify_webhook_signature(&body, signature, &signing.secret) {
+            eprintln!("Rejected webhook: invalid signature");
+            return (StatusCode::UNAUTHORIZED, "invalid signature\n".into());
+        }
+    }
+
+    let payload: WebhookPayload = match serde_json::from_slice(&body) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Rejected webhook: invalid payload: {e}");
+            return (
+                StatusCode::BAD_REQUEST,
+                "expected {\"workoutId\":\"<UUID>\"}\n".into(),
+            );
+        }
+    };
+
+    match process::summarize(&state.client, &payload.workout_id, &state.style).await {
+        Ok(summary) => {
+            print!("{summary}");
+            (StatusCode::OK, "ok\n".into())
+        }
+        Err(e) => {
+            eprintln!("Failed to process workout {}: {e:#}", payload.workout_id);
+            (StatusCode::BAD_GATEWAY, format!("{e:#}\n"))
+        }
+    }
+}
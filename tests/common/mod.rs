@@ -0,0 +1,101 @@
+//! Running the hevy-bridge binary in isolation, for the CLI tests.
+
+// Each test file uses only some of these helpers.
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use serde_json::Value;
+use tempfile::TempDir;
+
+/// A home directory of its own for each run, so no stored config, API key
+/// or cache from the machine running the tests leaks in.
+pub struct Sandbox {
+    home: TempDir,
+}
+
+impl Sandbox {
+    pub fn new() -> Self {
+        Sandbox {
+            home: tempfile::tempdir().expect("failed to create a temporary home"),
+        }
+    }
+
+    pub fn home(&self) -> &Path {
+        self.home.path()
+    }
+
+    /// Where the binary reads its config file.
+    pub fn config_path(&self) -> PathBuf {
+        self.home()
+            .join("config")
+            .join("hevy-bridge")
+            .join("config.json")
+    }
+
+    pub fn write_config(&self, config: &Value) {
+        let path = self.config_path();
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, serde_json::to_string_pretty(config).unwrap()).unwrap();
+    }
+
+    pub fn read_config(&self) -> Value {
+        let data = std::fs::read_to_string(self.config_path()).expect("no config file written");
+        serde_json::from_str(&data).expect("config file is not JSON")
+    }
+
+    /// The binary with `args`, its settings read from the sandbox only.
+    pub fn command(&self, args: &[&str]) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_hevy-bridge"));
+        for (name, _) in std::env::vars_os() {
+            if name.to_string_lossy().starts_with("HEVY_") || name == "NO_COLOR" {
+                cmd.env_remove(name);
+            }
+        }
+        cmd.env("HOME", self.home())
+            .env("XDG_CONFIG_HOME", self.home().join("config"))
+            .env("XDG_CACHE_HOME", self.home().join("cache"))
+            .args(args);
+        cmd
+    }
+
+    /// Run the binary with `args` and wait for it.
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.command(args)
+            .output()
+            .expect("failed to run hevy-bridge")
+    }
+
+    /// Run the binary with `args`, answered from [`cassettes`].
+    pub fn replay(&self, args: &[&str]) -> Output {
+        let dir = cassettes();
+        self.run(&[args, &["--replay", dir.to_str().unwrap()]].concat())
+    }
+}
+
+/// Recorded API responses for `--replay`.
+pub fn cassettes() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("cassettes")
+}
+
+pub fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+pub fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Serve `router` on a free local port until the test ends, returning the
+/// base URL to point a client at (with the `/v1` prefix the routes use).
+pub async fn serve(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("failed to bind a local port");
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    format!("http://{addr}/v1")
+}
@@ -0,0 +1,23 @@
+mod common;
+
+use clap::ValueEnum;
+use clap_complete::Shell;
+
+#[test]
+fn completions_for_every_shell() {
+    let sandbox = common::Sandbox::new();
+    for shell in Shell::value_variants() {
+        let name = shell.to_possible_value().unwrap().get_name().to_string();
+        let output = sandbox.run(&["completions", &name]);
+        assert!(
+            output.status.success(),
+            "completions {name} failed: {}",
+            common::stderr(&output)
+        );
+        let script = common::stdout(&output);
+        assert!(!script.trim().is_empty(), "empty completions for {name}");
+        for word in ["hevy-bridge", "workouts", "process-workout"] {
+            assert!(script.contains(word), "completions {name} lack {word}");
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Fetching the pages of a list several at a time, against a local server
+//! that records when each request arrives.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::Router;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use futures::TryStreamExt;
+use hevy_bridge::models::Workout;
+use hevy_bridge::{HevyApi, HevyClient};
+use serde_json::json;
+
+const PAGES: u32 = 6;
+
+/// What the server saw: when each page request started and ended, and the
+/// most requests it had in flight at once.
+#[derive(Default)]
+struct Log {
+    requests: Vec<(u32, Instant, Instant)>,
+    in_flight: usize,
+    max_in_flight: usize,
+    /// Pages still to be refused with a 429 once.
+    rate_limit: Vec<u32>,
+}
+
+type Shared = Arc<Mutex<Log>>;
+
+/// Page `n` of [`PAGES`] holds one workout, "w{n}". Later pages answer
+/// faster, so they finish before the earlier ones.
+async fn workouts(
+    State(log): State<Shared>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let page: u32 = query["page"].parse().unwrap();
+    let started = Instant::now();
+    {
+        let mut log = log.lock().unwrap();
+        if let Some(i) = log.rate_limit.iter().position(|&p| p == page) {
+            log.rate_limit.remove(i);
+            log.requests.push((page, started, started));
+            return (
+                StatusCode::TOO_MANY_REQUESTS,
+                [("retry-after", "1")],
+                "slow down",
+            )
+                .into_response();
+        }
+        log.in_flight += 1;
+        log.max_in_flight = log.max_in_flight.max(log.in_flight);
+    }
+    tokio::time::sleep(Duration::from_millis(50 * u64::from(PAGES + 1 - page))).await;
+    let mut log = log.lock().unwrap();
+    log.in_flight -= 1;
+    log.requests.push((page, started, Instant::now()));
+    axum::Json(json!({
+        "page": page,
+        "page_count": PAGES,
+        "workouts": [{ "id": format!("w{page}"), "title": format!("Workout {page}") }],
+    }))
+    .into_response()
+}
+
+async fn server(rate_limit: Vec<u32>) -> (String, Shared) {
+    let log = Arc::new(Mutex::new(Log {
+        rate_limit,
+        ..Default::default()
+    }));
+    let router = Router::new()
+        .route("/v1/workouts", get(workouts))
+        .with_state(Arc::clone(&log));
+    (common::serve(router).await, log)
+}
+
+fn client(base_url: &str, concurrency: usize) -> HevyClient {
+    HevyClient::builder("test-key".to_string())
+        .base_url(base_url)
+        .concurrency(concurrency)
+        .max_rps(0.0)
+        .build()
+        .unwrap()
+}
+
+fn ids(workouts: &[Workout]) -> Vec<&str> {
+    workouts.iter().map(|w| w.id.as_deref().unwrap()).collect()
+}
+
+const IN_ORDER: [&str; PAGES as usize] = ["w1", "w2", "w3", "w4", "w5", "w6"];
+
+#[tokio::test]
+async fn pages_after_the_first_are_fetched_in_parallel_and_kept_in_order() {
+    let (base_url, log) = server(Vec::new()).await;
+    let workouts: Vec<Workout> = client(&base_url, 3)
+        .workouts_stream()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(ids(&workouts), IN_ORDER);
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.max_in_flight, 3);
+    // Page 1 alone, since it tells how many pages there are.
+    let (first, _, first_done) = log.requests.iter().min_by_key(|r| r.1).unwrap();
+    assert_eq!(*first, 1);
+    assert!(log.requests.iter().all(|r| r.0 == 1 || r.1 >= *first_done));
+    // Later pages answer first, so finishing order differs from page order.
+    let mut finished: Vec<_> = log.requests.iter().collect();
+    finished.sort_by_key(|r| r.2);
+    let finished: Vec<u32> = finished.iter().map(|r| r.0).collect();
+    assert_ne!(finished, [1, 2, 3, 4, 5, 6]);
+}
+
+#[tokio::test]
+async fn concurrency_one_fetches_one_page_at_a_time() {
+    let (base_url, log) = server(Vec::new()).await;
+    let workouts: Vec<Workout> = client(&base_url, 1)
+        .workouts_stream()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(ids(&workouts), IN_ORDER);
+    assert_eq!(log.lock().unwrap().max_in_flight, 1);
+}
+
+#[tokio::test]
+async fn a_rate_limited_page_holds_back_the_others() {
+    let (base_url, log) = server(vec![2]).await;
+    let workouts: Vec<Workout> = client(&base_url, 3)
+        .workouts_stream()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(ids(&workouts), IN_ORDER);
+
+    let log = log.lock().unwrap();
+    let (_, limited_at, _) = log.requests.iter().find(|r| r.1 == r.2).unwrap();
+    // Pages already on their way when the 429 arrived carry on; any sent
+    // after it waited out its Retry-After, retry of the page included.
+    let later: Vec<_> = log
+        .requests
+        .iter()
+        .filter(|r| r.1.duration_since(*limited_at) > Duration::from_millis(20))
+        .collect();
+    assert!(
+        later.len() >= 3,
+        "only {} requests after the 429",
+        later.len()
+    );
+    for (page, started, _) in later {
+        assert!(
+            started.duration_since(*limited_at) >= Duration::from_millis(950),
+            "page {page} was sent {:?} after the 429",
+            started.duration_since(*limited_at)
+        );
+    }
+}
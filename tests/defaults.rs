@@ -0,0 +1,119 @@
+//! Stored defaults: an explicit flag beats its environment variable, which
+//! beats the config file, which beats the built-in default.
+
+mod common;
+
+use common::{Sandbox, stderr, stdout};
+use serde_json::{Value, json};
+
+/// `plates 100` shows its units in the bar weight (45 lbs or 20 kg) and its
+/// format in the shape of the output.
+fn plates(sandbox: &Sandbox, args: &[&str], env: &[(&str, &str)]) -> String {
+    let mut command = sandbox.command(&[&["plates", "100"], args].concat());
+    command.envs(env.iter().copied());
+    let output = command.output().unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    stdout(&output)
+}
+
+fn bar(json_output: &str) -> f64 {
+    let loading: Value = serde_json::from_str(json_output)
+        .unwrap_or_else(|e| panic!("not JSON ({e}):\n{json_output}"));
+    loading["bar"].as_f64().unwrap()
+}
+
+/// A sandbox whose config file asks for kg and tables.
+fn kg_and_tables() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.write_config(&json!({
+        "version": 1,
+        "defaults": { "units": "kg", "format": "table" },
+    }));
+    sandbox
+}
+
+#[test]
+fn built_in_defaults_are_lbs_and_json() {
+    assert_eq!(bar(&plates(&Sandbox::new(), &[], &[])), 45.0);
+}
+
+#[test]
+fn config_beats_the_built_in_defaults() {
+    let output = plates(&kg_and_tables(), &[], &[]);
+    assert!(output.starts_with("100 kg: bar 20"), "{output}");
+}
+
+#[test]
+fn env_beats_the_config() {
+    let sandbox = kg_and_tables();
+    let output = plates(&sandbox, &[], &[("HEVY_UNITS", "lbs")]);
+    assert!(output.starts_with("100 lbs: bar 45"), "{output}");
+
+    let output = plates(&sandbox, &[], &[("HEVY_FORMAT", "json")]);
+    assert_eq!(bar(&output), 20.0);
+}
+
+#[test]
+fn flag_beats_the_env() {
+    let sandbox = kg_and_tables();
+    let env = [("HEVY_UNITS", "lbs"), ("HEVY_FORMAT", "table")];
+    let output = plates(&sandbox, &["--units", "kg", "--format", "json"], &env);
+    assert_eq!(bar(&output), 20.0);
+}
+
+#[test]
+fn config_set_validates_and_config_get_reads_back() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run(&["config", "set", "units", "stone"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("expected one of lbs, kg"),
+        "{}",
+        stderr(&output)
+    );
+
+    assert!(
+        sandbox
+            .run(&["config", "set", "format", "table"])
+            .status
+            .success()
+    );
+    assert_eq!(
+        stdout(&sandbox.run(&["config", "get", "format"])),
+        "table\n"
+    );
+    assert_eq!(
+        sandbox.read_config()["defaults"],
+        json!({ "format": "table" })
+    );
+    // Nothing stored prints nothing.
+    assert_eq!(stdout(&sandbox.run(&["config", "get", "units"])), "");
+}
+
+#[test]
+fn ics_is_only_for_workouts_export() {
+    let sandbox = Sandbox::new();
+    let output = sandbox.run(&["plates", "100", "--format", "ics"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout(&output), "");
+    assert!(
+        stderr(&output).contains("--format ics is only supported by `workouts export`"),
+        "{}",
+        stderr(&output)
+    );
+
+    let output = sandbox.run(&["config", "set", "format", "ics"]);
+    assert!(!output.status.success());
+    assert!(!sandbox.config_path().exists());
+
+    // A stored ics default fails the same way, and can still be changed.
+    sandbox.write_config(&json!({ "version": 1, "defaults": { "format": "ics" } }));
+    assert_eq!(sandbox.run(&["plates", "100"]).status.code(), Some(2));
+    assert!(
+        sandbox
+            .run(&["config", "set", "format", "json"])
+            .status
+            .success()
+    );
+    assert_eq!(bar(&plates(&sandbox, &[], &[])), 45.0);
+}
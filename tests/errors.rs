@@ -0,0 +1,239 @@
+//! How failures are reported, from recorded error responses.
+
+mod common;
+
+use common::{Sandbox, cassettes, stderr, stdout};
+use serde_json::{Value, json};
+
+const REJECTED: &str = "✗ The Hevy API rejected the API key (401 Unauthorized).";
+
+/// `workouts count`, answered by a recorded 401.
+fn count_rejected(sandbox: &Sandbox, args: &[&str], env: &[(&str, &str)]) -> std::process::Output {
+    let dir = cassettes();
+    let mut cmd = sandbox.command(
+        &[
+            &["workouts", "count", "--replay", dir.to_str().unwrap()],
+            args,
+        ]
+        .concat(),
+    );
+    cmd.envs(env.iter().copied());
+    cmd.output().expect("failed to run hevy-bridge")
+}
+
+#[test]
+fn rejected_flag_key_gets_the_auth_help_and_exit_status() {
+    let output = count_rejected(&Sandbox::new(), &["--api-key", "expired-key-1234"], &[]);
+    assert_eq!(output.status.code(), Some(3));
+    assert_eq!(stdout(&output), "");
+
+    let message = stderr(&output);
+    assert!(message.starts_with(REJECTED), "{message}");
+    assert!(
+        message.contains("The key came from the --api-key flag."),
+        "{message}"
+    );
+    assert!(
+        message.contains("https://hevy.com/settings?developer"),
+        "{message}"
+    );
+    assert!(
+        message.contains("hevy-bridge config set-key <KEY>"),
+        "{message}"
+    );
+    // The raw API error and the key itself stay out of it.
+    assert!(!message.contains("returned 401"), "{message}");
+    assert!(!message.contains("expired-key-1234"), "{message}");
+}
+
+#[test]
+fn rejected_key_names_where_it_came_from() {
+    let from_env = count_rejected(
+        &Sandbox::new(),
+        &[],
+        &[("HEVY_API_KEY", "expired-key-1234")],
+    );
+    assert_eq!(from_env.status.code(), Some(3));
+    assert!(
+        stderr(&from_env).contains("The key came from the HEVY_API_KEY environment variable."),
+        "{}",
+        stderr(&from_env)
+    );
+
+    let sandbox = Sandbox::new();
+    sandbox.write_config(&json!({
+        "version": 1,
+        "profiles": { "default": { "api_key": "expired-key-1234" } }
+    }));
+    let from_config = count_rejected(&sandbox, &[], &[]);
+    assert_eq!(from_config.status.code(), Some(3));
+    assert!(
+        stderr(&from_config).contains("The key came from the config file."),
+        "{}",
+        stderr(&from_config)
+    );
+}
+
+#[test]
+fn config_validate_gives_the_auth_help_for_a_rejected_key() {
+    let sandbox = Sandbox::new();
+    sandbox.write_config(&json!({
+        "version": 1,
+        "profiles": { "default": { "api_key": "expired-key-1234" } }
+    }));
+    let configured = sandbox.replay(&["config", "validate"]);
+    assert_eq!(configured.status.code(), Some(3));
+    let message = stderr(&configured);
+    assert!(
+        message.starts_with("✗ API key is invalid (403 Forbidden)."),
+        "{message}"
+    );
+    assert!(
+        message.contains("The key came from the config file."),
+        "{message}"
+    );
+    assert!(
+        message.contains("hevy-bridge config set-key <KEY>"),
+        "{message}"
+    );
+
+    // A key given to check is not the configured one, so no source.
+    let given = sandbox.replay(&["config", "validate", "other-key-5678"]);
+    assert_eq!(given.status.code(), Some(3));
+    let message = stderr(&given);
+    assert!(
+        message.starts_with("✗ API key is invalid (403 Forbidden)."),
+        "{message}"
+    );
+    assert!(!message.contains("The key came from"), "{message}");
+}
+
+/// The one JSON object `--error-format json` printed, checking it holds
+/// exactly the documented fields.
+fn error_report(output: &std::process::Output) -> Value {
+    let text = stderr(output);
+    assert_eq!(text.lines().count(), 1, "{text}");
+    let report: Value = serde_json::from_str(&text).expect("stderr is not JSON");
+    let mut fields: Vec<&str> = report
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect();
+    fields.sort_unstable();
+    assert_eq!(
+        fields,
+        ["endpoint", "error_kind", "hint", "message", "status"]
+    );
+    report
+}
+
+#[test]
+fn not_found_as_json() {
+    let id = "00000000-0000-4000-8000-000000000404";
+    let output = Sandbox::new().replay(&["workouts", "get", id, "--error-format", "json"]);
+    assert_eq!(output.status.code(), Some(4));
+    assert_eq!(stdout(&output), "");
+
+    let report = error_report(&output);
+    assert_eq!(report["error_kind"], "not_found");
+    assert_eq!(report["status"], 404);
+    assert_eq!(report["endpoint"], format!("GET /workouts/{id}"));
+    assert_eq!(
+        report["message"],
+        format!("GET /workouts/{id} returned 404 Not Found: {{\"error\":\"Workout not found\"}}")
+    );
+    assert_eq!(
+        report["hint"],
+        "Check the ID; list commands show the valid ones"
+    );
+}
+
+#[test]
+fn invalid_json_argument_as_json() {
+    let output = Sandbox::new().replay(&[
+        "routines",
+        "create",
+        "--json",
+        r#"{"routine": "#,
+        "--error-format",
+        "json",
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+
+    let report = error_report(&output);
+    assert_eq!(report["error_kind"], "invalid_input");
+    assert_eq!(
+        (&report["status"], &report["endpoint"]),
+        (&Value::Null, &Value::Null)
+    );
+    let message = report["message"].as_str().unwrap();
+    assert!(
+        message.starts_with("Invalid JSON for routine body."),
+        "{message}"
+    );
+    assert!(message.contains("EOF while parsing"), "{message}");
+    assert_eq!(
+        report["hint"],
+        "See the command's --help for the expected input"
+    );
+}
+
+#[test]
+fn unparseable_response_as_json() {
+    let output = Sandbox::new().replay(&[
+        "routines",
+        "get",
+        "e5d0a7b2-1c4f-4b9e-8a63-0f2d7c9e0bad",
+        "--error-format",
+        "json",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let report = error_report(&output);
+    assert_eq!(report["error_kind"], "api_error");
+    assert_eq!(
+        report["message"],
+        "Failed to parse routine response: expected value at line 1 column 1"
+    );
+    assert_eq!(report["hint"], Value::Null);
+}
+
+#[test]
+fn rejected_key_as_json() {
+    let output = count_rejected(
+        &Sandbox::new(),
+        &["--api-key", "expired-key-1234", "--error-format", "json"],
+        &[],
+    );
+    assert_eq!(output.status.code(), Some(3));
+    let report = error_report(&output);
+    assert_eq!(report["error_kind"], "auth");
+    assert_eq!(report["status"], 401);
+    assert_eq!(report["endpoint"], "GET /workouts/count");
+}
+
+#[test]
+fn rejected_key_on_config_validate_and_set_key_as_json() {
+    let sandbox = Sandbox::new();
+    for args in [
+        ["config", "validate", "expired-key-1234"],
+        ["config", "set-key", "expired-key-1234"],
+    ] {
+        let output = sandbox.replay(&[&args[..], &["--error-format", "json"]].concat());
+        assert_eq!(output.status.code(), Some(3), "{args:?}");
+        let report = error_report(&output);
+        assert_eq!(report["error_kind"], "auth");
+        assert_eq!(report["status"], 403);
+        assert_eq!(report["endpoint"], "GET /user/info");
+        assert!(
+            report["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("API key is invalid: "),
+            "{report}"
+        );
+    }
+    // set-key stored nothing.
+    assert!(!sandbox.config_path().exists());
+}
@@ -0,0 +1,117 @@
+//! `get --ids-from-stdin` over recorded responses: two workouts that exist
+//! and one that the API answers with 404.
+
+mod common;
+
+use std::io::Write;
+use std::process::{Output, Stdio};
+
+use common::{Sandbox, cassettes, stderr, stdout};
+use serde_json::Value;
+
+const PUSH_DAY: &str = "b7e1c0de-5d2a-4f0e-9c11-3e8f2a6b0001";
+const TEMPO_SQUATS: &str = "b7e1c0de-5d2a-4f0e-9c11-3e8f2a6b0009";
+const MISSING: &str = "00000000-0000-4000-8000-000000000404";
+
+/// Run `args` with `--ids-from-stdin`, feeding it `ids`.
+fn get_ids(args: &[&str], ids: &str) -> Output {
+    let dir = cassettes();
+    let mut child = Sandbox::new()
+        .command(
+            &[
+                args,
+                &["--ids-from-stdin", "--replay", dir.to_str().unwrap()],
+            ]
+            .concat(),
+        )
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run hevy-bridge");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(ids.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn lines(output: &Output) -> Vec<Value> {
+    stdout(output)
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("not one JSON object per line"))
+        .collect()
+}
+
+#[test]
+fn results_and_errors_come_back_in_input_order() {
+    let ids = format!("{TEMPO_SQUATS}\n{MISSING}\n\n  {PUSH_DAY}  \n");
+    let output = get_ids(&["workouts", "get"], &ids);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let lines = lines(&output);
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0]["id"], TEMPO_SQUATS);
+    assert_eq!(lines[0]["title"], "Tempo Squats");
+    assert_eq!(lines[1]["id"], MISSING);
+    let error = lines[1]["error"].as_str().unwrap();
+    assert!(error.contains("returned 404 Not Found"), "{error}");
+    assert_eq!(lines[2]["id"], PUSH_DAY);
+    assert_eq!(lines[2]["title"], "Push Day");
+    assert!(
+        stderr(&output).contains("1 of 3 ID(s) could not be fetched."),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn fail_fast_stops_at_the_first_error() {
+    let ids = format!("{PUSH_DAY}\n{MISSING}\n{TEMPO_SQUATS}\n");
+    let output = get_ids(&["workouts", "get", "--fail-fast"], &ids);
+    assert_eq!(output.status.code(), Some(4));
+
+    let message = stderr(&output);
+    assert!(
+        message.contains(&format!("Failed to get {MISSING}")),
+        "{message}"
+    );
+    let lines = lines(&output);
+    assert!(lines.iter().all(|line| line.get("error").is_none()));
+    assert!(lines.iter().all(|line| line["id"] != TEMPO_SQUATS));
+}
+
+#[test]
+fn all_good_ids_print_only_results() {
+    let output = get_ids(
+        &["workouts", "get"],
+        &format!("{PUSH_DAY}\n{TEMPO_SQUATS}\n"),
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let ids: Vec<Value> = lines(&output)
+        .iter()
+        .map(|line| line["id"].clone())
+        .collect();
+    assert_eq!(ids, [PUSH_DAY, TEMPO_SQUATS]);
+    assert!(!stderr(&output).contains("could not be fetched"));
+}
+
+#[test]
+fn routines_report_unreadable_responses_per_id() {
+    let output = get_ids(
+        &["routines", "get"],
+        "e5d0a7b2-1c4f-4b9e-8a63-0f2d7c9e0bad\n",
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let lines = lines(&output);
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0]["id"], "e5d0a7b2-1c4f-4b9e-8a63-0f2d7c9e0bad");
+    assert!(
+        lines[0]["error"]
+            .as_str()
+            .unwrap()
+            .contains("Failed to parse routine response")
+    );
+}
@@ -0,0 +1,150 @@
+//! Named profiles: migrating the old single-key layout, and which key wins
+//! when several are available.
+
+mod common;
+
+use common::{Sandbox, stderr, stdout};
+use serde_json::{Value, json};
+
+/// Two profiles, "me" being the default.
+fn two_profiles() -> Sandbox {
+    let sandbox = Sandbox::new();
+    sandbox.write_config(&json!({
+        "version": 1,
+        "profiles": {
+            "me": {"api_key": "key-of-me-1111"},
+            "partner": {"api_key": "key-of-partner-2222"},
+        },
+        "default_profile": "me",
+    }));
+    sandbox
+}
+
+/// The key `config show` would use, as (masked key, source).
+fn shown_key(output: &std::process::Output) -> (String, String) {
+    assert!(output.status.success(), "{}", stderr(output));
+    let shown: Value = serde_json::from_str(&stdout(output)).unwrap();
+    (
+        shown["api_key"].as_str().unwrap_or_default().to_string(),
+        shown["api_key_source"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+    )
+}
+
+#[test]
+fn single_key_layout_moves_into_the_default_profile_on_write() {
+    let sandbox = Sandbox::new();
+    sandbox.write_config(&json!({"api_key": "old-key-1234"}));
+
+    // Still readable before anything is written.
+    let (key, source) = shown_key(&sandbox.run(&["config", "show"]));
+    assert_eq!((key.as_str(), source.as_str()), ("…1234", "file"));
+
+    let output = sandbox.run(&[
+        "config",
+        "set-key",
+        "--no-check",
+        "--profile",
+        "partner",
+        "partner-key-5678",
+    ]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let config = sandbox.read_config();
+    assert_eq!(config["version"], 1);
+    assert!(config.get("api_key").is_none());
+    assert_eq!(config["profiles"]["default"]["api_key"], "old-key-1234");
+    assert_eq!(config["profiles"]["partner"]["api_key"], "partner-key-5678");
+    // The migrated key stays the one used without --profile.
+    assert_eq!(config["default_profile"], "default");
+}
+
+#[test]
+fn config_use_changes_the_default_profile() {
+    let sandbox = two_profiles();
+    let output = sandbox.run(&["config", "use", "partner"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(sandbox.read_config()["default_profile"], "partner");
+
+    let (key, _) = shown_key(&sandbox.run(&["config", "show"]));
+    assert_eq!(key, "…2222");
+
+    let output = sandbox.run(&["config", "use", "nobody"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("No profile named \"nobody\""));
+}
+
+#[test]
+fn default_profile_is_used_without_flags() {
+    let (key, source) = shown_key(&two_profiles().run(&["config", "show"]));
+    assert_eq!((key.as_str(), source.as_str()), ("…1111", "file"));
+}
+
+#[test]
+fn env_var_beats_the_default_profile() {
+    let sandbox = two_profiles();
+    let output = sandbox
+        .command(&["config", "show"])
+        .env("HEVY_API_KEY", "key-from-env-3333")
+        .output()
+        .unwrap();
+    assert_eq!(shown_key(&output), ("…3333".to_string(), "env".to_string()));
+}
+
+#[test]
+fn profile_flag_beats_the_env_var() {
+    let sandbox = two_profiles();
+    let output = sandbox
+        .command(&["config", "show", "--profile", "partner"])
+        .env("HEVY_API_KEY", "key-from-env-3333")
+        .output()
+        .unwrap();
+    assert_eq!(
+        shown_key(&output),
+        ("…2222".to_string(), "file".to_string())
+    );
+
+    // HEVY_PROFILE selects a profile just like the flag.
+    let output = sandbox
+        .command(&["config", "show"])
+        .env("HEVY_PROFILE", "partner")
+        .env("HEVY_API_KEY", "key-from-env-3333")
+        .output()
+        .unwrap();
+    assert_eq!(shown_key(&output).0, "…2222");
+}
+
+#[test]
+fn api_key_flag_beats_everything() {
+    let sandbox = two_profiles();
+    let output = sandbox
+        .command(&[
+            "config",
+            "show",
+            "--profile",
+            "partner",
+            "--api-key",
+            "key-from-flag-4444",
+        ])
+        .env("HEVY_API_KEY", "key-from-env-3333")
+        .output()
+        .unwrap();
+    assert_eq!(
+        shown_key(&output),
+        ("…4444".to_string(), "flag".to_string())
+    );
+}
+
+#[test]
+fn unknown_profile_is_an_error_not_a_fallback() {
+    let sandbox = two_profiles();
+    let output = sandbox
+        .command(&["config", "validate", "--profile", "nobody"])
+        .env("HEVY_API_KEY", "key-from-env-3333")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("No API key stored for profile \"nobody\""));
+}
@@ -0,0 +1,53 @@
+//! Commands answered from the recorded responses in tests/cassettes, with
+//! no API key and no network.
+
+mod common;
+
+use common::{Sandbox, stderr, stdout};
+use serde_json::Value;
+
+#[test]
+fn workouts_list_replays_without_an_api_key() {
+    let output = Sandbox::new().replay(&["workouts", "list"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let page: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(page["page"], 1);
+    assert_eq!(page["page_count"], 3);
+    let titles: Vec<&str> = page["workouts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|w| w["title"].as_str().unwrap())
+        .collect();
+    assert_eq!(titles, ["Push Day", "Pull Day"]);
+    // A bodyweight set comes back without a weight.
+    assert_eq!(
+        page["workouts"][1]["exercises"][0]["sets"][0]["weight_kg"],
+        Value::Null
+    );
+}
+
+#[test]
+fn workouts_list_replays_as_a_table() {
+    let output = Sandbox::new().replay(&["workouts", "list", "--format", "table"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let table = stdout(&output);
+    assert!(
+        table.contains("Push Day") && table.contains("Pull Day"),
+        "{table}"
+    );
+    assert!(stderr(&output).contains("Page 1 of 3"));
+}
+
+#[test]
+fn unrecorded_request_fails_and_names_the_missing_cassette() {
+    let output = Sandbox::new().replay(&["workouts", "list", "--page", "2"]);
+    assert!(!output.status.success());
+    let message = stderr(&output);
+    assert!(
+        message.contains("No recorded response for GET /workouts"),
+        "{message}"
+    );
+    assert!(message.contains("page=2"), "{message}");
+}
@@ -0,0 +1,133 @@
+//! The on-disk response cache, against a local server counting the
+//! requests it gets.
+//!
+//! The cache lives under XDG_CACHE_HOME, which this file points at a
+//! temporary directory; the steps share it, so they run as one test.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::extract::{Path, State};
+use axum::http::{Method, Uri};
+use axum::routing::get;
+use hevy_bridge::models::PostWorkoutBody;
+use hevy_bridge::{HevyApi, HevyClient};
+use serde_json::{Value, json};
+
+type Hits = Arc<Mutex<HashMap<String, usize>>>;
+
+fn record(hits: &Hits, method: &Method, uri: &Uri) {
+    *hits
+        .lock()
+        .unwrap()
+        .entry(format!("{method} {uri}"))
+        .or_default() += 1;
+}
+
+async fn workout(
+    State(hits): State<Hits>,
+    method: Method,
+    uri: Uri,
+    Path(id): Path<String>,
+) -> axum::Json<Value> {
+    record(&hits, &method, &uri);
+    axum::Json(json!({ "id": id, "title": "Push" }))
+}
+
+async fn workouts(State(hits): State<Hits>, method: Method, uri: Uri) -> axum::Json<Value> {
+    record(&hits, &method, &uri);
+    axum::Json(json!({ "page": 1, "page_count": 1, "workouts": [] }))
+}
+
+async fn count(State(hits): State<Hits>, method: Method, uri: Uri) -> axum::Json<Value> {
+    record(&hits, &method, &uri);
+    axum::Json(json!({ "workout_count": 1 }))
+}
+
+fn client(base_url: &str, api_key: &str, cache: bool) -> HevyClient {
+    HevyClient::builder(api_key.to_string())
+        .base_url(base_url)
+        .http_cache(cache)
+        .max_rps(0.0)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn cached_responses_are_not_fetched_again() {
+    let cache_home = tempfile::tempdir().unwrap();
+    // SAFETY: the only test in this binary, and no other thread has
+    // started reading the environment.
+    unsafe { std::env::set_var("XDG_CACHE_HOME", cache_home.path()) };
+
+    let hits = Hits::default();
+    let router = Router::new()
+        .route("/v1/workouts", get(workouts))
+        .route("/v1/workouts/count", get(count))
+        .route("/v1/workouts/{id}", get(workout).put(workout))
+        .with_state(Arc::clone(&hits));
+    let base_url = common::serve(router).await;
+    let hits_for = |request: &str| hits.lock().unwrap().get(request).copied().unwrap_or(0);
+
+    let cached = client(&base_url, "key-a", true);
+
+    // A second identical call is answered from disk.
+    assert_eq!(
+        cached.get_workout("w1").await.unwrap().id.as_deref(),
+        Some("w1")
+    );
+    assert_eq!(
+        cached.get_workout("w1").await.unwrap().id.as_deref(),
+        Some("w1")
+    );
+    assert_eq!(hits_for("GET /v1/workouts/w1"), 1);
+    cached.list_workouts(1, 5).await.unwrap();
+    cached.list_workouts(1, 5).await.unwrap();
+    assert_eq!(hits_for("GET /v1/workouts?page=1&pageSize=5"), 1);
+
+    // A fresh client with the same key finds the entries too.
+    client(&base_url, "key-a", true)
+        .get_workout("w1")
+        .await
+        .unwrap();
+    assert_eq!(hits_for("GET /v1/workouts/w1"), 1);
+
+    // Other URLs and other keys are separate entries.
+    cached.list_workouts(2, 5).await.unwrap();
+    assert_eq!(hits_for("GET /v1/workouts?page=2&pageSize=5"), 1);
+    client(&base_url, "key-b", true)
+        .get_workout("w1")
+        .await
+        .unwrap();
+    assert_eq!(hits_for("GET /v1/workouts/w1"), 2);
+
+    // Counts are never cached, and a client without the cache skips it.
+    cached.workout_count().await.unwrap();
+    cached.workout_count().await.unwrap();
+    assert_eq!(hits_for("GET /v1/workouts/count"), 2);
+    client(&base_url, "key-a", false)
+        .get_workout("w1")
+        .await
+        .unwrap();
+    assert_eq!(hits_for("GET /v1/workouts/w1"), 3);
+
+    // A write empties the cache, so the next read goes to the server.
+    let body: PostWorkoutBody = serde_json::from_value(json!({
+        "workout": {
+            "title": "Push",
+            "start_time": "2026-03-02T18:00:00Z",
+            "end_time": "2026-03-02T19:00:00Z",
+            "exercises": []
+        }
+    }))
+    .unwrap();
+    cached.update_workout("w1", &body).await.unwrap();
+    assert_eq!(hits_for("PUT /v1/workouts/w1"), 1);
+    cached.get_workout("w1").await.unwrap();
+    assert_eq!(hits_for("GET /v1/workouts/w1"), 4);
+    cached.get_workout("w1").await.unwrap();
+    assert_eq!(hits_for("GET /v1/workouts/w1"), 4);
+}
@@ -0,0 +1,109 @@
+//! The paginated streams against a local server: empty accounts, a single
+//! page, and a page that fails partway through.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use futures::StreamExt;
+use hevy_bridge::{HevyApi, HevyClient, Result};
+use serde_json::json;
+
+/// Pages served for `/v1/workouts`, by number; a missing page is a 500.
+/// Records the `page` and `pageSize` of each request.
+#[derive(Clone, Default)]
+struct Server {
+    pages: Arc<HashMap<u32, Vec<&'static str>>>,
+    page_count: i64,
+    requests: Arc<Mutex<Vec<(u32, u32)>>>,
+}
+
+async fn workouts(
+    State(server): State<Server>,
+    Query(query): Query<HashMap<String, u32>>,
+) -> Response {
+    let (page, page_size) = (query["page"], query["pageSize"]);
+    server.requests.lock().unwrap().push((page, page_size));
+    let Some(ids) = server.pages.get(&page) else {
+        return (StatusCode::INTERNAL_SERVER_ERROR, "boom").into_response();
+    };
+    let workouts: Vec<_> = ids.iter().map(|id| json!({ "id": id })).collect();
+    axum::Json(json!({ "page": page, "page_count": server.page_count, "workouts": workouts }))
+        .into_response()
+}
+
+async fn client(server: &Server) -> HevyClient {
+    let router = Router::new()
+        .route("/v1/workouts", get(workouts))
+        .with_state(server.clone());
+    HevyClient::builder("test-key".to_string())
+        .base_url(common::serve(router).await)
+        .page_size(4)
+        .max_retries(0)
+        .max_rps(0.0)
+        .build()
+        .unwrap()
+}
+
+fn server(pages: &[(u32, &[&'static str])], page_count: i64) -> Server {
+    Server {
+        pages: Arc::new(pages.iter().map(|(n, ids)| (*n, ids.to_vec())).collect()),
+        page_count,
+        ..Default::default()
+    }
+}
+
+/// Every item the stream yields, errors included, as IDs or messages.
+async fn drain(client: &HevyClient) -> Vec<Result<String, String>> {
+    client
+        .workouts_stream()
+        .map(|item| item.map(|w| w.id.unwrap()).map_err(|e| e.to_string()))
+        .collect()
+        .await
+}
+
+#[tokio::test]
+async fn empty_account_yields_nothing_after_one_request() {
+    let server = server(&[(1, &[])], 0);
+    assert!(drain(&client(&server).await).await.is_empty());
+    assert_eq!(*server.requests.lock().unwrap(), [(1, 4)]);
+}
+
+#[tokio::test]
+async fn exactly_one_page() {
+    let server = server(&[(1, &["w1", "w2", "w3", "w4"])], 1);
+    let items = drain(&client(&server).await).await;
+    assert_eq!(
+        items,
+        [Ok("w1"), Ok("w2"), Ok("w3"), Ok("w4")].map(|i| i.map(String::from))
+    );
+    assert_eq!(*server.requests.lock().unwrap(), [(1, 4)]);
+}
+
+#[tokio::test]
+async fn a_failed_page_ends_the_stream_with_an_err_item() {
+    // Page 2 of 4 fails; pages 3 and 4 may already be on their way.
+    let server = server(&[(1, &["w1", "w2"]), (3, &["w5"]), (4, &["w6"])], 4);
+    let items = drain(&client(&server).await).await;
+
+    assert_eq!(items.len(), 3, "{items:?}");
+    assert_eq!(items[..2], [Ok("w1".to_string()), Ok("w2".to_string())]);
+    let err = items[2].as_ref().unwrap_err();
+    assert!(err.starts_with("GET /workouts returned 500"), "{err}");
+}
+
+#[tokio::test]
+async fn the_stream_is_lazy() {
+    let server = server(&[(1, &["w1", "w2"]), (2, &["w3"])], 2);
+    let client = client(&server).await;
+    let first: Vec<_> = client.workouts_stream().take(1).collect().await;
+    assert_eq!(first.len(), 1);
+    // Page 2 was never needed.
+    assert_eq!(*server.requests.lock().unwrap(), [(1, 4)]);
+}
@@ -0,0 +1,58 @@
+//! `--strict` against a recorded workout carrying fields the models do not
+//! capture: `location`, `exercises[].tempo` and
+//! `exercises[].sets[].avg_heart_rate`.
+
+mod common;
+
+use common::{Sandbox, stderr, stdout};
+use serde_json::Value;
+
+const WORKOUT_ID: &str = "b7e1c0de-5d2a-4f0e-9c11-3e8f2a6b0009";
+
+const UNCAPTURED: &str = "exercises[].sets[].avg_heart_rate, exercises[].tempo, location";
+
+#[test]
+fn extra_fields_are_dropped_silently_by_default() {
+    let output = Sandbox::new().replay(&["workouts", "get", WORKOUT_ID]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(stderr(&output), "");
+
+    let workout: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(workout["title"], "Tempo Squats");
+    assert!(workout.get("location").is_none());
+    assert!(workout["exercises"][0].get("tempo").is_none());
+    assert_eq!(workout["exercises"][0]["sets"][1]["reps"], 5);
+}
+
+#[test]
+fn strict_warns_on_stderr_and_still_succeeds() {
+    let output = Sandbox::new().replay(&["workouts", "get", WORKOUT_ID, "--strict"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let warning = stderr(&output);
+    assert!(
+        warning.contains(&format!(
+            "GET /workouts/{WORKOUT_ID} returned fields hevy-bridge does not capture: {UNCAPTURED}"
+        )),
+        "{warning}"
+    );
+    // The null is_private carries no data, so it is not reported.
+    assert!(!warning.contains("is_private"), "{warning}");
+    let workout: Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(workout["id"], WORKOUT_ID);
+}
+
+#[test]
+fn strict_error_fails_the_command() {
+    let output = Sandbox::new().replay(&["workouts", "get", WORKOUT_ID, "--strict=error"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), "");
+    assert!(stderr(&output).contains(UNCAPTURED), "{}", stderr(&output));
+}
+
+#[test]
+fn strict_is_quiet_when_every_field_is_captured() {
+    let output = Sandbox::new().replay(&["workouts", "list", "--strict=error"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(!stderr(&output).contains("does not capture"));
+}
@@ -0,0 +1,91 @@
+//! The exercise template cache, against a local server whose custom
+//! templates depend on the API key.
+//!
+//! The cache lives under XDG_CACHE_HOME, which this file points at a
+//! temporary directory; the steps share it, so they run as one test.
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::routing::get;
+use hevy_bridge::{HevyApi, HevyClient};
+use serde_json::{Value, json};
+
+type Hits = Arc<Mutex<HashMap<String, usize>>>;
+
+/// One page holding the built-in bench press and a custom template named
+/// after the requesting key.
+async fn templates(State(hits): State<Hits>, headers: HeaderMap) -> axum::Json<Value> {
+    let key = headers["api-key"].to_str().unwrap().to_string();
+    *hits.lock().unwrap().entry(key.clone()).or_default() += 1;
+    axum::Json(json!({
+        "page": 1,
+        "page_count": 1,
+        "exercise_templates": [
+            { "id": "79D0BB3A", "title": "Bench Press (Barbell)", "is_custom": false },
+            { "id": format!("custom-{key}"), "title": format!("Custom {key}"), "is_custom": true }
+        ]
+    }))
+}
+
+fn client(base_url: &str, api_key: &str) -> HevyClient {
+    HevyClient::builder(api_key.to_string())
+        .base_url(base_url)
+        .max_rps(0.0)
+        .build()
+        .unwrap()
+}
+
+async fn custom_ids(client: &HevyClient) -> Vec<String> {
+    client
+        .all_exercise_templates()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|t| t.is_custom == Some(true))
+        .filter_map(|t| t.id)
+        .collect()
+}
+
+#[tokio::test]
+async fn each_api_key_has_its_own_template_cache() {
+    let cache_home = tempfile::tempdir().unwrap();
+    // SAFETY: the only test in this binary, and no other thread has
+    // started reading the environment.
+    unsafe { std::env::set_var("XDG_CACHE_HOME", cache_home.path()) };
+
+    let hits = Hits::default();
+    let router = Router::new()
+        .route("/v1/exercise_templates", get(templates))
+        .with_state(Arc::clone(&hits));
+    let base_url = common::serve(router).await;
+    let hits_for = |key: &str| hits.lock().unwrap().get(key).copied().unwrap_or(0);
+
+    // The second fetch with the same key comes from disk.
+    assert_eq!(
+        custom_ids(&client(&base_url, "key-a")).await,
+        ["custom-key-a"]
+    );
+    assert_eq!(
+        custom_ids(&client(&base_url, "key-a")).await,
+        ["custom-key-a"]
+    );
+    assert_eq!(hits_for("key-a"), 1);
+
+    // Another key never sees the first one's custom templates.
+    assert_eq!(
+        custom_ids(&client(&base_url, "key-b")).await,
+        ["custom-key-b"]
+    );
+    assert_eq!(hits_for("key-b"), 1);
+    assert_eq!(
+        custom_ids(&client(&base_url, "key-a")).await,
+        ["custom-key-a"]
+    );
+    assert_eq!(hits_for("key-a"), 1);
+}
@@ -0,0 +1,103 @@
+//! Request time limits and the User-Agent, against a local server.
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::Router;
+use axum::http::HeaderMap;
+use axum::routing::get;
+use hevy_bridge::{Error, HevyApi, HevyClient};
+use serde_json::json;
+
+fn client(base_url: &str, timeout: Duration) -> HevyClient {
+    HevyClient::builder("test-key".to_string())
+        .base_url(base_url)
+        .timeout(timeout)
+        .max_retries(0)
+        .max_rps(0.0)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn slow_response_times_out_with_a_plain_message() {
+    let router = Router::new().route(
+        "/v1/workouts",
+        get(|| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            axum::Json(json!({ "page": 1, "page_count": 1, "workouts": [] }))
+        }),
+    );
+    let base_url = common::serve(router).await;
+
+    let started = std::time::Instant::now();
+    let err = client(&base_url, Duration::from_millis(300))
+        .list_workouts(1, 5)
+        .await
+        .unwrap_err();
+    assert!(started.elapsed() < Duration::from_secs(3));
+
+    assert!(
+        matches!(
+            &err,
+            Error::Timeout {
+                endpoint,
+                limit,
+                connecting: false,
+            } if endpoint == "GET /workouts" && *limit == Duration::from_millis(300)
+        ),
+        "{err:?}"
+    );
+    assert_eq!(
+        err.to_string(),
+        "Request to GET /workouts timed out after 300ms"
+    );
+}
+
+#[tokio::test]
+async fn response_within_the_limit_succeeds() {
+    let router = Router::new().route(
+        "/v1/workouts/count",
+        get(|| async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            axum::Json(json!({ "workout_count": 7 }))
+        }),
+    );
+    let base_url = common::serve(router).await;
+
+    let count = client(&base_url, Duration::from_secs(5))
+        .workout_count()
+        .await
+        .unwrap();
+    assert_eq!(count.workout_count, 7);
+}
+
+#[tokio::test]
+async fn requests_identify_the_client() {
+    let seen = Arc::new(Mutex::new(None));
+    let router = Router::new().route(
+        "/v1/workouts/count",
+        get({
+            let seen = Arc::clone(&seen);
+            move |headers: HeaderMap| async move {
+                let header = |name: &str| headers[name].to_str().unwrap().to_string();
+                *seen.lock().unwrap() = Some((header("user-agent"), header("api-key")));
+                axum::Json(json!({ "workout_count": 0 }))
+            }
+        }),
+    );
+    let base_url = common::serve(router).await;
+
+    client(&base_url, Duration::from_secs(5))
+        .workout_count()
+        .await
+        .unwrap();
+    let (user_agent, api_key) = seen.lock().unwrap().take().unwrap();
+    assert_eq!(
+        user_agent,
+        concat!("hevy-bridge/", env!("CARGO_PKG_VERSION"))
+    );
+    assert_eq!(api_key, "test-key");
+}